@@ -0,0 +1,75 @@
+//! Compares the owned tree with `ArenaDocument` on a large generated document
+//!
+//! Run with `cargo bench --bench arena`.
+
+use std::{hint::black_box, time::Instant};
+
+use rs_tml::{arena::ArenaDocument, prelude::*};
+
+const ITERATIONS: u32 = 20;
+
+fn source(rows: usize) -> String {
+    let mut source = String::from("table {\n");
+    for i in 0..rows {
+        source.push_str(&format!(
+            "  tr {{ .class = \"row\" td {{ \"{i}\" }} td {{ a {{ .href = \"/items/{i}\" \"Item {i}\" }} }} }}\n"
+        ));
+    }
+    source.push('}');
+    source
+}
+
+fn time(name: &str, mut run: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        run();
+    }
+    println!("{name:<24} {:>10.2?}", start.elapsed() / ITERATIONS);
+}
+
+fn count_owned(nodes: &[Node]) -> usize {
+    nodes.len()
+        + nodes
+            .iter()
+            .map(|node| match node {
+                Node::Element(element) => count_owned(&element.children),
+                _ => 0,
+            })
+            .sum::<usize>()
+}
+
+fn count_arena(
+    document: &ArenaDocument,
+    ids: impl Iterator<Item = rs_tml::arena::NodeId>,
+) -> usize {
+    ids.map(|id| 1 + count_arena(document, document.children(id)))
+        .sum()
+}
+
+fn main() {
+    let source = source(5_000);
+    let (_, block) = Block::parse_ignoring_comments(&source).unwrap();
+    let arena = ArenaDocument::from(&block);
+    assert_eq!(count_owned(&block.children), arena.len());
+
+    time("parse owned", || {
+        black_box(Block::parse_ignoring_comments(black_box(&source)).unwrap());
+    });
+    time("parse arena", || {
+        black_box(ArenaDocument::parse(black_box(&source)).unwrap());
+    });
+    time("clone + drop owned", || drop(black_box(block.clone())));
+    time("clone + drop arena", || drop(black_box(arena.clone())));
+    time("traverse owned", || {
+        black_box(count_owned(black_box(&block.children)));
+    });
+    time("traverse arena", || {
+        black_box(count_arena(&arena, black_box(&arena).roots()));
+    });
+    time("owned to arena", || {
+        black_box(ArenaDocument::from(black_box(&block)));
+    });
+    time("arena to owned", || {
+        black_box(Block::from(black_box(&arena)));
+    });
+}
@@ -0,0 +1,60 @@
+//! Parses large generated documents, dominated by delimiter scanning
+//!
+//! Run with `cargo bench --bench scan`.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rs_tml::prelude::*;
+
+// Deeply nested sections, exercising brace matching
+fn nested_source(depth: usize, width: usize) -> String {
+    let mut source = String::new();
+    for i in 0..width {
+        for level in 0..depth {
+            source.push_str(&format!("section {{ .class = \"level-{level}\" "));
+        }
+        source.push_str(&format!("p {{ \"Leaf {i}\" }}"));
+        source.push_str(&"}".repeat(depth));
+        source.push('\n');
+    }
+    source
+}
+
+// Long paragraphs with escaped quotes, exercising quoted string scanning
+fn text_source(paragraphs: usize) -> String {
+    let sentence = r#"Lorem ipsum dolor sit amet, \"consectetur\" adipiscing elit. "#;
+    let mut source = String::new();
+    for _ in 0..paragraphs {
+        source.push_str(&format!("p {{ \"{}\" }}\n", sentence.repeat(40)));
+    }
+    source
+}
+
+// Elements separated by large block comments
+fn comment_source(items: usize) -> String {
+    let comment = format!("/* {} */", "commented out { markup } ".repeat(40));
+    let mut source = String::new();
+    for i in 0..items {
+        source.push_str(&format!("{comment}\nli {{ \"Item {i}\" }}\n"));
+    }
+    source
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, source) in [
+        ("nested", nested_source(32, 500)),
+        ("text", text_source(2_000)),
+        ("comments", comment_source(2_000)),
+    ] {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| Block::parse_ignoring_comments(black_box(&source)).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
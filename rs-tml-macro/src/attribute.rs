@@ -1,5 +1,5 @@
 use quote::ToTokens;
-use syn::{Expr, Ident, LitStr, Token, parse::Parse};
+use syn::{Expr, Ident, LitStr, Token, parse::Parse, parse::discouraged::Speculative};
 
 pub enum AttributeKey {
     Static(String),
@@ -21,6 +21,9 @@ fn parse_hyphenated_ident(input: syn::parse::ParseStream) -> syn::Result<String>
     Ok(out)
 }
 
+// Attribute families whose `prefix:name` sugar expands to `prefix-name`
+const HYPHENATED_PREFIXES: &[&str] = &["data", "aria", "hx"];
+
 impl Parse for AttributeKey {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         if !(input.peek(Token![.]) || input.peek(Token![#])) {
@@ -52,7 +55,18 @@ impl Parse for AttributeKey {
                 AttributeKey::DynamicId(expr)
             }
         } else {
-            let name = parse_hyphenated_ident(input)?;
+            let mut name = parse_hyphenated_ident(input)?;
+            // Prefixed names, e.g. `.data:id` becomes `data-id` while `.xlink:href` is kept
+            if input.peek(Token![:]) && !input.peek(Token![::]) {
+                input.parse::<Token![:]>()?;
+                let suffix = parse_hyphenated_ident(input)?;
+                let separator = if HYPHENATED_PREFIXES.contains(&name.as_str()) {
+                    '-'
+                } else {
+                    ':'
+                };
+                name = format!("{name}{separator}{suffix}");
+            }
             if is_class {
                 AttributeKey::Static(name)
             } else {
@@ -63,14 +77,103 @@ impl Parse for AttributeKey {
     }
 }
 
+/// Entry of a class list, either always present or gated by a condition
+///
+/// Examples:
+/// "btn"                           // always present
+/// ("active", `is_active`)         // present when `is_active` is true
+pub struct ClassListItem {
+    pub(crate) class: Expr,
+    pub(crate) condition: Option<Expr>,
+}
+
+impl Parse for ClassListItem {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            let class = content.parse()?;
+            content.parse::<Token![,]>()?;
+            let condition = content.parse()?;
+            return Ok(ClassListItem {
+                class,
+                condition: Some(condition),
+            });
+        }
+        Ok(ClassListItem {
+            class: input.parse()?,
+            condition: None,
+        })
+    }
+}
+
+/// Property of a `style: { ... }` attribute
+///
+/// Examples:
+/// padding: "1rem"
+/// background-color: `color`
+/// "--accent": "red"                 // custom properties are written as strings
+pub struct StyleProperty {
+    pub(crate) name: String,
+    pub(crate) span: proc_macro2::Span,
+    pub(crate) value: Expr,
+}
+
+impl Parse for StyleProperty {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let span = input.span();
+        let name = if input.peek(LitStr) {
+            input.parse::<LitStr>()?.value()
+        } else {
+            parse_hyphenated_ident(input)?
+        };
+        input.parse::<Token![:]>()?;
+        let value = input.parse()?;
+        Ok(StyleProperty { name, span, value })
+    }
+}
+
 pub enum AttributeValue {
     Static(LitStr),
     Dynamic(Expr),
+    List(Vec<ClassListItem>),
+}
+
+impl AttributeValue {
+    // Expands a class list into a block joining the enabled entries with spaces
+    fn list_tokens(items: &[ClassListItem]) -> proc_macro2::TokenStream {
+        let joined = Ident::new("classes", proc_macro2::Span::mixed_site());
+        let pushes = items.iter().map(|ClassListItem { class, condition }| {
+            let condition = condition
+                .as_ref()
+                .map_or_else(|| quote::quote! { true }, |c| quote::quote! { #c });
+            quote::quote! {
+                if #condition {
+                    if !#joined.is_empty() {
+                        #joined.push(' ');
+                    }
+                    #joined.push_str(::core::convert::AsRef::<str>::as_ref(&(#class)));
+                }
+            }
+        });
+        quote::quote! {
+            {
+                let mut #joined = ::std::string::String::new();
+                #(#pushes)*
+                #joined
+            }
+        }
+    }
 }
 
 impl Parse for AttributeValue {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        if input.peek(LitStr) {
+        if input.peek(syn::token::Bracket) {
+            let content;
+            syn::bracketed!(content in input);
+            let items = content.parse_terminated(ClassListItem::parse, Token![,])?;
+            Ok(AttributeValue::List(items.into_iter().collect()))
+        } else if input.peek(LitStr) {
             let lit: LitStr = input.parse()?;
             Ok(AttributeValue::Static(lit))
         } else {
@@ -85,14 +188,20 @@ impl Parse for AttributeValue {
 /// Examples:
 /// .title = "Hello World"          // `KeyValue` with static key and static value
 /// .data-id = `some_variable`        // `KeyValue` with static key and dynamic value
+/// .data:id = `some_variable`        // same as `.data-id`, also `.aria:label` and `.hx:get`
 /// .*`dynamic_key` = "Static Value"  // `KeyValue` with dynamic key and static value
 /// .*`dynamic_key` = `dynamic_value`   // `KeyValue` with dynamic key and dynamic value
+/// .class = ["btn", ("active", `is_active`)] // `KeyValue` with a space-joined class list
+/// .title =? `maybe_title`           // `Optional`, only set when the value is `Some`
+/// .disabled?(`is_disabled`)         // `Toggle`, boolean attribute present when true
 /// .*(expr) = expr                 // `KeyValue` with dynamic key and dynamic value
 /// #id                             // `KeyOnly` with static key (id shorthand)
 /// #*(expr)                        // `KeyOnly` with dynamic key (id shorthand)
 /// .disabled                       // `KeyOnly` with static key (class shorthand)
 /// .*`dynamic_key`                   // `KeyOnly` with dynamic key (class shorthand)
-/// ..*attrs                        // `KeySpread` with dynamic key
+/// ..items                        // `Spread` of attributes, or of children when they are nodes
+/// on:click = `handler`              // `Handler`, requires the `events` feature of `rs-tml`
+/// style: { padding: "1rem" }      // `Style`, a `style` attribute built with `rs_tml::style::Style`
 pub enum Attribute {
     KeyValue {
         key: AttributeKey,
@@ -101,24 +210,100 @@ pub enum Attribute {
     Key {
         key: AttributeKey,
     },
+    Optional {
+        key: AttributeKey,
+        value: Expr,
+    },
+    Toggle {
+        key: AttributeKey,
+        condition: Expr,
+    },
     Spread {
         key: Expr,
     },
+    Handler {
+        event: String,
+        handler: Expr,
+    },
+    Style {
+        properties: Vec<StyleProperty>,
+    },
 }
 
 impl Attribute {
-    pub fn is_spread(&self) -> bool {
-        matches!(self, Attribute::Spread { .. })
+    // Key of the attribute if it is known at compile time, class shorthands are excluded
+    pub(crate) fn static_key(&self) -> Option<&str> {
+        match self {
+            Attribute::KeyValue { key, .. }
+            | Attribute::Optional { key, .. }
+            | Attribute::Toggle { key, .. } => match key {
+                AttributeKey::Static(name) => Some(name),
+                _ => None,
+            },
+            Attribute::Key {
+                key: AttributeKey::StaticId(_),
+            } => Some("id"),
+            Attribute::Style { .. } => Some("style"),
+            _ => None,
+        }
+    }
+
+    // Skips doc comments placed before an attribute, which reach the macro as `#[doc]`
+    pub fn skip_doc_comments(input: syn::parse::ParseStream) -> syn::Result<()> {
+        if !(input.peek(Token![#]) && (input.peek2(syn::token::Bracket) || input.peek2(Token![!])))
+        {
+            return Ok(());
+        }
+        let fork = input.fork();
+        let mut attrs = fork.call(syn::Attribute::parse_inner)?;
+        attrs.extend(fork.call(syn::Attribute::parse_outer)?);
+        if !attrs.is_empty() && attrs.iter().all(|a| a.path().is_ident("doc")) && Self::peek(&fork)
+        {
+            input.advance_to(&fork);
+        }
+        Ok(())
+    }
+
+    // Checks whether the input starts with an attribute rather than a child node
+    pub fn peek(input: syn::parse::ParseStream) -> bool {
+        input.peek(Token![.])
+            || (input.peek(Token![#])
+                && !input.peek2(syn::token::Bracket)
+                && !input.peek2(Token![!]))
+            || (input.peek(Ident)
+                && input.peek2(Token![:])
+                && !input.peek2(Token![::])
+                && input
+                    .fork()
+                    .parse::<Ident>()
+                    .is_ok_and(|name| name == "on" || name == "style"))
+    }
+
+    pub fn is_iterator_expand(&self) -> bool {
+        matches!(self, Attribute::Optional { .. } | Attribute::Toggle { .. })
     }
 
     pub fn to_child_tokens(&self) -> proc_macro2::TokenStream {
-        if self.is_spread() {
+        if let Attribute::Handler { event, handler } = self {
+            return quote::quote! {
+                .with_handler(::rs_tml::event::EventHandler::new(#event, #handler))
+            };
+        }
+        // Before the first child a spread may hold attributes or children
+        if let Attribute::Spread { key } = self {
+            return quote::quote! {
+                .with_spread({#key})
+            };
+        }
+        // Keys set more than once are merged, see `validate_attributes`
+        let policy = quote::quote! { ::rs_tml::attribute::MergePolicy::MARKUP };
+        if self.is_iterator_expand() {
             quote::quote! {
-                .with_attributes(#self)
+                .with_merged_attributes(#self, #policy)
             }
         } else {
             quote::quote! {
-                .with_attribute(#self)
+                .with_merged_attributes(::core::iter::once(#self), #policy)
             }
         }
     }
@@ -134,31 +319,78 @@ impl Parse for Attribute {
             return Ok(Attribute::Spread { key });
         }
 
+        // Event handlers, e.g. `on:click = handler`
+        if input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let on: Ident = input.fork().parse()?;
+            if on == "on" {
+                input.parse::<Ident>()?;
+                input.parse::<Token![:]>()?;
+                let event = parse_hyphenated_ident(input)?;
+                input.parse::<Token![=]>()?;
+                let handler = Expr::parse_without_eager_brace(input)?;
+                return Ok(Attribute::Handler { event, handler });
+            }
+            // Inline styles, e.g. `style: { padding: "1rem" }`
+            if on == "style" {
+                input.parse::<Ident>()?;
+                input.parse::<Token![:]>()?;
+                let content;
+                syn::braced!(content in input);
+                let properties = content.parse_terminated(StyleProperty::parse, Token![,])?;
+                return Ok(Attribute::Style {
+                    properties: properties.into_iter().collect(),
+                });
+            }
+        }
+
         let key = input.parse()?;
+        let is_id = matches!(key, AttributeKey::StaticId(_) | AttributeKey::DynamicId(_));
+        if input.peek(Token![?]) {
+            if is_id {
+                return Err(input.error("ID shorthand cannot be used as a boolean attribute"));
+            }
+            input.parse::<Token![?]>()?;
+            let content;
+            syn::parenthesized!(content in input);
+            let condition = content.parse()?;
+            return Ok(Attribute::Toggle { key, condition });
+        }
         if !input.peek(Token![=]) {
             return Ok(Attribute::Key { key });
         }
-        if matches!(key, AttributeKey::StaticId(_) | AttributeKey::DynamicId(_)) {
+        if is_id {
             return Err(input.error("ID shorthand cannot be used with key-value attributes"));
         }
         input.parse::<Token![=]>()?;
+        if input.peek(Token![?]) {
+            input.parse::<Token![?]>()?;
+            let value = Expr::parse_without_eager_brace(input)?;
+            return Ok(Attribute::Optional { key, value });
+        }
         let value = input.parse()?;
         Ok(Attribute::KeyValue { key, value })
     }
 }
 
+impl AttributeKey {
+    fn to_key_tokens(&self) -> proc_macro2::TokenStream {
+        match self {
+            AttributeKey::Static(name) => quote::quote! { #name },
+            AttributeKey::Dynamic(expr) => quote::quote! { #expr },
+            _ => unreachable!("ID shorthand cannot be used with key-value attributes"),
+        }
+    }
+}
+
 impl quote::ToTokens for Attribute {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         match self {
             Attribute::KeyValue { key, value } => {
-                let key_tokens = match key {
-                    AttributeKey::Static(name) => quote::quote! { #name },
-                    AttributeKey::Dynamic(expr) => quote::quote! { #expr },
-                    _ => unreachable!("ID shorthand cannot be used with key-value attributes"),
-                };
+                let key_tokens = key.to_key_tokens();
                 let value_tokens = match value {
                     AttributeValue::Static(lit) => quote::quote! { #lit },
                     AttributeValue::Dynamic(expr) => quote::quote! { #expr },
+                    AttributeValue::List(items) => AttributeValue::list_tokens(items),
                 };
                 tokens.extend(quote::quote! {
                     ::rs_tml::attribute::Attribute::new(#key_tokens, #value_tokens)
@@ -186,11 +418,38 @@ impl quote::ToTokens for Attribute {
                     });
                 }
             },
-            Attribute::Spread { key } => {
+            Attribute::Optional { key, value } => {
+                let key_tokens = key.to_key_tokens();
+                // Hygienic, so a dynamic key can use a variable of the same name
+                let value_ident = syn::Ident::new("value", proc_macro2::Span::mixed_site());
                 tokens.extend(quote::quote! {
-                    {#key}.into_iter().map(Into::into)
+                    (#value).map(|#value_ident| ::rs_tml::attribute::Attribute::new(#key_tokens, #value_ident))
                 });
             }
+            Attribute::Toggle { key, condition } => {
+                let key_tokens = key.to_key_tokens();
+                tokens.extend(quote::quote! {
+                    (#condition).then(|| ::rs_tml::attribute::Attribute::boolean(#key_tokens))
+                });
+            }
+            Attribute::Spread { .. } => {
+                unreachable!("Spreads may hold children, see `to_child_tokens`")
+            }
+            Attribute::Style { properties } => {
+                let properties = properties.iter().map(|StyleProperty { name, value, .. }| {
+                    quote::quote! {
+                        .with_property(#name, ::std::string::ToString::to_string(&(#value)))
+                    }
+                });
+                tokens.extend(quote::quote! {
+                    ::rs_tml::attribute::Attribute::from(
+                        ::rs_tml::style::Style::new()#(#properties)*
+                    )
+                });
+            }
+            Attribute::Handler { .. } => {
+                unreachable!("Event handlers are not attributes, see `to_child_tokens`")
+            }
         }
     }
 }
@@ -0,0 +1,80 @@
+use syn::{Data, DeriveInput, Fields, parse::Parser};
+
+use crate::Node;
+
+// Parses the template of a `#[rstml(...)]` attribute, which must have a single root node
+fn parse_template(attr: &syn::Attribute) -> syn::Result<Node> {
+    let list = attr.meta.require_list()?;
+    let parser = |input: syn::parse::ParseStream| {
+        let node: Node = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("A view template must have exactly one root node"));
+        }
+        Ok(node)
+    };
+    let node = parser.parse2(list.tokens.clone())?;
+    if node.is_iterator_expand() {
+        return Err(syn::Error::new_spanned(
+            &list.tokens,
+            "A view template root cannot expand into multiple nodes",
+        ));
+    }
+    Ok(node)
+}
+
+/// Derives `From<T> for Node`
+///
+/// With a `#[rstml(...)]` template, the struct's fields are destructured into scope
+/// so the template can refer to them by name. Without one, the struct's
+/// `fn view(&self)` is called, whose result must implement `Into<Node<'static>>`.
+pub fn derive_into_node(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    // The produced node may live as long as the caller needs
+    let lifetime = syn::Lifetime::new("'__rstml", proc_macro2::Span::mixed_site());
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, syn::parse_quote!(#lifetime));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let value = syn::Ident::new("value", proc_macro2::Span::mixed_site());
+
+    let template = input.attrs.iter().find(|a| a.path().is_ident("rstml"));
+    let body = if let Some(attr) = template {
+        let node = parse_template(attr)?;
+        let Data::Struct(data) = &input.data else {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`#[rstml(...)]` templates are only supported on structs",
+            ));
+        };
+        let bindings = match &data.fields {
+            Fields::Named(fields) => {
+                let names = fields.named.iter().map(|f| &f.ident);
+                quote::quote! { let #name { #(#names),* } = #value; }
+            }
+            Fields::Unit => quote::quote! {},
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`#[rstml(...)]` templates require named fields",
+                ));
+            }
+        };
+        quote::quote! {
+            #bindings
+            ::rs_tml::node::Node::from(#node)
+        }
+    } else {
+        quote::quote! {
+            let node: ::rs_tml::node::Node<'static> = ::core::convert::Into::into(#name::view(&#value));
+            node
+        }
+    };
+    Ok(quote::quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for ::rs_tml::node::Node<#lifetime> #where_clause {
+            #[allow(unused_variables)]
+            fn from(#value: #name #ty_generics) -> Self {
+                #body
+            }
+        }
+    })
+}
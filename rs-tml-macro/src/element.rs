@@ -1,26 +1,43 @@
 use quote::ToTokens;
-use syn::Ident;
+use syn::{Expr, Ident};
 
 use crate::{Attribute, Node};
 
+pub enum ElementName {
+    Static(Ident),
+    // Tag name computed at runtime, e.g. `*(heading) { ... }`
+    Dynamic(Box<Expr>),
+}
+
 pub struct Element {
-    name: Ident,
-    attributes: Vec<Attribute>,
-    children: Vec<Node>,
+    pub(crate) name: ElementName,
+    pub(crate) attributes: Vec<Attribute>,
+    pub(crate) children: Vec<Node>,
 }
 
-impl syn::parse::Parse for Element {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let name: Ident = input.parse()?;
+impl ElementName {
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            ElementName::Static(ident) => ident.span(),
+            ElementName::Dynamic(expr) => syn::spanned::Spanned::span(expr),
+        }
+    }
+}
+
+impl Element {
+    // Parses the braced body of an element whose name has already been parsed
+    pub fn parse_body(name: ElementName, input: syn::parse::ParseStream) -> syn::Result<Self> {
         let content;
         syn::braced!(content in input);
         let mut attributes = Vec::new();
-        while let Ok(attr) = content.parse() {
-            attributes.push(attr);
+        Attribute::skip_doc_comments(&content)?;
+        while Attribute::peek(&content) {
+            attributes.push(content.parse()?);
+            Attribute::skip_doc_comments(&content)?;
         }
         let mut children = Vec::new();
-        while let Ok(child) = content.parse() {
-            children.push(child);
+        while !content.is_empty() {
+            children.push(content.parse()?);
         }
         Ok(Element {
             name,
@@ -30,13 +47,23 @@ impl syn::parse::Parse for Element {
     }
 }
 
+impl syn::parse::Parse for Element {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        Element::parse_body(ElementName::Static(name), input)
+    }
+}
+
 impl ToTokens for Element {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        let name = &self.name;
+        let name = match &self.name {
+            ElementName::Static(name) => quote::quote! { ::core::stringify!(#name) },
+            ElementName::Dynamic(expr) => quote::quote! { #expr },
+        };
         let attrs = self.attributes.iter().map(Attribute::to_child_tokens);
         let children = self.children.iter().map(Node::to_child_tokens);
         tokens.extend(quote::quote! {
-            ::rs_tml::element::Element::new(stringify!(#name))
+            ::rs_tml::element::Element::new(#name)
             #(#attrs)*
             #(#children)*
         });
@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use quote::quote;
+use rs_tml::{environment::Environment, templates::RegistryError};
+use syn::LitStr;
+
+// The directory the templates are named relative to, the pattern up to its first
+// component with a wildcard, e.g. `templates` for `templates/**/*.rstml`
+fn base_dir(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|part| !part.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+        .collect()
+}
+
+// The template name of a file, its path below the base directory with `/` separators
+fn template_name(path: &Path, base: &Path) -> Option<String> {
+    let parts: Option<Vec<&str>> = path
+        .strip_prefix(base)
+        .ok()?
+        .iter()
+        .map(|part| part.to_str())
+        .collect();
+    Some(parts?.join("/"))
+}
+
+/// Embeds the template files matching a glob pattern, relative to the crate root
+///
+/// Every file is parsed and every template resolved at compile time, so a
+/// template that does not parse or includes a missing one fails the build. The
+/// expansion builds an `Environment` holding the sources.
+pub fn embed_templates(pattern: &LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let error = |message: String| syn::Error::new(pattern.span(), message);
+    let root = PathBuf::from(
+        std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|_| error("`embed_templates!` must be expanded by Cargo".into()))?,
+    );
+    let pattern_value = pattern.value();
+    let paths = glob::glob(&root.join(&pattern_value).to_string_lossy())
+        .map_err(|err| error(format!("Invalid pattern: {err}")))?;
+    let base = root.join(base_dir(&pattern_value));
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path.map_err(|err| error(format!("Cannot read {err}")))?;
+        if !path.is_file() {
+            continue;
+        }
+        let name = template_name(&path, &base)
+            .ok_or_else(|| error(format!("Invalid template path {}", path.display())))?;
+        files.push((name, path));
+    }
+    if files.is_empty() {
+        return Err(error(format!("No template files match `{pattern_value}`")));
+    }
+    files.sort();
+
+    let mut environment = Environment::new();
+    for (name, path) in &files {
+        let source = std::fs::read_to_string(path)
+            .map_err(|err| error(format!("Cannot read {}: {err}", path.display())))?;
+        let shown = path.strip_prefix(&root).unwrap_or(path).display();
+        environment
+            .add_template(name.as_str(), &source)
+            .map_err(|err| match err {
+                RegistryError::Parse { message, .. } => error(format!("{shown}:{message}")),
+                err => error(format!("{shown}: {err}")),
+            })?;
+    }
+    for (name, path) in &files {
+        if let Err(err) = environment.get_template(name) {
+            let shown = path.strip_prefix(&root).unwrap_or(path).display();
+            return Err(error(format!("{shown}: {err}")));
+        }
+    }
+
+    let names = files.iter().map(|(name, _)| name);
+    let paths = files.iter().map(|(_, path)| path.to_string_lossy());
+    Ok(quote! {
+        {
+            let mut environment = ::rs_tml::environment::Environment::new();
+            #(
+                environment
+                    .add_template(#names, ::core::include_str!(#paths))
+                    .expect("embedded templates are checked at compile time");
+            )*
+            environment
+        }
+    })
+}
@@ -1,23 +1,44 @@
-use syn::{Expr, Pat, parse::Parse};
+use syn::{Expr, Ident, Pat, parse::Parse};
 
-use crate::RSTMLBlock;
+use crate::{Node, RSTMLBlock};
 
 pub struct RSTMLFor {
+    pub(crate) for_token: syn::Token![for],
     pattern: Box<Pat>,
     iterable: Box<Expr>,
-    body: RSTMLBlock,
+    key: Option<Box<Expr>>,
+    pub(crate) body: RSTMLBlock,
 }
 
 impl Parse for RSTMLFor {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        input.parse::<syn::Token![for]>()?;
+        let for_token = input.parse::<syn::Token![for]>()?;
         let pattern = Pat::parse_single(input)?;
         input.parse::<syn::Token![in]>()?;
         let iterable = Expr::parse_without_eager_brace(input)?;
+        // Optional `key expr` clause, e.g. `for item in items key item.id { ... }`
+        let key = if input.peek(Ident) && input.fork().parse::<Ident>()? == "key" {
+            input.parse::<Ident>()?;
+            Some(Box::new(Expr::parse_without_eager_brace(input)?))
+        } else {
+            None
+        };
         let body: RSTMLBlock = input.parse()?;
+        // Only elements have attributes to hold the key, see `Node::with_key`, and
+        // expressions may yield any node, so the body must be a single element
+        if let Some(key) = &key
+            && !matches!(body.children.as_slice(), [Node::Element(_)])
+        {
+            return Err(syn::Error::new_spanned(
+                key,
+                "Only elements can be keyed, wrap the loop body in an element",
+            ));
+        }
         Ok(RSTMLFor {
+            for_token,
             pattern: Box::new(pattern),
             iterable: Box::new(iterable),
+            key,
             body,
         })
     }
@@ -28,9 +49,20 @@ impl quote::ToTokens for RSTMLFor {
         let pattern = &self.pattern;
         let iterable = &self.iterable;
         let body = &self.body;
+        let Some(key) = &self.key else {
+            tokens.extend(quote::quote! {
+              (#iterable).into_iter().map(|#pattern| {
+                  #body
+              })
+            });
+            return;
+        };
+        // The key is evaluated before the body, which may move out of the pattern bindings
+        let key_ident = Ident::new("key", proc_macro2::Span::mixed_site());
         tokens.extend(quote::quote! {
           (#iterable).into_iter().map(|#pattern| {
-              #body
+              let #key_ident = ::std::string::ToString::to_string(&(#key));
+              ::rs_tml::node::Node::from(#body).with_key(#key_ident)
           })
         });
     }
@@ -0,0 +1,110 @@
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+// The `#[form(...)]` settings of a struct field
+#[derive(Default)]
+struct FieldOptions {
+    label: Option<LitStr>,
+    kind: Option<LitStr>,
+    placeholder: Option<LitStr>,
+    skip: bool,
+}
+
+fn field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("form")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else if meta.path.is_ident("label") {
+                options.label = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("kind") {
+                options.kind = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("placeholder") {
+                options.placeholder = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error(
+                    "Expected `label = \"...\"`, `kind = \"...\"`, `placeholder = \"...\"` or `skip`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+// The label of a field without one, e.g. `First name` for `first_name`
+pub(crate) fn default_label(name: &str) -> String {
+    let words = name.trim_start_matches("r#").replace('_', " ");
+    let mut chars = words.trim().chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Derives `FormFields`, with a form field per struct field
+///
+/// The field's type decides the kind of control through `FormValue`, unless
+/// `#[form(kind = "...")]` names an input type or `textarea`.
+pub fn derive_form(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`#[derive(Form)]` requires named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`#[derive(Form)]` is only supported on structs",
+            ));
+        }
+    };
+
+    let mut built = Vec::new();
+    for field in fields {
+        let options = field_options(field)?;
+        if options.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let ty = &field.ty;
+        let field_name = ident.to_string().trim_start_matches("r#").to_string();
+        let label = options
+            .label
+            .map_or_else(|| default_label(&field_name), |label| label.value());
+        let control = match options.kind {
+            Some(kind) if kind.value() == "textarea" => {
+                quote! { ::rs_tml::forms::Field::textarea(#field_name) }
+            }
+            Some(kind) => quote! { ::rs_tml::forms::Field::input(#field_name, #kind) },
+            None => quote! { <#ty as ::rs_tml::forms::FormValue>::field(#field_name) },
+        };
+        let placeholder = options
+            .placeholder
+            .map(|placeholder| quote! { .with_placeholder(#placeholder) });
+        built.push(quote! {
+            ::rs_tml::forms::FormValue::fill(
+                &self.#ident,
+                #control
+                    .with_label(#label)
+                    .with_required(<#ty as ::rs_tml::forms::FormValue>::REQUIRED)
+                    #placeholder,
+            )
+        });
+    }
+    Ok(quote! {
+        impl #impl_generics ::rs_tml::forms::FormFields for #name #ty_generics #where_clause {
+            fn fields(&self) -> ::std::vec::Vec<::rs_tml::forms::Field<'static>> {
+                ::std::vec![#(#built),*]
+            }
+        }
+    })
+}
@@ -1,77 +1,32 @@
 use crate::RSTMLBlock;
 use quote::ToTokens;
-use syn::{Expr, Pat, Token, parse::Parse};
+use syn::{Expr, Token, parse::Parse};
 
-pub struct LetGaurd {
-    let_token: Token![let],
-    pattern: Box<Pat>,
-    equal_token: Token![=],
-    expr: Box<Expr>,
-}
-
-impl Parse for LetGaurd {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let let_token: Token![let] = input.parse()?;
-        let pattern: Pat = Pat::parse_single(input)?;
-        let equal_token: Token![=] = input.parse()?;
-        let expr: Expr = Expr::parse_without_eager_brace(input)?;
-        Ok(LetGaurd {
-            let_token,
-            pattern: Box::new(pattern),
-            equal_token,
-            expr: Box::new(expr),
-        })
-    }
-}
-
-impl ToTokens for LetGaurd {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        let let_token = &self.let_token;
-        let pattern = &self.pattern;
-        let equal_token = &self.equal_token;
-        let expr = &self.expr;
-        tokens.extend(quote::quote! {
-            #let_token #pattern #equal_token #expr
-        });
-    }
-}
-
-pub enum IfCond {
-    Condition(Box<Expr>),
-    Gaurd(LetGaurd),
-}
+/// Condition of an `if` block
+///
+/// Any boolean expression, `let` guard or `&&`-chain of both is accepted,
+/// e.g. `let Some(x) = a && x > 3`, following stable let-chains.
+pub struct IfCond(Box<Expr>);
 
 impl Parse for IfCond {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        if input.peek(syn::Token![let]) {
-            let expr_let = input.parse()?;
-            Ok(IfCond::Gaurd(expr_let))
-        } else {
-            let expr: Expr = Expr::parse_without_eager_brace(input)?;
-            Ok(IfCond::Condition(Box::new(expr)))
-        }
+        let expr = Expr::parse_without_eager_brace(input)?;
+        Ok(IfCond(Box::new(expr)))
     }
 }
 
 impl ToTokens for IfCond {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        match self {
-            IfCond::Condition(expr) => {
-                expr.to_tokens(tokens);
-            }
-            IfCond::Gaurd(expr_let) => {
-                expr_let.to_tokens(tokens);
-            }
-        }
+        self.0.to_tokens(tokens);
     }
 }
 
 pub struct RSTMLIf {
-    if_token: Token![if],
+    pub(crate) if_token: Token![if],
     condition: IfCond,
-    then: RSTMLBlock,
-    else_if: Option<Box<RSTMLIf>>,
-    else_block: Option<(Token![else], RSTMLBlock)>,
+    pub(crate) then: RSTMLBlock,
+    pub(crate) else_if: Option<Box<RSTMLIf>>,
+    pub(crate) else_block: Option<(Token![else], RSTMLBlock)>,
 }
 
 impl RSTMLIf {
@@ -89,7 +44,7 @@ impl RSTMLIf {
         // conditionally add a child without requiring an else branch from the user.
         out.extend(quote::quote! {
             #if_token #condition {
-                Some(Node::from(#then))
+                ::core::option::Option::Some(::rs_tml::node::Node::from(#then))
             }
         });
 
@@ -100,13 +55,13 @@ impl RSTMLIf {
             // If an explicit else block exists, return Some(node) for it.
             out.extend(quote::quote! {
                  #else_token {
-                    Some(Node::from(#else_blk))
+                    ::core::option::Option::Some(::rs_tml::node::Node::from(#else_blk))
                 }
             });
         } else {
             // No else provided by the user; default to None so the Option compiles.
             out.extend(quote::quote! {
-                 else { None }
+                 else { ::core::option::Option::None }
             });
         }
     }
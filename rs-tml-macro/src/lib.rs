@@ -1,38 +1,102 @@
 use proc_macro::TokenStream;
 use quote::ToTokens;
-use syn::{Expr, Ident, LitStr, Token, parse::Parse, token::Paren};
+use syn::{
+    Expr, Ident, LitStr, Token,
+    parse::Parse,
+    token::{Brace, Paren},
+};
 
 mod attribute;
+mod derive;
 use attribute::Attribute;
 mod element;
-use element::Element;
+mod embed;
+mod form;
+use element::{Element, ElementName};
 
 use crate::{forblock::RSTMLFor, ifblock::RSTMLIf, matchblock::RSTMLMatch};
 mod forblock;
 mod ifblock;
 mod matchblock;
+mod static_template;
+mod table;
+mod validate;
 
 struct Document {
+    // Existing `Block` or `Element` to append to, e.g. `in my_block => { ... }`
+    target: Option<Box<Expr>>,
     children: Vec<Node>,
 }
 
 impl Parse for Document {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![in]) {
+            input.parse::<Token![in]>()?;
+            let target = Expr::parse_without_eager_brace(input)?;
+            input.parse::<Token![=>]>()?;
+            let block: RSTMLBlock = input.parse()?;
+            if !input.is_empty() {
+                return Err(input.error("Unexpected content after the appended block"));
+            }
+            return Ok(Document {
+                target: Some(Box::new(target)),
+                children: block.children,
+            });
+        }
         let mut children = Vec::new();
         while !input.is_empty() {
             let node: Node = input.parse()?;
             children.push(node);
         }
-        Ok(Document { children })
+        Ok(Document {
+            target: None,
+            children,
+        })
     }
 }
 
 impl quote::ToTokens for Document {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        tokens.extend(quote::quote! {
-            ::rs_tml::block::Block::new()
-        });
-        tokens.extend(self.children.iter().map(Node::to_child_tokens));
+        let body = match &self.target {
+            None => {
+                let children = self.children.iter().map(Node::to_child_tokens);
+                quote::quote! {
+                    ::rs_tml::block::Block::new()
+                    #(#children)*
+                }
+            }
+            Some(target) => {
+                // Appends in place through the `add_*` methods shared by `Block` and `Element`,
+                // the target is a place expression so auto-ref covers both owned and `&mut` bindings
+                let additions = self.children.iter().map(|child| {
+                    if child.is_iterator_expand() {
+                        quote::quote! { (#target).add_children(#child); }
+                    } else {
+                        quote::quote! { (#target).add_child(#child); }
+                    }
+                });
+                quote::quote! {
+                    {
+                        #(#additions)*
+                    }
+                }
+            }
+        };
+        tokens.extend(with_diagnostics(&self.children, body));
+    }
+}
+
+// Prefixes an expansion with the validation warnings for its markup
+fn with_diagnostics(children: &[Node], body: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let checks = validate::diagnostics(&validate::validate(children));
+    if checks.is_empty() {
+        return body;
+    }
+    quote::quote! {
+        {
+            #checks
+            #body
+        }
     }
 }
 
@@ -115,7 +179,7 @@ impl quote::ToTokens for TextNode {
             }
             TextNode::Dynamic(lit) => {
                 tokens.extend(quote::quote! {
-                    ::rs_tml::node::Node::text(format!(#lit))
+                    ::rs_tml::node::Node::text(::std::format!(#lit))
                 });
             }
         }
@@ -130,6 +194,14 @@ enum Node {
     Match(RSTMLMatch),
     Expand(Box<Expr>),
     ExpandMany(Box<Expr>),
+    // `{ expr }`, a node or an iterator of nodes
+    Inline(Box<Expr>),
+    Raw(Box<Expr>),
+    Comment(Box<Expr>),
+    // `t!("key", name = value)`, translated at render time
+    Translate(Box<Expr>, Vec<(Ident, Expr)>),
+    // Node compiled only when all of its `#[cfg(...)]` predicates hold
+    Cfg(Vec<proc_macro2::TokenStream>, Box<Node>),
 }
 
 impl Node {
@@ -157,11 +229,111 @@ impl Node {
         let content;
         syn::parenthesized!(content in input);
         let expr = content.parse()?;
+        // `*(tag) { ... }` is an element with a dynamic tag name
+        if input.peek(Brace) {
+            let element = Element::parse_body(ElementName::Dynamic(expr), input)?;
+            return Ok(Node::Element(element));
+        }
         Ok(Node::Expand(expr))
     }
 
+    // Parses a children spread, e.g. `..children`
+    //
+    // Inside an element, a spread before the first child is parsed as an
+    // attribute spread, which also accepts children, see `Element::add_spread`.
+    fn parse_spread(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![..]>()?;
+        let expr = Expr::parse_without_eager_brace(input)?;
+        Ok(Node::ExpandMany(Box::new(expr)))
+    }
+
+    // Parses the argument of a built-in macro-like node, e.g. `raw!("<b>hi</b>")`
+    fn parse_builtin(input: syn::parse::ParseStream) -> syn::Result<Box<Expr>> {
+        input.parse::<Ident>()?;
+        input.parse::<Token![!]>()?;
+        let content;
+        syn::parenthesized!(content in input);
+        content.parse()
+    }
+
+    // Parses a translation, e.g. `t!("greeting", name = user.name)`
+    fn parse_translate(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        input.parse::<Ident>()?;
+        input.parse::<Token![!]>()?;
+        let content;
+        syn::parenthesized!(content in input);
+        let key = content.parse()?;
+        let mut args = Vec::new();
+        while !content.is_empty() {
+            content.parse::<Token![,]>()?;
+            if content.is_empty() {
+                break;
+            }
+            let name = content.parse()?;
+            content.parse::<Token![=]>()?;
+            args.push((name, content.parse()?));
+        }
+        Ok(Node::Translate(key, args))
+    }
+
+    fn is_builtin(input: syn::parse::ParseStream, name: &str) -> bool {
+        input.peek2(Token![!]) && input.fork().parse::<Ident>().is_ok_and(|i| i == name)
+    }
+
+    // Parses an inline expression child, e.g. `{ some_expr }`
+    fn parse_inline_expr(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::braced!(content in input);
+        let expr = content.parse()?;
+        Ok(Node::Inline(expr))
+    }
+
+    // Parses `#[cfg(...)]` attributes followed by the node they gate
+    //
+    // Doc comments (`///` and `//!`) reach the macro as `#[doc]` attributes
+    // and are discarded, so templates pasted from `.rstml` files still compile.
+    fn parse_cfg(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut predicates = Vec::new();
+        let mut attrs = input.call(syn::Attribute::parse_inner)?;
+        attrs.extend(input.call(syn::Attribute::parse_outer)?);
+        for attr in attrs {
+            if attr.path().is_ident("doc") {
+                continue;
+            }
+            if !attr.path().is_ident("cfg") {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "Only `#[cfg(...)]` attributes are supported on RSTML nodes",
+                ));
+            }
+            predicates.push(attr.meta.require_list()?.tokens.clone());
+        }
+        if input.is_empty() {
+            if !predicates.is_empty() {
+                return Err(input.error("Expected a node after `#[cfg(...)]`"));
+            }
+            // Trailing doc comments document nothing
+            return Ok(Node::ExpandMany(Box::new(syn::parse_quote! {
+                ::core::iter::empty::<::rs_tml::node::Node>()
+            })));
+        }
+        let node = input.parse()?;
+        if predicates.is_empty() {
+            return Ok(node);
+        }
+        Ok(Node::Cfg(predicates, Box::new(node)))
+    }
+
     fn is_iterator_expand(&self) -> bool {
-        matches!(self, Node::ExpandMany(_) | Node::For(_) | Node::If(_))
+        matches!(
+            self,
+            Node::ExpandMany(_)
+                | Node::Inline(_)
+                | Node::For(_)
+                | Node::If(_)
+                | Node::Match(_)
+                | Node::Cfg(..)
+        )
     }
 
     fn to_child_tokens(&self) -> proc_macro2::TokenStream {
@@ -178,23 +350,52 @@ impl Node {
 }
 
 impl Parse for Node {
+    // Dispatches on the leading tokens so errors inside a node keep their original span
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        if let Ok(if_block) = input.parse::<RSTMLIf>() {
-            return Ok(Node::If(if_block));
+        if input.peek(Token![#]) && (input.peek2(syn::token::Bracket) || input.peek2(Token![!])) {
+            return Node::parse_cfg(input);
         }
-        if let Ok(for_block) = input.parse::<RSTMLFor>() {
-            return Ok(Node::For(for_block));
+        if input.peek(Token![if]) {
+            return input.parse().map(Node::If);
         }
-        if let Ok(match_block) = input.parse::<RSTMLMatch>() {
-            return Ok(Node::Match(match_block));
+        if input.peek(Token![for]) {
+            return input.parse().map(Node::For);
         }
-        if let Ok(text) = input.parse() {
-            return Ok(Node::Text(text));
+        if input.peek(Token![match]) {
+            return input.parse().map(Node::Match);
         }
-        if let Ok(element) = input.parse::<Element>() {
-            return Ok(Node::Element(element));
+        if input.peek(LitStr) {
+            return input.parse().map(Node::Text);
+        }
+        if input.peek(Brace) {
+            return Node::parse_inline_expr(input);
+        }
+        if input.peek(Token![..]) {
+            return Node::parse_spread(input);
+        }
+        if input.peek(Token![*]) {
+            return Node::parse_expand(input);
+        }
+        if Node::is_builtin(input, "raw") {
+            return Node::parse_builtin(input).map(Node::Raw);
+        }
+        if Node::is_builtin(input, "comment") {
+            return Node::parse_builtin(input).map(Node::Comment);
         }
-        Node::parse_expand(input)
+        if Node::is_builtin(input, "t") {
+            return Node::parse_translate(input);
+        }
+        if input.peek(Ident) {
+            return input.parse().map(Node::Element);
+        }
+        if Attribute::peek(input) {
+            return Err(input.error(
+                "Attributes must be declared inside an element, before any of its children",
+            ));
+        }
+        Err(input.error(
+            "Expected a valid RSTML node: text, element, `if`, `for`, `match`, `{ expr }` or `*expr`",
+        ))
     }
 }
 
@@ -222,13 +423,133 @@ impl quote::ToTokens for Node {
             Node::ExpandMany(expr) => tokens.extend(quote::quote! {
                 (#expr).into_iter().map(::rs_tml::node::Node::from)
             }),
+            Node::Inline(expr) => tokens.extend(quote::quote! {
+                {
+                    use ::rs_tml::node::{InlineNode as _, InlineNodes as _};
+                    (&::rs_tml::node::Inline::new(#expr)).expand_nodes()
+                }
+            }),
+            Node::Raw(expr) => tokens.extend(quote::quote! {
+                ::rs_tml::node::Node::raw(#expr)
+            }),
+            Node::Comment(expr) => tokens.extend(quote::quote! {
+                ::rs_tml::node::Node::comment(#expr)
+            }),
+            Node::Translate(key, args) => {
+                let args = args.iter().map(|(name, value)| {
+                    let name = name.to_string();
+                    quote::quote! {
+                        .with_arg(#name, ::std::string::ToString::to_string(&(#value)))
+                    }
+                });
+                tokens.extend(quote::quote! {
+                    ::rs_tml::node::Node::translation(
+                        ::rs_tml::i18n::Translation::new(#key)#(#args)*
+                    )
+                });
+            }
+            Node::Cfg(predicates, node) => {
+                // Expands to an iterator of nodes that is empty when the node is compiled out
+                let nodes = Ident::new("nodes", proc_macro2::Span::mixed_site());
+                let enabled = if node.is_iterator_expand() {
+                    quote::quote! { ::core::option::Option::Some(#node).into_iter().flatten() }
+                } else {
+                    quote::quote! { ::core::option::Option::Some(#node) }
+                };
+                tokens.extend(quote::quote! {
+                    {
+                        #[cfg(all(#(#predicates),*))]
+                        let #nodes = #enabled;
+                        #[cfg(not(all(#(#predicates),*)))]
+                        let #nodes = ::core::option::Option::None::<::rs_tml::node::Node>;
+                        #nodes
+                    }
+                });
+            }
         }
     }
 }
 
 /// A procedural macro that returns an RSTML document
+///
+/// `rstml!(in target => { ... })` instead appends the nodes to an existing
+/// `Block` or `Element`, where `target` is a variable or field.
 #[proc_macro]
 pub fn rstml(input: TokenStream) -> TokenStream {
     let document = syn::parse_macro_input!(input as Document);
     document.into_token_stream().into()
 }
+
+/// A procedural macro that renders a fully static RSTML document at compile time
+///
+/// Expands to a `&'static str` of HTML, usable in `const` and `static` items.
+/// Any dynamic content, such as interpolation or control flow, is a compile error.
+#[proc_macro]
+pub fn rstml_static(input: TokenStream) -> TokenStream {
+    let document = syn::parse_macro_input!(input as Document);
+    match static_template::render_static(&document) {
+        Ok(html) => {
+            let html = LitStr::new(&html, proc_macro2::Span::call_site());
+            with_diagnostics(&document.children, html.into_token_stream()).into()
+        }
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+/// A procedural macro that embeds template files in the binary, e.g.
+/// `embed_templates!("templates/**/*.rstml")`
+///
+/// The pattern is relative to the crate root. Expands to an
+/// `rs_tml::environment::Environment` with every matching file added as a template,
+/// named by its path below the pattern's directory, e.g. `layouts/base.rstml`. The
+/// templates are parsed and their layouts and partials resolved at compile time,
+/// so a broken template fails the build with its file, line and column. Changing
+/// an embedded file rebuilds the crate, adding one needs a rebuild.
+#[proc_macro]
+pub fn embed_templates(input: TokenStream) -> TokenStream {
+    let pattern = syn::parse_macro_input!(input as LitStr);
+    embed::embed_templates(&pattern)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `From<T> for Node`, so view-model structs can be expanded with `*value`
+///
+/// The view is either given as a `#[rstml(...)]` template with a single root node,
+/// which can refer to the struct's fields by name, or by the struct's `fn view(&self)`.
+#[proc_macro_derive(IntoNode, attributes(rstml))]
+pub fn derive_into_node(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    derive::derive_into_node(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `rs_tml::forms::FormFields`, building a form field for each struct field
+///
+/// Fields are labelled with their name, e.g. `First name` for `first_name`, and
+/// edited with the control their type implies, see `rs_tml::forms::FormValue`.
+/// `#[form(label = "...", placeholder = "...", kind = "email")]` adjusts a field,
+/// where `kind` is an input type or `textarea`, and `#[form(skip)]` leaves it out.
+#[proc_macro_derive(Form, attributes(form))]
+pub fn derive_form(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    form::derive_form(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `rs_tml::table::TableRow`, making a column of each struct field
+///
+/// Columns are headed by the field's name, e.g. `First name` for `first_name`, and
+/// show its value through `rs_tml::table::TableCell`. `#[table(header = "...")]`
+/// renames a column, `#[table(order = 0)]` moves it, `#[table(format = path)]`
+/// builds the cell with a function of `&value` returning anything that converts into
+/// a `Node`, and `#[table(skip)]` leaves the field out.
+#[proc_macro_derive(TableRow, attributes(table))]
+pub fn derive_table_row(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    table::derive_table_row(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -5,7 +5,7 @@ use crate::Node;
 pub struct RSTMLMatchArm {
     pattern: Box<Pat>,
     guard: Option<(Token![if], Box<Expr>)>,
-    body: Vec<Node>,
+    pub(crate) body: Vec<Node>,
 }
 
 impl Parse for RSTMLMatchArm {
@@ -47,9 +47,9 @@ impl Parse for RSTMLMatchArm {
 }
 
 pub struct RSTMLMatch {
-    match_token: Token![match],
+    pub(crate) match_token: Token![match],
     expression: Box<Expr>,
-    arms: Vec<RSTMLMatchArm>,
+    pub(crate) arms: Vec<RSTMLMatchArm>,
 }
 
 impl Parse for RSTMLMatch {
@@ -80,9 +80,11 @@ impl quote::ToTokens for RSTMLMatch {
         let arms = self.arms.iter().map(|arm| {
             let pattern = &arm.pattern;
             let guard = arm.guard.as_ref().map(|(i, g)| quote::quote! { #i #g });
-            let body = arm.body.iter();
+            // Each arm produces a block, so arms can yield any number of sibling nodes
+            let body = arm.body.iter().map(Node::to_child_tokens);
             quote::quote! {
                 #pattern #guard => {
+                    ::rs_tml::block::Block::new()
                     #(#body)*
                 }
             }
@@ -0,0 +1,149 @@
+use std::collections::BTreeSet;
+
+use rs_tml::{
+    attribute::Attribute as StaticAttribute, element::Element as StaticElement,
+    node::Node as StaticNode, render::Render,
+};
+use syn::{Expr, ExprLit, Lit, spanned::Spanned};
+
+use crate::{
+    Document, Node, TextNode,
+    attribute::{Attribute, AttributeKey, AttributeValue, ClassListItem},
+    element::{Element, ElementName},
+};
+
+fn dynamic_error(span: impl Spanned, what: &str) -> syn::Error {
+    syn::Error::new(
+        span.span(),
+        format!("{what} is not static, use `rstml!` for dynamic content"),
+    )
+}
+
+// Extracts the value of a string literal expression
+fn literal_str(expr: &Expr, what: &str) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => Ok(lit.value()),
+        _ => Err(dynamic_error(expr, what)),
+    }
+}
+
+fn static_key(key: &AttributeKey) -> syn::Result<String> {
+    match key {
+        AttributeKey::Static(name) | AttributeKey::StaticId(name) => Ok(name.clone()),
+        AttributeKey::Dynamic(expr) | AttributeKey::DynamicId(expr) => {
+            Err(dynamic_error(expr, "Attribute key"))
+        }
+    }
+}
+
+fn static_class_list(items: &[ClassListItem]) -> syn::Result<String> {
+    let mut classes = Vec::new();
+    for item in items {
+        if let Some(condition) = &item.condition {
+            return Err(dynamic_error(condition, "Class condition"));
+        }
+        classes.push(literal_str(&item.class, "Class")?);
+    }
+    Ok(classes.join(" "))
+}
+
+fn static_attribute(attribute: &Attribute) -> syn::Result<StaticAttribute<'static>> {
+    match attribute {
+        Attribute::KeyValue { key, value } => {
+            let value = match value {
+                AttributeValue::Static(lit) => lit.value(),
+                AttributeValue::Dynamic(expr) => literal_str(expr, "Attribute value")?,
+                AttributeValue::List(items) => static_class_list(items)?,
+            };
+            Ok(StaticAttribute::new(static_key(key)?, value))
+        }
+        Attribute::Key { key } => {
+            let name = static_key(key)?;
+            Ok(match key {
+                AttributeKey::StaticId(_) => StaticAttribute::new("id", name),
+                _ => StaticAttribute::new("class", name),
+            })
+        }
+        Attribute::Optional { value, .. } => Err(dynamic_error(value, "Optional attribute")),
+        Attribute::Toggle { condition, .. } => Err(dynamic_error(condition, "Boolean attribute")),
+        Attribute::Spread { key } => Err(dynamic_error(key, "Attribute spread")),
+        Attribute::Handler { handler, .. } => Err(dynamic_error(handler, "Event handler")),
+        Attribute::Style { properties } => {
+            let mut style = rs_tml::style::Style::new();
+            for property in properties {
+                style.add_property(&property.name, literal_str(&property.value, "Style value")?);
+            }
+            Ok(style.into())
+        }
+    }
+}
+
+// Collects the static tag names, which the rendered elements borrow
+fn collect_names(nodes: &[Node], names: &mut BTreeSet<String>) {
+    for node in nodes {
+        if let Node::Element(element) = node {
+            if let ElementName::Static(name) = &element.name {
+                names.insert(name.to_string());
+            }
+            collect_names(&element.children, names);
+        }
+    }
+}
+
+fn static_element<'n>(
+    element: &Element,
+    names: &'n BTreeSet<String>,
+) -> syn::Result<StaticElement<'n>> {
+    let name = match &element.name {
+        ElementName::Static(name) => name.to_string(),
+        ElementName::Dynamic(expr) => return Err(dynamic_error(expr, "Tag name")),
+    };
+    let name = names
+        .get(&name)
+        .expect("tag names are collected before rendering");
+    let mut out = StaticElement::new(name.as_str());
+    for attribute in &element.attributes {
+        out.add_attribute(static_attribute(attribute)?);
+    }
+    for child in &element.children {
+        out.add_child(static_node(child, names)?);
+    }
+    Ok(out)
+}
+
+fn static_node<'n>(node: &Node, names: &'n BTreeSet<String>) -> syn::Result<StaticNode<'n>> {
+    match node {
+        Node::Text(TextNode::Literal(lit)) => Ok(StaticNode::text(lit.value())),
+        Node::Text(TextNode::Dynamic(lit)) => Err(dynamic_error(lit, "Formatted text")),
+        Node::Element(element) => static_element(element, names).map(StaticNode::Element),
+        Node::Raw(expr) => Ok(StaticNode::raw(literal_str(expr, "Raw markup")?)),
+        Node::Comment(expr) => Ok(StaticNode::comment(literal_str(expr, "Comment")?)),
+        Node::If(block) => Err(dynamic_error(block.if_token, "Control flow")),
+        Node::For(block) => Err(dynamic_error(block.for_token, "Control flow")),
+        Node::Match(block) => Err(dynamic_error(block.match_token, "Control flow")),
+        Node::Cfg(predicates, _) => Err(dynamic_error(&predicates[0], "Conditional compilation")),
+        Node::Expand(expr) | Node::ExpandMany(expr) | Node::Inline(expr) => {
+            Err(dynamic_error(expr, "Expansion"))
+        }
+        Node::Translate(key, _) => Err(dynamic_error(key, "Translation")),
+    }
+}
+
+/// Renders a fully static document to HTML at compile time
+pub fn render_static(document: &Document) -> syn::Result<String> {
+    if let Some(target) = &document.target {
+        return Err(syn::Error::new_spanned(
+            target,
+            "`rstml_static!` cannot append to an existing block",
+        ));
+    }
+    let mut names = BTreeSet::new();
+    collect_names(&document.children, &mut names);
+    let mut html = String::new();
+    for child in &document.children {
+        html.push_str(&static_node(child, &names)?.render());
+    }
+    Ok(html)
+}
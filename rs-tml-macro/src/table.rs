@@ -0,0 +1,101 @@
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitInt, LitStr};
+
+use crate::form::default_label;
+
+// The `#[table(...)]` settings of a struct field
+#[derive(Default)]
+struct ColumnOptions {
+    header: Option<LitStr>,
+    order: Option<LitInt>,
+    format: Option<syn::Path>,
+    skip: bool,
+}
+
+fn column_options(field: &syn::Field) -> syn::Result<ColumnOptions> {
+    let mut options = ColumnOptions::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("table")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else if meta.path.is_ident("header") {
+                options.header = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("order") {
+                options.order = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("format") {
+                options.format = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error(
+                    "Expected `header = \"...\"`, `order = ...`, `format = path` or `skip`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// Derives `TableRow`, with a column per struct field
+///
+/// Columns keep the order of the fields, except those given an `order`, which are
+/// placed as if the field were declared at that position, ahead of the field there.
+pub fn derive_table_row(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`#[derive(TableRow)]` requires named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`#[derive(TableRow)]` is only supported on structs",
+            ));
+        }
+    };
+
+    let mut columns = Vec::new();
+    for (position, field) in fields.iter().enumerate() {
+        let options = column_options(field)?;
+        if options.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let header = options.header.map_or_else(
+            || default_label(ident.to_string().trim_start_matches("r#")),
+            |header| header.value(),
+        );
+        // Explicit orders come before the field declared at the same position
+        let order = match options.order {
+            Some(order) => (order.base10_parse::<usize>()?, false),
+            None => (position, true),
+        };
+        let cell = match options.format {
+            Some(format) => quote! {
+                ::rs_tml::node::Node::from(#format(&self.#ident))
+            },
+            None => quote! { ::rs_tml::table::TableCell::to_cell(&self.#ident) },
+        };
+        columns.push((order, header, cell));
+    }
+    columns.sort_by_key(|(order, ..)| *order);
+    let headers = columns.iter().map(|(_, header, _)| header);
+    let cells = columns.iter().map(|(.., cell)| cell);
+    Ok(quote! {
+        impl #impl_generics ::rs_tml::table::TableRow for #name #ty_generics #where_clause {
+            fn headers() -> ::std::vec::Vec<::std::borrow::Cow<'static, str>> {
+                ::std::vec![#(::std::borrow::Cow::Borrowed(#headers)),*]
+            }
+
+            fn cells(&self) -> ::std::vec::Vec<::rs_tml::node::Node<'static>> {
+                ::std::vec![#(#cells),*]
+            }
+        }
+    })
+}
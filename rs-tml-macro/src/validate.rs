@@ -0,0 +1,153 @@
+use proc_macro2::{Span, TokenStream};
+use rs_tml::{
+    lint::{can_contain, unknown_tag_message},
+    render::is_void_element,
+    style::is_known_property,
+};
+use syn::Ident;
+
+use crate::{
+    Node,
+    attribute::Attribute,
+    element::{Element, ElementName},
+};
+
+/// Checks markup for unknown tags, void elements with children, invalid nesting,
+/// duplicate attributes and unknown CSS properties
+///
+/// Subtrees in a foreign namespace, i.e. inside `svg` or `math`, are not checked.
+pub fn validate(nodes: &[Node]) -> Vec<syn::Error> {
+    let mut errors = Vec::new();
+    validate_nodes(nodes, None, false, &mut errors);
+    errors
+}
+
+fn validate_nodes(
+    nodes: &[Node],
+    parent: Option<&str>,
+    foreign: bool,
+    errors: &mut Vec<syn::Error>,
+) {
+    for node in nodes {
+        match node {
+            Node::Element(element) => validate_element(element, parent, foreign, errors),
+            // Control flow is transparent, its nodes end up as children of `parent`
+            Node::If(block) => {
+                let mut block = Some(block);
+                while let Some(current) = block {
+                    validate_nodes(&current.then.children, parent, foreign, errors);
+                    if let Some((_, else_block)) = &current.else_block {
+                        validate_nodes(&else_block.children, parent, foreign, errors);
+                    }
+                    block = current.else_if.as_deref();
+                }
+            }
+            Node::For(block) => validate_nodes(&block.body.children, parent, foreign, errors),
+            Node::Match(block) => {
+                for arm in &block.arms {
+                    validate_nodes(&arm.body, parent, foreign, errors);
+                }
+            }
+            Node::Cfg(_, node) => {
+                validate_nodes(std::slice::from_ref(node), parent, foreign, errors);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn validate_element(
+    element: &Element,
+    parent: Option<&str>,
+    foreign: bool,
+    errors: &mut Vec<syn::Error>,
+) {
+    validate_attributes(element, errors);
+    let ElementName::Static(ident) = &element.name else {
+        // The tag is only known at runtime
+        validate_nodes(&element.children, None, foreign, errors);
+        return;
+    };
+    let tag = ident.to_string();
+    if !foreign {
+        if let Some(message) = unknown_tag_message(&tag) {
+            errors.push(syn::Error::new(ident.span(), message));
+        }
+        if let Some(parent) = parent
+            && !can_contain(parent, &tag)
+        {
+            errors.push(syn::Error::new(
+                ident.span(),
+                format!("`<{tag}>` cannot be nested inside `<{parent}>`"),
+            ));
+        }
+        if is_void_element(&tag) && !element.children.is_empty() {
+            errors.push(syn::Error::new(
+                ident.span(),
+                format!("Void element `<{tag}>` cannot have children"),
+            ));
+        }
+    }
+    let foreign = match tag.as_str() {
+        "svg" | "math" => true,
+        "foreignObject" => false,
+        _ => foreign,
+    };
+    validate_nodes(&element.children, Some(&tag), foreign, errors);
+}
+
+// Reports static attribute keys that are set more than once and unknown CSS properties
+fn validate_attributes(element: &Element, errors: &mut Vec<syn::Error>) {
+    for attribute in &element.attributes {
+        if let Attribute::Style { properties } = attribute {
+            for property in properties.iter().filter(|p| !is_known_property(&p.name)) {
+                errors.push(syn::Error::new(
+                    property.span,
+                    format!("Unknown CSS property `{}`", property.name),
+                ));
+            }
+        }
+    }
+    let mut seen = Vec::new();
+    for key in element.attributes.iter().filter_map(Attribute::static_key) {
+        if seen.contains(&key) {
+            let merge = if key == "class" {
+                "the values are joined"
+            } else {
+                "the last value wins"
+            };
+            errors.push(syn::Error::new(
+                element.name.span(),
+                format!("Attribute `{key}` is set more than once, {merge}"),
+            ));
+        } else {
+            seen.push(key);
+        }
+    }
+}
+
+/// Turns validation errors into statements reporting them
+///
+/// Stable Rust has no API for proc macro warnings, so each one is raised by
+/// using a deprecated constant at the offending span. With the `strict` feature
+/// they are compile errors instead.
+pub fn diagnostics(errors: &[syn::Error]) -> TokenStream {
+    errors
+        .iter()
+        .map(|error| {
+            if cfg!(feature = "strict") {
+                return error.to_compile_error();
+            }
+            let message = error.to_string();
+            let warning = Ident::new("rstml_warning", error.span().resolved_at(Span::call_site()));
+            quote::quote! {
+                {
+                    #[deprecated(note = #message)]
+                    #[allow(non_upper_case_globals)]
+                    const #warning: () = ();
+                    let _ = #warning;
+                }
+            }
+        })
+        .collect()
+}
@@ -260,3 +260,774 @@ fn test_complex_document() {
     assert_eq!(document.children.len(), 1);
     assert_eq!(document.children[0], expected);
 }
+
+#[test]
+fn test_inline_expr_child() {
+    let name = "World";
+    let child = element("span").with_child("Child");
+    let document = rstml! {
+        div {
+            { format!("Hello, {name}!") }
+            { child.clone() }
+        }
+    };
+    let expected = element("div")
+        .with_child("Hello, World!")
+        .with_child(element("span").with_child("Child"))
+        .into_node();
+    assert_eq!(document.children.len(), 1);
+    assert_eq!(document.children[0], expected);
+}
+
+#[test]
+fn test_inline_expr_iterator_child() {
+    let items = ["One", "Two"];
+    let missing: Option<Node> = None;
+    let document = rstml! {
+        ul {
+            { items.iter().map(|item| element("li").with_child(*item)) }
+            { vec!["text", "more"] }
+            { missing }
+            { Some(element("li")) }
+        }
+    };
+    let expected = element("ul")
+        .with_child(element("li").with_child("One"))
+        .with_child(element("li").with_child("Two"))
+        .with_child("text")
+        .with_child("more")
+        .with_child(element("li"))
+        .into_node();
+    assert_eq!(document.children, [expected]);
+}
+
+#[test]
+fn test_optional_attribute() {
+    let some_title: Option<&str> = Some("Tooltip");
+    let no_title: Option<&str> = None;
+    let document = rstml! {
+        div {
+            .title =? some_title
+            "Content"
+        }
+        div {
+            .title =? no_title
+            "Content"
+        }
+    };
+    let expected = element("div")
+        .with_key_value("title", "Tooltip")
+        .with_child("Content")
+        .into_node();
+    assert_eq!(document.children.len(), 2);
+    assert_eq!(document.children[0], expected);
+    assert_eq!(
+        document.children[1],
+        element("div").with_child("Content").into_node()
+    );
+}
+
+#[test]
+fn test_optional_attribute_with_value_variable() {
+    // The expansion's own bindings do not shadow a user variable named `value`
+    let value = "data-tip";
+    let title: Option<&str> = Some("Tooltip");
+    let document = rstml! {
+        div { .*value =? title }
+    };
+    assert_eq!(
+        document.children[0],
+        element("div")
+            .with_key_value("data-tip", "Tooltip")
+            .into_node()
+    );
+}
+
+#[test]
+fn test_boolean_attribute_toggle() {
+    let is_disabled = true;
+    let is_checked = false;
+    let document = rstml! {
+        input {
+            .disabled?(is_disabled)
+            .checked?(is_checked)
+        }
+    };
+    let expected = element("input")
+        .with_attribute(Attribute::boolean("disabled"))
+        .into_node();
+    assert_eq!(document.children.len(), 1);
+    assert_eq!(document.children[0], expected);
+}
+
+#[test]
+fn test_class_list() {
+    let is_active = true;
+    let is_hidden = false;
+    let size = String::from("btn-lg");
+    let document = rstml! {
+        button {
+            .class = ["btn", "btn-primary", size, ("active", is_active), ("hidden", is_hidden)]
+            "Submit"
+        }
+    };
+    let expected = element("button")
+        .with_key_value("class", "btn btn-primary btn-lg active")
+        .with_child("Submit")
+        .into_node();
+    assert_eq!(document.children.len(), 1);
+    assert_eq!(document.children[0], expected);
+}
+
+#[test]
+fn test_children_spread() {
+    let items: Vec<Node> = vec![
+        element("li").with_child("Item 1").into_node(),
+        element("li").with_child("Item 2").into_node(),
+    ];
+    let document = rstml! {
+        ..items.clone()
+        ul {
+            li { "Item 0" }
+            ..items
+        }
+    };
+    let expected_items = [
+        element("li").with_child("Item 1").into_node(),
+        element("li").with_child("Item 2").into_node(),
+    ];
+    let expected_list = element("ul")
+        .with_child(element("li").with_child("Item 0"))
+        .with_children(expected_items.clone())
+        .into_node();
+    assert_eq!(document.children.len(), 3);
+    assert_eq!(document.children[..2], expected_items);
+    assert_eq!(document.children[2], expected_list);
+}
+
+#[test]
+fn test_leading_children_spread() {
+    let items: Vec<Node> = vec![
+        element("li").with_child("Item 1").into_node(),
+        element("li").with_child("Item 2").into_node(),
+    ];
+    let document = rstml! {
+        ul {
+            .class = "items"
+            ..items.clone()
+            li { "Item 3" }
+        }
+    };
+    let expected = element("ul")
+        .with_key_value("class", "items")
+        .with_children(items)
+        .with_child(element("li").with_child("Item 3"))
+        .into_node();
+    assert_eq!(document.children, [expected]);
+}
+
+#[test]
+fn test_keyed_for_block() {
+    struct Item {
+        id: u32,
+        name: &'static str,
+    }
+    let items = vec![
+        Item {
+            id: 7,
+            name: "First",
+        },
+        Item {
+            id: 9,
+            name: "Second",
+        },
+    ];
+    let document = rstml! {
+        for item in items key item.id {
+            li { { item.name } }
+        }
+    };
+    assert_eq!(document.children.len(), 2);
+    assert_eq!(
+        document.children[0],
+        element("li")
+            .with_child("First")
+            .with_key_value("data-key", "7")
+            .into_node()
+    );
+}
+
+#[test]
+fn test_dynamic_tag_name() {
+    let level = 2;
+    let headings = ["h1", "h2", "h3", "h4", "h5", "h6"];
+    let document = rstml! {
+        *(headings[level - 1]) {
+            .class = "title"
+            "Heading"
+        }
+    };
+    let expected = element("h2")
+        .with_key_value("class", "title")
+        .with_child("Heading")
+        .into_node();
+    assert_eq!(document.children.len(), 1);
+    assert_eq!(document.children[0], expected);
+}
+
+#[test]
+fn test_event_handler() {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    let clicks = AtomicI32::new(0);
+    let document = rstml! {
+        button {
+            .class = "counter"
+            on:click = |_| {
+                clicks.fetch_add(1, Ordering::Relaxed);
+            }
+            "Increment"
+        }
+    };
+    let Node::Element(button) = &document.children[0] else {
+        panic!("Expected an element");
+    };
+    assert_eq!(button.attributes, vec![Attribute::class("counter")]);
+    assert_eq!(button.handlers.len(), 1);
+    assert_eq!(button.handlers[0].event, "click");
+    button.handlers[0].call(&());
+    assert_eq!(clicks.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_raw_child() {
+    let trusted = String::from("<em>trusted</em>");
+    let document = rstml! {
+        div {
+            raw!("<b>hi</b>")
+            raw!(trusted)
+            "<b>hi</b>"
+        }
+    };
+    let expected = element("div")
+        .with_child(Node::raw("<b>hi</b>"))
+        .with_child(Node::raw("<em>trusted</em>"))
+        .with_child("<b>hi</b>")
+        .into_node();
+    assert_eq!(document.children.len(), 1);
+    assert_eq!(document.children[0], expected);
+    let Node::Element(div) = &document.children[0] else {
+        panic!("Expected an element");
+    };
+    assert!(div.children[0].is_raw());
+    assert!(div.children[2].is_text());
+}
+
+#[test]
+fn test_derive_into_node() {
+    use rs_tml_macro::IntoNode;
+
+    #[derive(IntoNode)]
+    #[rstml(li { .class = "user" "{name} ({age})" })]
+    struct User {
+        name: String,
+        age: u32,
+    }
+
+    #[derive(IntoNode)]
+    struct Badge {
+        label: &'static str,
+    }
+
+    impl Badge {
+        fn view(&self) -> Element<'static> {
+            element("span").with_child(self.label)
+        }
+    }
+
+    let users = vec![
+        User {
+            name: "Alice".into(),
+            age: 30,
+        },
+        User {
+            name: "Bob".into(),
+            age: 25,
+        },
+    ];
+    let badge = Badge { label: "Admin" };
+    let document = rstml! {
+        *badge
+        ul { **users }
+    };
+    let expected = Block::new()
+        .with_child(element("span").with_child("Admin"))
+        .with_child(
+            element("ul")
+                .with_child(
+                    element("li")
+                        .with_key_value("class", "user")
+                        .with_child("Alice (30)"),
+                )
+                .with_child(
+                    element("li")
+                        .with_key_value("class", "user")
+                        .with_child("Bob (25)"),
+                ),
+        );
+    assert_eq!(document, expected);
+}
+
+#[test]
+fn test_cfg_gated_nodes() {
+    let items = ["a", "b"];
+    let document = rstml! {
+        div {
+            #[cfg(test)]
+            p { "Only in tests" }
+            #[cfg(not(test))]
+            p { "Never in tests" }
+            #[cfg(all(test, debug_assertions))]
+            for item in items {
+                span { "{item}" }
+            }
+            #[cfg(not(test))]
+            for item in items {
+                span { "{item}" }
+            }
+        }
+    };
+    let mut expected = element("div").with_child(element("p").with_child("Only in tests"));
+    if cfg!(debug_assertions) {
+        expected.add_children(items.map(|item| element("span").with_child(item)));
+    }
+    assert_eq!(document.children.len(), 1);
+    assert_eq!(document.children[0], expected.into_node());
+}
+
+#[test]
+fn test_svg_namespace() {
+    let document = rstml! {
+        svg {
+            .viewBox = "0 0 10 10"
+            circle { .cx = "5" .cy = "5" .r = "4" }
+            foreignObject { div { "HTML" } }
+        }
+    };
+    let Node::Element(svg) = &document.children[0] else {
+        panic!("Expected an element");
+    };
+    assert_eq!(svg.namespace, Namespace::Svg);
+    assert_eq!(svg.attributes, vec![Attribute::new("viewBox", "0 0 10 10")]);
+    let Node::Element(foreign) = &svg.children[1] else {
+        panic!("Expected an element");
+    };
+    assert_eq!(foreign.namespace, Namespace::Svg);
+    assert_eq!(
+        document.render(),
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">"#,
+            r#"<circle cx="5" cy="5" r="4"/>"#,
+            r#"<foreignObject><div xmlns="http://www.w3.org/1999/xhtml">HTML</div></foreignObject>"#,
+            "</svg>"
+        )
+    );
+}
+
+#[test]
+fn test_prefixed_attributes() {
+    let id = "42";
+    let document = rstml! {
+        svg {
+            .aria:label = "Avatar"
+            .xlink:href = "#avatar"
+            .hx:get = "/avatar"
+            .data:user-id = id
+        }
+    };
+    let expected = element("svg")
+        .with_attribute(Attribute::aria("label", "Avatar"))
+        .with_key_value("xlink:href", "#avatar")
+        .with_attribute(rs_tml::htmx::hx_get("/avatar"))
+        .with_attribute(Attribute::data("user-id", "42"))
+        .into_node();
+    assert_eq!(document.children.len(), 1);
+    assert_eq!(document.children[0], expected);
+}
+
+#[test]
+fn test_match_multiple_nodes() {
+    let value: Option<&str> = Some("Alice");
+    let document = rstml! {
+        div {
+            match value {
+                Some(name) => {
+                    h1 { "Hello" }
+                    p { "{name}" }
+                }
+                None => {}
+            }
+            footer { "End" }
+        }
+    };
+    let expected = element("div")
+        .with_child(element("h1").with_child("Hello"))
+        .with_child(element("p").with_child("Alice"))
+        .with_child(element("footer").with_child("End"))
+        .into_node();
+    assert_eq!(document.children.len(), 1);
+    assert_eq!(document.children[0], expected);
+}
+
+#[test]
+fn test_if_let_chain() {
+    let render = |value: Option<i32>, other: Option<&str>| {
+        rstml! {
+            if let Some(x) = value && x > 3 && let Some(name) = other {
+                p { "{name}: {x}" }
+            } else if let Some(x) = value && matches!(x, 1 | 2) {
+                p { "Small {x}" }
+            } else {
+                p { "None" }
+            }
+        }
+    };
+    let paragraph = |text: &str| element("p").with_child(text.to_string()).into_node();
+    assert_eq!(
+        render(Some(5), Some("Five")).children,
+        vec![paragraph("Five: 5")]
+    );
+    assert_eq!(render(Some(5), None).children, vec![paragraph("None")]);
+    assert_eq!(render(Some(2), None).children, vec![paragraph("Small 2")]);
+    assert_eq!(render(None, None).children, vec![paragraph("None")]);
+}
+
+#[test]
+fn test_comments() {
+    let document = rstml! {
+        //! Page template
+        /// Main container
+        div {
+            /// Attributes can be documented too
+            .class = "page"
+            // Regular comments never reach the macro
+            /* Neither do block comments */
+            comment!(" rendered comment ")
+            /// Paragraph
+            p { "Content" }
+            /// Trailing doc comment
+        }
+    };
+    let expected = element("div")
+        .with_key_value("class", "page")
+        .with_child(Node::comment(" rendered comment "))
+        .with_child(element("p").with_child("Content"))
+        .into_node();
+    assert_eq!(document.children.len(), 1);
+    assert_eq!(document.children[0], expected);
+    assert_eq!(
+        document.render(),
+        r#"<div class="page"><!-- rendered comment --><p>Content</p></div>"#
+    );
+}
+
+#[test]
+fn test_static_template() {
+    use rs_tml_macro::rstml_static;
+
+    const FOOTER: &str = rstml_static! {
+        footer {
+            #site-footer
+            .class = ["footer", "dark"]
+            p { "Fish & Chips" }
+            raw!("&copy; 2024")
+            br {}
+        }
+    };
+    let dynamic = rstml! {
+        footer {
+            #site-footer
+            .class = ["footer", "dark"]
+            p { "Fish & Chips" }
+            raw!("&copy; 2024")
+            br {}
+        }
+    };
+    assert_eq!(FOOTER, dynamic.render());
+    assert_eq!(
+        FOOTER,
+        r#"<footer id="site-footer" class="footer dark"><p>Fish &amp; Chips</p>&copy; 2024<br></footer>"#
+    );
+}
+
+#[test]
+fn test_append_into_existing() {
+    fn add_header(page: &mut Block, title: &str) {
+        rstml!(in page => {
+            header { h1 { "{title}" } }
+        });
+    }
+
+    let items = ["One", "Two"];
+    let mut page = Block::new();
+    add_header(&mut page, "Title");
+    let mut list = element("ul");
+    rstml!(in list => {
+        for item in items {
+            li { "{item}" }
+        }
+    });
+    rstml!(in page => { *list });
+
+    let expected = Block::new()
+        .with_child(element("header").with_child(element("h1").with_child("Title")))
+        .with_child(
+            element("ul")
+                .with_child(element("li").with_child("One"))
+                .with_child(element("li").with_child("Two")),
+        );
+    assert_eq!(page, expected);
+}
+
+#[test]
+fn test_duplicate_attributes_merge() {
+    let active = true;
+    let link = rstml! {
+        a {
+            .nav-link
+            #home
+            .class = ["current", ("active", active)]
+            .href = "/"
+        }
+    };
+    let expected = Block::new().with_child(
+        element("a")
+            .with_key_value("class", "nav-link current active")
+            .with_key_value("id", "home")
+            .with_key_value("href", "/"),
+    );
+    assert_eq!(link, expected);
+}
+
+#[test]
+fn test_inline_style() {
+    let color = "blue";
+    let document = rstml! {
+        p {
+            style: { padding: "1rem", background-color: color, "--accent": 2 }
+            "Hi"
+        }
+    };
+    let style = rs_tml::style::Style::new()
+        .padding("1rem")
+        .background_color("blue")
+        .with_property("--accent", "2");
+    let expected =
+        Block::new().with_child(element("p").with_attribute(style.into()).with_child("Hi"));
+    assert_eq!(document, expected);
+    assert_eq!(
+        document.render(),
+        r#"<p style="padding:1rem;background-color:blue;--accent:2">Hi</p>"#
+    );
+}
+
+#[test]
+fn test_embed_templates() {
+    let environment = rs_tml_macro::embed_templates!("tests/templates/**/*.rstml");
+    assert_eq!(
+        environment.template_names(),
+        ["index.rstml", "layouts/base.rstml", "partials/nav.rstml"]
+    );
+    let context = rs_tml::template::Context::new().with_value("name", "Ann");
+    assert_eq!(
+        environment
+            .get_template("index.rstml")
+            .unwrap()
+            .render(&context)
+            .unwrap(),
+        r#"<html><body><nav><a href="/">Home</a></nav><main><h1>Hello, Ann</h1></main></body></html>"#
+    );
+}
+
+#[test]
+fn test_derive_form() {
+    use rs_tml::forms::FormFields;
+
+    #[derive(rs_tml_macro::Form)]
+    struct Signup {
+        first_name: String,
+        #[form(kind = "email", placeholder = "you@example.com")]
+        email: String,
+        age: Option<u32>,
+        #[form(label = "Send me news")]
+        newsletter: bool,
+        #[form(kind = "textarea")]
+        bio: String,
+        #[allow(dead_code)]
+        #[form(skip)]
+        id: u64,
+    }
+
+    let signup = Signup {
+        first_name: "Ann".into(),
+        email: "<ann>".into(),
+        age: None,
+        newsletter: true,
+        bio: String::new(),
+        id: 7,
+    };
+    let form = signup
+        .to_form("/signup")
+        .with_error("email", "Invalid email");
+    assert_eq!(
+        form.to_element().render(),
+        concat!(
+            r#"<form action="/signup" method="post">"#,
+            r#"<div class="field"><label for="first_name">First name</label>"#,
+            r#"<input type="text" value="Ann" name="first_name" id="first_name" required></div>"#,
+            r#"<div class="field has-errors"><label for="email">Email</label>"#,
+            r#"<input type="email" value="&lt;ann&gt;" name="email" id="email" placeholder="you@example.com" required aria-invalid="true" aria-describedby="email-errors">"#,
+            r#"<ul class="errors" id="email-errors"><li>Invalid email</li></ul></div>"#,
+            r#"<div class="field"><label for="age">Age</label><input type="number" name="age" id="age"></div>"#,
+            r#"<div class="field"><input type="checkbox" checked name="newsletter" id="newsletter">"#,
+            r#"<label for="newsletter">Send me news</label></div>"#,
+            r#"<div class="field"><label for="bio">Bio</label><textarea name="bio" id="bio" required></textarea></div>"#,
+            r#"<button type="submit">Submit</button></form>"#,
+        )
+    );
+}
+
+#[test]
+fn test_derive_table_row() {
+    fn money(cents: &u64) -> String {
+        format!("${}.{:02}", cents / 100, cents % 100)
+    }
+
+    fn status(active: &bool) -> Element<'static> {
+        element("span").with_class(if *active { "active" } else { "inactive" })
+    }
+
+    #[derive(rs_tml_macro::TableRow)]
+    struct Customer {
+        first_name: String,
+        #[table(header = "Balance", format = money)]
+        balance_cents: u64,
+        #[table(order = 0)]
+        id: u32,
+        #[table(format = status)]
+        active: bool,
+        #[allow(dead_code)]
+        #[table(skip)]
+        notes: String,
+    }
+
+    let customers = vec![Customer {
+        first_name: "<Ann>".into(),
+        balance_cents: 1205,
+        id: 7,
+        active: true,
+        notes: String::new(),
+    }];
+    assert_eq!(
+        rs_tml::table::from_rows(&customers).render(),
+        concat!(
+            r#"<table><thead><tr><th scope="col">Id</th><th scope="col">First name</th>"#,
+            r#"<th scope="col">Balance</th><th scope="col">Active</th></tr></thead>"#,
+            r#"<tbody><tr><td>7</td><td>&lt;Ann&gt;</td><td>$12.05</td>"#,
+            r#"<td><span class="active"></span></td></tr></tbody></table>"#,
+        )
+    );
+}
+
+#[test]
+fn test_translations() {
+    use rs_tml::i18n::Translation;
+
+    let user = "Ada";
+    let count = 3;
+    let document = rstml! {
+        h1 { t!("welcome", name = user, count = count + 1,) }
+        p { t!("empty") }
+    };
+    assert_eq!(
+        document.children[0].as_element().unwrap().children[0],
+        Node::translation(
+            Translation::new("welcome")
+                .with_arg("name", "Ada")
+                .with_arg("count", "4")
+        )
+    );
+    assert_eq!(document.render(), "<h1>welcome</h1><p>empty</p>");
+
+    let translator = |translation: &Translation| {
+        (translation.key == "welcome").then(|| {
+            format!(
+                "<{}> has {}",
+                translation.arg("name").unwrap_or_default(),
+                translation.arg("count").unwrap_or_default()
+            )
+        })
+    };
+    assert_eq!(
+        document.render_localized(&translator),
+        "<h1>&lt;Ada&gt; has 4</h1><p>empty</p>"
+    );
+}
+
+#[test]
+fn test_table_row_columns() {
+    fn shout(value: &str) -> String {
+        format!("<{}>!", value.to_uppercase())
+    }
+
+    #[derive(rs_tml_macro::TableRow)]
+    struct Entry<'a, T: rs_tml::table::TableCell> {
+        #[table(header = "Q&A <notes>")]
+        r#note: Option<String>,
+        #[table(format = shout)]
+        label: &'a str,
+        #[table(order = 0)]
+        value: T,
+        #[table(skip, header = "Hidden")]
+        #[allow(dead_code)]
+        hidden: u8,
+        // Placed ahead of `label`, the field declared at position 1
+        #[table(order = 1)]
+        flag: bool,
+    }
+
+    let rows = [
+        Entry {
+            note: Some(r#"a "quoted" & <b>bold</b>"#.into()),
+            label: "x & y",
+            value: 1.5,
+            hidden: 0,
+            flag: false,
+        },
+        Entry {
+            note: None,
+            label: "z",
+            value: -2.0,
+            hidden: 0,
+            flag: true,
+        },
+    ];
+    assert_eq!(
+        rs_tml::table::from_rows(&rows).render(),
+        concat!(
+            r#"<table><thead><tr><th scope="col">Value</th><th scope="col">Q&amp;A &lt;notes&gt;</th>"#,
+            r#"<th scope="col">Flag</th><th scope="col">Label</th></tr></thead><tbody>"#,
+            r#"<tr><td>1.5</td><td>a "quoted" &amp; &lt;b&gt;bold&lt;/b&gt;</td><td>No</td>"#,
+            r#"<td>&lt;X &amp; Y&gt;!</td></tr>"#,
+            r#"<tr><td>-2</td><td></td><td>Yes</td><td>&lt;Z&gt;!</td></tr></tbody></table>"#,
+        )
+    );
+    assert_eq!(
+        rs_tml::table::from_rows(Vec::<Entry<f64>>::new()).render(),
+        concat!(
+            r#"<table><thead><tr><th scope="col">Value</th><th scope="col">Q&amp;A &lt;notes&gt;</th>"#,
+            r#"<th scope="col">Flag</th><th scope="col">Label</th></tr></thead>"#,
+            r#"<tbody></tbody></table>"#,
+        )
+    );
+}
@@ -0,0 +1,34 @@
+// The expansion must not rely on anything being imported at the call site
+mod page {
+    pub fn render(logged_in: bool, level: Option<u8>) -> rs_tml::block::Block<'static> {
+        let items = ["One", "Two"];
+        rs_tml_macro::rstml! {
+            div {
+                .class = ["page", ("auth", logged_in)]
+                ..[("data-level", "1")]
+                if logged_in {
+                    p { "Welcome back" }
+                } else if let Some(level) = level {
+                    p { "Level {level}" }
+                } else {
+                    p { "Guest" }
+                }
+                match level {
+                    Some(0) => p { "Beginner" },
+                    _ => p { "Other" },
+                }
+                ul {
+                    for item in items key item {
+                        li { "{item}" }
+                    }
+                }
+                *("expanded")
+                **items
+            }
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(page::render(true, None).children.len(), 1);
+}
@@ -0,0 +1,14 @@
+#![deny(deprecated)]
+
+use rs_tml_macro::rstml;
+
+fn main() {
+    let _ = rstml! {
+        p {
+            div { "block in a paragraph" }
+            br { "text" }
+        }
+        dvi { }
+        svg { circle { } }
+    };
+}
@@ -0,0 +1,11 @@
+#![deny(deprecated)]
+
+use rs_tml_macro::rstml;
+
+fn main() {
+    let _ = rstml! {
+        p {
+            style: { padding: "1rem", colour: "red" }
+        }
+    };
+}
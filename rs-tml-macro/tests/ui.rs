@@ -0,0 +1,11 @@
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    // Markup warnings fail through `#![deny(deprecated)]`, or as errors when strict
+    #[cfg(not(feature = "strict"))]
+    t.compile_fail("tests/warnings/*.rs");
+    #[cfg(feature = "strict")]
+    t.compile_fail("tests/ui-strict/*.rs");
+    t.pass("tests/pass/*.rs");
+}
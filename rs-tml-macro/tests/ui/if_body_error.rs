@@ -0,0 +1,10 @@
+use rs_tml_macro::rstml;
+
+fn main() {
+    let _ = rstml! {
+        if true {
+            p { "valid" }
+            p { .class = }
+        }
+    };
+}
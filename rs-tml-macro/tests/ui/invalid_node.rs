@@ -0,0 +1,10 @@
+use rs_tml_macro::rstml;
+
+fn main() {
+    let _ = rstml! {
+        div {
+            "text"
+            42
+        }
+    };
+}
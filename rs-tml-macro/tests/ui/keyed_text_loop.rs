@@ -0,0 +1,12 @@
+use rs_tml_macro::rstml;
+
+fn main() {
+    let items = ["a", "b"];
+    let _ = rstml! {
+        ul {
+            for item in items key item {
+                "{item}"
+            }
+        }
+    };
+}
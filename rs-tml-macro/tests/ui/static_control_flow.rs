@@ -0,0 +1,12 @@
+use rs_tml_macro::rstml_static;
+
+const LIST: &str = rstml_static! {
+    ul {
+        li { "First" }
+        for item in ["a", "b"] {
+            li { "{item}" }
+        }
+    }
+};
+
+fn main() {}
@@ -0,0 +1,9 @@
+use rs_tml_macro::rstml_static;
+
+const HEADER: &str = rstml_static! {
+    header {
+        h1 { "{title}" }
+    }
+};
+
+fn main() {}
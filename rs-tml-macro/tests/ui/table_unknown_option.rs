@@ -0,0 +1,7 @@
+#[derive(rs_tml_macro::TableRow)]
+struct Row {
+    #[table(width = 3)]
+    name: String,
+}
+
+fn main() {}
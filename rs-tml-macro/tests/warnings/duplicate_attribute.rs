@@ -0,0 +1,14 @@
+#![deny(deprecated)]
+
+use rs_tml_macro::rstml;
+
+fn main() {
+    let _ = rstml! {
+        a {
+            .class = "link"
+            .href = "/old"
+            .class = "external"
+            .href = "/new"
+        }
+    };
+}
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+
+use crate::{diagnostics::Diagnostic, prelude::*, validate::Violation};
+
+// `input` types that need no label, as they are hidden or labelled by their value
+const UNLABELLED_INPUT_TYPES: &[&str] = &["hidden", "submit", "reset", "button", "image"];
+
+// Attributes giving an element an accessible name on their own
+const NAMING_ATTRIBUTES: &[&str] = &["aria-label", "aria-labelledby", "title"];
+
+fn has_naming_attribute(element: &Element) -> bool {
+    NAMING_ATTRIBUTES.iter().any(|key| {
+        element
+            .attr(key)
+            .is_some_and(|value| !value.trim().is_empty())
+    })
+}
+
+fn heading_level(tag: &str) -> Option<u8> {
+    match tag.as_bytes() {
+        [b'h', level @ b'1'..=b'6'] => Some(level - b'0'),
+        _ => None,
+    }
+}
+
+struct Checker<'n> {
+    violations: Vec<Violation>,
+    path: NodePath,
+    // Ids that a `<label for>` points at
+    labelled_ids: HashSet<&'n str>,
+    label_depth: usize,
+    last_heading: Option<u8>,
+}
+
+impl<'n> Checker<'n> {
+    fn report(&mut self, tag: &str, code: &'static str, message: String) {
+        self.violations
+            .push(Violation::new(self.path.clone(), tag, code, message));
+    }
+
+    fn check_nodes(&mut self, nodes: &'n [Node]) {
+        for (index, node) in nodes.iter().enumerate() {
+            if let Some(element) = node.as_element() {
+                self.path.push(index);
+                self.check_element(element);
+                self.path.pop();
+            }
+        }
+    }
+
+    fn is_labelled(&self, element: &Element) -> bool {
+        self.label_depth > 0
+            || has_naming_attribute(element)
+            || element
+                .id()
+                .is_some_and(|id| self.labelled_ids.contains(id))
+    }
+
+    // Whether the element has a name from its content, i.e. text or an image's alt
+    fn has_content_name(element: &Element) -> bool {
+        !element.inner_text().trim().is_empty()
+            || element.descendants().filter_map(Node::as_element).any(|e| {
+                e.name.as_str() == "img" && e.attr("alt").is_some_and(|alt| !alt.trim().is_empty())
+            })
+    }
+
+    fn check_element(&mut self, element: &'n Element) {
+        let tag = element.name.as_str();
+        match tag {
+            "html"
+                if element
+                    .attr("lang")
+                    .is_none_or(|lang| lang.trim().is_empty()) =>
+            {
+                self.report(tag, "html-lang", "`<html>` has no `lang` attribute".into());
+            }
+            "img" if element.attr("alt").is_none() => self.report(
+                tag,
+                "img-alt",
+                "`<img>` has no `alt` text, use `alt=\"\"` for decorative images".into(),
+            ),
+            "input" | "select" | "textarea" => {
+                let needs_label = tag != "input"
+                    || !UNLABELLED_INPUT_TYPES.contains(&element.attr("type").unwrap_or("text"));
+                if needs_label && !self.is_labelled(element) {
+                    self.report(
+                        tag,
+                        "form-label",
+                        format!("`<{tag}>` has no associated `<label>`"),
+                    );
+                }
+            }
+            "button" if !has_naming_attribute(element) && !Self::has_content_name(element) => {
+                self.report(
+                    tag,
+                    "button-name",
+                    "`<button>` has no accessible name".into(),
+                );
+            }
+            _ => {}
+        }
+        if let Some(level) = heading_level(tag) {
+            if let Some(last) = self.last_heading
+                && level > last + 1
+            {
+                self.report(
+                    tag,
+                    "heading-order",
+                    format!("`<{tag}>` skips heading levels after `<h{last}>`"),
+                );
+            }
+            self.last_heading = Some(level);
+        }
+        let is_label = tag == "label";
+        self.label_depth += usize::from(is_label);
+        self.check_nodes(&element.children);
+        self.label_depth -= usize::from(is_label);
+    }
+}
+
+/// Checks the document for common accessibility problems, in document order
+///
+/// Violations cover `img` without `alt`, form controls without a label, buttons
+/// without an accessible name, headings skipping levels and `html` without `lang`.
+/// Controls are labelled by an enclosing `label`, a `label` whose `for` is their
+/// id, or an `aria-label`, `aria-labelledby` or `title` attribute.
+#[must_use]
+pub fn check(document: &Document) -> Vec<Violation> {
+    let labelled_ids = document
+        .iter()
+        .filter_map(Node::as_element)
+        .filter(|element| element.name.as_str() == "label")
+        .filter_map(|label| label.attr("for"))
+        .collect();
+    let mut checker = Checker {
+        violations: Vec::new(),
+        path: NodePath::root(),
+        labelled_ids,
+        label_depth: 0,
+        last_heading: None,
+    };
+    checker.check_nodes(&document.children);
+    checker.violations
+}
+
+/// Parses RSTML source and checks it with [`check`], as warnings spanning the
+/// offending elements
+///
+/// Source that does not parse gives no diagnostics, see
+/// [`crate::diagnostics::diagnostics`] for parse errors.
+#[must_use]
+pub fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let Ok((_, document)) = Document::parse_ignoring_comments(source) else {
+        return Vec::new();
+    };
+    check(&document)
+        .iter()
+        .filter_map(|violation| Diagnostic::for_violation(source, &document, violation))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, diagnostics};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_check() {
+        let (_, document) = Document::parse(
+            r#"html { body {
+                h1 { "Title" } h3 { "Skipped" } h2 {}
+                img { .src = "a.png" } img { .src = "b.png" .alt = "" }
+                label { "Name" input {} } label { .for = "email" "Email" }
+                input { #email } input { .type = "hidden" } textarea {}
+                button { "Save" } button { img { .alt = "Close" } } button {}
+            } }"#,
+        )
+        .unwrap();
+        let violations: Vec<String> = check(&document).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            violations,
+            [
+                "/0 <html>: `<html>` has no `lang` attribute",
+                "/0/0/1 <h3>: `<h3>` skips heading levels after `<h1>`",
+                "/0/0/3 <img>: `<img>` has no `alt` text, use `alt=\"\"` for decorative images",
+                "/0/0/9 <textarea>: `<textarea>` has no associated `<label>`",
+                "/0/0/12 <button>: `<button>` has no accessible name",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let source = "div {\n  img { .src = \"a.png\" }\n}";
+        let diagnostics = diagnostics(source);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "img-alt");
+        assert_eq!(
+            (diagnostics[0].span.line, diagnostics[0].span.column),
+            (2, 3)
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use actix_web::{HttpRequest, HttpResponse, Responder, body::BoxBody};
+
+use crate::{prelude::*, render::HTML_CONTENT_TYPE};
+
+fn html_response(html: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(HTML_CONTENT_TYPE)
+        .body(html)
+}
+
+impl Responder for Block<'_> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        html_response(self.render())
+    }
+}
+
+impl Responder for Element<'_> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        html_response(self.render())
+    }
+}
+
+impl Responder for Node<'_> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        html_response(self.render())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        Responder,
+        body::MessageBody,
+        http::{StatusCode, header::CONTENT_TYPE},
+        test::TestRequest,
+    };
+
+    use crate::{prelude::*, render::HTML_CONTENT_TYPE};
+
+    #[test]
+    fn test_respond_to() {
+        let request = TestRequest::default().to_http_request();
+        let response = Document::new()
+            .with_child(element("h1").with_child("Hi"))
+            .respond_to(&request);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            HTML_CONTENT_TYPE
+        );
+        let body = response.into_body().try_into_bytes().unwrap();
+        assert_eq!(body, "<h1>Hi</h1>");
+
+        let response = element("p")
+            .with_child("Missing")
+            .customize()
+            .with_status(StatusCode::NOT_FOUND)
+            .respond_to(&request);
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,461 @@
+use std::{borrow::Cow, ops::Range};
+
+use crate::prelude::*;
+
+/// Index of a node in an [`ArenaDocument`]
+///
+/// Ids are only meaningful for the document that created them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Content of a node in an [`ArenaDocument`], children are reached through the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArenaNode<'a> {
+    Text(Text<'a>),
+    Element {
+        name: Tag<'a>,
+        namespace: Namespace,
+        // Range into the document's shared attribute list
+        attributes: Range<usize>,
+    },
+    Raw(Cow<'a, str>),
+    Comment(Cow<'a, str>),
+    Slot(Cow<'a, str>),
+    // Directives keep their children, as they are resolved before rendering
+    Directive(Directive<'a>),
+    #[cfg(feature = "i18n")]
+    Translation(crate::i18n::Translation<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry<'a> {
+    node: ArenaNode<'a>,
+    parent: Option<NodeId>,
+    next_sibling: Option<NodeId>,
+    first_child: Option<NodeId>,
+    last_child: Option<NodeId>,
+    // Set by `remove`, the slot is never reused so stale ids cannot alias new nodes
+    removed: bool,
+}
+
+/// A document stored in two flat lists, one for nodes and one for attributes
+///
+/// Elements link to their children by index instead of owning a `Vec` each, so a
+/// document of any size lives in a handful of allocations and is freed at once.
+/// Convert from and to the owned tree with `From`, event handlers are not kept.
+///
+/// Nodes can be moved with [`ArenaDocument::detach`] and [`ArenaDocument::attach`].
+/// Removed nodes keep their slot until the document is dropped and their ids go
+/// stale: [`ArenaDocument::contains`] returns false for them and other methods panic.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArenaDocument<'a> {
+    nodes: Vec<Entry<'a>>,
+    attributes: Vec<Attribute<'a>>,
+    first_root: Option<NodeId>,
+    last_root: Option<NodeId>,
+    removed: usize,
+}
+
+impl<'a> ArenaDocument<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        ArenaDocument::default()
+    }
+
+    /// Parses a document, see [`Block::parse_ignoring_comments`]
+    ///
+    /// # Errors
+    /// Errors if the input is not a valid document
+    pub fn parse(input: &'a str) -> ParseResult<'a, Self> {
+        let (rest, block) = Block::parse_ignoring_comments(input)?;
+        Ok((rest, ArenaDocument::from(&block)))
+    }
+
+    /// Number of nodes in the document, at any depth, detached ones included
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len() - self.removed
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the id is of a node of the document that was not removed
+    #[must_use]
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.nodes
+            .get(id.index())
+            .is_some_and(|entry| !entry.removed)
+    }
+
+    // The entry of a node, panicking for stale ids
+    fn entry(&self, id: NodeId) -> &Entry<'a> {
+        let entry = &self.nodes[id.index()];
+        assert!(!entry.removed, "node {} was removed", id.index());
+        entry
+    }
+
+    #[must_use]
+    pub fn get(&self, id: NodeId) -> &ArenaNode<'a> {
+        &self.entry(id).node
+    }
+
+    /// The parent of the node, `None` for top-level and detached nodes
+    #[must_use]
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id).parent
+    }
+
+    /// Attributes of the node, empty unless it is an element
+    #[must_use]
+    pub fn attributes(&self, id: NodeId) -> &[Attribute<'a>] {
+        match &self.entry(id).node {
+            ArenaNode::Element { attributes, .. } => &self.attributes[attributes.clone()],
+            _ => &[],
+        }
+    }
+
+    /// Iterates over the top-level nodes
+    #[must_use]
+    pub fn roots(&self) -> Children<'_, 'a> {
+        Children {
+            document: self,
+            next: self.first_root,
+        }
+    }
+
+    /// Iterates over the direct children of the node
+    #[must_use]
+    pub fn children(&self, id: NodeId) -> Children<'_, 'a> {
+        Children {
+            document: self,
+            next: self.entry(id).first_child,
+        }
+    }
+
+    // The first and last child of the parent, or the first and last top-level node
+    fn ends(&mut self, parent: Option<NodeId>) -> (&mut Option<NodeId>, &mut Option<NodeId>) {
+        match parent {
+            Some(parent) => {
+                let parent = &mut self.nodes[parent.index()];
+                (&mut parent.first_child, &mut parent.last_child)
+            }
+            None => (&mut self.first_root, &mut self.last_root),
+        }
+    }
+
+    // Links an unlinked node as the last child of the parent
+    fn link(&mut self, parent: Option<NodeId>, id: NodeId) {
+        self.nodes[id.index()].parent = parent;
+        let (first, last) = self.ends(parent);
+        let previous = last.replace(id);
+        first.get_or_insert(id);
+        if let Some(previous) = previous {
+            self.nodes[previous.index()].next_sibling = Some(id);
+        }
+    }
+
+    /// Copies a node and its descendants into the document, as the last child of
+    /// `parent` or as the last top-level node
+    ///
+    /// # Panics
+    /// Panics if the document holds more than `u32::MAX` nodes
+    pub fn append(&mut self, parent: Option<NodeId>, node: &Node<'a>) -> NodeId {
+        let node_data = match node {
+            Node::Text(text) => ArenaNode::Text(text.clone()),
+            Node::Raw(raw) => ArenaNode::Raw(raw.clone()),
+            Node::Comment(comment) => ArenaNode::Comment(comment.clone()),
+            Node::Slot(name) => ArenaNode::Slot(name.clone()),
+            Node::Directive(directive) => ArenaNode::Directive(directive.clone()),
+            #[cfg(feature = "i18n")]
+            Node::Translation(translation) => ArenaNode::Translation(translation.clone()),
+            Node::Element(element) => self.element_data(element),
+            Node::Shared(element) => self.element_data(element),
+        };
+        let id = NodeId(u32::try_from(self.nodes.len()).expect("too many nodes for an arena"));
+        self.nodes.push(Entry {
+            node: node_data,
+            parent,
+            next_sibling: None,
+            first_child: None,
+            last_child: None,
+            removed: false,
+        });
+        self.link(parent, id);
+        if let Some(element) = node.as_element() {
+            for child in &element.children {
+                self.append(Some(id), child);
+            }
+        }
+        id
+    }
+
+    /// Unlinks the node from its parent or the top-level nodes, keeping it and its
+    /// descendants to be attached again, does nothing if it is already detached
+    ///
+    /// # Panics
+    /// Panics if the node was removed
+    pub fn detach(&mut self, id: NodeId) {
+        let parent = self.entry(id).parent;
+        let next = self.nodes[id.index()].next_sibling;
+        // Siblings are singly linked, so the previous one is found from the first
+        let mut previous = None;
+        let mut current = *self.ends(parent).0;
+        while current != Some(id) {
+            let Some(sibling) = current else {
+                return;
+            };
+            previous = current;
+            current = self.nodes[sibling.index()].next_sibling;
+        }
+        match previous {
+            Some(previous) => self.nodes[previous.index()].next_sibling = next,
+            None => *self.ends(parent).0 = next,
+        }
+        let last = self.ends(parent).1;
+        if *last == Some(id) {
+            *last = previous;
+        }
+        let entry = &mut self.nodes[id.index()];
+        entry.parent = None;
+        entry.next_sibling = None;
+    }
+
+    /// Moves the node and its descendants to the end of the parent's children, or of
+    /// the top-level nodes
+    ///
+    /// # Panics
+    /// Panics if either node was removed, or the parent is the node or one of its
+    /// descendants
+    pub fn attach(&mut self, parent: Option<NodeId>, id: NodeId) {
+        let mut ancestor = parent;
+        while let Some(current) = ancestor {
+            assert!(current != id, "cannot attach a node inside itself");
+            ancestor = self.entry(current).parent;
+        }
+        self.detach(id);
+        self.link(parent, id);
+    }
+
+    /// Removes the node and its descendants, whose ids go stale
+    ///
+    /// # Panics
+    /// Panics if the node was already removed
+    pub fn remove(&mut self, id: NodeId) {
+        self.detach(id);
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            pending.extend(self.children(id));
+            self.nodes[id.index()].removed = true;
+            self.removed += 1;
+        }
+    }
+
+    // Stores the element's attributes, returning its content without children
+    fn element_data(&mut self, element: &Element<'a>) -> ArenaNode<'a> {
+        let start = self.attributes.len();
+        self.attributes.extend(element.attributes.iter().cloned());
+        ArenaNode::Element {
+            name: element.name.clone(),
+            namespace: element.namespace,
+            attributes: start..self.attributes.len(),
+        }
+    }
+
+    /// Copies the node and its descendants back into an owned node
+    #[must_use]
+    pub fn to_node(&self, id: NodeId) -> Node<'a> {
+        match self.get(id) {
+            ArenaNode::Text(text) => Node::Text(text.clone()),
+            ArenaNode::Raw(raw) => Node::Raw(raw.clone()),
+            ArenaNode::Comment(comment) => Node::Comment(comment.clone()),
+            ArenaNode::Slot(name) => Node::Slot(name.clone()),
+            ArenaNode::Directive(directive) => Node::Directive(directive.clone()),
+            #[cfg(feature = "i18n")]
+            ArenaNode::Translation(translation) => Node::Translation(translation.clone()),
+            ArenaNode::Element {
+                name, namespace, ..
+            } => {
+                let mut element = Element::new(name.clone());
+                element.namespace = *namespace;
+                element.attributes = self.attributes(id).to_vec();
+                element.children = self.children(id).map(|child| self.to_node(child)).collect();
+                Node::Element(element)
+            }
+        }
+    }
+}
+
+/// Iterator over sibling nodes of an [`ArenaDocument`]
+#[derive(Debug, Clone)]
+pub struct Children<'d, 'a> {
+    document: &'d ArenaDocument<'a>,
+    next: Option<NodeId>,
+}
+
+impl Iterator for Children<'_, '_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.next?;
+        self.next = self.document.nodes[id.index()].next_sibling;
+        Some(id)
+    }
+}
+
+impl<'a> From<&Block<'a>> for ArenaDocument<'a> {
+    fn from(block: &Block<'a>) -> Self {
+        let mut document = ArenaDocument::new();
+        for node in &block.children {
+            document.append(None, node);
+        }
+        document
+    }
+}
+
+impl<'a> From<&ArenaDocument<'a>> for Block<'a> {
+    fn from(document: &ArenaDocument<'a>) -> Self {
+        document.roots().map(|id| document.to_node(id)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArenaDocument, ArenaNode};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_arena_roundtrip() {
+        let input = r#"
+            ul {
+                .class = "menu"
+                li { a { .href = "/" "Home" } }
+                li { "About" }
+            }
+            p { "Footer" }"#;
+        let (_, block) = Block::parse_ignoring_comments(input).unwrap();
+        let (_, arena) = ArenaDocument::parse(input).unwrap();
+        assert_eq!(arena.len(), 8);
+        assert_eq!(Block::from(&arena), block);
+
+        let list = arena.roots().next().unwrap();
+        assert_eq!(arena.attributes(list), [Attribute::class("menu")]);
+        let items: Vec<_> = arena.children(list).collect();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| arena.parent(*item) == Some(list)));
+        let about = arena.children(items[1]).next().unwrap();
+        assert_eq!(arena.get(about), &ArenaNode::Text(Text::new("About")));
+    }
+
+    // The document's markup, e.g. to compare trees after moving nodes
+    fn render(arena: &ArenaDocument) -> String {
+        Block::from(arena).render()
+    }
+
+    #[test]
+    fn test_detach_and_attach() {
+        let (_, mut arena) =
+            ArenaDocument::parse(r#"ul { li { "a" } li { "b" } li { "c" } } ol {}"#).unwrap();
+        let mut roots = arena.roots();
+        let (ul, ol) = (roots.next().unwrap(), roots.next().unwrap());
+        let items: Vec<_> = arena.children(ul).collect();
+
+        // Detaching the middle, last and first child keeps the links in order
+        arena.detach(items[1]);
+        assert_eq!(render(&arena), "<ul><li>a</li><li>c</li></ul><ol></ol>");
+        assert_eq!(arena.parent(items[1]), None);
+        assert_eq!(arena.len(), 8);
+        arena.detach(items[1]);
+        arena.detach(items[2]);
+        arena.detach(items[0]);
+        assert_eq!(render(&arena), "<ul></ul><ol></ol>");
+
+        // Detached nodes keep their descendants and can be attached anywhere
+        arena.attach(Some(ol), items[2]);
+        arena.attach(Some(ol), items[0]);
+        arena.attach(None, items[1]);
+        assert_eq!(
+            render(&arena),
+            "<ul></ul><ol><li>c</li><li>a</li></ol><li>b</li>"
+        );
+        assert_eq!(arena.parent(items[0]), Some(ol));
+        assert_eq!(arena.parent(items[1]), None);
+
+        // Attaching moves a node from where it is, here to the end of its own list
+        arena.attach(Some(ol), items[2]);
+        arena.attach(Some(items[2]), ul);
+        arena.attach(None, ol);
+        assert_eq!(
+            render(&arena),
+            "<li>b</li><ol><li>a</li><li>c<ul></ul></li></ol>"
+        );
+        assert_eq!(arena.children(ol).count(), 2);
+        assert_eq!(arena.roots().count(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot attach a node inside itself")]
+    fn test_attach_inside_itself() {
+        let (_, mut arena) = ArenaDocument::parse(r#"div { p { "a" } }"#).unwrap();
+        let div = arena.roots().next().unwrap();
+        let p = arena.children(div).next().unwrap();
+        arena.attach(Some(p), div);
+    }
+
+    #[test]
+    fn test_remove() {
+        let (_, mut arena) = ArenaDocument::parse(
+            r#"ul { .class = "menu" li { a { "Home" } } li { "About" } } p { "Footer" }"#,
+        )
+        .unwrap();
+        let list = arena.roots().next().unwrap();
+        let items: Vec<_> = arena.children(list).collect();
+        let home = arena.children(items[0]).next().unwrap();
+        let text = arena.children(home).next().unwrap();
+        assert_eq!(arena.len(), 8);
+
+        arena.remove(items[0]);
+        assert_eq!(arena.len(), 5);
+        assert_eq!(
+            render(&arena),
+            r#"<ul class="menu"><li>About</li></ul><p>Footer</p>"#
+        );
+        for stale in [items[0], home, text] {
+            assert!(!arena.contains(stale));
+        }
+        assert!(arena.contains(items[1]));
+
+        // Appending after a removal never reuses a stale id
+        let (_, new) = Block::parse_ignoring_comments(r#"li { "Blog" }"#).unwrap();
+        let blog = arena.append(Some(list), &new.children[0]);
+        assert!(![items[0], home, text].contains(&blog));
+        assert!(!arena.contains(items[0]));
+        assert_eq!(arena.children(list).collect::<Vec<_>>(), [items[1], blog]);
+
+        // Removing a top-level node, then everything
+        arena.remove(list);
+        assert_eq!(render(&arena), "<p>Footer</p>");
+        let footer = arena.roots().next().unwrap();
+        arena.remove(footer);
+        assert!(arena.is_empty());
+        assert_eq!(arena.roots().count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "was removed")]
+    fn test_stale_id() {
+        let (_, mut arena) = ArenaDocument::parse(r#"p { "a" } p { "b" }"#).unwrap();
+        let first = arena.roots().next().unwrap();
+        let text = arena.children(first).next().unwrap();
+        arena.remove(first);
+        let _ = arena.get(text);
+    }
+}
@@ -0,0 +1,54 @@
+use axum_core::{
+    body::Body,
+    response::{IntoResponse, Response},
+};
+use http::{HeaderValue, header::CONTENT_TYPE};
+
+use crate::{prelude::*, render::HTML_CONTENT_TYPE};
+
+fn html_response(html: String) -> Response {
+    let mut response = Response::new(Body::from(html));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(HTML_CONTENT_TYPE));
+    response
+}
+
+impl IntoResponse for Block<'_> {
+    fn into_response(self) -> Response {
+        html_response(self.render())
+    }
+}
+
+impl IntoResponse for Element<'_> {
+    fn into_response(self) -> Response {
+        html_response(self.render())
+    }
+}
+
+impl IntoResponse for Node<'_> {
+    fn into_response(self) -> Response {
+        html_response(self.render())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_core::response::IntoResponse;
+    use http::{StatusCode, header::CONTENT_TYPE};
+
+    use crate::prelude::*;
+
+    #[test]
+    fn test_into_response() {
+        let response = Document::new()
+            .with_child(element("h1").with_child("Hi"))
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/html; charset=utf-8");
+
+        let response = (StatusCode::NOT_FOUND, element("p").with_child("Missing")).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/html; charset=utf-8");
+    }
+}
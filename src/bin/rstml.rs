@@ -0,0 +1,205 @@
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use rs_tml::{
+    convert::format,
+    error::{ParseError, line_column},
+    parse::parse_document,
+    prelude::*,
+    templates::TEMPLATE_EXTENSION,
+};
+
+const USAGE: &str = "\
+Usage: rstml <command> <paths>...
+
+Commands:
+    fmt [--check] <paths>...  Formats files in place, with --check lists unformatted files instead
+    check <paths>...          Reports parse errors with their line and column
+    render <file>             Prints the file rendered as HTML
+    serve <dir> [<address>]   Serves the templates of the directory on 127.0.0.1:8000
+                              unless set, reloading pages when they change; needs
+                              the dev-server feature
+
+Directories are searched recursively for .rstml files. Exits with 1 if a file
+has errors or, with --check, is not formatted.";
+
+// Collects the files to process, searching directories for templates
+fn files(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+        let error = |error: std::io::Error| format!("{}: {error}", dir.display());
+        let mut entries = std::fs::read_dir(dir)
+            .map_err(error)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)?;
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                walk(&path, files)?;
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext == TEMPLATE_EXTENSION)
+            {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    if paths.is_empty() {
+        return Err(format!("No paths given\n\n{USAGE}"));
+    }
+    let mut files = Vec::new();
+    for path in paths.iter().map(PathBuf::from) {
+        if path.is_dir() {
+            walk(&path, &mut files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))
+}
+
+fn report(path: &Path, source: &str, error: &ParseError) {
+    match error.offset(source) {
+        Some(offset) => {
+            let (line, column) = line_column(source, offset);
+            eprintln!("{}:{line}:{column}: error: {error}", path.display());
+        }
+        None => eprintln!("{}: error: {error}", path.display()),
+    }
+}
+
+// Whether the source has `//` or `/* */` comments outside strings
+fn has_comments(source: &str) -> bool {
+    let mut chars = source.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            '/' if !in_string && matches!(chars.peek(), Some('/' | '*')) => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn fmt(args: &[String]) -> Result<bool, String> {
+    let check = args.first().is_some_and(|arg| arg == "--check");
+    let args = if check { &args[1..] } else { args };
+    let mut ok = true;
+    for path in files(args)? {
+        let source = read(&path)?;
+        let formatted = match format(&source) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                report(&path, &source, &error);
+                ok = false;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        // The formatter works on the parsed document, which has no comments
+        if has_comments(&source) {
+            eprintln!(
+                "{}: warning: skipped, formatting would remove its comments",
+                path.display()
+            );
+        } else if check {
+            println!("{}", path.display());
+            ok = false;
+        } else {
+            std::fs::write(&path, formatted)
+                .map_err(|error| format!("{}: {error}", path.display()))?;
+        }
+    }
+    Ok(ok)
+}
+
+fn check(args: &[String]) -> Result<bool, String> {
+    let mut ok = true;
+    for path in files(args)? {
+        let source = read(&path)?;
+        if let Err(error) = parse_document(&source) {
+            report(&path, &source, &error);
+            ok = false;
+        }
+    }
+    Ok(ok)
+}
+
+fn render(args: &[String]) -> Result<bool, String> {
+    let [path] = args else {
+        return Err(format!("Expected a single file to render\n\n{USAGE}"));
+    };
+    let path = Path::new(path);
+    let source = read(path)?;
+    match parse_document(&source) {
+        Ok(document) => {
+            println!("{}", document.render());
+            Ok(true)
+        }
+        Err(error) => {
+            report(path, &source, &error);
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(feature = "dev-server")]
+fn serve(args: &[String]) -> Result<bool, String> {
+    let (dir, address) = match args {
+        [dir] => (dir, "127.0.0.1:8000"),
+        [dir, address] => (dir, address.as_str()),
+        _ => return Err(format!("Expected a directory to serve\n\n{USAGE}")),
+    };
+    let mut server = rs_tml::dev::DevServer::new(dir).map_err(|error| error.to_string())?;
+    eprintln!("Serving {dir} on http://{address}");
+    server
+        .serve(address)
+        .map_err(|error| format!("{address}: {error}"))?;
+    Ok(true)
+}
+
+#[cfg(not(feature = "dev-server"))]
+fn serve(_: &[String]) -> Result<bool, String> {
+    Err("`serve` needs rstml built with the dev-server feature".into())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, args)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+    let result = match command.as_str() {
+        "fmt" => fmt(args),
+        "check" => check(args),
+        "render" => render(args),
+        "serve" => serve(args),
+        "help" | "-h" | "--help" => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => Err(format!("Unknown command `{command}`\n\n{USAGE}")),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::from(2)
+        }
+    }
+}
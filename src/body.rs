@@ -0,0 +1,80 @@
+use std::{
+    convert::Infallible,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http_body::{Body, Frame};
+
+use crate::prelude::*;
+
+/// Response body that renders a document one top-level node at a time
+///
+/// Each node is rendered only when the previous chunk has been taken, so very
+/// large pages are never buffered whole. Usable directly as a hyper body, or with
+/// axum through `axum::body::Body::new`.
+#[derive(Debug)]
+pub struct HtmlBody<'a> {
+    nodes: std::vec::IntoIter<Node<'a>>,
+}
+
+impl<'a> From<Block<'a>> for HtmlBody<'a> {
+    fn from(block: Block<'a>) -> Self {
+        HtmlBody {
+            nodes: block.children.into_iter(),
+        }
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Converts the document into a streaming [`HtmlBody`]
+    #[must_use]
+    pub fn into_body(self) -> HtmlBody<'a> {
+        self.into()
+    }
+}
+
+impl Body for HtmlBody<'_> {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        let chunk = self.get_mut().nodes.next().map(|node| node.render());
+        Poll::Ready(chunk.map(|html| Ok(Frame::data(Bytes::from(html)))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.nodes.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::Pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use http_body::Body;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn test_body_streams_top_level_nodes() {
+        let mut body = Document::new()
+            .with_child(element("h1").with_child("Report"))
+            .with_child(element("p").with_child("1 & 2"))
+            .into_body();
+        let mut context = Context::from_waker(Waker::noop());
+        let mut chunks = Vec::new();
+        while let Poll::Ready(Some(frame)) = Pin::new(&mut body).poll_frame(&mut context) {
+            chunks.push(frame.unwrap().into_data().unwrap());
+        }
+        assert_eq!(chunks, ["<h1>Report</h1>", "<p>1 &amp; 2</p>"]);
+        assert!(body.is_end_stream());
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    prelude::*,
+    render::{render_element, render_node},
+};
+
+/// Rendered fragments keyed by their fingerprint, so static subtrees render once
+///
+/// Documents rendered with [`RenderCache::render`] look up their
+/// [`Node::Shared`] elements, the fragments such as headers and navigation that
+/// pages hold without cloning, and only render them on a miss. Hashing a subtree
+/// is much cheaper than rendering it, as nothing is escaped or written. Any other
+/// node can be cached with [`RenderCache::render_node`].
+///
+/// Entries are keyed by [`Node::fingerprint`], so a mutated tree misses and is
+/// rendered again, and the same fragment in different trees shares one entry. As
+/// fingerprints ignore the order of attributes, fragments differing only in that
+/// order share the rendering of whichever was cached first. Stale entries are
+/// dropped with the invalidation methods, or bounded with
+/// [`RenderCache::with_max_entries`].
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    entries: Mutex<HashMap<(u64, Namespace), Arc<str>>>,
+    max_entries: Option<usize>,
+}
+
+impl RenderCache {
+    #[must_use]
+    pub fn new() -> Self {
+        RenderCache::default()
+    }
+
+    /// Bounds the number of cached fragments, evicting an arbitrary one when full
+    #[must_use]
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Renders the document, taking its shared elements from the cache
+    #[must_use]
+    pub fn render(&self, block: &Block) -> String {
+        let mut out = String::new();
+        for node in block.iter_nodes() {
+            render_node(node, Namespace::Html, Some(self), &mut out)
+                .expect("writing to a String cannot fail");
+        }
+        out
+    }
+
+    /// Returns the rendered node from the cache, rendering and storing it on a miss
+    pub fn render_node(&self, node: &Node) -> Arc<str> {
+        self.get_or_render((node.fingerprint(), Namespace::Html), |out| {
+            render_node(node, Namespace::Html, Some(self), out)
+        })
+    }
+
+    // Shared elements render differently inside foreign content, so the parent is
+    // part of the key
+    pub(crate) fn render_shared(&self, element: &Element, parent: Namespace) -> Arc<str> {
+        self.get_or_render((element.fingerprint(), parent), |out| {
+            render_element(element, parent, Some(self), out)
+        })
+    }
+
+    fn get_or_render(
+        &self,
+        key: (u64, Namespace),
+        render: impl FnOnce(&mut String) -> std::fmt::Result,
+    ) -> Arc<str> {
+        if let Some(html) = self.lock().get(&key) {
+            return html.clone();
+        }
+        // Rendered without the lock held, as nested shared elements take it again
+        let mut out = String::new();
+        render(&mut out).expect("writing to a String cannot fail");
+        let html: Arc<str> = out.into();
+        let mut entries = self.lock();
+        if let Some(max_entries) = self.max_entries
+            && entries.len() >= max_entries
+            && let Some(evicted) = entries.keys().next().copied()
+        {
+            entries.remove(&evicted);
+        }
+        if self.max_entries != Some(0) {
+            entries.insert(key, html.clone());
+        }
+        html
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<(u64, Namespace), Arc<str>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Drops the cached rendering of the node, e.g. before mutating a subtree whose
+    /// old markup will not be rendered again. Returns true if it was cached.
+    pub fn invalidate(&self, node: &Node) -> bool {
+        self.invalidate_fingerprint(node.fingerprint())
+    }
+
+    /// Drops the cached renderings with the fingerprint, see [`Node::fingerprint`]
+    pub fn invalidate_fingerprint(&self, fingerprint: u64) -> bool {
+        let mut entries = self.lock();
+        let len = entries.len();
+        entries.retain(|(cached, _), _| *cached != fingerprint);
+        entries.len() != len
+    }
+
+    /// Keeps only the cached renderings whose fingerprint matches the predicate
+    pub fn retain(&self, mut predicate: impl FnMut(u64) -> bool) {
+        self.lock()
+            .retain(|(fingerprint, _), _| predicate(*fingerprint));
+    }
+
+    /// Drops every cached rendering
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Number of cached renderings
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenderCache;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_render_cache() {
+        let nav = Node::shared(
+            element("nav").with_child(element("a").with_key_value("href", "/").with_child("Home")),
+        );
+        let page = |title: &str| {
+            Block::new()
+                .with_child(nav.clone())
+                .with_child(element("h1").with_child(title.to_string()))
+        };
+        let cache = RenderCache::new();
+        for title in ["One", "Two"] {
+            assert_eq!(cache.render(&page(title)), page(title).render());
+        }
+        assert_eq!(cache.len(), 1);
+
+        // A mutated fragment misses, the old entry stays until invalidated
+        let mut edited = nav.clone();
+        edited.as_element_mut().unwrap().add_child("!");
+        assert_eq!(&*cache.render_node(&edited), edited.render());
+        assert_eq!(cache.len(), 2);
+        assert!(cache.invalidate(&nav));
+        assert!(!cache.invalidate(&nav));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_max_entries() {
+        let cache = RenderCache::new().with_max_entries(2);
+        for i in 0..5 {
+            cache.render_node(&Node::text(i.to_string()));
+        }
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,589 @@
+use std::borrow::Cow;
+
+use crate::{attribute::MergePolicy, prelude::*};
+
+/// A labelled link of [`Breadcrumbs`] or a [`NavBar`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link<'a> {
+    pub label: Cow<'a, str>,
+    pub href: Cow<'a, str>,
+}
+
+impl<'a> Link<'a> {
+    pub fn new(label: impl Into<Cow<'a, str>>, href: impl Into<Cow<'a, str>>) -> Self {
+        Link {
+            label: label.into(),
+            href: href.into(),
+        }
+    }
+}
+
+// Attributes given by the caller, merged into the built elements so classes are
+// joined and other keys replaced
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Injected<'a> {
+    root: Vec<Attribute<'a>>,
+    links: Vec<Attribute<'a>>,
+}
+
+impl<'a> Injected<'a> {
+    fn root(&self, mut root: Element<'a>) -> Element<'a> {
+        root.merge_attributes(self.root.iter().cloned(), MergePolicy::DEFAULT);
+        root
+    }
+
+    fn link(&self, href: impl Into<Cow<'a, str>>, label: impl Into<Cow<'a, str>>) -> Element<'a> {
+        let mut link = element("a")
+            .with_key_value("href", href)
+            .with_child(Node::text(label));
+        link.merge_attributes(self.links.iter().cloned(), MergePolicy::DEFAULT);
+        link
+    }
+}
+
+// The path of a URL without its origin, query, fragment or trailing slash
+fn url_path(url: &str) -> &str {
+    let url = match url.find("://") {
+        Some(scheme) => {
+            let rest = &url[scheme + 3..];
+            rest.find('/').map_or("", |path| &rest[path..])
+        }
+        None => url,
+    };
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    match path.trim_end_matches('/') {
+        "" => "/",
+        path => path,
+    }
+}
+
+/// Links to the pages of a paginated list, built into a `nav` with
+/// [`Pagination::to_element`]
+///
+/// Pages count from 1, a page outside of `1..=total` is shown as the nearest one.
+/// The first and last pages are always linked, along with the
+/// pages within [`Pagination::window`] of the current one, and the gaps between
+/// them are shown as `…`. Links go to `?page=N` unless set with
+/// [`Pagination::with_href_fn`].
+pub struct Pagination<'a> {
+    pub page: usize,
+    pub total: usize,
+    /// How many pages are linked on each side of the current one, 2 unless set
+    pub window: usize,
+    pub previous: Cow<'a, str>,
+    pub next: Cow<'a, str>,
+    href: Box<dyn Fn(usize) -> String + 'a>,
+    injected: Injected<'a>,
+}
+
+impl<'a> Pagination<'a> {
+    /// Pagination on the page out of the total number of pages
+    pub fn new(page: usize, total: usize) -> Self {
+        Pagination {
+            page,
+            total,
+            window: 2,
+            previous: Cow::Borrowed("Previous"),
+            next: Cow::Borrowed("Next"),
+            href: Box::new(|page| format!("?page={page}")),
+            injected: Injected::default(),
+        }
+    }
+
+    pub fn set_href_fn(&mut self, href: impl Fn(usize) -> String + 'a) {
+        self.href = Box::new(href);
+    }
+    #[must_use]
+    pub fn with_href_fn(mut self, href: impl Fn(usize) -> String + 'a) -> Self {
+        self.set_href_fn(href);
+        self
+    }
+
+    pub fn set_window(&mut self, window: usize) {
+        self.window = window;
+    }
+    #[must_use]
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.set_window(window);
+        self
+    }
+
+    pub fn set_labels(&mut self, previous: impl Into<Cow<'a, str>>, next: impl Into<Cow<'a, str>>) {
+        self.previous = previous.into();
+        self.next = next.into();
+    }
+    #[must_use]
+    pub fn with_labels(
+        mut self,
+        previous: impl Into<Cow<'a, str>>,
+        next: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        self.set_labels(previous, next);
+        self
+    }
+
+    /// Adds an attribute to the `nav`, joining classes with its own
+    pub fn add_attribute(&mut self, attribute: Attribute<'a>) {
+        self.injected.root.push(attribute);
+    }
+    #[must_use]
+    pub fn with_attribute(mut self, attribute: Attribute<'a>) -> Self {
+        self.add_attribute(attribute);
+        self
+    }
+
+    /// Adds an attribute to every link
+    pub fn add_link_attribute(&mut self, attribute: Attribute<'a>) {
+        self.injected.links.push(attribute);
+    }
+    #[must_use]
+    pub fn with_link_attribute(mut self, attribute: Attribute<'a>) -> Self {
+        self.add_link_attribute(attribute);
+        self
+    }
+
+    // The current page, clamped to the pages there are
+    fn current(&self) -> usize {
+        self.page.clamp(1, self.total.max(1))
+    }
+
+    // The linked pages in order, `None` standing for a gap
+    fn pages(&self) -> Vec<Option<usize>> {
+        if self.total == 0 {
+            return Vec::new();
+        }
+        let low = self.current().saturating_sub(self.window).max(1);
+        let high = self.current().saturating_add(self.window).min(self.total);
+        let mut pages = vec![Some(1)];
+        if low > 2 {
+            pages.push(None);
+        }
+        pages.extend((low.max(2)..=high).map(Some));
+        if high < self.total {
+            if high + 1 < self.total {
+                pages.push(None);
+            }
+            pages.push(Some(self.total));
+        }
+        pages
+    }
+
+    // A `rel` link to the page, or a disabled label when there is no such page
+    fn step(&self, label: &Cow<'a, str>, page: Option<usize>, rel: &'a str) -> Element<'a> {
+        let item = match page.filter(|page| (1..=self.total).contains(page)) {
+            Some(page) => self
+                .injected
+                .link((self.href)(page), label.clone())
+                .with_key_value("rel", rel),
+            None => element("span")
+                .with_key_value("aria-disabled", "true")
+                .with_child(Node::text(label.clone())),
+        };
+        element("li").with_child(item)
+    }
+
+    /// Builds the `nav class="pagination"` with a list of the previous, numbered and
+    /// next links, the current page marked with `aria-current="page"`
+    #[must_use]
+    pub fn to_element(&self) -> Element<'a> {
+        let current = self.current();
+        let mut list = element("ul");
+        list.add_child(self.step(&self.previous, current.checked_sub(1), "prev"));
+        for page in self.pages() {
+            let item = match page {
+                Some(page) => {
+                    let link = self.injected.link((self.href)(page), page.to_string());
+                    if page == current {
+                        link.with_key_value("aria-current", "page")
+                    } else {
+                        link
+                    }
+                }
+                None => element("span")
+                    .with_class("ellipsis")
+                    .with_child(Node::text("…")),
+            };
+            list.add_child(element("li").with_child(item));
+        }
+        list.add_child(self.step(&self.next, current.checked_add(1), "next"));
+        self.injected.root(
+            element("nav")
+                .with_key_value("aria-label", "Pagination")
+                .with_class("pagination")
+                .with_child(list),
+        )
+    }
+}
+
+/// The trail of links to the current page, built into a `nav` with
+/// [`Breadcrumbs::to_element`]
+///
+/// The last crumb is the current page. `Breadcrumbs::from("/docs/getting-started")`
+/// builds the trail `Home`, `Docs` and `Getting started` from the path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Breadcrumbs<'a> {
+    pub crumbs: Vec<Link<'a>>,
+    injected: Injected<'a>,
+}
+
+impl<'a> Breadcrumbs<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        Breadcrumbs::default()
+    }
+
+    pub fn add_crumb(&mut self, label: impl Into<Cow<'a, str>>, href: impl Into<Cow<'a, str>>) {
+        self.crumbs.push(Link::new(label, href));
+    }
+    #[must_use]
+    pub fn with_crumb(
+        mut self,
+        label: impl Into<Cow<'a, str>>,
+        href: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        self.add_crumb(label, href);
+        self
+    }
+
+    /// Adds an attribute to the `nav`, joining classes with its own
+    pub fn add_attribute(&mut self, attribute: Attribute<'a>) {
+        self.injected.root.push(attribute);
+    }
+    #[must_use]
+    pub fn with_attribute(mut self, attribute: Attribute<'a>) -> Self {
+        self.add_attribute(attribute);
+        self
+    }
+
+    /// Adds an attribute to every link
+    pub fn add_link_attribute(&mut self, attribute: Attribute<'a>) {
+        self.injected.links.push(attribute);
+    }
+    #[must_use]
+    pub fn with_link_attribute(mut self, attribute: Attribute<'a>) -> Self {
+        self.add_link_attribute(attribute);
+        self
+    }
+
+    /// Builds the `nav class="breadcrumbs"` with an ordered list of the crumbs, the
+    /// last marked with `aria-current="page"`
+    #[must_use]
+    pub fn to_element(&self) -> Element<'a> {
+        let last = self.crumbs.len().saturating_sub(1);
+        let items = self.crumbs.iter().enumerate().map(|(i, crumb)| {
+            let link = self.injected.link(crumb.href.clone(), crumb.label.clone());
+            element("li").with_child(if i == last {
+                link.with_key_value("aria-current", "page")
+            } else {
+                link
+            })
+        });
+        self.injected.root(
+            element("nav")
+                .with_key_value("aria-label", "Breadcrumb")
+                .with_class("breadcrumbs")
+                .with_child(element("ol").with_children(items)),
+        )
+    }
+}
+
+/// A trail from `Home` through each segment of the path, labelled like `Getting
+/// started` for `getting-started`
+impl<'a> From<&str> for Breadcrumbs<'a> {
+    fn from(path: &str) -> Self {
+        let mut crumbs = Breadcrumbs::new().with_crumb("Home", "/");
+        let mut href = String::new();
+        for segment in url_path(path).split('/').filter(|s| !s.is_empty()) {
+            href.push('/');
+            href.push_str(segment);
+            let words = segment.replace(['-', '_'], " ");
+            let mut chars = words.chars();
+            let label: String = chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default();
+            crumbs.add_crumb(label, href.clone());
+        }
+        crumbs
+    }
+}
+
+/// Site navigation links, built into a `nav` with [`NavBar::to_element`]
+///
+/// Given the current URL, the link to it, or else to the closest section containing
+/// it, is marked with the `active` class and `aria-current`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NavBar<'a> {
+    pub links: Vec<Link<'a>>,
+    pub current: Option<Cow<'a, str>>,
+    injected: Injected<'a>,
+}
+
+impl<'a> NavBar<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        NavBar::default()
+    }
+
+    pub fn add_link(&mut self, label: impl Into<Cow<'a, str>>, href: impl Into<Cow<'a, str>>) {
+        self.links.push(Link::new(label, href));
+    }
+    #[must_use]
+    pub fn with_link(
+        mut self,
+        label: impl Into<Cow<'a, str>>,
+        href: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        self.add_link(label, href);
+        self
+    }
+
+    /// Sets the URL of the current page, with or without its origin
+    pub fn set_current(&mut self, url: impl Into<Cow<'a, str>>) {
+        self.current = Some(url.into());
+    }
+    #[must_use]
+    pub fn with_current(mut self, url: impl Into<Cow<'a, str>>) -> Self {
+        self.set_current(url);
+        self
+    }
+
+    /// Adds an attribute to the `nav`, joining classes with its own
+    pub fn add_attribute(&mut self, attribute: Attribute<'a>) {
+        self.injected.root.push(attribute);
+    }
+    #[must_use]
+    pub fn with_attribute(mut self, attribute: Attribute<'a>) -> Self {
+        self.add_attribute(attribute);
+        self
+    }
+
+    /// Adds an attribute to every link
+    pub fn add_link_attribute(&mut self, attribute: Attribute<'a>) {
+        self.injected.links.push(attribute);
+    }
+    #[must_use]
+    pub fn with_link_attribute(mut self, attribute: Attribute<'a>) -> Self {
+        self.add_link_attribute(attribute);
+        self
+    }
+
+    /// The index of the link to the current page, or to the longest path containing it
+    #[must_use]
+    pub fn active(&self) -> Option<usize> {
+        let current = url_path(self.current.as_deref()?);
+        self.links
+            .iter()
+            .enumerate()
+            .filter(|(_, link)| {
+                let href = url_path(&link.href);
+                current == href
+                    || (href != "/"
+                        && current
+                            .strip_prefix(href)
+                            .is_some_and(|rest| rest.starts_with('/')))
+            })
+            .max_by_key(|(_, link)| url_path(&link.href).len())
+            .map(|(i, _)| i)
+    }
+
+    /// Builds the `nav class="navbar"` with a list of the links
+    ///
+    /// The active link gets `aria-current="page"` when it is the current page, and
+    /// `aria-current="true"` when it is a section containing it.
+    #[must_use]
+    pub fn to_element(&self) -> Element<'a> {
+        let active = self.active();
+        let current = self.current.as_deref().map(url_path);
+        let items = self.links.iter().enumerate().map(|(i, link)| {
+            let mut anchor = self.injected.link(link.href.clone(), link.label.clone());
+            if active == Some(i) {
+                let exact = current == Some(url_path(&link.href));
+                anchor.add_class("active");
+                anchor.add_key_value("aria-current", if exact { "page" } else { "true" });
+            }
+            element("li").with_child(anchor)
+        });
+        self.injected.root(
+            element("nav")
+                .with_class("navbar")
+                .with_child(element("ul").with_children(items)),
+        )
+    }
+}
+
+impl<'a> From<Pagination<'a>> for Node<'a> {
+    fn from(pagination: Pagination<'a>) -> Self {
+        pagination.to_element().into()
+    }
+}
+
+impl<'a> From<Breadcrumbs<'a>> for Node<'a> {
+    fn from(breadcrumbs: Breadcrumbs<'a>) -> Self {
+        breadcrumbs.to_element().into()
+    }
+}
+
+impl<'a> From<NavBar<'a>> for Node<'a> {
+    fn from(nav: NavBar<'a>) -> Self {
+        nav.to_element().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Breadcrumbs, NavBar, Pagination};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_pagination() {
+        let pagination = Pagination::new(5, 9)
+            .with_window(1)
+            .with_href_fn(|page| format!("/posts/{page}"))
+            .with_attribute(Attribute::new("class", "mt-4"));
+        assert_eq!(
+            pagination.to_element().render(),
+            concat!(
+                r#"<nav aria-label="Pagination" class="pagination mt-4"><ul>"#,
+                r#"<li><a href="/posts/4" rel="prev">Previous</a></li>"#,
+                r#"<li><a href="/posts/1">1</a></li><li><span class="ellipsis">…</span></li>"#,
+                r#"<li><a href="/posts/4">4</a></li>"#,
+                r#"<li><a href="/posts/5" aria-current="page">5</a></li>"#,
+                r#"<li><a href="/posts/6">6</a></li><li><span class="ellipsis">…</span></li>"#,
+                r#"<li><a href="/posts/9">9</a></li>"#,
+                r#"<li><a href="/posts/6" rel="next">Next</a></li></ul></nav>"#,
+            )
+        );
+        assert_eq!(
+            Pagination::new(1, 1).to_element().render(),
+            concat!(
+                r#"<nav aria-label="Pagination" class="pagination"><ul>"#,
+                r#"<li><span aria-disabled="true">Previous</span></li>"#,
+                r#"<li><a href="?page=1" aria-current="page">1</a></li>"#,
+                r#"<li><span aria-disabled="true">Next</span></li></ul></nav>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_breadcrumbs_and_nav() {
+        assert_eq!(
+            Breadcrumbs::from("/docs/getting-started/?tab=1")
+                .to_element()
+                .render(),
+            concat!(
+                r#"<nav aria-label="Breadcrumb" class="breadcrumbs"><ol>"#,
+                r#"<li><a href="/">Home</a></li><li><a href="/docs">Docs</a></li>"#,
+                r#"<li><a href="/docs/getting-started" aria-current="page">Getting started</a></li>"#,
+                "</ol></nav>",
+            )
+        );
+
+        let nav = NavBar::new()
+            .with_link("Home", "/")
+            .with_link("Docs", "/docs")
+            .with_link("Blog", "/blog")
+            .with_link_attribute(Attribute::new("class", "nav-link"));
+        let current = nav
+            .clone()
+            .with_current("https://example.com/docs/install#setup");
+        assert_eq!(current.active(), Some(1));
+        assert_eq!(
+            current.to_element().render(),
+            concat!(
+                r#"<nav class="navbar"><ul><li><a href="/" class="nav-link">Home</a></li>"#,
+                r#"<li><a href="/docs" class="nav-link active" aria-current="true">Docs</a></li>"#,
+                r#"<li><a href="/blog" class="nav-link">Blog</a></li></ul></nav>"#,
+            )
+        );
+        assert_eq!(nav.clone().with_current("/").active(), Some(0));
+        assert_eq!(nav.with_current("/blog/").active(), Some(2));
+    }
+
+    #[test]
+    fn test_defaults() {
+        // Without an href function, labels or attributes, the defaults are used
+        let pagination = Pagination::new(2, 3);
+        assert_eq!(pagination.window, 2);
+        assert_eq!(
+            pagination.to_element().render(),
+            concat!(
+                r#"<nav aria-label="Pagination" class="pagination"><ul>"#,
+                r#"<li><a href="?page=1" rel="prev">Previous</a></li>"#,
+                r#"<li><a href="?page=1">1</a></li>"#,
+                r#"<li><a href="?page=2" aria-current="page">2</a></li>"#,
+                r#"<li><a href="?page=3">3</a></li>"#,
+                r#"<li><a href="?page=3" rel="next">Next</a></li></ul></nav>"#,
+            )
+        );
+        assert_eq!(
+            Pagination::new(1, 2)
+                .with_labels("<", "Next & last")
+                .to_element()
+                .render(),
+            concat!(
+                r#"<nav aria-label="Pagination" class="pagination"><ul>"#,
+                r#"<li><span aria-disabled="true">&lt;</span></li>"#,
+                r#"<li><a href="?page=1" aria-current="page">1</a></li>"#,
+                r#"<li><a href="?page=2">2</a></li>"#,
+                r#"<li><a href="?page=2" rel="next">Next &amp; last</a></li></ul></nav>"#,
+            )
+        );
+
+        // An empty path or trail still links home, as the current page
+        let home = r#"<nav aria-label="Breadcrumb" class="breadcrumbs"><ol><li><a href="/" aria-current="page">Home</a></li></ol></nav>"#;
+        assert_eq!(Breadcrumbs::from("").to_element().render(), home);
+        assert_eq!(
+            Breadcrumbs::from("https://example.com")
+                .to_element()
+                .render(),
+            home
+        );
+        assert_eq!(
+            Breadcrumbs::new().to_element().render(),
+            r#"<nav aria-label="Breadcrumb" class="breadcrumbs"><ol></ol></nav>"#
+        );
+
+        // Without a current URL, or one outside of the links, no link is active
+        let nav = NavBar::new().with_link("Docs", "/docs");
+        assert_eq!(nav.active(), None);
+        assert_eq!(nav.clone().with_current("/documents").active(), None);
+        assert_eq!(
+            nav.to_element().render(),
+            r#"<nav class="navbar"><ul><li><a href="/docs">Docs</a></li></ul></nav>"#
+        );
+        assert_eq!(
+            NavBar::new().with_current("/").to_element().render(),
+            r#"<nav class="navbar"><ul></ul></nav>"#
+        );
+    }
+
+    #[test]
+    fn test_pages_out_of_range() {
+        // Pages past the end show the last page, page 0 the first
+        assert_eq!(
+            Pagination::new(12, 3).to_element(),
+            Pagination::new(3, 3).to_element()
+        );
+        assert_eq!(
+            Pagination::new(0, 3).to_element(),
+            Pagination::new(1, 3).to_element()
+        );
+        // Without pages there is nothing to link to
+        assert_eq!(
+            Pagination::new(1, 0).to_element().render(),
+            concat!(
+                r#"<nav aria-label="Pagination" class="pagination"><ul>"#,
+                r#"<li><span aria-disabled="true">Previous</span></li>"#,
+                r#"<li><span aria-disabled="true">Next</span></li></ul></nav>"#,
+            )
+        );
+        // Only the linked pages are visited, however many there are
+        assert_eq!(
+            Pagination::new(usize::MAX, usize::MAX)
+                .with_window(1)
+                .pages(),
+            [Some(1), None, Some(usize::MAX - 1), Some(usize::MAX)]
+        );
+    }
+}
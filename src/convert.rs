@@ -0,0 +1,713 @@
+use std::{borrow::Cow, fmt::Write};
+
+use crate::{parse::parse_document, prelude::*, render::is_void_element};
+
+/// Elements whose content is not markup, kept verbatim as a raw node
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Elements whose content is text with character references but no tags
+const ESCAPABLE_RAW_TEXT_ELEMENTS: &[&str] = &["textarea", "title"];
+
+/// Elements in which whitespace is significant
+const PREFORMATTED_ELEMENTS: &[&str] = &["pre", "textarea"];
+
+/// Block-level elements whose start tag closes an open `p`
+const CLOSES_PARAGRAPH: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "div",
+    "dl",
+    "fieldset",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+// Whether opening `tag` implicitly closes the open element `open`, e.g. a second `li`
+fn closes(tag: &str, open: &str) -> bool {
+    match open {
+        "p" => CLOSES_PARAGRAPH.contains(&tag),
+        "li" => tag == "li",
+        "dt" | "dd" => matches!(tag, "dt" | "dd"),
+        "option" => matches!(tag, "option" | "optgroup"),
+        "td" | "th" => matches!(tag, "td" | "th" | "tr"),
+        "tr" => tag == "tr",
+        _ => false,
+    }
+}
+
+fn lowercase(name: &str) -> Cow<'_, str> {
+    if name.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(name.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+fn tag(name: &str) -> Tag<'_> {
+    Tag::from(lowercase(name))
+}
+
+fn entity(name: &str) -> Option<char> {
+    let number = |digits: &str, radix| u32::from_str_radix(digits, radix).ok()?.try_into().ok();
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => match name.strip_prefix('#')? {
+            hex if hex.starts_with(['x', 'X']) => number(&hex[1..], 16),
+            decimal => number(decimal, 10),
+        },
+    }
+}
+
+// Decodes character references, leaving unknown ones as written
+fn decode(input: &str) -> Cow<'_, str> {
+    if !input.contains('&') {
+        return Cow::Borrowed(input);
+    }
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((entity(&rest[1..end])?, end)));
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+// Collapses whitespace runs to a single space, returning `None` for formatting
+// whitespace between tags
+fn collapse(text: &str) -> Option<Cow<'_, str>> {
+    if text.trim_ascii().is_empty() {
+        return (!text.contains('\n')).then_some(Cow::Borrowed(" "));
+    }
+    if !text.contains(['\t', '\n', '\r', '\x0c']) && !text.contains("  ") {
+        return Some(Cow::Borrowed(text));
+    }
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if !c.is_ascii_whitespace() {
+            out.push(c);
+        } else if !out.ends_with(' ') {
+            out.push(' ');
+        }
+    }
+    Some(Cow::Owned(out))
+}
+
+fn text(text: &str, preformatted: bool) -> Option<Node<'_>> {
+    let text = if preformatted {
+        Cow::Borrowed(text)
+    } else {
+        collapse(text)?
+    };
+    match text {
+        Cow::Borrowed(text) => Some(Node::text(decode(text))),
+        Cow::Owned(text) => Some(Node::text(decode(&text).into_owned())),
+    }
+}
+
+// Parses the attributes of a start tag, up to its closing `>`
+//
+// Returns the rest of the input after the tag and whether it was self-closing
+fn attributes<'a>(mut input: &'a str, element: &mut Element<'a>) -> (&'a str, bool) {
+    loop {
+        input = input.trim_start();
+        if let Some(rest) = input.strip_prefix("/>") {
+            return (rest, true);
+        }
+        if let Some(rest) = input.strip_prefix('>') {
+            return (rest, false);
+        }
+        if input.is_empty() {
+            return (input, false);
+        }
+        let end = input
+            .find(|c: char| c.is_ascii_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(input.len())
+            .max(1);
+        let key = lowercase(&input[..end]);
+        input = input[end..].trim_start();
+        let Some(rest) = input.strip_prefix('=') else {
+            if !matches!(key.as_ref(), "/" | "=") {
+                element.add_attribute(Attribute::boolean(key));
+            }
+            continue;
+        };
+        let rest = rest.trim_start();
+        let (value, rest) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let rest = &rest[1..];
+                let end = rest.find(quote).unwrap_or(rest.len());
+                (&rest[..end], rest.get(end + 1..).unwrap_or_default())
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                    .unwrap_or(rest.len());
+                rest.split_at(end)
+            }
+        };
+        element.add_attribute(Attribute::new(key, decode(value)));
+        input = rest;
+    }
+}
+
+struct HtmlParser<'a> {
+    // Open elements, the document's top-level nodes are collected in `root`
+    open: Vec<Element<'a>>,
+    root: Vec<Node<'a>>,
+}
+
+impl<'a> HtmlParser<'a> {
+    fn push(&mut self, node: Node<'a>) {
+        let collapse = !self.preformatted();
+        let siblings = match self.open.last_mut() {
+            Some(parent) => &mut parent.children,
+            None => &mut self.root,
+        };
+        // Text split at a stray `<` continues the text before it
+        if let (Node::Text(text), Some(Node::Text(previous))) = (&node, siblings.last_mut()) {
+            let mut content = text.content.as_ref();
+            if collapse && previous.content.ends_with(' ') {
+                content = content.trim_start_matches(' ');
+            }
+            previous.content.to_mut().push_str(content);
+            return;
+        }
+        siblings.push(node);
+    }
+
+    fn close(&mut self) {
+        if let Some(element) = self.open.pop() {
+            self.push(element.into());
+        }
+    }
+
+    fn preformatted(&self) -> bool {
+        self.open
+            .iter()
+            .any(|open| PREFORMATTED_ELEMENTS.contains(&open.name.as_str()))
+    }
+
+    fn parse(mut self, mut input: &'a str) -> Block<'a> {
+        while !input.is_empty() {
+            if input.starts_with('<') && self.markup(&mut input) {
+                continue;
+            }
+            // Text runs up to the next tag, a stray `<` is part of it
+            let end = match input.find('<') {
+                Some(0) => input[1..].find('<').map_or(input.len(), |end| end + 1),
+                Some(end) => end,
+                None => input.len(),
+            };
+            if let Some(node) = text(&input[..end], self.preformatted()) {
+                self.push(node);
+            }
+            input = &input[end..];
+        }
+        while !self.open.is_empty() {
+            self.close();
+        }
+        Block::new().with_children(self.root)
+    }
+
+    // Parses a tag, comment or declaration, returning false for a stray `<`
+    fn markup(&mut self, input: &mut &'a str) -> bool {
+        let rest = &input[1..];
+        if let Some(rest) = rest.strip_prefix("!--") {
+            let (comment, rest) = rest.split_once("-->").unwrap_or((rest, ""));
+            self.push(Node::comment(comment));
+            *input = rest;
+        } else if rest.starts_with(['!', '?']) {
+            // Doctypes and processing instructions are kept verbatim
+            let end = rest.find('>').map_or(input.len(), |end| end + 2);
+            self.push(Node::raw(&input[..end]));
+            *input = &input[end..];
+        } else if let Some(rest) = rest.strip_prefix('/') {
+            let end = rest.find('>').unwrap_or(rest.len());
+            let name = lowercase(rest[..end].trim());
+            if let Some(depth) = self
+                .open
+                .iter()
+                .rposition(|open| open.name.as_str() == name)
+            {
+                while self.open.len() > depth {
+                    self.close();
+                }
+            }
+            *input = rest.get(end + 1..).unwrap_or_default();
+        } else if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let end = rest
+                .find(|c: char| c.is_ascii_whitespace() || matches!(c, '>' | '/'))
+                .unwrap_or(rest.len());
+            self.start_tag(tag(&rest[..end]), &rest[end..], input);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    fn start_tag(&mut self, name: Tag<'a>, rest: &'a str, input: &mut &'a str) {
+        while self
+            .open
+            .last()
+            .is_some_and(|open| closes(name.as_str(), open.name.as_str()))
+        {
+            self.close();
+        }
+        let mut element = Element::new(name);
+        let (mut rest, self_closing) = attributes(rest, &mut element);
+        let name = element.name.as_str();
+        let raw = RAW_TEXT_ELEMENTS.contains(&name);
+        if raw || ESCAPABLE_RAW_TEXT_ELEMENTS.contains(&name) {
+            // The content runs up to the matching end tag, whatever it looks like
+            let end = rest
+                .to_ascii_lowercase()
+                .find(&format!("</{name}"))
+                .unwrap_or(rest.len());
+            let content = &rest[..end];
+            if !content.is_empty() {
+                let node = if raw {
+                    Some(Node::raw(content))
+                } else {
+                    text(content, name == "textarea")
+                };
+                element.add_children(node);
+            }
+            rest = rest[end..].split_once('>').map_or("", |(_, rest)| rest);
+            self.push(element.into());
+        } else if self_closing || is_void_element(name) {
+            self.push(element.into());
+        } else {
+            self.open.push(element);
+        }
+        *input = rest;
+    }
+}
+
+/// Parses HTML into a document, for migrating existing pages and templates
+///
+/// The parser is lenient and never fails: tag and attribute names are lowercased,
+/// void elements and `p`, `li`, `td` and similar elements are closed implicitly,
+/// unmatched end tags are ignored and elements left open at the end are closed.
+/// `script` and `style` contents and doctypes become raw nodes, `textarea` and `title`
+/// contents text in which tags are not parsed. Outside `pre` and
+/// `textarea`, whitespace runs collapse to a single space and whitespace-only text
+/// spanning lines, which only formats the source, is dropped.
+#[must_use]
+pub fn from_html(input: &str) -> Document<'_> {
+    HtmlParser {
+        open: Vec::new(),
+        root: Vec::new(),
+    }
+    .parse(input)
+}
+
+const INDENT: &str = "    ";
+
+// How strings are quoted when printing RSTML
+#[derive(Clone, Copy)]
+enum Quoting {
+    // As Rust string literals, for the `rstml!` macro
+    Escaped,
+    // As they were read by the runtime parser, which does not unescape them
+    Verbatim,
+}
+
+impl Quoting {
+    fn quote(self, string: &str) -> String {
+        match self {
+            Quoting::Escaped => format!("{string:?}"),
+            Quoting::Verbatim => format!("\"{string}\""),
+        }
+    }
+}
+
+fn write_node<W: Write>(
+    node: &Node,
+    depth: usize,
+    quoting: Quoting,
+    out: &mut W,
+) -> std::fmt::Result {
+    let indent = INDENT.repeat(depth);
+    match node {
+        Node::Text(text) => writeln!(out, "{indent}{}", quoting.quote(&text.content)),
+        Node::Element(element) => write_element(element, depth, quoting, out),
+        Node::Shared(element) => write_element(element, depth, quoting, out),
+        Node::Raw(raw) => writeln!(out, "{indent}raw!({})", quoting.quote(raw)),
+        Node::Comment(comment) => writeln!(out, "{indent}comment!({})", quoting.quote(comment)),
+        Node::Slot(name) => writeln!(out, "{indent}@slot({name})"),
+        Node::Directive(Directive::Extends(name)) => {
+            writeln!(out, "{indent}@extends({})", quoting.quote(name))
+        }
+        Node::Directive(Directive::Include(name)) => {
+            writeln!(out, "{indent}@include({})", quoting.quote(name))
+        }
+        Node::Directive(directive) => {
+            let header = match directive {
+                Directive::Block { name, .. } => format!("@block({name})"),
+                Directive::If { condition, .. } => format!("@if {condition}"),
+                Directive::Else {
+                    condition: Some(condition),
+                    ..
+                } => format!("@else if {condition}"),
+                Directive::Else { .. } => "@else".to_string(),
+                Directive::For { item, path, .. } => format!("@for {item} in {path}"),
+                Directive::Extends(_) | Directive::Include(_) => unreachable!(),
+            };
+            let children = directive.children();
+            if children.is_empty() {
+                return writeln!(out, "{indent}{header} {{}}");
+            }
+            writeln!(out, "{indent}{header} {{")?;
+            for child in children {
+                write_node(child, depth + 1, quoting, out)?;
+            }
+            writeln!(out, "{indent}}}")
+        }
+        #[cfg(feature = "i18n")]
+        Node::Translation(translation) => {
+            write!(out, "{indent}@t({}", quoting.quote(&translation.key))?;
+            for (name, value) in &translation.args {
+                write!(out, ", {name} = {}", quoting.quote(value))?;
+            }
+            writeln!(out, ")")
+        }
+    }
+}
+
+// Whether an id can be written with the `#id` shorthand
+fn is_shorthand_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_alphanumeric() || c == '-')
+}
+
+fn write_element<W: Write>(
+    element: &Element,
+    depth: usize,
+    quoting: Quoting,
+    out: &mut W,
+) -> std::fmt::Result {
+    let indent = INDENT.repeat(depth);
+    let name = element.name.as_str();
+    match (element.attributes.as_slice(), element.children.as_slice()) {
+        ([], []) => return writeln!(out, "{indent}{name} {{}}"),
+        ([], [Node::Text(text)]) => {
+            return writeln!(out, "{indent}{name} {{ {} }}", quoting.quote(&text.content));
+        }
+        _ => {}
+    }
+    writeln!(out, "{indent}{name} {{")?;
+    for attribute in &element.attributes {
+        if attribute.key == "id" && is_shorthand_id(&attribute.value) {
+            writeln!(out, "{indent}{INDENT}#{}", attribute.value)?;
+        } else {
+            writeln!(
+                out,
+                "{indent}{INDENT}.{} = {}",
+                attribute.key,
+                quoting.quote(&attribute.value)
+            )?;
+        }
+    }
+    for child in &element.children {
+        write_node(child, depth + 1, quoting, out)?;
+    }
+    writeln!(out, "{indent}}}")
+}
+
+impl Node<'_> {
+    /// Prints the node as RSTML, one element or text per line, indented by four spaces
+    ///
+    /// Text is written as a Rust string literal and raw markup and comments use the
+    /// `raw!` and `comment!` forms of the `rstml!` macro.
+    #[must_use]
+    pub fn to_rstml(&self) -> String {
+        let mut out = String::new();
+        write_node(self, 0, Quoting::Escaped, &mut out).expect("writing to a String cannot fail");
+        out
+    }
+}
+
+impl Element<'_> {
+    /// Prints the element as RSTML, see [`Node::to_rstml`]
+    #[must_use]
+    pub fn to_rstml(&self) -> String {
+        let mut out = String::new();
+        write_element(self, 0, Quoting::Escaped, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+}
+
+impl Block<'_> {
+    /// Prints the document as RSTML, see [`Node::to_rstml`]
+    #[must_use]
+    pub fn to_rstml(&self) -> String {
+        self.print(Quoting::Escaped)
+    }
+
+    fn print(&self, quoting: Quoting) -> String {
+        let mut out = String::new();
+        for node in &self.children {
+            write_node(node, 0, quoting, &mut out).expect("writing to a String cannot fail");
+        }
+        out
+    }
+}
+
+/// Formats RSTML source in the canonical layout of [`Block::to_rstml`]
+///
+/// Strings are kept as written, since the runtime parser does not unescape them,
+/// so formatting is idempotent. Comments are not part of the parsed document
+/// and are removed.
+///
+/// # Errors
+/// Errors if the input is not a valid RSTML document
+pub fn format(input: &str) -> Result<String, ParseError<'_>> {
+    parse_document(input).map(|document| document.print(Quoting::Verbatim))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, from_html};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_from_html() {
+        let document = from_html(
+            r#"<!DOCTYPE html>
+            <UL class=menu>
+                <li>One &amp; <b>two</b>
+                <li DISABLED>Three</ul>
+            <p>First<p>Second<br/>line
+            <script>if (a < b) {}</script>
+            <!-- note -->"#,
+        );
+        assert_eq!(
+            document,
+            Document::new()
+                .with_child(Node::raw("<!DOCTYPE html>"))
+                .with_child(
+                    element("ul")
+                        .with_key_value("class", "menu")
+                        .with_child(
+                            element("li")
+                                .with_child("One & ")
+                                .with_child(element("b").with_child("two"))
+                        )
+                        .with_child(
+                            element("li")
+                                .with_attribute(Attribute::boolean("disabled"))
+                                .with_child("Three")
+                        )
+                )
+                .with_child(element("p").with_child("First"))
+                .with_child(
+                    element("p")
+                        .with_child("Second")
+                        .with_child(element("br"))
+                        .with_child("line ")
+                        .with_child(element("script").with_child(Node::raw("if (a < b) {}")))
+                        .with_child(Node::comment(" note "))
+                )
+        );
+    }
+
+    #[test]
+    fn test_to_rstml_roundtrip() {
+        let document = Document::new().with_child(
+            element("div")
+                .with_attribute(Attribute::id("main"))
+                .with_key_value("class", "card wide")
+                .with_child(element("h1").with_child("Title"))
+                .with_child(element("hr"))
+                .with_child("Say \"hi\""),
+        );
+        let rstml = document.to_rstml();
+        assert_eq!(
+            rstml,
+            concat!(
+                "div {\n",
+                "    #main\n",
+                "    .class = \"card wide\"\n",
+                "    h1 { \"Title\" }\n",
+                "    hr {}\n",
+                "    \"Say \\\"hi\\\"\"\n",
+                "}\n",
+            )
+        );
+        let (_, parsed) = Document::parse(&rstml).unwrap();
+        assert_eq!(parsed.children[0].as_element().unwrap().children.len(), 3);
+        assert_eq!(
+            parsed.children[0].as_element().unwrap().attributes,
+            document.children[0].as_element().unwrap().attributes
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let input = r#"div{#main .class="a \"b\"" // note
+  p{"one"}   br{} }"#;
+        let formatted = format(input).unwrap();
+        assert_eq!(
+            formatted,
+            concat!(
+                "div {\n",
+                "    #main\n",
+                "    .class = \"a \\\"b\\\"\"\n",
+                "    p { \"one\" }\n",
+                "    br {}\n",
+                "}\n",
+            )
+        );
+        assert_eq!(format(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_raw_text_elements() {
+        let document = from_html(concat!(
+            "<SCRIPT type=module>if (a <b && c> d) { x = '</p>' }</Script >",
+            "<style>p > a::after { content: '&amp;' }</STYLE>",
+            "<textarea name=body>\n  <b>Hi</b> &amp; </div>\n</textarea>",
+            "<title> A  <i>B</i> &lt; C </title>",
+            "<script></script><textarea></textarea><p>After</p>",
+        ));
+        assert_eq!(
+            document,
+            Document::new()
+                .with_child(
+                    element("script")
+                        .with_key_value("type", "module")
+                        .with_child(Node::raw("if (a <b && c> d) { x = '</p>' }"))
+                )
+                .with_child(
+                    element("style").with_child(Node::raw("p > a::after { content: '&amp;' }"))
+                )
+                .with_child(
+                    element("textarea")
+                        .with_key_value("name", "body")
+                        .with_child("\n  <b>Hi</b> & </div>\n")
+                )
+                .with_child(element("title").with_child(" A <i>B</i> < C "))
+                .with_child(element("script"))
+                .with_child(element("textarea"))
+                .with_child(element("p").with_child("After"))
+        );
+        // Text content is escaped again when rendered, raw text is not
+        assert_eq!(
+            from_html("<textarea><b>&amp;</textarea><style>a>b{}</style>").render(),
+            "<textarea>&lt;b&gt;&amp;</textarea><style>a>b{}</style>"
+        );
+        // Unclosed raw text runs to the end of the input
+        assert_eq!(
+            from_html("<script>let a = 1;<p>"),
+            Document::new().with_child(element("script").with_child(Node::raw("let a = 1;<p>")))
+        );
+    }
+
+    #[test]
+    fn test_entities() {
+        let document = from_html(concat!(
+            "<p title=\"&quot;Q&quot; &amp;amp;\">",
+            "&lt;&gt;&amp;&quot;&apos;&nbsp;&#65;&#x42;&#X43; ",
+            "&amp;lt; &unknown; &amp &#xD800; &#99999999; &#; AT&T &;",
+            "</p>",
+        ));
+        let p = document.children[0].as_element().unwrap();
+        assert_eq!(p.attr("title"), Some("\"Q\" &amp;"));
+        assert_eq!(
+            p.children,
+            [Node::text(
+                "<>&\"'\u{a0}ABC &lt; &unknown; &amp &#xD800; &#99999999; &#; AT&T &;"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_malformed_html() {
+        let cases = [
+            ("", Document::new()),
+            ("<", Document::new().with_child("<")),
+            ("a < b <3", Document::new().with_child("a < b <3")),
+            ("a \t<\n b", Document::new().with_child("a < b")),
+            ("< p>x", Document::new().with_child("< p>x")),
+            ("</div>text</p>", Document::new().with_child("text")),
+            (
+                "<div><span>open",
+                Document::new()
+                    .with_child(element("div").with_child(element("span").with_child("open"))),
+            ),
+            (
+                "<b><i>x</b>y</i>",
+                Document::new()
+                    .with_child(element("b").with_child(element("i").with_child("x")))
+                    .with_child("y"),
+            ),
+            (
+                "<a href='x>y",
+                Document::new().with_child(element("a").with_key_value("href", "x>y")),
+            ),
+            (
+                "<a href=",
+                Document::new().with_child(element("a").with_key_value("href", "")),
+            ),
+            (
+                "<p =x / a=>t",
+                Document::new().with_child(
+                    element("p")
+                        .with_attribute(Attribute::boolean("x"))
+                        .with_key_value("a", "")
+                        .with_child("t"),
+                ),
+            ),
+            (
+                "<!-- unclosed",
+                Document::new().with_child(Node::comment(" unclosed")),
+            ),
+            (
+                "<!DOCTYPE html",
+                Document::new().with_child(Node::raw("<!DOCTYPE html")),
+            ),
+            ("<br></br>", Document::new().with_child(element("br"))),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(from_html(input), expected, "{input:?}");
+        }
+    }
+}
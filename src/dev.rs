@@ -0,0 +1,399 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    render::{HTML_CONTENT_TYPE, escape_text},
+    template::Context,
+    templates::{Registry, RegistryError, TEMPLATE_EXTENSION, TemplateLoader},
+};
+
+/// Path of the server-sent events stream telling pages to reload
+pub const RELOAD_PATH: &str = "/__rstml/reload";
+
+/// Script added to every page served, reloading it when the stream says so
+pub const RELOAD_SCRIPT: &str =
+    r#"<script>new EventSource("/__rstml/reload").onmessage = () => location.reload();</script>"#;
+
+// How often the templates are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Events sent on an idle reload stream, to notice closed connections
+const HEARTBEAT_POLLS: u32 = 150;
+
+/// A response of the [`DevServer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn page(status: u16, body: String) -> Self {
+        Response {
+            status,
+            content_type: HTML_CONTENT_TYPE,
+            body: inject_reload_script(body),
+        }
+    }
+
+    // An error page, reloaded like any other once the templates change
+    fn error(status: u16, message: &str) -> Self {
+        Response::page(
+            status,
+            format!(
+                "<!DOCTYPE html><html><body><pre>{}</pre></body></html>",
+                escape_text(message)
+            ),
+        )
+    }
+
+    fn write_to(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        };
+        write!(
+            out,
+            "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+             Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+            self.status,
+            self.content_type,
+            self.body.len()
+        )?;
+        out.write_all(self.body.as_bytes())
+    }
+}
+
+/// Adds the [`RELOAD_SCRIPT`] before the page's last `</body>`, in any case, or at
+/// its end
+#[must_use]
+pub fn inject_reload_script(mut page: String) -> String {
+    match page.to_ascii_lowercase().rfind("</body>") {
+        Some(end) => page.insert_str(end, RELOAD_SCRIPT),
+        None => page.push_str(RELOAD_SCRIPT),
+    }
+    page
+}
+
+/// A development server rendering the templates of a directory, with pages that
+/// reload in the browser whenever a template changes
+///
+/// `/` renders the index template, `index.rstml` unless set, and any other path
+/// the template with that name, with or without its `.rstml` extension, e.g.
+/// `/posts/list` for `posts/list.rstml`. Templates are rendered with the context
+/// set on the server, and pages that fail to render show the error instead.
+/// Pages listen to [`RELOAD_PATH`], a stream of server-sent events, which the
+/// server writes to once the watched templates change.
+///
+/// Meant for prototyping: requests are served one at a time on the calling thread,
+/// apart from the reload streams.
+#[derive(Debug)]
+pub struct DevServer<'c> {
+    registry: Registry,
+    index: String,
+    context: Context<'c>,
+    // Bumped whenever open pages should reload
+    version: Arc<AtomicU64>,
+    epoch: u64,
+    errors: String,
+}
+
+impl<'c> DevServer<'c> {
+    /// Loads and watches the templates of the directory
+    ///
+    /// # Errors
+    /// Errors if the directory cannot be read or watched, or a template fails to parse
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, RegistryError> {
+        let registry = Registry::load(dir)?;
+        registry.watch()?;
+        Ok(DevServer {
+            epoch: registry.epoch(),
+            registry,
+            index: format!("index.{TEMPLATE_EXTENSION}"),
+            context: Context::new(),
+            version: Arc::default(),
+            errors: String::new(),
+        })
+    }
+
+    pub fn set_index(&mut self, name: impl Into<String>) {
+        self.index = name.into();
+    }
+    #[must_use]
+    pub fn with_index(mut self, name: impl Into<String>) -> Self {
+        self.set_index(name);
+        self
+    }
+
+    pub fn set_context(&mut self, context: Context<'c>) {
+        self.context = context;
+    }
+    #[must_use]
+    pub fn with_context(mut self, context: Context<'c>) -> Self {
+        self.set_context(context);
+        self
+    }
+
+    #[must_use]
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Renders the page at the path, which may have a query
+    #[must_use]
+    pub fn respond(&self, path: &str) -> Response {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let name = match path.trim_matches('/') {
+            "" => self.index.clone(),
+            name if name.ends_with(&format!(".{TEMPLATE_EXTENSION}")) => name.to_string(),
+            name => format!("{name}.{TEMPLATE_EXTENSION}"),
+        };
+        match self.registry.render(&name, &self.context) {
+            Ok(page) => Response::page(200, page),
+            Err(RegistryError::NotFound(missing)) if missing == name => {
+                Response::error(404, &format!("No template named {name}"))
+            }
+            Err(error) => Response::error(500, &error.to_string()),
+        }
+    }
+
+    /// Re-reads changed templates, returning true if open pages should reload
+    ///
+    /// Templates that keep failing to parse are retried without reloading the
+    /// pages again, until they are fixed or fail differently.
+    pub fn refresh(&mut self) -> bool {
+        let failed = self.registry.apply_changes();
+        let epoch = self.registry.epoch();
+        if epoch == self.epoch {
+            return false;
+        }
+        self.epoch = epoch;
+        let mut errors: Vec<String> = failed.values().map(ToString::to_string).collect();
+        errors.sort();
+        let errors = errors.join("\n");
+        let reload = errors.is_empty() || errors != self.errors;
+        self.errors = errors;
+        if reload {
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+        reload
+    }
+
+    /// Serves on the address until accepting a connection fails
+    ///
+    /// # Errors
+    /// Errors if the address cannot be bound or the listener fails
+    pub fn serve(&mut self, address: impl ToSocketAddrs) -> std::io::Result<()> {
+        self.serve_on(TcpListener::bind(address)?)
+    }
+
+    /// Serves on a bound listener until accepting a connection fails
+    ///
+    /// # Errors
+    /// Errors if the listener fails
+    pub fn serve_on(&mut self, listener: TcpListener) -> std::io::Result<()> {
+        listener.set_nonblocking(true)?;
+        loop {
+            self.refresh();
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    // A client going away mid-request is not the server's failure
+                    let _ = self.handle(stream);
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                    thread::sleep(POLL_INTERVAL);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn handle(&self, stream: TcpStream) -> std::io::Result<()> {
+        stream.set_nonblocking(false)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        // The headers are not needed, but are read so the client sees a response
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+        let mut parts = request.split_whitespace();
+        let (method, path) = (
+            parts.next().unwrap_or_default(),
+            parts.next().unwrap_or("/"),
+        );
+        let mut stream = stream;
+        if method != "GET" {
+            return Response::error(405, &format!("Cannot {method} {path}")).write_to(&mut stream);
+        }
+        if path == RELOAD_PATH {
+            let version = Arc::clone(&self.version);
+            thread::spawn(move || stream_reloads(stream, &version));
+            return Ok(());
+        }
+        self.respond(path).write_to(&mut stream)
+    }
+}
+
+// Sends an event whenever the version changes, until the page goes away
+fn stream_reloads(mut stream: TcpStream, version: &AtomicU64) -> std::io::Result<()> {
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\n\r\n",
+    )?;
+    let mut seen = version.load(Ordering::SeqCst);
+    let mut idle = 0;
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let current = version.load(Ordering::SeqCst);
+        if current != seen {
+            seen = current;
+            idle = 0;
+            stream.write_all(b"data: reload\n\n")?;
+        } else if idle == HEARTBEAT_POLLS {
+            idle = 0;
+            stream.write_all(b": heartbeat\n\n")?;
+        } else {
+            idle += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{DevServer, RELOAD_SCRIPT, inject_reload_script};
+    use crate::template::Context;
+
+    #[test]
+    fn test_inject_reload_script() {
+        assert_eq!(
+            inject_reload_script("<p>Hi</p>".into()),
+            format!("<p>Hi</p>{RELOAD_SCRIPT}")
+        );
+        assert_eq!(inject_reload_script(String::new()), RELOAD_SCRIPT);
+        assert_eq!(
+            inject_reload_script("<html><body><p>Hi</p></html>".into()),
+            format!("<html><body><p>Hi</p></html>{RELOAD_SCRIPT}")
+        );
+        assert_eq!(
+            inject_reload_script("<BODY>Hi</BODY>".into()),
+            format!("<BODY>Hi{RELOAD_SCRIPT}</BODY>")
+        );
+        // The last `</body>` closes the page, earlier ones are text or code
+        assert_eq!(
+            inject_reload_script("<body><pre>&lt;/body></pre><i>\"</body>\"</i></body>".into()),
+            format!("<body><pre>&lt;/body></pre><i>\"</body>\"</i>{RELOAD_SCRIPT}</body>")
+        );
+    }
+
+    #[test]
+    fn test_error_pages() {
+        let dir = std::env::temp_dir().join(format!("rs-tml-dev-errors-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("index.rstml"), r#"p { "{user.name}" }"#).unwrap();
+        fs::write(
+            dir.join("script.rstml"),
+            r#"script { "let name = {name};" }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("fragment.rstml"), r#"p { "Hi" }"#).unwrap();
+
+        let server = DevServer::new(&dir)
+            .unwrap()
+            .with_context(Context::new().with_value("name", "</pre><b>"));
+        // Pages without a `body` still get the script, at their end
+        assert_eq!(
+            server.respond("/fragment").body,
+            format!("<p>Hi</p>{RELOAD_SCRIPT}")
+        );
+        let missing = server.respond("/");
+        assert_eq!(missing.status, 500);
+        assert_eq!(
+            missing.body,
+            format!(
+                "<!DOCTYPE html><html><body><pre>Cannot render index.rstml: \
+                 No value for placeholder `{{user.name}}`</pre>{RELOAD_SCRIPT}</body></html>"
+            )
+        );
+        // Messages are escaped, so markup in them cannot break out of the page
+        let unsafe_value = server.respond("/script");
+        assert_eq!(unsafe_value.status, 500);
+        assert!(
+            unsafe_value
+                .body
+                .contains("Placeholder `{name}` inside `&lt;script&gt;`")
+        );
+        assert!(!unsafe_value.body.contains("</pre><b>"));
+        let not_found = server.respond("/a<b>");
+        assert_eq!(not_found.status, 404);
+        assert!(
+            not_found
+                .body
+                .contains("<pre>No template named a&lt;b&gt;.rstml</pre>")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dev_server() {
+        let dir = std::env::temp_dir().join(format!("rs-tml-dev-{}", std::process::id()));
+        fs::create_dir_all(dir.join("posts")).unwrap();
+        fs::write(dir.join("index.rstml"), r#"body { h1 { "Hi {name}" } }"#).unwrap();
+        fs::write(dir.join("posts/list.rstml"), r#"ul { "{missing}" }"#).unwrap();
+
+        let mut server = DevServer::new(&dir)
+            .unwrap()
+            .with_context(Context::new().with_value("name", "Ann"));
+        let home = server.respond("/?draft=1");
+        assert_eq!(home.status, 200);
+        assert_eq!(
+            home.body,
+            format!("<body><h1>Hi Ann</h1>{RELOAD_SCRIPT}</body>")
+        );
+        assert_eq!(server.respond("/nope").status, 404);
+        let broken = server.respond("/posts/list");
+        assert_eq!(broken.status, 500);
+        assert!(
+            broken
+                .body
+                .starts_with("<!DOCTYPE html><html><body><pre>Cannot render")
+        );
+        assert!(
+            broken
+                .body
+                .ends_with(&format!("{RELOAD_SCRIPT}</body></html>"))
+        );
+
+        assert!(!server.refresh());
+        fs::write(dir.join("index.rstml"), r#"body { h1 { "Bye {name}" } }"#).unwrap();
+        server.registry().mark_changed("index.rstml");
+        assert!(server.refresh());
+        assert!(
+            server
+                .respond("/")
+                .body
+                .starts_with("<body><h1>Bye Ann</h1>")
+        );
+        // A template that keeps failing to parse reloads pages once
+        fs::write(dir.join("index.rstml"), r#"body { "#).unwrap();
+        server.registry().mark_changed("index.rstml");
+        assert!(server.refresh());
+        assert!(!server.refresh());
+        assert_eq!(server.respond("/").status, 500);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,389 @@
+use crate::{
+    error::line_column,
+    lint::{can_contain, is_known_attribute, suggest_attribute, unknown_tag_message},
+    parse::{ParseOptions, consume_comments, node_error},
+    prelude::*,
+    render::is_void_element,
+    util::{nested, offset_in},
+    validate::Violation,
+};
+
+/// A range of the source, as byte offsets and 1-based lines and columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    pub(crate) fn new(source: &str, start: usize, end: usize) -> Self {
+        let (line, column) = line_column(source, start);
+        let (end_line, end_column) = line_column(source, end);
+        Span {
+            start,
+            end,
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+
+    // The span of `part`, which must be a slice of `source`
+    fn of(source: &str, part: &str) -> Option<Self> {
+        let start = offset_in(source, part)?;
+        Some(Span::new(source, start, start + part.len()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// A problem found in RSTML source, see [`diagnostics`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub span: Span,
+    /// Kind of problem, e.g. `parse-error` or `unknown-tag`
+    pub code: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, span: Span, code: &str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            span,
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Diagnostic {
+    // A warning spanning the element a violation was found on, or its parent for text
+    pub(crate) fn for_violation(
+        source: &str,
+        document: &Block,
+        violation: &Violation,
+    ) -> Option<Self> {
+        let path = violation.path.indices();
+        let element = document.get_path(path)?.as_element().or_else(|| {
+            let parent = &path[..path.len().checked_sub(1)?];
+            document.get_path(parent)?.as_element()
+        })?;
+        let (_, span) = element_spans(source, element)?;
+        Some(Diagnostic::new(
+            Severity::Warning,
+            span,
+            violation.code,
+            &violation.message,
+        ))
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.span.line, self.span.column, self.severity, self.message
+        )
+    }
+}
+
+/// An element in the outline of a document, see [`document_symbols`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocumentSymbol {
+    /// Tag of the element
+    pub name: String,
+    /// The element's `#id` and `.class` names, if any
+    pub detail: Option<String>,
+    /// The whole element, from its tag to its closing brace
+    pub span: Span,
+    /// The element's tag
+    pub selection: Span,
+    pub children: Vec<DocumentSymbol>,
+}
+
+// The spans of the element's tag and of the whole element, if it was parsed from `source`
+fn element_spans(source: &str, element: &Element) -> Option<(Span, Span)> {
+    let name = element.name.as_str();
+    let selection = Span::of(source, name)?;
+    let rest = consume_comments(&source[selection.end..]);
+    let end = nested(rest, "{", "}").map_or(selection.end, |(rest, _)| source.len() - rest.len());
+    Some((Span::new(source, selection.start, end), selection))
+}
+
+struct Checker<'s> {
+    source: &'s str,
+    options: &'s ParseOptions,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn warn(&mut self, span: Span, code: &str, message: String) {
+        self.diagnostics
+            .push(Diagnostic::new(Severity::Warning, span, code, message));
+    }
+
+    fn check_nodes(&mut self, nodes: &[Node], parent: Option<&str>, foreign: bool) {
+        for element in nodes.iter().filter_map(Node::as_element) {
+            self.check_element(element, parent, foreign);
+        }
+    }
+
+    fn check_element(&mut self, element: &Element, parent: Option<&str>, foreign: bool) {
+        let Some((_, span)) = element_spans(self.source, element) else {
+            return;
+        };
+        let tag = element.name.as_str();
+        if !foreign {
+            if let Some(message) = unknown_tag_message(tag) {
+                self.warn(span, "unknown-tag", message);
+            }
+            if let Some(parent) = parent
+                && !can_contain(parent, tag)
+            {
+                self.warn(
+                    span,
+                    "invalid-nesting",
+                    format!("`<{tag}>` cannot be nested inside `<{parent}>`"),
+                );
+            }
+            if is_void_element(tag) && !element.children.is_empty() {
+                self.warn(
+                    span,
+                    "void-children",
+                    format!("Void element `<{tag}>` cannot have children"),
+                );
+            }
+        }
+        // The attributes of a style block are CSS declarations
+        let is_style_block = tag == "style" && parent != Some("head");
+        for attribute in &element.attributes {
+            let key = &attribute.key;
+            if !foreign
+                && !is_style_block
+                && !is_known_attribute(tag, key)
+                && !self.options.is_allowed_attribute(key)
+            {
+                let key_span = Span::of(self.source, key).unwrap_or(span);
+                let message = match suggest_attribute(tag, key) {
+                    Some(suggestion) => format!(
+                        "Unknown attribute `{key}` on `<{tag}>`, did you mean `{suggestion}`?"
+                    ),
+                    None => format!("Unknown attribute `{key}` on `<{tag}>`"),
+                };
+                self.warn(key_span, "unknown-attribute", message);
+                continue;
+            }
+            let kind = if attribute.is_unknown_aria() {
+                "aria"
+            } else if attribute.is_unknown_htmx() {
+                "htmx"
+            } else {
+                continue;
+            };
+            let key_span = Span::of(self.source, &attribute.key).unwrap_or(span);
+            self.warn(
+                key_span,
+                &format!("unknown-{kind}"),
+                format!("Unknown {kind} attribute `{}`", attribute.key),
+            );
+        }
+        let foreign = match tag {
+            "svg" | "math" => true,
+            "foreignObject" => false,
+            _ => foreign,
+        };
+        self.check_nodes(&element.children, Some(tag), foreign);
+    }
+}
+
+/// Checks RSTML source, returning its parse error and lint warnings in source order
+///
+/// Warnings cover unknown tags, invalid nesting, void elements with children and
+/// unknown `aria-*` and `hx-*` attributes, as checked by the `rstml!` macro,
+/// attributes not valid on their element and every use of an id shared by several
+/// elements. Nodes before a parse error are still checked. Subtrees inside `svg`
+/// or `math` are not checked for tags or attributes.
+#[must_use]
+pub fn diagnostics(input: &str) -> Vec<Diagnostic> {
+    diagnostics_with(input, &ParseOptions::default())
+}
+
+/// Checks RSTML source like [`diagnostics`], accepting the attributes the options
+/// allow
+#[must_use]
+pub fn diagnostics_with(input: &str, options: &ParseOptions) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        source: input,
+        options,
+        diagnostics: Vec::new(),
+    };
+    let Ok((rest, document)) = Block::parse_ignoring_comments(input) else {
+        return checker.diagnostics;
+    };
+    checker.check_nodes(&document.children, None, false);
+    for duplicate in document.duplicate_ids() {
+        let message = format!(
+            "Duplicate id `{}`, used by {} elements",
+            duplicate.id,
+            duplicate.paths.len()
+        );
+        for path in &duplicate.paths {
+            let Some(element) = document.get_path(path).and_then(Node::as_element) else {
+                continue;
+            };
+            let span = element
+                .id()
+                .and_then(|id| Span::of(input, id))
+                .or_else(|| element_spans(input, element).map(|(_, tag)| tag));
+            if let Some(span) = span {
+                checker.warn(span, "duplicate-id", message.clone());
+            }
+        }
+    }
+    let rest = consume_comments(rest);
+    if !rest.is_empty() {
+        let error = node_error(rest).first_line();
+        let line = rest.lines().next().unwrap_or(rest);
+        let span = error
+            .offset(input)
+            .and_then(|offset| {
+                let found = input[offset..].lines().next().unwrap_or_default();
+                Span::of(input, found)
+            })
+            .or_else(|| Span::of(input, line))
+            .expect("the rest is a slice of the input");
+        checker.diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            span,
+            "parse-error",
+            error.to_string(),
+        ));
+    }
+    checker.diagnostics.sort_by_key(|d| d.span.start);
+    checker.diagnostics
+}
+
+fn symbols(source: &str, nodes: &[Node]) -> Vec<DocumentSymbol> {
+    nodes
+        .iter()
+        .filter_map(Node::as_element)
+        .filter_map(|element| {
+            let (span, selection) = element_spans(source, element)?;
+            let mut detail: Vec<String> = element
+                .id()
+                .map(|id| format!("#{id}"))
+                .into_iter()
+                .collect();
+            detail.extend(element.classes().map(|class| format!(".{class}")));
+            Some(DocumentSymbol {
+                name: element.name.to_string(),
+                detail: (!detail.is_empty()).then(|| detail.join(" ")),
+                span,
+                selection,
+                children: symbols(source, &element.children),
+            })
+        })
+        .collect()
+}
+
+/// The outline of RSTML source, one symbol per element
+///
+/// Elements before a parse error are still included.
+#[must_use]
+pub fn document_symbols(input: &str) -> Vec<DocumentSymbol> {
+    Block::parse_ignoring_comments(input)
+        .map(|(_, document)| symbols(input, &document.children))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Severity, diagnostics, diagnostics_with, document_symbols};
+    use crate::parse::ParseOptions;
+
+    const SOURCE: &str = "nav {\n    #main\n    p { div { \"x\" } }\n    dvi { .aria-lable = \"a\" }\n}\nspan { .x = }";
+
+    #[test]
+    fn test_diagnostics() {
+        let diagnostics = diagnostics(SOURCE);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.code.as_str(), d.span.line, d.span.column))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Severity::Warning, "invalid-nesting", 3, 9),
+                (Severity::Warning, "unknown-tag", 4, 5),
+                (Severity::Warning, "unknown-aria", 4, 12),
+                (Severity::Error, "parse-error", 6, 8),
+            ]
+        );
+        let duplicates: Vec<_> = super::diagnostics("p { #intro }\np { #intro }")
+            .iter()
+            .map(|d| (d.code.clone(), d.span.line, d.span.column))
+            .collect();
+        assert_eq!(
+            duplicates,
+            [("duplicate-id".into(), 1, 6), ("duplicate-id".into(), 2, 6)]
+        );
+    }
+
+    #[test]
+    fn test_unknown_attributes() {
+        let source = "a { .herf = \"/\" .x-data = \"{}\" .data-id = \"1\" }";
+        let found = diagnostics(source);
+        let messages: Vec<_> = found.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Unknown attribute `herf` on `<a>`, did you mean `href`?",
+                "Unknown attribute `x-data` on `<a>`"
+            ]
+        );
+        assert_eq!((found[0].span.line, found[0].span.column), (1, 6));
+        let options = ParseOptions::new().with_allowed_attribute("x-*");
+        assert_eq!(diagnostics_with(source, &options).len(), 1);
+    }
+
+    #[test]
+    fn test_document_symbols() {
+        let symbols = document_symbols(SOURCE);
+        assert_eq!(symbols.len(), 1);
+        let nav = &symbols[0];
+        assert_eq!(nav.name, "nav");
+        assert_eq!(nav.detail.as_deref(), Some("#main"));
+        assert_eq!(
+            (nav.span.line, nav.span.end_line, nav.span.end_column),
+            (1, 5, 2)
+        );
+        assert_eq!(nav.children.len(), 2);
+        assert_eq!(nav.children[0].children[0].name, "div");
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Mutex, PoisonError},
+};
+
+use dioxus_core::{
+    Attribute as DxAttribute, AttributeValue, DynamicNode, Template, TemplateAttribute,
+    TemplateNode, VNode, VText,
+};
+
+use crate::{lint::HTML_TAGS, prelude::*};
+
+/// Attribute that Dioxus renderers set as the element's inner HTML
+const INNER_HTML: &str = "dangerous_inner_html";
+
+// A single root that is entirely dynamic, for text and fragments
+const DYNAMIC_ROOT: Template = Template {
+    roots: &[TemplateNode::Dynamic { id: 0 }],
+    node_paths: &[&[0]],
+    attr_paths: &[],
+};
+
+/// Most tag and attribute names besides the standard tags kept for Dioxus, which
+/// needs names to live forever; elements using any other name are converted as markup
+pub const MAX_NAMES: usize = 1024;
+
+// Names leaked for Dioxus, at most `MAX_NAMES`
+static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+// Templates must live forever, so one is leaked per tag and namespace, shared by
+// every thread and bounded by the number of names
+static TEMPLATES: Mutex<BTreeMap<(&'static str, Option<&'static str>), Template>> =
+    Mutex::new(BTreeMap::new());
+
+// A template for the tag with dynamic attributes and a single dynamic child
+fn element_template(tag: &'static str, namespace: Option<&'static str>) -> Template {
+    let mut templates = TEMPLATES.lock().unwrap_or_else(PoisonError::into_inner);
+    *templates.entry((tag, namespace)).or_insert_with(|| {
+        let roots = Box::leak(Box::new([TemplateNode::Element {
+            tag,
+            namespace,
+            attrs: &[TemplateAttribute::Dynamic { id: 0 }],
+            children: &[TemplateNode::Dynamic { id: 0 }],
+        }]));
+        Template {
+            roots,
+            node_paths: &[&[0, 0]],
+            attr_paths: &[&[0]],
+        }
+    })
+}
+
+// Returns the name with a `'static` lifetime, standard tags being reused and other
+// names leaked once, or `None` once `MAX_NAMES` names are leaked
+fn intern(name: &str) -> Option<&'static str> {
+    intern_into(
+        &mut NAMES.lock().unwrap_or_else(PoisonError::into_inner),
+        name,
+    )
+}
+
+fn intern_into(names: &mut BTreeSet<&'static str>, name: &str) -> Option<&'static str> {
+    if let Some(known) = HTML_TAGS.iter().find(|tag| **tag == name) {
+        return Some(known);
+    }
+    if let Some(existing) = names.get(name) {
+        return Some(existing);
+    }
+    if names.len() >= MAX_NAMES {
+        return None;
+    }
+    let leaked: &'static str = Box::leak(name.into());
+    names.insert(leaked);
+    Some(leaked)
+}
+
+fn dynamic(node: DynamicNode) -> VNode {
+    VNode::new(None, DYNAMIC_ROOT, Box::new([node]), Box::new([]))
+}
+
+fn element_vnode(element: &Element, raw: Option<&str>) -> VNode {
+    let names: Option<Vec<_>> = std::iter::once(element.name.as_str())
+        .chain(
+            element
+                .attributes
+                .iter()
+                .map(|attribute| attribute.key.as_ref()),
+        )
+        .map(intern)
+        .collect();
+    let Some(names) = names else {
+        return element_vnode(&crate::prelude::element("span"), Some(&element.render()));
+    };
+    let namespace = (!element.namespace.is_html()).then(|| element.namespace.uri());
+    let mut attributes: Vec<_> = element
+        .attributes
+        .iter()
+        .zip(&names[1..])
+        .map(|(attribute, name)| {
+            let value = if attribute.is_boolean() {
+                AttributeValue::Bool(true)
+            } else {
+                AttributeValue::Text(attribute.value.to_string())
+            };
+            DxAttribute::new(name, value, None, false)
+        })
+        .collect();
+    if let Some(raw) = raw {
+        attributes.push(DxAttribute::new(INNER_HTML, raw, None, false));
+    }
+    let children: Vec<VNode> = element.children.iter().map(VNode::from).collect();
+    let children = if children.is_empty() {
+        DynamicNode::default()
+    } else {
+        DynamicNode::Fragment(children)
+    };
+    VNode::new(
+        None,
+        element_template(names[0], namespace),
+        Box::new([children]),
+        Box::new([attributes.into_boxed_slice()]),
+    )
+}
+
+/// Converts a node and its descendants into a Dioxus `VNode`
+///
+/// Tag and attribute names are kept for the program's lifetime, as Dioxus needs
+/// them to live forever. Past [`MAX_NAMES`] distinct names, elements with new names
+/// become the inner HTML of a `span`, so memory stays bounded. Dioxus has no raw or
+/// comment nodes, so raw markup also becomes the inner HTML of a `span` and comments, unfilled slots and unresolved directives become placeholders. Event handlers are
+/// not converted.
+impl From<&Node<'_>> for VNode {
+    fn from(node: &Node<'_>) -> Self {
+        match node {
+            Node::Text(text) => dynamic(DynamicNode::Text(VText::new(&text.content))),
+            Node::Element(element) => element_vnode(element, None),
+            Node::Shared(element) => element_vnode(element, None),
+            Node::Raw(raw) => element_vnode(&element("span"), Some(raw)),
+            Node::Comment(_) | Node::Slot(_) | Node::Directive(_) => {
+                dynamic(DynamicNode::default())
+            }
+            #[cfg(feature = "i18n")]
+            Node::Translation(translation) => {
+                dynamic(DynamicNode::Text(VText::new(&translation.key)))
+            }
+        }
+    }
+}
+
+impl From<&Element<'_>> for VNode {
+    fn from(element: &Element<'_>) -> Self {
+        element_vnode(element, None)
+    }
+}
+
+impl From<Element<'_>> for VNode {
+    fn from(element: Element<'_>) -> Self {
+        element_vnode(&element, None)
+    }
+}
+
+impl From<&Block<'_>> for VNode {
+    fn from(block: &Block<'_>) -> Self {
+        let nodes: Vec<VNode> = block.children.iter().map(VNode::from).collect();
+        if nodes.is_empty() {
+            return VNode::placeholder();
+        }
+        dynamic(DynamicNode::Fragment(nodes))
+    }
+}
+
+impl From<Block<'_>> for VNode {
+    fn from(block: Block<'_>) -> Self {
+        VNode::from(&block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use dioxus_core::{AttributeValue, DynamicNode, TemplateNode, VNode};
+
+    use super::{MAX_NAMES, intern_into};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_into_vnode() {
+        let vnode = VNode::from(
+            element("a")
+                .with_key_value("href", "/home")
+                .with_attribute(Attribute::boolean("download"))
+                .with_child("Home"),
+        );
+        let TemplateNode::Element { tag, .. } = vnode.template.roots[0] else {
+            panic!("expected an element root");
+        };
+        assert_eq!(tag, "a");
+        let attributes = &vnode.dynamic_attrs[0];
+        assert_eq!(attributes[0].name, "href");
+        assert_eq!(attributes[0].value, AttributeValue::Text("/home".into()));
+        assert_eq!(attributes[1].value, AttributeValue::Bool(true));
+        let DynamicNode::Fragment(children) = &vnode.dynamic_nodes[0] else {
+            panic!("expected children");
+        };
+        let DynamicNode::Text(text) = &children[0].dynamic_nodes[0] else {
+            panic!("expected a text child");
+        };
+        assert_eq!(text.value, "Home");
+    }
+
+    // The element at the root of a converted element
+    fn root(vnode: &VNode) -> (&'static str, Option<&'static str>) {
+        let TemplateNode::Element { tag, namespace, .. } = vnode.template.roots[0] else {
+            panic!("expected an element root");
+        };
+        (tag, namespace)
+    }
+
+    #[test]
+    fn test_attributes() {
+        let vnode = VNode::from(
+            element("input")
+                .with_key_value("data-user-id", "7")
+                .with_key_value("value", "")
+                .with_attribute(Attribute::boolean("checked")),
+        );
+        let values: Vec<_> = vnode.dynamic_attrs[0]
+            .iter()
+            .map(|attribute| (attribute.name, attribute.value.clone()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("data-user-id", AttributeValue::Text("7".into())),
+                ("value", AttributeValue::Text(String::new())),
+                ("checked", AttributeValue::Bool(true)),
+            ]
+        );
+        // Raw markup becomes the inner HTML of a span
+        let raw = VNode::from(&Node::raw("<b>Hi</b>"));
+        assert_eq!(root(&raw).0, "span");
+        assert_eq!(raw.dynamic_attrs[0][0].name, "dangerous_inner_html");
+        assert_eq!(
+            raw.dynamic_attrs[0][0].value,
+            AttributeValue::Text("<b>Hi</b>".into())
+        );
+    }
+
+    #[test]
+    fn test_nesting() {
+        let document = Document::new()
+            .with_child(
+                element("ul")
+                    .with_child(element("li").with_child("One"))
+                    .with_child(element("li").with_child(element("em").with_child("Two"))),
+            )
+            .with_child(element("svg").with_child(element("circle")));
+        let vnode = VNode::from(&document);
+        let DynamicNode::Fragment(roots) = &vnode.dynamic_nodes[0] else {
+            panic!("expected a fragment");
+        };
+        assert_eq!(root(&roots[0]), ("ul", None));
+        let DynamicNode::Fragment(items) = &roots[0].dynamic_nodes[0] else {
+            panic!("expected children");
+        };
+        assert_eq!(items.len(), 2);
+        let DynamicNode::Fragment(emphasis) = &items[1].dynamic_nodes[0] else {
+            panic!("expected children");
+        };
+        assert_eq!(root(&emphasis[0]).0, "em");
+        let svg = Some("http://www.w3.org/2000/svg");
+        assert_eq!(root(&roots[1]), ("svg", svg));
+        let DynamicNode::Fragment(shapes) = &roots[1].dynamic_nodes[0] else {
+            panic!("expected children");
+        };
+        assert_eq!(root(&shapes[0]), ("circle", svg));
+    }
+
+    #[test]
+    fn test_repeated_conversions() {
+        // Templates and names are reused rather than leaked again
+        let card = element("x-card").with_key_value("x-size", "2");
+        let first = VNode::from(&card);
+        for _ in 0..100 {
+            let vnode = VNode::from(&card);
+            assert!(std::ptr::eq(vnode.template.roots, first.template.roots));
+            assert!(std::ptr::eq(
+                vnode.dynamic_attrs[0][0].name,
+                first.dynamic_attrs[0][0].name
+            ));
+        }
+        let other = std::thread::spawn(move || VNode::from(&card).template.roots.as_ptr() as usize);
+        assert_eq!(
+            other.join().unwrap(),
+            first.template.roots.as_ptr() as usize
+        );
+    }
+
+    #[test]
+    fn test_name_limit() {
+        // A set of its own, as filling the shared one would affect other tests
+        let mut names = BTreeSet::new();
+        for i in 0..MAX_NAMES {
+            assert!(intern_into(&mut names, &format!("x-many-{i}")).is_some());
+        }
+        assert_eq!(intern_into(&mut names, "x-last"), None);
+        assert_eq!(intern_into(&mut names, "div"), Some("div"));
+        assert_eq!(intern_into(&mut names, "x-many-0"), Some("x-many-0"));
+        assert_eq!(names.len(), MAX_NAMES);
+    }
+}
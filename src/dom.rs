@@ -0,0 +1,230 @@
+use web_sys::{
+    HtmlTemplateElement,
+    wasm_bindgen::{JsCast, JsValue},
+};
+
+use crate::{patch::Patch, prelude::*};
+
+fn create_element(
+    element: &Element,
+    document: &web_sys::Document,
+) -> Result<web_sys::Node, JsValue> {
+    let name = element.name.as_str();
+    let dom = if element.namespace.is_html() {
+        document.create_element(name)?
+    } else {
+        document.create_element_ns(Some(element.namespace.uri()), name)?
+    };
+    for attribute in &element.attributes {
+        dom.set_attribute(&attribute.key, &attribute.value)?;
+    }
+    for child in &element.children {
+        dom.append_child(&child.to_dom(document)?)?;
+    }
+    Ok(dom.into())
+}
+
+impl Node<'_> {
+    /// Creates the browser DOM node for this node and its descendants
+    ///
+    /// Raw markup is parsed by the browser into a fragment, and unfilled slots and
+    /// unresolved directives become empty text nodes so that child indices match the tree's for
+    /// [`apply_patches`]. Event handlers are not attached.
+    ///
+    /// # Errors
+    /// Errors with the browser's exception if a node cannot be created, e.g. for
+    /// an invalid tag or attribute name
+    pub fn to_dom(&self, document: &web_sys::Document) -> Result<web_sys::Node, JsValue> {
+        match self {
+            Node::Text(text) => Ok(document.create_text_node(&text.content).into()),
+            Node::Element(element) => create_element(element, document),
+            Node::Shared(element) => create_element(element, document),
+            Node::Raw(raw) => {
+                let template: HtmlTemplateElement =
+                    document.create_element("template")?.dyn_into()?;
+                template.set_inner_html(raw);
+                Ok(template.content().into())
+            }
+            Node::Comment(comment) => Ok(document.create_comment(comment).into()),
+            Node::Slot(_) | Node::Directive(_) => Ok(document.create_text_node("").into()),
+            #[cfg(feature = "i18n")]
+            Node::Translation(translation) => {
+                Ok(document.create_text_node(&translation.key).into())
+            }
+        }
+    }
+}
+
+fn owner_document(node: &web_sys::Node) -> Result<web_sys::Document, JsValue> {
+    node.owner_document()
+        .ok_or_else(|| JsValue::from_str("Node is not in a document"))
+}
+
+// The DOM node at the path below `root`, following child node indices
+fn dom_at(root: &web_sys::Node, path: &[usize]) -> Option<web_sys::Node> {
+    path.iter().try_fold(root.clone(), |node, &i| {
+        node.child_nodes().item(u32::try_from(i).ok()?)
+    })
+}
+
+fn apply_dom_patch(root: &web_sys::Node, patch: &Patch) -> Result<(), JsValue> {
+    let path = patch.path();
+    let invalid = || JsValue::from_str(&format!("No node at path {path}"));
+    let node = || dom_at(root, path.indices()).ok_or_else(invalid);
+    let element = || -> Result<web_sys::Element, JsValue> {
+        node()?
+            .dyn_into()
+            .map_err(|_| JsValue::from_str(&format!("Node at path {path} is not an element")))
+    };
+    match patch {
+        Patch::Replace { node: new, .. } => {
+            let old = node()?;
+            let parent = old.parent_node().ok_or_else(invalid)?;
+            parent.replace_child(&new.to_dom(&owner_document(root)?)?, &old)?;
+        }
+        Patch::Insert { node: new, .. } => {
+            let (last, parent) = path.indices().split_last().ok_or_else(invalid)?;
+            let parent = dom_at(root, parent).ok_or_else(invalid)?;
+            let before = parent
+                .child_nodes()
+                .item(u32::try_from(*last).map_err(|_| invalid())?);
+            parent.insert_before(&new.to_dom(&owner_document(root)?)?, before.as_ref())?;
+        }
+        Patch::Remove { .. } => {
+            let old = node()?;
+            old.parent_node().ok_or_else(invalid)?.remove_child(&old)?;
+        }
+        Patch::SetAttribute { key, value, .. } => element()?.set_attribute(key, value)?,
+        Patch::RemoveAttribute { key, .. } => element()?.remove_attribute(key)?,
+        Patch::SetText { text, .. } => node()?.set_text_content(Some(text)),
+    }
+    Ok(())
+}
+
+impl Block<'_> {
+    /// Appends the DOM nodes for the document to `parent`, see [`Node::to_dom`]
+    ///
+    /// # Errors
+    /// Errors with the browser's exception if a node cannot be created or appended
+    pub fn render_dom(&self, parent: &web_sys::Element) -> Result<(), JsValue> {
+        let document = owner_document(parent)?;
+        for node in &self.children {
+            parent.append_child(&node.to_dom(&document)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Applies patches from [`Block::diff`] to the DOM previously rendered into `parent`
+///
+/// Paths are resolved by child node index, so `parent` must hold only the
+/// rendered document, and raw markup must have produced exactly one DOM node
+/// for paths through or after it to line up.
+///
+/// # Errors
+/// Errors if a path does not resolve to a suitable DOM node, or with the
+/// browser's exception. Patches before the failing one remain applied.
+pub fn apply_patches(parent: &web_sys::Element, patches: &[Patch]) -> Result<(), JsValue> {
+    patches
+        .iter()
+        .try_for_each(|patch| apply_dom_patch(parent, patch))
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+    use web_sys::wasm_bindgen::JsCast;
+
+    use super::apply_patches;
+    use crate::prelude::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn document() -> web_sys::Document {
+        web_sys::window().unwrap().document().unwrap()
+    }
+
+    fn html(node: &web_sys::Node) -> String {
+        node.dyn_ref::<web_sys::Element>().unwrap().outer_html()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_element_creation() {
+        let list = Node::from(
+            element("ul")
+                .with_child(element("li").with_child("One & two"))
+                .with_child(Node::comment("note"))
+                .with_child(Node::Slot("items".into())),
+        );
+        let dom = list.to_dom(&document()).unwrap();
+        assert_eq!(html(&dom), "<ul><li>One &amp; two</li><!--note--></ul>");
+        // The slot is an empty text node, so child indices match the tree's
+        assert_eq!(dom.child_nodes().length(), 3);
+
+        let svg = Node::from(element("svg").with_child(element("circle")));
+        let dom = svg.to_dom(&document()).unwrap();
+        let circle: web_sys::Element = dom.first_child().unwrap().dyn_into().unwrap();
+        assert_eq!(
+            circle.namespace_uri().as_deref(),
+            Some("http://www.w3.org/2000/svg")
+        );
+
+        let raw = Node::raw("<b>Bold</b><i>Italic</i>")
+            .to_dom(&document())
+            .unwrap();
+        assert_eq!(raw.child_nodes().length(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_attributes() {
+        let input = Node::from(
+            element("input")
+                .with_key_value("name", "q")
+                .with_key_value("value", "")
+                .with_attribute(Attribute::boolean("required")),
+        );
+        let dom: web_sys::Element = input.to_dom(&document()).unwrap().dyn_into().unwrap();
+        assert_eq!(dom.get_attribute("name").as_deref(), Some("q"));
+        assert_eq!(dom.get_attribute("value").as_deref(), Some(""));
+        assert!(dom.has_attribute("required"));
+
+        let bad = Node::from(element("p").with_key_value("a b", "x"));
+        assert!(bad.to_dom(&document()).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_render_and_patch() {
+        let parent = document().create_element("div").unwrap();
+        let old = Document::new().with_child(element("a").with_key_value("href", "/old"));
+        let new = Document::new()
+            .with_child(element("a").with_key_value("title", "Home"))
+            .with_child(element("p").with_child("Added"));
+        old.render_dom(&parent).unwrap();
+        apply_patches(&parent, &old.diff(&new)).unwrap();
+        assert_eq!(parent.inner_html(), new.render());
+    }
+
+    #[cfg(feature = "events")]
+    #[wasm_bindgen_test]
+    fn test_events_are_not_attached() {
+        use std::sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        };
+
+        let clicked = Arc::new(AtomicBool::new(false));
+        let flag = clicked.clone();
+        let button = Node::from(
+            element("button")
+                .with_handler(EventHandler::new("click", move |_| {
+                    flag.store(true, Ordering::SeqCst);
+                }))
+                .with_child("Go"),
+        );
+        let dom = button.to_dom(&document()).unwrap();
+        assert_eq!(html(&dom), "<button>Go</button>");
+        dom.dispatch_event(&web_sys::Event::new("click").unwrap())
+            .unwrap();
+        assert!(!clicked.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,316 @@
+use crate::{path::children_at_mut, prelude::*};
+
+// Removes matching nodes at any depth, returning how many were removed
+fn remove_where<'a>(
+    nodes: &mut Vec<Node<'a>>,
+    predicate: &mut impl FnMut(&Node<'a>) -> bool,
+) -> usize {
+    let before = nodes.len();
+    nodes.retain(|node| !predicate(node));
+    let mut removed = before - nodes.len();
+    for node in nodes {
+        if let Node::Element(element) = node {
+            removed += remove_where(&mut element.children, predicate);
+        }
+    }
+    removed
+}
+
+/// Elements whose text content is whitespace-sensitive
+pub const PREFORMATTED_ELEMENTS: &[&str] = &["pre", "textarea", "script", "style"];
+
+// Merges adjacent text nodes and drops empty ones, optionally collapsing whitespace runs
+fn normalize_nodes(nodes: &mut Vec<Node>, collapse: bool) {
+    let mut merged: Vec<Node> = Vec::with_capacity(nodes.len());
+    for mut node in nodes.drain(..) {
+        match &mut node {
+            Node::Text(text) => {
+                if collapse {
+                    collapse_whitespace(text);
+                }
+                if let Some(Node::Text(previous)) = merged.last_mut() {
+                    previous.content.to_mut().push_str(&text.content);
+                    if collapse {
+                        collapse_whitespace(previous);
+                    }
+                    continue;
+                }
+            }
+            Node::Element(element) => {
+                let collapse = collapse && !PREFORMATTED_ELEMENTS.contains(&element.name.as_str());
+                normalize_nodes(&mut element.children, collapse);
+            }
+            _ => {}
+        }
+        merged.push(node);
+    }
+    merged.retain(|node| !matches!(node, Node::Text(text) if text.content.is_empty()));
+    *nodes = merged;
+}
+
+fn collapse_whitespace(text: &mut Text) {
+    let content = &text.content;
+    let needs_collapse =
+        content.contains(|c: char| c.is_whitespace() && c != ' ') || content.contains("  ");
+    if !needs_collapse {
+        return;
+    }
+    let mut collapsed = String::with_capacity(content.len());
+    let mut in_space = false;
+    for c in content.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                collapsed.push(' ');
+            }
+            in_space = true;
+        } else {
+            collapsed.push(c);
+            in_space = false;
+        }
+    }
+    text.content = collapsed.into();
+}
+
+impl<'a> Element<'a> {
+    /// Merges adjacent text nodes and removes empty text in the element's subtree,
+    /// shared elements are left as they are
+    pub fn normalize(&mut self) {
+        normalize_nodes(&mut self.children, false);
+    }
+
+    /// Normalizes the subtree and collapses whitespace runs in text to a single space,
+    /// except inside preformatted elements such as `pre`
+    pub fn normalize_whitespace(&mut self) {
+        let collapse = !PREFORMATTED_ELEMENTS.contains(&self.name.as_str());
+        normalize_nodes(&mut self.children, collapse);
+    }
+
+    /// Keeps only the direct children for which the predicate returns true
+    pub fn retain_children(&mut self, predicate: impl FnMut(&Node<'a>) -> bool) {
+        self.children.retain(predicate);
+    }
+
+    /// Removes every descendant matching the predicate, returning how many were removed
+    ///
+    /// Children of a removed element are removed with it and not checked themselves,
+    /// shared elements are not searched.
+    pub fn remove_children_where(&mut self, mut predicate: impl FnMut(&Node<'a>) -> bool) -> usize {
+        remove_where(&mut self.children, &mut predicate)
+    }
+
+    /// Replaces the child at the index, returning the previous child
+    ///
+    /// Returns `None` and leaves the element unchanged if the index is out of bounds.
+    pub fn replace_child(&mut self, index: usize, child: impl Into<Node<'a>>) -> Option<Node<'a>> {
+        let slot = self.children.get_mut(index)?;
+        let mut child = child.into();
+        if let Node::Element(element) = &mut child {
+            Self::inherit_namespace(self.namespace, &self.name, element);
+        }
+        Some(std::mem::replace(slot, child))
+    }
+
+    /// Moves all children into a new element with the tag, which becomes the only child
+    ///
+    /// Returns the new element, e.g. to add attributes to it.
+    pub fn wrap_children(&mut self, tag: impl Into<Tag<'a>>) -> &mut Element<'a> {
+        let mut wrapper = Element::new(tag);
+        wrapper.children = std::mem::take(&mut self.children);
+        self.add_child(wrapper);
+        let Some(Node::Element(wrapper)) = self.children.last_mut() else {
+            unreachable!("the wrapper was just added");
+        };
+        wrapper
+    }
+}
+
+impl<'a> Node<'a> {
+    /// Returns the nodes that replace this one when it is unwrapped
+    ///
+    /// An element or shared element gives its children, any other node is kept as is.
+    #[must_use]
+    pub fn unwrap(self) -> Vec<Node<'a>> {
+        match self.unshare() {
+            Node::Element(element) => element.children,
+            node => vec![node],
+        }
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Replaces every element matching a CSS selector with its children, returning
+    /// how many were unwrapped
+    ///
+    /// Matches are found before any are unwrapped, so removing a parent does not
+    /// change which of its descendants match.
+    ///
+    /// # Errors
+    /// Errors if the selector is invalid
+    pub fn unwrap_all<'s>(&mut self, selector: &'s str) -> Result<usize, ParseError<'s>> {
+        let mut paths = Selector::new(selector)?.select_paths(&self.children);
+        // Last first, so that unwrapping a node does not shift the paths still to go
+        paths.reverse();
+        for path in &paths {
+            let Some((last, parent)) = path.indices().split_last() else {
+                continue;
+            };
+            let Some(siblings) = children_at_mut(&mut self.children, parent) else {
+                continue;
+            };
+            let node = siblings.remove(*last);
+            siblings.splice(*last..*last, node.unwrap());
+        }
+        Ok(paths.len())
+    }
+
+    /// Merges adjacent text nodes and removes empty text across the document,
+    /// shared elements are left as they are
+    pub fn normalize(&mut self) {
+        normalize_nodes(&mut self.children, false);
+    }
+
+    /// Normalizes the document and collapses whitespace runs in text to a single space,
+    /// except inside preformatted elements such as `pre`
+    pub fn normalize_whitespace(&mut self) {
+        normalize_nodes(&mut self.children, true);
+    }
+
+    /// Keeps only the top-level nodes for which the predicate returns true
+    pub fn retain_children(&mut self, predicate: impl FnMut(&Node<'a>) -> bool) {
+        self.children.retain(predicate);
+    }
+
+    /// Removes every node matching the predicate at any depth, returning how many were removed
+    ///
+    /// Children of a removed element are removed with it and not checked themselves,
+    /// shared elements are not searched.
+    pub fn remove_children_where(&mut self, mut predicate: impl FnMut(&Node<'a>) -> bool) -> usize {
+        remove_where(&mut self.children, &mut predicate)
+    }
+
+    /// Replaces the top-level node at the index, returning the previous node
+    ///
+    /// Returns `None` and leaves the document unchanged if the index is out of bounds.
+    pub fn replace_child(&mut self, index: usize, child: impl Into<Node<'a>>) -> Option<Node<'a>> {
+        let slot = self.children.get_mut(index)?;
+        Some(std::mem::replace(slot, child.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn is_ad(node: &Node) -> bool {
+        matches!(node, Node::Element(element) if element.has_class("ad"))
+    }
+
+    #[test]
+    fn test_remove_children_where() {
+        let mut document = Document::new()
+            .with_child(element("div").with_attribute(Attribute::class("ad")))
+            .with_child(
+                element("main")
+                    .with_child(element("p").with_child("Story"))
+                    .with_child(element("aside").with_attribute(Attribute::class("ad banner"))),
+            );
+        assert_eq!(document.remove_children_where(is_ad), 2);
+        assert_eq!(
+            document,
+            Document::new()
+                .with_child(element("main").with_child(element("p").with_child("Story")))
+        );
+    }
+
+    #[test]
+    fn test_retain_and_replace() {
+        let mut list = element("ul")
+            .with_child(element("li").with_child("one"))
+            .with_child("  ")
+            .with_child(element("li").with_child("two"));
+        list.retain_children(Node::is_element);
+        assert_eq!(list.children.len(), 2);
+
+        let old = list.replace_child(1, element("li").with_child("three"));
+        assert_eq!(old, Some(element("li").with_child("two").into()));
+        assert_eq!(list.replace_child(5, "missing"), None);
+
+        let mut svg = element("svg");
+        svg.add_child(element("rect"));
+        svg.replace_child(0, element("circle"));
+        assert!(
+            svg.children_elements()
+                .all(|e| e.namespace == Namespace::Svg)
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut document = Document::new()
+            .with_child("Hello, ")
+            .with_child("")
+            .with_child("world")
+            .with_child(element("p").with_child("a").with_child("b"))
+            .with_child("");
+        document.normalize();
+        assert_eq!(
+            document,
+            Document::new()
+                .with_child("Hello, world")
+                .with_child(element("p").with_child("ab"))
+        );
+    }
+
+    #[test]
+    fn test_normalize_whitespace() {
+        let mut document = Document::new().with_child(
+            element("div")
+                .with_child("  Some\n\t")
+                .with_child(" text ")
+                .with_child(element("pre").with_child("  keep\n  this")),
+        );
+        document.normalize_whitespace();
+        assert_eq!(
+            document,
+            Document::new().with_child(
+                element("div")
+                    .with_child(" Some text ")
+                    .with_child(element("pre").with_child("  keep\n  this"))
+            )
+        );
+    }
+
+    #[test]
+    fn test_wrap_and_unwrap() {
+        let mut card = element("div")
+            .with_child(element("h2").with_child("Title"))
+            .with_child("Body");
+        card.wrap_children("section")
+            .add_attribute(Attribute::class("inner"));
+        assert_eq!(card.children.len(), 1);
+        assert_eq!(card.text_content(), "TitleBody");
+
+        let mut document = Document::new().with_child(card);
+        assert!(document.unwrap_all("div, section").is_err());
+        assert_eq!(document.unwrap_all("div > section").unwrap(), 1);
+        assert_eq!(document.unwrap_all("div").unwrap(), 1);
+        assert_eq!(
+            document,
+            Document::new()
+                .with_child(element("h2").with_child("Title"))
+                .with_child("Body")
+        );
+    }
+
+    #[test]
+    fn test_unwrap_nested_matches() {
+        let mut document = Document::new().with_child(
+            element("span")
+                .with_child(element("span").with_child("a"))
+                .with_child(element("span").with_child("b")),
+        );
+        assert_eq!(document.unwrap_all("span").unwrap(), 3);
+        assert_eq!(document, Document::new().with_child("a").with_child("b"));
+        assert_eq!(Node::text("c").unwrap(), [Node::text("c")]);
+    }
+}
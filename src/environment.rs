@@ -0,0 +1,976 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{
+    prelude::*,
+    template::{CompiledTemplate, Context, ErrorLocation, RenderError},
+    templates::{RegistryError, Template, TemplateLoader, parse_template},
+    value::{FromValue, Value},
+};
+
+/// A filter transforming the value of a placeholder, called with the value and
+/// the filter's arguments, e.g. `{name | truncate(20)}`
+///
+/// Errors are messages reported as [`RenderError::Filter`].
+pub type Filter = dyn Fn(&Value, &[Value]) -> Result<Value<'static>, String> + Send + Sync;
+
+/// A function callable from runtime templates with the values of its arguments,
+/// e.g. `{asset_url("app.css")}`
+///
+/// Errors are messages reported as [`RenderError::Function`].
+pub type Function = dyn Fn(&[Value]) -> Result<Value<'static>, String> + Send + Sync;
+
+/// A Rust function registered with [`Environment::register_function`]
+///
+/// Implemented for functions and closures taking up to four arguments that
+/// implement [`FromValue`], returning a value or a `Result` with a displayable error.
+pub trait TemplateFunction<Args>: Send + Sync + 'static {
+    /// Calls the function with the values of the arguments
+    ///
+    /// # Errors
+    /// Errors if the number or the types of the arguments are wrong, or if the
+    /// function fails
+    fn call(&self, args: &[Value]) -> Result<Value<'static>, String>;
+}
+
+/// The return value of a [`TemplateFunction`]
+pub trait FunctionResult {
+    /// # Errors
+    /// Errors with the function's error as a message
+    fn into_result(self) -> Result<Value<'static>, String>;
+}
+
+impl<T: Into<Value<'static>>> FunctionResult for T {
+    fn into_result(self) -> Result<Value<'static>, String> {
+        Ok(self.into())
+    }
+}
+
+impl<T: Into<Value<'static>>, E: std::fmt::Display> FunctionResult for Result<T, E> {
+    fn into_result(self) -> Result<Value<'static>, String> {
+        self.map(Into::into).map_err(|error| error.to_string())
+    }
+}
+
+macro_rules! impl_template_function {
+    ($count:literal $(, $arg:ident $value:ident)*) => {
+        impl<F, R, $($arg),*> TemplateFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + Send + Sync + 'static,
+            R: FunctionResult,
+            $($arg: FromValue,)*
+        {
+            fn call(&self, args: &[Value]) -> Result<Value<'static>, String> {
+                let [$($value),*] = args else {
+                    return Err(format!("expected {} arguments, got {}", $count, args.len()));
+                };
+                let mut _position = 0;
+                $(
+                    _position += 1;
+                    let $value = $arg::from_value($value)
+                        .map_err(|message| format!("argument {_position}: {message}"))?;
+                )*
+                self($($value),*).into_result()
+            }
+        }
+    };
+}
+
+impl_template_function!(0);
+impl_template_function!(1, A a);
+impl_template_function!(2, A a, B b);
+impl_template_function!(3, A a, B b, C c);
+impl_template_function!(4, A a, B b, C c, D d);
+
+/// The filters and functions available to templates rendered at runtime
+///
+/// A new environment has the built-in filters `upper`, `lower`, `trim`,
+/// `truncate(length)`, `date(format)` and `json`. Values are escaped for the text
+/// or attribute they end up in unless piped through `safe`, which writes them into
+/// text content as is and cannot be replaced. Attribute values are always escaped.
+/// Rendering a document with [`Block::render_with`] uses [`Environment::builtin`],
+/// rendering it with [`Environment::render`] makes the registered filters and
+/// functions available as well.
+///
+/// Templates added by name with [`Environment::add_template`] are resolved and
+/// compiled once, then rendered through [`Environment::get_template`].
+#[derive(Clone)]
+pub struct Environment {
+    filters: HashMap<String, Arc<Filter>>,
+    functions: HashMap<String, Arc<Function>>,
+    // Sources of the added templates, parsed again only to compile templates that
+    // depend on them, as documents cannot always be shared between threads
+    sources: HashMap<String, Arc<str>>,
+    // Templates whose layouts and partials resolved, by name
+    compiled: HashMap<String, Arc<CompiledTemplate>>,
+    // Names of the templates each template extends or includes
+    dependencies: HashMap<String, Vec<String>>,
+}
+
+/// A template of an [`Environment`], compiled with its layouts and partials
+#[derive(Debug, Clone, Copy)]
+pub struct NamedTemplate<'e> {
+    environment: &'e Environment,
+    name: &'e str,
+    compiled: &'e CompiledTemplate,
+}
+
+impl<'e> NamedTemplate<'e> {
+    #[must_use]
+    pub fn name(&self) -> &'e str {
+        self.name
+    }
+
+    #[must_use]
+    pub fn compiled(&self) -> &'e CompiledTemplate {
+        self.compiled
+    }
+
+    /// Names of the templates this one extends or includes directly, see
+    /// [`Environment::dependencies`]
+    #[must_use]
+    pub fn dependencies(&self) -> &'e [String] {
+        self.environment.dependencies(self.name).unwrap_or_default()
+    }
+
+    /// Renders the template with the context and the filters and functions of
+    /// the environment
+    ///
+    /// # Errors
+    /// Errors like [`Environment::bind`], wrapped in [`RenderError::InTemplate`]
+    /// with the template and line of the failing placeholder, condition or loop
+    pub fn render(&self, context: &Context) -> Result<String, RenderError> {
+        let mut out = String::new();
+        self.render_into(context, &mut out)?;
+        Ok(out)
+    }
+
+    /// Appends the rendered template to a string, see [`NamedTemplate::render`]
+    ///
+    /// # Errors
+    /// Errors like [`NamedTemplate::render`]. The output written before the error
+    /// is kept.
+    pub fn render_into(&self, context: &Context, out: &mut String) -> Result<(), RenderError> {
+        self.compiled
+            .render_traced(self.environment, context, out)
+            .map_err(|(error, at)| self.locate(error, at))
+    }
+
+    // Finds the failing text in the template or, as it may have been written in
+    // a layout or partial, in the templates it depends on
+    fn locate(&self, error: RenderError, written: &str) -> RenderError {
+        let environment = self.environment;
+        let mut seen = HashSet::new();
+        let mut pending = vec![self.name];
+        while let Some(name) = pending.pop() {
+            if !seen.insert(name) {
+                continue;
+            }
+            if let Some(source) = environment.sources.get(name)
+                && let Some(location) = ErrorLocation::find(name, source, written)
+            {
+                return RenderError::InTemplate {
+                    location: Box::new(location),
+                    error: Box::new(error),
+                };
+            }
+            let dependencies = environment.dependencies(name).unwrap_or_default();
+            pending.extend(dependencies.iter().rev().map(String::as_str));
+        }
+        error
+    }
+}
+
+impl Environment {
+    #[must_use]
+    pub fn new() -> Self {
+        let mut environment = Environment {
+            filters: HashMap::new(),
+            functions: HashMap::new(),
+            sources: HashMap::new(),
+            compiled: HashMap::new(),
+            dependencies: HashMap::new(),
+        };
+        environment.register_filter("upper", |value, _| {
+            Ok(value.to_text().to_uppercase().into())
+        });
+        environment.register_filter("lower", |value, _| {
+            Ok(value.to_text().to_lowercase().into())
+        });
+        environment.register_filter("trim", |value, _| {
+            Ok(value.to_text().trim().to_string().into())
+        });
+        environment.register_filter("truncate", truncate);
+        environment.register_filter("date", date);
+        environment.register_filter("json", |value, _| Ok(value.to_json().into()));
+        environment
+    }
+
+    /// The environment with only the built-in filters, used when rendering
+    /// without one
+    #[must_use]
+    pub fn builtin() -> &'static Environment {
+        static BUILTIN: OnceLock<Environment> = OnceLock::new();
+        BUILTIN.get_or_init(Environment::new)
+    }
+
+    /// Adds a filter, replacing any filter with the same name
+    pub fn register_filter(
+        &mut self,
+        name: impl Into<String>,
+        filter: impl Fn(&Value, &[Value]) -> Result<Value<'static>, String> + Send + Sync + 'static,
+    ) {
+        self.filters.insert(name.into(), Arc::new(filter));
+    }
+    #[must_use]
+    pub fn with_filter(
+        mut self,
+        name: impl Into<String>,
+        filter: impl Fn(&Value, &[Value]) -> Result<Value<'static>, String> + Send + Sync + 'static,
+    ) -> Self {
+        self.register_filter(name, filter);
+        self
+    }
+
+    #[must_use]
+    pub fn filter(&self, name: &str) -> Option<&Filter> {
+        self.filters.get(name).map(AsRef::as_ref)
+    }
+
+    /// Adds a function callable from placeholders and conditions, replacing any
+    /// function with the same name
+    ///
+    /// Arguments are converted with [`FromValue`], so a wrong argument fails the
+    /// render with [`RenderError::Function`] naming the call and the argument.
+    pub fn register_function<Args>(
+        &mut self,
+        name: impl Into<String>,
+        function: impl TemplateFunction<Args>,
+    ) {
+        self.functions.insert(
+            name.into(),
+            Arc::new(move |args: &[Value]| function.call(args)),
+        );
+    }
+    #[must_use]
+    pub fn with_function<Args>(
+        mut self,
+        name: impl Into<String>,
+        function: impl TemplateFunction<Args>,
+    ) -> Self {
+        self.register_function(name, function);
+        self
+    }
+
+    #[must_use]
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name).map(AsRef::as_ref)
+    }
+
+    /// Parses the source as the template with the name, replacing any template of
+    /// the same name
+    ///
+    /// The template is compiled with the layouts it extends and the partials it
+    /// includes, see [`TemplateLoader::resolve`], and so are the templates that
+    /// depend on it. Templates may be added in any order: one whose dependencies
+    /// are missing compiles once they are added.
+    ///
+    /// # Errors
+    /// Errors if the source is not a valid RSTML document, in which case the
+    /// previous template of the same name is kept
+    pub fn add_template(
+        &mut self,
+        name: impl Into<String>,
+        source: &str,
+    ) -> Result<(), RegistryError> {
+        let name = name.into();
+        let template = parse_template(source, &name, 0)?;
+        let dependencies = template.dependencies().into_iter().map(str::to_string);
+        self.dependencies
+            .insert(name.clone(), dependencies.collect());
+        self.sources.insert(name.clone(), source.into());
+        let loader = SourceLoader {
+            sources: &self.sources,
+            parsed: RefCell::new(HashMap::from([(name.clone(), Arc::from(template))])),
+        };
+        let mut affected = self.dependents(&name);
+        affected.push(name);
+        let compiled: Vec<_> = affected
+            .into_iter()
+            .map(|name| {
+                let compiled = compile_template(&loader, &name).ok();
+                (name, compiled)
+            })
+            .collect();
+        for (name, compiled) in compiled {
+            match compiled {
+                Some(compiled) => self.compiled.insert(name, Arc::new(compiled)),
+                None => self.compiled.remove(&name),
+            };
+        }
+        Ok(())
+    }
+    /// Adds a template, see [`Environment::add_template`]
+    ///
+    /// # Errors
+    /// Errors if the source is not a valid RSTML document
+    pub fn with_template(
+        mut self,
+        name: impl Into<String>,
+        source: &str,
+    ) -> Result<Self, RegistryError> {
+        self.add_template(name, source)?;
+        Ok(self)
+    }
+
+    /// Returns the compiled template with the name
+    ///
+    /// # Errors
+    /// Errors with [`RegistryError::NotFound`] if no template has the name, or with
+    /// the reason it did not compile, e.g. a missing partial
+    pub fn get_template<'e>(&'e self, name: &str) -> Result<NamedTemplate<'e>, RegistryError> {
+        let Some((name, compiled)) = self.compiled.get_key_value(name) else {
+            let loader = SourceLoader {
+                sources: &self.sources,
+                parsed: RefCell::default(),
+            };
+            return Err(compile_template(&loader, name)
+                .err()
+                .unwrap_or_else(|| RegistryError::NotFound(name.to_string())));
+        };
+        Ok(NamedTemplate {
+            environment: self,
+            name,
+            compiled,
+        })
+    }
+
+    /// Names of the added templates, sorted
+    #[must_use]
+    pub fn template_names(&self) -> Vec<String> {
+        sorted_names(&self.sources)
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Names of the templates the template extends or includes directly, in the
+    /// order they appear, `None` if no template has the name
+    #[must_use]
+    pub fn dependencies(&self, name: &str) -> Option<&[String]> {
+        self.dependencies.get(name).map(Vec::as_slice)
+    }
+
+    /// Names of the templates that extend or include the template, directly or
+    /// through others, sorted
+    ///
+    /// These are the templates to invalidate when the template changes.
+    #[must_use]
+    pub fn dependents(&self, name: &str) -> Vec<String> {
+        let mut found = HashSet::new();
+        let mut pending = vec![name];
+        while let Some(dependency) = pending.pop() {
+            for (template, dependencies) in &self.dependencies {
+                if dependencies.iter().any(|name| name == dependency)
+                    && template != name
+                    && found.insert(template.as_str())
+                {
+                    pending.push(template);
+                }
+            }
+        }
+        let mut dependents: Vec<_> = found.into_iter().map(str::to_string).collect();
+        dependents.sort();
+        dependents
+    }
+
+    /// Returns a copy of the document with its placeholders, conditions and loops
+    /// evaluated, see [`Block::bind`]
+    ///
+    /// # Errors
+    /// Errors if a placeholder or condition is malformed, a key is missing, or a
+    /// filter is unknown or fails
+    pub fn bind<'a>(&self, block: &Block<'a>, context: &Context) -> Result<Block<'a>, RenderError> {
+        crate::template::bind(block, self, context)
+    }
+
+    /// Renders the document with the context, see [`Block::render_with`]
+    ///
+    /// # Errors
+    /// Errors like [`Environment::bind`]
+    pub fn render(&self, block: &Block, context: &Context) -> Result<String, RenderError> {
+        Ok(self.bind(block, context)?.render())
+    }
+
+    /// Renders a compiled template with the context, see [`CompiledTemplate::render_with`]
+    ///
+    /// # Errors
+    /// Errors like [`Environment::bind`]
+    pub fn render_compiled(
+        &self,
+        template: &CompiledTemplate,
+        context: &Context,
+    ) -> Result<String, RenderError> {
+        let mut out = String::new();
+        template.render_in(self, context, &mut out)?;
+        Ok(out)
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment::new()
+    }
+}
+
+// Parses the sources of an environment's templates as they are needed, at most once
+struct SourceLoader<'s> {
+    sources: &'s HashMap<String, Arc<str>>,
+    parsed: RefCell<HashMap<String, Arc<Template>>>,
+}
+
+impl TemplateLoader for SourceLoader<'_> {
+    fn load(&self, name: &str) -> Result<Arc<Template>, RegistryError> {
+        if let Some(template) = self.parsed.borrow().get(name) {
+            return Ok(Arc::clone(template));
+        }
+        let Some(source) = self.sources.get(name) else {
+            return Err(RegistryError::NotFound(name.to_string()));
+        };
+        let template = Arc::from(parse_template(source, name, 0)?);
+        self.parsed
+            .borrow_mut()
+            .insert(name.to_string(), Arc::clone(&template));
+        Ok(template)
+    }
+}
+
+// Resolves the layouts and partials of a template and compiles the result
+fn compile_template(loader: &SourceLoader, name: &str) -> Result<CompiledTemplate, RegistryError> {
+    loader
+        .resolve(name)?
+        .compile()
+        .map_err(|error| RegistryError::Render {
+            name: name.to_string(),
+            error,
+        })
+}
+
+fn sorted_names<V>(map: &HashMap<String, V>) -> Vec<&str> {
+    let mut names: Vec<_> = map.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    names
+}
+
+impl std::fmt::Debug for Environment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Environment")
+            .field("filters", &sorted_names(&self.filters))
+            .field("functions", &sorted_names(&self.functions))
+            .field("templates", &sorted_names(&self.sources))
+            .finish()
+    }
+}
+
+// The argument at the index as a count, e.g. the length given to `truncate`
+fn count_argument(args: &[Value], index: usize, filter: &str) -> Result<usize, String> {
+    match args.get(index) {
+        Some(&Value::Int(count)) if count >= 0 => Ok(count as usize),
+        Some(value) => Err(format!("`{filter}` expects a count, got `{value}`")),
+        None => Err(format!("`{filter}` expects a count")),
+    }
+}
+
+// Keeps at most `length` characters, ending cut text with an ellipsis
+fn truncate(value: &Value, args: &[Value]) -> Result<Value<'static>, String> {
+    let length = count_argument(args, 0, "truncate")?;
+    let text = value.to_text();
+    if text.chars().count() <= length {
+        return Ok(text.into_owned().into());
+    }
+    let mut truncated: String = text.chars().take(length.saturating_sub(1)).collect();
+    truncated.push('…');
+    Ok(truncated.into())
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+// A date and time in UTC, as `date` formats it
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl DateTime {
+    // Converts seconds since the Unix epoch, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    fn from_timestamp(seconds: i64) -> Self {
+        let days = seconds.div_euclid(86_400);
+        let time = seconds.rem_euclid(86_400) as u32;
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        DateTime {
+            year: year_of_era + era * 400 + i64::from(month <= 2),
+            month,
+            day,
+            hour: time / 3600,
+            minute: time / 60 % 60,
+            second: time % 60,
+        }
+    }
+
+    // Parses `YYYY-MM-DD`, optionally followed by `THH:MM` or `THH:MM:SS` and
+    // anything else such as fractions or an offset, which are ignored
+    fn parse(text: &str) -> Option<Self> {
+        let number = |range: std::ops::Range<usize>| text.get(range)?.parse::<u32>().ok();
+        let bytes = text.as_bytes();
+        if bytes.get(4) != Some(&b'-') || bytes.get(7) != Some(&b'-') {
+            return None;
+        }
+        let mut date = DateTime {
+            year: text.get(..4)?.parse().ok()?,
+            month: number(5..7).filter(|month| (1..=12).contains(month))?,
+            day: number(8..10).filter(|day| (1..=31).contains(day))?,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        };
+        if matches!(bytes.get(10), Some(b'T' | b' ')) {
+            date.hour = number(11..13)?;
+            date.minute = number(14..16)?;
+            date.second = number(17..19).unwrap_or(0);
+        } else if text.len() > 10 {
+            return None;
+        }
+        Some(date)
+    }
+}
+
+// Formats a Unix timestamp or an ISO 8601 date with `%Y`, `%m`, `%d`, `%e`, `%H`,
+// `%M`, `%S`, `%b`, `%B` and `%%`, `%Y-%m-%d` by default
+fn date(value: &Value, args: &[Value]) -> Result<Value<'static>, String> {
+    let date = match value {
+        Value::Int(seconds) => DateTime::from_timestamp(*seconds),
+        Value::Float(seconds) => DateTime::from_timestamp(seconds.floor() as i64),
+        Value::String(text) => {
+            DateTime::parse(text).ok_or_else(|| format!("`date` cannot read `{text}` as a date"))?
+        }
+        value => {
+            return Err(format!(
+                "`date` expects a timestamp or a date, got `{value}`"
+            ));
+        }
+    };
+    let format = match args.first() {
+        Some(Value::String(format)) => format.as_ref(),
+        Some(value) => return Err(format!("`date` expects a format, got `{value}`")),
+        None => "%Y-%m-%d",
+    };
+    let month = MONTHS[date.month as usize - 1];
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let written = match chars.next() {
+            Some('Y') => write!(out, "{}", date.year),
+            Some('m') => write!(out, "{:02}", date.month),
+            Some('d') => write!(out, "{:02}", date.day),
+            Some('e') => write!(out, "{}", date.day),
+            Some('H') => write!(out, "{:02}", date.hour),
+            Some('M') => write!(out, "{:02}", date.minute),
+            Some('S') => write!(out, "{:02}", date.second),
+            Some('b') => write!(out, "{}", &month[..3]),
+            Some('B') => write!(out, "{month}"),
+            Some('%') => write!(out, "%"),
+            Some(other) => return Err(format!("`date` does not support `%{other}`")),
+            None => return Err("`date` format ends with `%`".into()),
+        };
+        written.expect("writing to a String cannot fail");
+    }
+    Ok(out.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Environment;
+    use crate::{
+        prelude::*,
+        template::{Context, Missing, RenderError},
+        templates::RegistryError,
+        value::Value,
+    };
+
+    #[test]
+    fn test_filters() {
+        let (_, page) = Block::parse_ignoring_comments(
+            r#"p { .title = "{name | upper}" "{ name | truncate(4) | lower }" }
+            time { "{posted | date('%e %B %Y, %H:%M')} / {updated | date}" }
+            pre { "{tags | json}" }"#,
+        )
+        .unwrap();
+        let context = Context::new()
+            .with_value("name", "  Annabel ")
+            .with_value("posted", 1_700_000_000)
+            .with_value("updated", "2024-02-29T10:00:00Z")
+            .with_value("tags", vec!["a", "b"]);
+        let expected = "<p title=\"  ANNABEL \">  a…</p>\
+            <time>14 November 2023, 22:13 / 2024-02-29</time>\
+            <pre>[\"a\",\"b\"]</pre>";
+        assert_eq!(page.render_with(&context).unwrap(), expected);
+        let compiled = page.compile().unwrap();
+        assert_eq!(compiled.render_with(&context).unwrap(), expected);
+
+        let environment = Environment::new().with_filter("shout", |value, args| {
+            let marks = args.first().and_then(Value::as_str).unwrap_or("!");
+            Ok(format!("{}{marks}", value.to_text().trim()).into())
+        });
+        let (_, page) =
+            Block::parse_ignoring_comments(r#"p { "{name | shout('?') | upper}" }"#).unwrap();
+        assert_eq!(
+            environment.render(&page, &context).unwrap(),
+            "<p>ANNABEL?</p>"
+        );
+        assert_eq!(
+            page.render_with(&context),
+            Err(RenderError::UnknownFilter("shout".into()))
+        );
+        assert!(matches!(
+            context.interpolate("{name | truncate('x')}"),
+            Err(RenderError::Filter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_builtin_filters() {
+        let context = Context::new()
+            .with_value("name", " Ann Lee ")
+            .with_value("count", 42)
+            .with_value("flag", true)
+            .with_value("empty", Value::Null)
+            .with_value("timestamp", 951_827_696)
+            .with_value("fraction", 951_827_696.9)
+            .with_value("day", "2024-01-05")
+            .with_value("tags", vec!["a", "b"]);
+        for (input, expected) in [
+            ("{name | upper}", " ANN LEE "),
+            ("{count | upper}", "42"),
+            ("{name | lower}", " ann lee "),
+            ("{name | trim}", "Ann Lee"),
+            ("{empty | trim}", ""),
+            ("{name | trim | truncate(3)}", "An…"),
+            ("{name | trim | truncate(7)}", "Ann Lee"),
+            ("{name | trim | truncate(0)}", "…"),
+            ("{count | truncate(1)}", "…"),
+            ("{timestamp | date}", "2000-02-29"),
+            ("{fraction | date('%H:%M:%S')}", "12:34:56"),
+            ("{day | date('%e %b %Y, 100%%')}", "5 Jan 2024, 100%"),
+            ("{day | date('%d %B')}", "05 January"),
+            ("{tags | json}", r#"["a","b"]"#),
+            ("{flag | json}", "true"),
+            ("{empty | json}", "null"),
+            ("{name | json}", r#"" Ann Lee ""#),
+        ] {
+            assert_eq!(context.interpolate(input).unwrap(), expected, "{input}");
+        }
+
+        let failure = |input: &str| match context.interpolate(input) {
+            Err(RenderError::Filter { name, message }) => format!("{name}: {message}"),
+            other => panic!("Expected `{input}` to fail, got {other:?}"),
+        };
+        assert_eq!(
+            failure("{name | truncate}"),
+            "truncate: `truncate` expects a count"
+        );
+        assert_eq!(
+            failure("{name | truncate(-1)}"),
+            "truncate: `truncate` expects a count, got `-1`"
+        );
+        assert_eq!(
+            failure("{name | truncate(1.5)}"),
+            "truncate: `truncate` expects a count, got `1.5`"
+        );
+        assert_eq!(
+            failure("{name | date}"),
+            "date: `date` cannot read ` Ann Lee ` as a date"
+        );
+        assert_eq!(
+            failure("{tags | date}"),
+            r#"date: `date` expects a timestamp or a date, got `["a","b"]`"#
+        );
+        assert_eq!(
+            failure("{day | date(1)}"),
+            "date: `date` expects a format, got `1`"
+        );
+        assert_eq!(
+            failure("{day | date('%q')}"),
+            "date: `date` does not support `%q`"
+        );
+        assert_eq!(
+            failure("{day | date('%Y%')}"),
+            "date: `date` format ends with `%`"
+        );
+        for day in [
+            "2024-13-01",
+            "2024-01-32",
+            "2024/01/01",
+            "2024-01-01x",
+            "24-01-01",
+        ] {
+            let context = Context::new().with_value("day", day);
+            assert!(
+                matches!(
+                    context.interpolate("{day | date}"),
+                    Err(RenderError::Filter { .. })
+                ),
+                "{day}"
+            );
+        }
+        assert_eq!(
+            context.interpolate("{name | shout}"),
+            Err(RenderError::UnknownFilter("shout".into()))
+        );
+    }
+
+    #[test]
+    fn test_function_arguments() {
+        let environment = Environment::new()
+            .with_function("scale", |value: f64, factor: Option<f64>| {
+                value * factor.unwrap_or(2.0)
+            })
+            .with_function("either", |a: bool, b: bool| a || b)
+            .with_function("count", |items: Vec<String>| items.len() as i64)
+            .with_function("label", |text: String| format!("[{text}]"))
+            .with_function("echo", |value: Value<'static>| value);
+        let context = Context::new()
+            .with_value("tags", vec!["a", "b"])
+            .with_value("nested", vec![vec!["a"]]);
+        let render = |source: &str| {
+            let (_, page) = Block::parse_ignoring_comments(source).unwrap();
+            environment
+                .render(&page, &context)
+                .map_err(|error| error.to_string())
+        };
+        assert_eq!(
+            render(
+                r#"p { "{scale(2, null)} {scale(1.5, 3)} {scale(1, null)} {either(false, true)}" }
+                p { "{count(tags)} {label(1)} {label(true)} {echo(tags)}" }"#
+            ),
+            Ok(r#"<p>4 4.5 2 true</p><p>2 [1] [true] ["a","b"]</p>"#.into())
+        );
+        for (source, message) in [
+            (
+                "{scale('x', 1)}",
+                "Cannot call `scale('x', 1)`: argument 1: expected a number, got `x`",
+            ),
+            (
+                "{scale(1, true)}",
+                "Cannot call `scale(1, true)`: argument 2: expected a number, got `true`",
+            ),
+            (
+                "{scale(1)}",
+                "Cannot call `scale(1)`: expected 2 arguments, got 1",
+            ),
+            (
+                "{scale(1, 2, 3)}",
+                "Cannot call `scale(1, 2, 3)`: expected 2 arguments, got 3",
+            ),
+            (
+                "{either(1, true)}",
+                "Cannot call `either(1, true)`: argument 1: expected a boolean, got `1`",
+            ),
+            (
+                "{count(missing)}",
+                "Cannot call `count(missing)`: argument 1: expected a list, got null",
+            ),
+            (
+                "{count(nested)}",
+                r#"Cannot call `count(nested)`: argument 1: expected a string, got `["a"]`"#,
+            ),
+            (
+                "{label(tags)}",
+                r#"Cannot call `label(tags)`: argument 1: expected a string, got `["a","b"]`"#,
+            ),
+        ] {
+            assert_eq!(
+                render(&format!(r#"p {{ "{source}" }}"#)),
+                Err(message.to_string()),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_functions() {
+        let environment = Environment::new()
+            .with_function("asset_url", |path: String| format!("/static/{path}?v=3"))
+            .with_function("add", |a: i64, b: Option<i64>| a + b.unwrap_or(0))
+            .with_function("is_active", |page: String| page == "home")
+            .with_function("fail", || Err::<i64, _>("out of luck"));
+        let (_, page) = Block::parse_ignoring_comments(
+            r#"link { .href = "{asset_url('app.css')}" }
+            p { "{add(count, 1)} {asset_url(name) | upper}" }
+            @if is_active(page) { "Home" }"#,
+        )
+        .unwrap();
+        let context = Context::new()
+            .with_value("count", 41)
+            .with_value("name", "a|b.js")
+            .with_value("page", "home");
+        let expected = r#"<link href="/static/app.css?v=3"><p>42 /STATIC/A|B.JS?V=3</p>Home"#;
+        assert_eq!(environment.render(&page, &context).unwrap(), expected);
+        let compiled = page.compile().unwrap();
+        assert_eq!(
+            environment.render_compiled(&compiled, &context).unwrap(),
+            expected
+        );
+
+        let render = |source: &str| {
+            let (_, page) = Block::parse_ignoring_comments(source).unwrap();
+            environment.render(&page, &context).unwrap_err().to_string()
+        };
+        assert_eq!(
+            render(r#"p { "{add('x', 1)}" }"#),
+            "Cannot call `add('x', 1)`: argument 1: expected an integer, got `x`"
+        );
+        assert_eq!(
+            render(r#"p { "{add()}" }"#),
+            "Cannot call `add()`: expected 2 arguments, got 0"
+        );
+        assert_eq!(
+            render(r#"p { "{fail()}" }"#),
+            "Cannot call `fail()`: out of luck"
+        );
+        assert_eq!(
+            render(r#"p { "{missing(1)}" }"#),
+            "No function named `missing`"
+        );
+    }
+
+    #[test]
+    fn test_named_templates() {
+        let mut environment = Environment::new()
+            .with_filter("shout", |value, _| Ok(format!("{value}!").into()))
+            .with_template(
+                "page.rstml",
+                r#"@extends("base.rstml") @block(content) { p { "{name | shout}" } }"#,
+            )
+            .unwrap();
+        let context = Context::new().with_value("name", "Ann");
+        assert!(matches!(
+            environment.get_template("page.rstml"),
+            Err(RegistryError::NotFound(name)) if name == "base.rstml"
+        ));
+
+        environment
+            .add_template(
+                "base.rstml",
+                r#"main { @include("nav.rstml") @block(content) {} }"#,
+            )
+            .unwrap();
+        environment
+            .add_template("nav.rstml", r#"nav { "Home" }"#)
+            .unwrap();
+        let page = environment.get_template("page.rstml").unwrap();
+        assert_eq!(
+            page.render(&context).unwrap(),
+            "<main><nav>Home</nav><p>Ann!</p></main>"
+        );
+        assert_eq!(page.dependencies(), ["base.rstml"]);
+        assert_eq!(
+            environment.dependents("nav.rstml"),
+            ["base.rstml", "page.rstml"]
+        );
+
+        // Changing a partial recompiles the templates that include it
+        environment
+            .add_template("nav.rstml", r#"nav { "Start" }"#)
+            .unwrap();
+        assert_eq!(
+            environment
+                .get_template("page.rstml")
+                .unwrap()
+                .render(&context)
+                .unwrap(),
+            "<main><nav>Start</nav><p>Ann!</p></main>"
+        );
+        assert!(environment.add_template("nav.rstml", "nav {").is_err());
+        assert_eq!(
+            environment.template_names(),
+            ["base.rstml", "nav.rstml", "page.rstml"]
+        );
+        assert!(matches!(
+            environment.get_template("missing.rstml"),
+            Err(RegistryError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_error_location() {
+        let environment = Environment::new()
+            .with_template(
+                "page.rstml",
+                "main {\n    @include(\"card.rstml\")\n    @if ok(1) { p {} }\n}",
+            )
+            .unwrap()
+            .with_template(
+                "card.rstml",
+                "div {\n    p { \"Hi {user.nmae | upper}\" }\n}",
+            )
+            .unwrap();
+        let page = environment.get_template("page.rstml").unwrap();
+        let context = Context::new()
+            .with_value("user", [("name", "Ann")].into_iter().collect::<Value>())
+            .with_missing(Missing::Error);
+        let error = page.render(&context).unwrap_err();
+        assert_eq!(error.root(), &RenderError::MissingKey("user.nmae".into()));
+        let location = error.location().unwrap();
+        assert_eq!(location.template, "card.rstml");
+        assert_eq!((location.span.line, location.span.column), (2, 13));
+        assert_eq!(
+            error.to_string(),
+            "card.rstml:2:13: No value for placeholder `{user.nmae}`\n\
+             2 |     p { \"Hi {user.nmae | upper}\" }\n  \
+               |             ^^^^^^^^^^^^^^^^^^^"
+        );
+        let context = Context::new().with_missing(Missing::Empty);
+        let error = page.render(&context).unwrap_err();
+        assert_eq!(error.root(), &RenderError::UnknownFunction("ok".into()));
+        assert_eq!(error.location().unwrap().template, "page.rstml");
+        assert_eq!(error.location().unwrap().span.line, 3);
+    }
+}
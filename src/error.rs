@@ -53,6 +53,70 @@ impl<'a> ParseError<'a> {
     }
 }
 
+impl<'a> ParseError<'a> {
+    fn found(&self) -> Option<&str> {
+        match self {
+            ParseError::MissingEndDelimiter { found, .. }
+            | ParseError::InvalidInput { found, .. }
+            | ParseError::MissingToken { found, .. } => Some(found),
+            ParseError::UnexpectedEndOfInput | ParseError::EmptyInput => None,
+        }
+    }
+
+    /// Byte offset in `source` of the input the error was found at
+    ///
+    /// Returns `None` if the error does not point into `source`, e.g. for
+    /// [`ParseError::EmptyInput`].
+    #[must_use]
+    pub fn offset(&self, source: &str) -> Option<usize> {
+        crate::util::offset_in(source, self.found()?)
+    }
+
+    // Shortens the input the error was found at to its first non-blank line
+    pub(crate) fn first_line(self) -> Self {
+        let shorten = |found: Cow<'a, str>| match found {
+            Cow::Borrowed(found) => {
+                let found = found.trim_start();
+                Cow::Borrowed(found.lines().next().unwrap_or(found))
+            }
+            Cow::Owned(found) => Cow::Owned(found),
+        };
+        match self {
+            ParseError::MissingEndDelimiter { expected, found } => {
+                ParseError::MissingEndDelimiter {
+                    expected,
+                    found: shorten(found),
+                }
+            }
+            ParseError::InvalidInput { found, context } => ParseError::InvalidInput {
+                found: shorten(found),
+                context,
+            },
+            ParseError::MissingToken {
+                expected,
+                found,
+                context,
+            } => ParseError::MissingToken {
+                expected,
+                found: shorten(found),
+                context,
+            },
+            error => error,
+        }
+    }
+}
+
+/// 1-based line and column of a byte offset in `source`, columns counting characters
+#[must_use]
+pub fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
 impl std::fmt::Display for ParseError<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,482 @@
+use std::borrow::Cow;
+
+use crate::{
+    template::{RenderError, Scope},
+    value::Value,
+};
+
+// An expression in a runtime template, e.g. an `@if` condition
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr<'e> {
+    // A key looked up in the scope, missing keys being `null`
+    Path(Cow<'e, str>),
+    Literal(Value<'e>),
+    Not(Box<Expr<'e>>),
+    Equals {
+        left: Box<Expr<'e>>,
+        right: Box<Expr<'e>>,
+        negated: bool,
+    },
+    And(Box<Expr<'e>>, Box<Expr<'e>>),
+    Or(Box<Expr<'e>>, Box<Expr<'e>>),
+    // A function of the environment, `source` being the call as written
+    Call {
+        name: Cow<'e, str>,
+        args: Vec<Expr<'e>>,
+        source: Cow<'e, str>,
+    },
+}
+
+impl<'e> Expr<'e> {
+    // Parses the whole input, returning a message naming the problem otherwise
+    pub(crate) fn parse(input: &'e str) -> Result<Self, String> {
+        let mut parser = Parser { rest: input };
+        let expr = parser.or()?;
+        parser.skip_whitespace();
+        if !parser.rest.is_empty() {
+            return Err(format!("Unexpected `{}` in expression", parser.rest));
+        }
+        Ok(expr)
+    }
+
+    pub(crate) fn into_owned(self) -> Expr<'static> {
+        match self {
+            Expr::Path(path) => Expr::Path(path.into_owned().into()),
+            Expr::Literal(value) => Expr::Literal(value.into_owned()),
+            Expr::Not(expr) => Expr::Not(Box::new(expr.into_owned())),
+            Expr::Equals {
+                left,
+                right,
+                negated,
+            } => Expr::Equals {
+                left: Box::new(left.into_owned()),
+                right: Box::new(right.into_owned()),
+                negated,
+            },
+            Expr::And(left, right) => {
+                Expr::And(Box::new(left.into_owned()), Box::new(right.into_owned()))
+            }
+            Expr::Or(left, right) => {
+                Expr::Or(Box::new(left.into_owned()), Box::new(right.into_owned()))
+            }
+            Expr::Call { name, args, source } => Expr::Call {
+                name: name.into_owned().into(),
+                args: args.into_iter().map(Expr::into_owned).collect(),
+                source: source.into_owned().into(),
+            },
+        }
+    }
+
+    // Evaluates the expression, failing only when a function call fails
+    pub(crate) fn eval<'s>(&'s self, scope: &Scope<'s>) -> Result<Cow<'s, Value<'s>>, RenderError> {
+        let truthy = |expr: &'s Expr| Ok::<_, RenderError>(expr.eval(scope)?.is_truthy());
+        Ok(match self {
+            Expr::Path(path) => scope
+                .lookup(path)
+                .map_or(Cow::Owned(Value::Null), Cow::Borrowed),
+            Expr::Literal(value) => Cow::Borrowed(value),
+            Expr::Not(expr) => Cow::Owned(Value::Bool(!truthy(expr)?)),
+            Expr::Equals {
+                left,
+                right,
+                negated,
+            } => {
+                let equal = left.eval(scope)?.loosely_equals(&*right.eval(scope)?);
+                Cow::Owned(Value::Bool(equal != *negated))
+            }
+            Expr::And(left, right) => Cow::Owned(Value::Bool(truthy(left)? && truthy(right)?)),
+            Expr::Or(left, right) => Cow::Owned(Value::Bool(truthy(left)? || truthy(right)?)),
+            Expr::Call { name, args, source } => {
+                let args = args
+                    .iter()
+                    .map(|arg| Ok(arg.eval(scope)?.into_owned()))
+                    .collect::<Result<Vec<_>, RenderError>>()?;
+                Cow::Owned(scope.call(name, &args, source)?)
+            }
+        })
+    }
+}
+
+// A filter applied to the value of a placeholder, e.g. `truncate(20)`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FilterCall<'e> {
+    pub(crate) name: &'e str,
+    pub(crate) args: Vec<Expr<'e>>,
+}
+
+// Splits a placeholder into its key and the filters its value is piped through,
+// e.g. `user.name | upper | truncate(20)`
+pub(crate) fn parse_pipeline(input: &str) -> Result<(&str, Vec<FilterCall<'_>>), String> {
+    let Some(bar) = first_bar(input) else {
+        return Ok((input.trim(), Vec::new()));
+    };
+    let mut parser = Parser {
+        rest: &input[bar..],
+    };
+    let mut filters = Vec::new();
+    while parser.eat("|") {
+        filters.push(parser.filter()?);
+    }
+    parser.skip_whitespace();
+    if !parser.rest.is_empty() {
+        return Err(format!("Unexpected `{}` after the filters", parser.rest));
+    }
+    Ok((input[..bar].trim(), filters))
+}
+
+// The first `|` outside of strings and calls, where the filters start
+pub(crate) fn first_bar(input: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut depth = 0usize;
+    for (i, c) in input.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, '|') if depth == 0 => return Some(i),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+// Whether the character may appear in a path, e.g. `user.first-name`
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+struct Parser<'e> {
+    rest: &'e str,
+}
+
+impl<'e> Parser<'e> {
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn filter(&mut self) -> Result<FilterCall<'e>, String> {
+        self.skip_whitespace();
+        let end = self
+            .rest
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(self.rest.len());
+        let name = &self.rest[..end];
+        if name.is_empty() {
+            return Err("Expected a filter name after `|`".into());
+        }
+        self.rest = &self.rest[end..];
+        let args = if self.eat("(") {
+            self.arguments(name)?
+        } else {
+            Vec::new()
+        };
+        Ok(FilterCall { name, args })
+    }
+
+    // Parses the arguments of a call after its `(`, up to and including the `)`
+    fn arguments(&mut self, name: &str) -> Result<Vec<Expr<'e>>, String> {
+        let mut args = Vec::new();
+        if self.eat(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.or()?);
+            if self.eat(")") {
+                return Ok(args);
+            }
+            if !self.eat(",") {
+                return Err(format!("Expected `,` or `)` in the arguments of `{name}`"));
+            }
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr<'e>, String> {
+        let mut left = self.and()?;
+        while self.eat("||") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr<'e>, String> {
+        let mut left = self.unary()?;
+        while self.eat("&&") {
+            left = Expr::And(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr<'e>, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        let left = self.operand()?;
+        let negated = if self.eat("==") {
+            false
+        } else if self.eat("!=") {
+            true
+        } else {
+            return Ok(left);
+        };
+        Ok(Expr::Equals {
+            left: Box::new(left),
+            right: Box::new(self.operand()?),
+            negated,
+        })
+    }
+
+    fn operand(&mut self) -> Result<Expr<'e>, String> {
+        if self.eat("(") {
+            let expr = self.or()?;
+            if !self.eat(")") {
+                return Err("Expected `)` to close the group".into());
+            }
+            return Ok(expr);
+        }
+        let rest = self.rest;
+        let Some(first) = rest.chars().next() else {
+            return Err("Expected a value or a path".into());
+        };
+        if first == '"' || first == '\'' {
+            let Some(end) = rest[1..].find(first) else {
+                return Err(format!("Unclosed string at `{rest}`"));
+            };
+            self.rest = &rest[end + 2..];
+            return Ok(Expr::Literal(rest[1..=end].into()));
+        }
+        let end = rest.find(|c| !is_path_char(c)).unwrap_or(rest.len());
+        let word = &rest[..end];
+        if word.is_empty() {
+            return Err(format!("Unexpected `{rest}` in expression"));
+        }
+        self.rest = &rest[end..];
+        if self.rest.starts_with('(') {
+            self.rest = &self.rest[1..];
+            let args = self.arguments(word)?;
+            let source = &rest[..rest.len() - self.rest.len()];
+            return Ok(Expr::Call {
+                name: word.into(),
+                args,
+                source: source.into(),
+            });
+        }
+        Ok(match word {
+            "true" => Expr::Literal(Value::Bool(true)),
+            "false" => Expr::Literal(Value::Bool(false)),
+            "null" => Expr::Literal(Value::Null),
+            word if first.is_ascii_digit() || first == '-' => {
+                if let Ok(value) = word.parse() {
+                    Expr::Literal(Value::Int(value))
+                } else if let Ok(value) = word.parse() {
+                    Expr::Literal(Value::Float(value))
+                } else {
+                    return Err(format!("Invalid number `{word}`"));
+                }
+            }
+            path => Expr::Path(path.into()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Expr, FilterCall, parse_pipeline};
+    use crate::{
+        environment::Environment,
+        template::{Context, Missing, RenderError, Scope},
+        value::Value,
+    };
+
+    fn eval(input: &str, context: &Context) -> Result<Value<'static>, RenderError> {
+        let expr = Expr::parse(input).unwrap();
+        let scope = Scope::new(Environment::builtin(), context);
+        Ok(expr.eval(&scope)?.into_owned().into_owned())
+    }
+
+    fn path(name: &str) -> Box<Expr<'_>> {
+        Box::new(Expr::Path(name.into()))
+    }
+
+    #[test]
+    fn test_precedence() {
+        // `&&` binds tighter than `||`, `!` and `==` tighter than both
+        assert_eq!(
+            Expr::parse("a || b && c").unwrap(),
+            Expr::Or(path("a"), Box::new(Expr::And(path("b"), path("c"))))
+        );
+        assert_eq!(
+            Expr::parse("!a && b == c").unwrap(),
+            Expr::And(
+                Box::new(Expr::Not(path("a"))),
+                Box::new(Expr::Equals {
+                    left: path("b"),
+                    right: path("c"),
+                    negated: false,
+                })
+            )
+        );
+        assert_eq!(
+            Expr::parse("(a || b) && c").unwrap(),
+            Expr::And(Box::new(Expr::Or(path("a"), path("b"))), path("c"))
+        );
+
+        let context = Context::new()
+            .with_value("yes", true)
+            .with_value("no", false);
+        for (input, expected) in [
+            ("yes || no && no", true),
+            ("(yes || no) && no", false),
+            ("!no && yes", true),
+            ("!(no || yes)", false),
+            ("!!yes", true),
+            ("no == no || no", true),
+            ("yes != no && yes", true),
+        ] {
+            assert_eq!(eval(input, &context), Ok(Value::Bool(expected)), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_comparisons_across_types() {
+        let context = Context::new()
+            .with_value("count", 2)
+            .with_value("ratio", 2.0)
+            .with_value("name", "2")
+            .with_value("tags", vec!["a"]);
+        for (input, expected) in [
+            ("count == 2", true),
+            ("count == ratio", true),
+            ("ratio == 2", true),
+            ("count == 2.5", false),
+            ("count == name", false),
+            ("name == '2'", true),
+            ("name == \"2\"", true),
+            ("count == true", false),
+            ("missing == null", true),
+            ("null == false", false),
+            ("'' == null", false),
+            ("tags == tags", true),
+            ("tags != null", true),
+            ("-1 == -1.0", true),
+        ] {
+            assert_eq!(eval(input, &context), Ok(Value::Bool(expected)), "{input}");
+        }
+        assert_eq!(eval("count", &context), Ok(Value::Int(2)));
+        assert_eq!(eval("\"it's\"", &context), Ok(Value::from("it's")));
+    }
+
+    #[test]
+    fn test_missing_variables() {
+        // Conditions test for presence, so missing keys are `null` in every mode,
+        // while a placeholder with the same key follows the mode
+        for (missing, placeholder) in [
+            (
+                Missing::Error,
+                Err(RenderError::MissingKey("user.name".into())),
+            ),
+            (Missing::Empty, Ok("Hi ".into())),
+            (Missing::Keep, Ok("Hi {user.name}".into())),
+        ] {
+            let context = Context::new().with_missing(missing);
+            assert_eq!(eval("user.name", &context), Ok(Value::Null));
+            assert_eq!(eval("!user.name", &context), Ok(Value::Bool(true)));
+            assert_eq!(eval("user.name == null", &context), Ok(Value::Bool(true)));
+            assert_eq!(
+                context
+                    .interpolate("Hi {user.name}")
+                    .map(|text| text.into_owned()),
+                placeholder
+            );
+        }
+    }
+
+    #[test]
+    fn test_pipeline() {
+        assert_eq!(parse_pipeline(" name "), Ok(("name", Vec::new())));
+        assert_eq!(
+            parse_pipeline("name | upper | truncate(20)"),
+            Ok((
+                "name",
+                vec![
+                    FilterCall {
+                        name: "upper",
+                        args: Vec::new(),
+                    },
+                    FilterCall {
+                        name: "truncate",
+                        args: vec![Expr::Literal(Value::Int(20))],
+                    },
+                ]
+            ))
+        );
+        // Bars inside strings and calls belong to the key
+        assert_eq!(
+            parse_pipeline("join('|', or(a || b)) | date('%H|%M')"),
+            Ok((
+                "join('|', or(a || b))",
+                vec![FilterCall {
+                    name: "date",
+                    args: vec![Expr::Literal("%H|%M".into())],
+                }]
+            ))
+        );
+        for input in [
+            "name |",
+            "name | | upper",
+            "name | truncate(",
+            "name | truncate(1 2)",
+            "name | upper lower",
+            "name | upper)",
+            "name | 'upper'",
+        ] {
+            assert!(parse_pipeline(input).is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_malformed_expressions() {
+        for input in [
+            "",
+            "   ",
+            "a &&",
+            "|| a",
+            "!",
+            "(a",
+            "(a || b",
+            "a)",
+            "'unclosed",
+            "\"unclosed",
+            "a == ",
+            "a === b",
+            "a = b",
+            "a b",
+            "1.2.3",
+            "-",
+            "f(a",
+            "f(a b)",
+            "f(,)",
+            "—",
+            "a == b == c",
+        ] {
+            assert!(Expr::parse(input).is_err(), "{input:?}");
+        }
+        let context = Context::new();
+        assert_eq!(
+            eval("unknown(1)", &context),
+            Err(RenderError::UnknownFunction("unknown".into()))
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::borrow::Cow;
+
+use crate::prelude::*;
+
+/// Declaration that starts every rendered feed
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="utf-8"?>"#;
+
+const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+
+/// A feed of entries, rendered as Atom with [`Feed::to_atom`] or RSS 2.0 with [`Feed::to_rss`]
+///
+/// Timestamps are RFC 3339, e.g. `2024-05-01T12:00:00Z`, and are converted to the
+/// RFC 822 form RSS expects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed<'a> {
+    pub title: Cow<'a, str>,
+    /// URL of the site the feed belongs to, also used as the Atom feed id
+    pub link: Cow<'a, str>,
+    pub updated: Cow<'a, str>,
+    pub subtitle: Option<Cow<'a, str>>,
+    pub author: Option<Cow<'a, str>>,
+    pub entries: Vec<Entry<'a>>,
+}
+
+/// An item of a [`Feed`], such as a blog post
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub title: Cow<'a, str>,
+    pub link: Cow<'a, str>,
+    pub updated: Cow<'a, str>,
+    /// Permanent unique id, the link if not set
+    pub id: Option<Cow<'a, str>>,
+    /// Plain text summary
+    pub summary: Option<Cow<'a, str>>,
+    /// Full content as HTML, e.g. a page rendered with [`Render::render`]
+    pub content: Option<Cow<'a, str>>,
+    pub author: Option<Cow<'a, str>>,
+}
+
+impl<'a> Feed<'a> {
+    pub fn new(
+        title: impl Into<Cow<'a, str>>,
+        link: impl Into<Cow<'a, str>>,
+        updated: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        Feed {
+            title: title.into(),
+            link: link.into(),
+            updated: updated.into(),
+            subtitle: None,
+            author: None,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn set_subtitle(&mut self, subtitle: impl Into<Cow<'a, str>>) {
+        self.subtitle = Some(subtitle.into());
+    }
+    #[must_use]
+    pub fn with_subtitle(mut self, subtitle: impl Into<Cow<'a, str>>) -> Self {
+        self.set_subtitle(subtitle);
+        self
+    }
+
+    pub fn set_author(&mut self, author: impl Into<Cow<'a, str>>) {
+        self.author = Some(author.into());
+    }
+    #[must_use]
+    pub fn with_author(mut self, author: impl Into<Cow<'a, str>>) -> Self {
+        self.set_author(author);
+        self
+    }
+
+    pub fn add_entry(&mut self, entry: Entry<'a>) {
+        self.entries.push(entry);
+    }
+    #[must_use]
+    pub fn with_entry(mut self, entry: Entry<'a>) -> Self {
+        self.add_entry(entry);
+        self
+    }
+
+    /// Builds the feed as an Atom document, render it with [`Block::render_xml`]
+    #[must_use]
+    pub fn to_atom(&self) -> Document<'a> {
+        let mut feed = element("feed")
+            .with_key_value("xmlns", ATOM_NAMESPACE)
+            .with_child(text_element("title", &self.title))
+            .with_child(element("link").with_key_value("href", self.link.clone()))
+            .with_child(text_element("id", &self.link))
+            .with_child(text_element("updated", &self.updated));
+        if let Some(subtitle) = &self.subtitle {
+            feed.add_child(text_element("subtitle", subtitle));
+        }
+        if let Some(author) = &self.author {
+            feed.add_child(atom_author(author));
+        }
+        for entry in &self.entries {
+            feed.add_child(entry.to_atom());
+        }
+        Document::new()
+            .with_child(Node::raw(XML_DECLARATION))
+            .with_child(feed)
+    }
+
+    /// Builds the feed as an RSS 2.0 document, render it with [`Block::render_xml`]
+    #[must_use]
+    pub fn to_rss(&self) -> Document<'a> {
+        // RSS requires a description, the title stands in for a missing subtitle
+        let description = self.subtitle.as_ref().unwrap_or(&self.title);
+        let mut channel = element("channel")
+            .with_child(text_element("title", &self.title))
+            .with_child(text_element("link", &self.link))
+            .with_child(text_element("description", description))
+            .with_child(text_element("lastBuildDate", &rfc822(&self.updated)));
+        for entry in &self.entries {
+            channel.add_child(entry.to_rss());
+        }
+        Document::new()
+            .with_child(Node::raw(XML_DECLARATION))
+            .with_child(
+                element("rss")
+                    .with_key_value("version", "2.0")
+                    .with_child(channel),
+            )
+    }
+}
+
+impl<'a> Entry<'a> {
+    pub fn new(
+        title: impl Into<Cow<'a, str>>,
+        link: impl Into<Cow<'a, str>>,
+        updated: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        Entry {
+            title: title.into(),
+            link: link.into(),
+            updated: updated.into(),
+            id: None,
+            summary: None,
+            content: None,
+            author: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_id(mut self, id: impl Into<Cow<'a, str>>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+    #[must_use]
+    pub fn with_summary(mut self, summary: impl Into<Cow<'a, str>>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+    #[must_use]
+    pub fn with_content(mut self, content: impl Into<Cow<'a, str>>) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+    #[must_use]
+    pub fn with_author(mut self, author: impl Into<Cow<'a, str>>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    fn id(&self) -> &Cow<'a, str> {
+        self.id.as_ref().unwrap_or(&self.link)
+    }
+
+    fn to_atom(&self) -> Element<'a> {
+        let mut entry = element("entry")
+            .with_child(text_element("title", &self.title))
+            .with_child(element("link").with_key_value("href", self.link.clone()))
+            .with_child(text_element("id", self.id()))
+            .with_child(text_element("updated", &self.updated));
+        if let Some(summary) = &self.summary {
+            entry.add_child(text_element("summary", summary));
+        }
+        if let Some(content) = &self.content {
+            entry.add_child(text_element("content", content).with_key_value("type", "html"));
+        }
+        if let Some(author) = &self.author {
+            entry.add_child(atom_author(author));
+        }
+        entry
+    }
+
+    fn to_rss(&self) -> Element<'a> {
+        let mut item = element("item")
+            .with_child(text_element("title", &self.title))
+            .with_child(text_element("link", &self.link))
+            .with_child(
+                text_element("guid", self.id())
+                    .with_key_value("isPermaLink", self.id.is_none().to_string()),
+            )
+            .with_child(text_element("pubDate", &rfc822(&self.updated)));
+        if let Some(description) = self.content.as_ref().or(self.summary.as_ref()) {
+            item.add_child(text_element("description", description));
+        }
+        if let Some(author) = &self.author {
+            item.add_child(text_element("author", author));
+        }
+        item
+    }
+}
+
+fn text_element<'a>(name: &'static str, text: &Cow<'a, str>) -> Element<'a> {
+    element(name).with_child(Node::text(text.clone()))
+}
+
+fn atom_author<'a>(name: &Cow<'a, str>) -> Element<'a> {
+    element("author").with_child(text_element("name", name))
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// Converts an RFC 3339 timestamp to RFC 822, returning it unchanged if it does not parse
+//
+// e.g. `2024-05-01T12:00:00+02:00` becomes `Wed, 01 May 2024 12:00:00 +0200`
+fn rfc822<'a>(timestamp: &Cow<'a, str>) -> Cow<'a, str> {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let parse = || {
+        let number = |range: std::ops::Range<usize>| timestamp.get(range)?.parse::<i64>().ok();
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        let time = timestamp.get(11..19)?;
+        let rest = timestamp.get(19..)?;
+        // Fractional seconds are dropped
+        let offset = rest.trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+        let offset = match offset {
+            "Z" | "z" => "+0000".to_string(),
+            offset if offset.len() == 6 && offset.as_bytes()[3] == b':' => {
+                format!("{}{}", &offset[..3], &offset[4..])
+            }
+            _ => return None,
+        };
+        let weekday =
+            DAYS[usize::try_from(days_from_civil(year, month, day).rem_euclid(7)).ok()?];
+        let month_name = MONTHS.get(usize::try_from(month - 1).ok()?)?;
+        Some(format!(
+            "{weekday}, {day:02} {month_name} {year} {time} {offset}"
+        ))
+    };
+    parse().map_or_else(|| timestamp.clone(), Cow::Owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, Feed, rfc822};
+
+    fn feed() -> Feed<'static> {
+        Feed::new("Blog", "https://example.com/", "2024-05-01T12:00:00Z").with_entry(
+            Entry::new(
+                "Hello & welcome",
+                "https://example.com/hello",
+                "2024-04-30T08:30:00+02:00",
+            )
+            .with_summary("First post")
+            .with_content("<p>Hi</p>"),
+        )
+    }
+
+    #[test]
+    fn test_atom() {
+        assert_eq!(
+            feed().to_atom().render_xml(),
+            concat!(
+                r#"<?xml version="1.0" encoding="utf-8"?>"#,
+                r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Blog</title>"#,
+                r#"<link href="https://example.com/"/><id>https://example.com/</id>"#,
+                "<updated>2024-05-01T12:00:00Z</updated>",
+                "<entry><title>Hello &amp; welcome</title>",
+                r#"<link href="https://example.com/hello"/><id>https://example.com/hello</id>"#,
+                "<updated>2024-04-30T08:30:00+02:00</updated><summary>First post</summary>",
+                r#"<content type="html">&lt;p&gt;Hi&lt;/p&gt;</content></entry></feed>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_rss() {
+        assert_eq!(
+            feed().to_rss().render_xml(),
+            concat!(
+                r#"<?xml version="1.0" encoding="utf-8"?>"#,
+                r#"<rss version="2.0"><channel><title>Blog</title>"#,
+                "<link>https://example.com/</link><description>Blog</description>",
+                "<lastBuildDate>Wed, 01 May 2024 12:00:00 +0000</lastBuildDate>",
+                "<item><title>Hello &amp; welcome</title><link>https://example.com/hello</link>",
+                r#"<guid isPermaLink="true">https://example.com/hello</guid>"#,
+                "<pubDate>Tue, 30 Apr 2024 08:30:00 +0200</pubDate>",
+                "<description>&lt;p&gt;Hi&lt;/p&gt;</description></item></channel></rss>",
+            )
+        );
+        assert_eq!(rfc822(&"yesterday".into()), "yesterday");
+    }
+}
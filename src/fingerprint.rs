@@ -0,0 +1,218 @@
+use crate::prelude::*;
+
+// 64-bit FNV-1a, fixed here so fingerprints do not change between Rust versions or platforms
+#[derive(Debug, Clone, Copy)]
+struct Fnv(u64);
+
+impl Fnv {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    fn new() -> Self {
+        Fnv(Self::OFFSET)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    // Length prefixed so that adjacent strings cannot run into each other
+    fn str(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.bytes(value.as_bytes());
+    }
+}
+
+fn hash_nodes(nodes: &[Node], state: &mut Fnv) {
+    state.u64(nodes.len() as u64);
+    for node in nodes {
+        hash_node(node, state);
+    }
+}
+
+fn hash_node(node: &Node, state: &mut Fnv) {
+    match node {
+        Node::Text(text) => {
+            state.u64(0);
+            state.str(&text.content);
+        }
+        // Shared elements hash like the element itself
+        Node::Element(element) => {
+            state.u64(1);
+            hash_element(element, state);
+        }
+        Node::Shared(element) => {
+            state.u64(1);
+            hash_element(element, state);
+        }
+        Node::Raw(raw) => {
+            state.u64(2);
+            state.str(raw);
+        }
+        Node::Comment(comment) => {
+            state.u64(3);
+            state.str(comment);
+        }
+        Node::Slot(name) => {
+            state.u64(4);
+            state.str(name);
+        }
+        Node::Directive(directive) => {
+            state.u64(6);
+            match directive {
+                Directive::Extends(name) => {
+                    state.u64(0);
+                    state.str(name);
+                }
+                Directive::Block { name, children } => {
+                    state.u64(1);
+                    state.str(name);
+                    hash_nodes(children, state);
+                }
+                Directive::Include(name) => {
+                    state.u64(2);
+                    state.str(name);
+                }
+                Directive::If {
+                    condition,
+                    children,
+                } => {
+                    state.u64(3);
+                    state.str(condition);
+                    hash_nodes(children, state);
+                }
+                Directive::Else {
+                    condition,
+                    children,
+                } => {
+                    state.u64(4);
+                    match condition {
+                        Some(condition) => {
+                            state.u64(1);
+                            state.str(condition);
+                        }
+                        None => state.u64(0),
+                    }
+                    hash_nodes(children, state);
+                }
+                Directive::For {
+                    item,
+                    path,
+                    children,
+                } => {
+                    state.u64(5);
+                    state.str(item);
+                    state.str(path);
+                    hash_nodes(children, state);
+                }
+            }
+        }
+        #[cfg(feature = "i18n")]
+        Node::Translation(translation) => {
+            state.u64(5);
+            state.str(&translation.key);
+            state.u64(translation.args.len() as u64);
+            for (name, value) in &translation.args {
+                state.str(name);
+                state.str(value);
+            }
+        }
+    }
+}
+
+fn hash_element(element: &Element, state: &mut Fnv) {
+    state.str(element.name.as_str());
+    state.str(element.namespace.uri());
+    // Attributes are hashed on their own and summed, so their order does not matter
+    let attributes = element.attributes.iter().fold(0u64, |sum, attribute| {
+        let mut hash = Fnv::new();
+        hash.str(&attribute.key);
+        hash.str(&attribute.value);
+        sum.wrapping_add(hash.0)
+    });
+    state.u64(attributes);
+    hash_nodes(&element.children, state);
+}
+
+impl Node<'_> {
+    /// Structural hash of the node, stable across runs, platforms and compiler versions
+    ///
+    /// Unlike [`Hash`], the order of attributes does not change the fingerprint and
+    /// event handlers are ignored, so equal markup gives equal fingerprints. Use it as
+    /// a cache key for rendered fragments.
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        let mut state = Fnv::new();
+        hash_node(self, &mut state);
+        state.0
+    }
+}
+
+impl Element<'_> {
+    /// Structural hash of the element, see [`Node::fingerprint`]
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        let mut state = Fnv::new();
+        state.u64(1);
+        hash_element(self, &mut state);
+        state.0
+    }
+}
+
+impl Block<'_> {
+    /// Structural hash of the document, see [`Node::fingerprint`]
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        let mut state = Fnv::new();
+        hash_nodes(&self.children, &mut state);
+        state.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::prelude::*;
+
+    fn link<'a>(attributes: impl IntoIterator<Item = (&'a str, &'a str)>) -> Element<'a> {
+        element("a")
+            .with_attributes(attributes.into_iter().map(Attribute::from))
+            .with_child("Home")
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_attribute_order() {
+        let a = link([("href", "/"), ("class", "nav")]);
+        let b = link([("class", "nav"), ("href", "/")]);
+        assert_ne!(a, b);
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_eq!(a.fingerprint(), Node::from(a.clone()).fingerprint());
+        assert_ne!(a.fingerprint(), link([("href", "/about")]).fingerprint());
+        assert_ne!(
+            Node::text("a").fingerprint(),
+            Node::comment("a").fingerprint()
+        );
+        // Pinned so that accidental changes to the algorithm are caught
+        assert_eq!(
+            element("p").with_child("hi").fingerprint(),
+            0xad24_5a8a_a720_fd77
+        );
+    }
+
+    #[test]
+    fn test_hash_and_ord() {
+        let mut nodes = vec![Node::text("b"), element("p").into(), Node::text("a")];
+        nodes.sort();
+        assert_eq!(nodes[0], Node::text("a"));
+        let unique: HashSet<_> = nodes.iter().chain(&nodes).collect();
+        assert_eq!(unique.len(), 3);
+    }
+}
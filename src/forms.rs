@@ -0,0 +1,738 @@
+use std::borrow::Cow;
+
+use crate::prelude::*;
+
+/// How a [`Field`] is edited
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldKind<'a> {
+    /// An `input` of the type, e.g. `text`, `email` or `number`
+    Input(Cow<'a, str>),
+    TextArea,
+    Select(Vec<SelectOption<'a>>),
+    /// An `input type="checkbox"`, checked if the field's value is `true` or `on`
+    Checkbox,
+}
+
+/// A choice of a select field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectOption<'a> {
+    pub value: Cow<'a, str>,
+    pub label: Cow<'a, str>,
+}
+
+impl<'a> SelectOption<'a> {
+    pub fn new(value: impl Into<Cow<'a, str>>, label: impl Into<Cow<'a, str>>) -> Self {
+        SelectOption {
+            value: value.into(),
+            label: label.into(),
+        }
+    }
+}
+
+/// A labelled form control with its validation errors, built into an [`Element`]
+/// with [`Field::to_element`]
+///
+/// The label's `for` always matches the control's `id`, which is the field's name
+/// unless set, prefixed with the form's id inside a [`Form`] that has one. Values
+/// are escaped when rendered like any attribute or text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field<'a> {
+    pub name: Cow<'a, str>,
+    pub kind: FieldKind<'a>,
+    pub label: Option<Cow<'a, str>>,
+    pub value: Option<Cow<'a, str>>,
+    pub id: Option<Cow<'a, str>>,
+    pub placeholder: Option<Cow<'a, str>>,
+    pub required: bool,
+    pub errors: Vec<Cow<'a, str>>,
+}
+
+impl<'a> Field<'a> {
+    pub fn new(name: impl Into<Cow<'a, str>>, kind: FieldKind<'a>) -> Self {
+        Field {
+            name: name.into(),
+            kind,
+            label: None,
+            value: None,
+            id: None,
+            placeholder: None,
+            required: false,
+            errors: Vec::new(),
+        }
+    }
+
+    /// An `input` of the type, e.g. `date` or `url`
+    pub fn input(name: impl Into<Cow<'a, str>>, input_type: impl Into<Cow<'a, str>>) -> Self {
+        Field::new(name, FieldKind::Input(input_type.into()))
+    }
+
+    pub fn text(name: impl Into<Cow<'a, str>>) -> Self {
+        Field::input(name, "text")
+    }
+
+    pub fn email(name: impl Into<Cow<'a, str>>) -> Self {
+        Field::input(name, "email")
+    }
+
+    /// A password input, which never renders its value back
+    pub fn password(name: impl Into<Cow<'a, str>>) -> Self {
+        Field::input(name, "password")
+    }
+
+    pub fn number(name: impl Into<Cow<'a, str>>) -> Self {
+        Field::input(name, "number")
+    }
+
+    /// A hidden input, rendered without a label or errors
+    pub fn hidden(name: impl Into<Cow<'a, str>>) -> Self {
+        Field::input(name, "hidden")
+    }
+
+    pub fn textarea(name: impl Into<Cow<'a, str>>) -> Self {
+        Field::new(name, FieldKind::TextArea)
+    }
+
+    pub fn select(name: impl Into<Cow<'a, str>>, options: Vec<SelectOption<'a>>) -> Self {
+        Field::new(name, FieldKind::Select(options))
+    }
+
+    pub fn checkbox(name: impl Into<Cow<'a, str>>) -> Self {
+        Field::new(name, FieldKind::Checkbox)
+    }
+
+    pub fn set_label(&mut self, label: impl Into<Cow<'a, str>>) {
+        self.label = Some(label.into());
+    }
+    #[must_use]
+    pub fn with_label(mut self, label: impl Into<Cow<'a, str>>) -> Self {
+        self.set_label(label);
+        self
+    }
+
+    pub fn set_value(&mut self, value: impl Into<Cow<'a, str>>) {
+        self.value = Some(value.into());
+    }
+    #[must_use]
+    pub fn with_value(mut self, value: impl Into<Cow<'a, str>>) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    /// Checks or unchecks a checkbox, by setting its value to `true` or removing it
+    pub fn set_checked(&mut self, checked: bool) {
+        self.value = checked.then_some(Cow::Borrowed("true"));
+    }
+    #[must_use]
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        self.set_checked(checked);
+        self
+    }
+
+    pub fn set_id(&mut self, id: impl Into<Cow<'a, str>>) {
+        self.id = Some(id.into());
+    }
+    #[must_use]
+    pub fn with_id(mut self, id: impl Into<Cow<'a, str>>) -> Self {
+        self.set_id(id);
+        self
+    }
+
+    pub fn set_placeholder(&mut self, placeholder: impl Into<Cow<'a, str>>) {
+        self.placeholder = Some(placeholder.into());
+    }
+    #[must_use]
+    pub fn with_placeholder(mut self, placeholder: impl Into<Cow<'a, str>>) -> Self {
+        self.set_placeholder(placeholder);
+        self
+    }
+
+    pub fn set_required(&mut self, required: bool) {
+        self.required = required;
+    }
+    #[must_use]
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.set_required(required);
+        self
+    }
+
+    /// Adds a validation error, shown below the control
+    pub fn add_error(&mut self, error: impl Into<Cow<'a, str>>) {
+        self.errors.push(error.into());
+    }
+    #[must_use]
+    pub fn with_error(mut self, error: impl Into<Cow<'a, str>>) -> Self {
+        self.add_error(error);
+        self
+    }
+
+    fn is_hidden(&self) -> bool {
+        matches!(&self.kind, FieldKind::Input(input_type) if input_type == "hidden")
+    }
+
+    fn is_checked(&self) -> bool {
+        matches!(self.value.as_deref(), Some("true" | "on"))
+    }
+
+    /// The `id` of the control, its name with characters not allowed in ids replaced
+    /// unless set
+    #[must_use]
+    pub fn control_id(&self) -> Cow<'a, str> {
+        self.id.clone().unwrap_or_else(|| {
+            Cow::Owned(
+                self.name
+                    .chars()
+                    .map(|c| match c {
+                        c if c.is_alphanumeric() || c == '-' || c == '_' => c,
+                        _ => '-',
+                    })
+                    .collect(),
+            )
+        })
+    }
+
+    /// Builds the control on its own, with its `id`, `name` and value
+    #[must_use]
+    pub fn control(&self) -> Element<'a> {
+        self.control_with_id(self.control_id())
+    }
+
+    fn control_with_id(&self, id: Cow<'a, str>) -> Element<'a> {
+        let mut control = match &self.kind {
+            FieldKind::Input(input_type) => {
+                let mut input = element("input").with_key_value("type", input_type.clone());
+                if let Some(value) = &self.value
+                    && input_type != "password"
+                {
+                    input.add_key_value("value", value.clone());
+                }
+                input
+            }
+            FieldKind::TextArea => {
+                element("textarea").with_child(Node::text(self.value.clone().unwrap_or_default()))
+            }
+            FieldKind::Select(options) => {
+                element("select").with_children(options.iter().map(|option| {
+                    let mut element = element("option")
+                        .with_key_value("value", option.value.clone())
+                        .with_child(Node::text(option.label.clone()));
+                    if self.value.as_ref() == Some(&option.value) {
+                        element.add_attribute(Attribute::boolean("selected"));
+                    }
+                    element
+                }))
+            }
+            FieldKind::Checkbox => {
+                let mut checkbox = element("input").with_key_value("type", "checkbox");
+                if self.is_checked() {
+                    checkbox.add_attribute(Attribute::boolean("checked"));
+                }
+                checkbox
+            }
+        };
+        control.add_key_value("name", self.name.clone());
+        if self.is_hidden() {
+            return control;
+        }
+        control.add_key_value("id", id.clone());
+        if let Some(placeholder) = &self.placeholder {
+            control.add_key_value("placeholder", placeholder.clone());
+        }
+        if self.required {
+            control.add_attribute(Attribute::boolean("required"));
+        }
+        if !self.errors.is_empty() {
+            control.add_attribute(Attribute::aria("invalid", "true"));
+            control.add_attribute(Attribute::aria("describedby", format!("{id}-errors")));
+        }
+        control
+    }
+
+    /// Builds the field as a `div` holding its label, control and errors, or only
+    /// the control for a hidden input
+    #[must_use]
+    pub fn to_element(&self) -> Element<'a> {
+        self.build(self.control_id())
+    }
+
+    fn build(&self, id: Cow<'a, str>) -> Element<'a> {
+        let control = self.control_with_id(id.clone());
+        if self.is_hidden() {
+            return control;
+        }
+        let label = self.label.clone().map(|label| {
+            element("label")
+                .with_key_value("for", id.clone())
+                .with_child(Node::text(label))
+        });
+        let mut field = element("div").with_class("field");
+        // A checkbox comes before its label
+        if self.kind == FieldKind::Checkbox {
+            field.add_child(control);
+            field.add_children(label);
+        } else {
+            field.add_children(label);
+            field.add_child(control);
+        }
+        if !self.errors.is_empty() {
+            field.add_class("has-errors");
+            field.add_child(error_list(format!("{id}-errors"), &self.errors));
+        }
+        field
+    }
+}
+
+// The list of validation errors with the id that controls are described by
+fn error_list<'a>(id: impl Into<Cow<'a, str>>, errors: &[Cow<'a, str>]) -> Element<'a> {
+    element("ul")
+        .with_class("errors")
+        .with_key_value("id", id)
+        .with_children(
+            errors
+                .iter()
+                .map(|error| element("li").with_child(Node::text(error.clone()))),
+        )
+}
+
+/// A form of [`Field`]s with a submit button, built with [`Form::to_element`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Form<'a> {
+    pub action: Cow<'a, str>,
+    /// `post` unless set
+    pub method: Cow<'a, str>,
+    /// Id of the form, which prefixes the ids of its controls, e.g. `signup-email`
+    pub id: Option<Cow<'a, str>>,
+    pub fields: Vec<Field<'a>>,
+    pub submit: Cow<'a, str>,
+    /// Errors that concern the whole form, shown above the fields
+    pub errors: Vec<Cow<'a, str>>,
+}
+
+impl<'a> Form<'a> {
+    pub fn new(action: impl Into<Cow<'a, str>>) -> Self {
+        Form {
+            action: action.into(),
+            method: Cow::Borrowed("post"),
+            id: None,
+            fields: Vec::new(),
+            submit: Cow::Borrowed("Submit"),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn set_method(&mut self, method: impl Into<Cow<'a, str>>) {
+        self.method = method.into();
+    }
+    #[must_use]
+    pub fn with_method(mut self, method: impl Into<Cow<'a, str>>) -> Self {
+        self.set_method(method);
+        self
+    }
+
+    pub fn set_id(&mut self, id: impl Into<Cow<'a, str>>) {
+        self.id = Some(id.into());
+    }
+    #[must_use]
+    pub fn with_id(mut self, id: impl Into<Cow<'a, str>>) -> Self {
+        self.set_id(id);
+        self
+    }
+
+    pub fn add_field(&mut self, field: Field<'a>) {
+        self.fields.push(field);
+    }
+    #[must_use]
+    pub fn with_field(mut self, field: Field<'a>) -> Self {
+        self.add_field(field);
+        self
+    }
+    #[must_use]
+    pub fn with_fields(mut self, fields: impl IntoIterator<Item = Field<'a>>) -> Self {
+        self.fields.extend(fields);
+        self
+    }
+
+    pub fn set_submit(&mut self, label: impl Into<Cow<'a, str>>) {
+        self.submit = label.into();
+    }
+    #[must_use]
+    pub fn with_submit(mut self, label: impl Into<Cow<'a, str>>) -> Self {
+        self.set_submit(label);
+        self
+    }
+
+    #[must_use]
+    pub fn field(&self, name: &str) -> Option<&Field<'a>> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut Field<'a>> {
+        self.fields.iter_mut().find(|field| field.name == name)
+    }
+
+    /// Adds a validation error to the field with the name, or to the form if it has
+    /// no such field
+    pub fn add_error(&mut self, field: &str, error: impl Into<Cow<'a, str>>) {
+        match self.field_mut(field) {
+            Some(field) => field.add_error(error),
+            None => self.errors.push(error.into()),
+        }
+    }
+    #[must_use]
+    pub fn with_error(mut self, field: &str, error: impl Into<Cow<'a, str>>) -> Self {
+        self.add_error(field, error);
+        self
+    }
+
+    /// Sets the values of the fields by name, e.g. to show submitted values again
+    /// next to their errors
+    ///
+    /// Checkboxes missing from the values are unchecked, as browsers do not submit them.
+    pub fn fill<K, V>(&mut self, values: impl IntoIterator<Item = (K, V)>)
+    where
+        K: AsRef<str>,
+        V: Into<Cow<'a, str>>,
+    {
+        for field in &mut self.fields {
+            if field.kind == FieldKind::Checkbox {
+                field.set_checked(false);
+            }
+        }
+        for (name, value) in values {
+            if let Some(field) = self.field_mut(name.as_ref()) {
+                field.set_value(value);
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty() || self.fields.iter().any(|field| !field.errors.is_empty())
+    }
+
+    /// Builds the `form` element with the form's errors, its fields and a submit button
+    ///
+    /// A form with a file input is sent as `multipart/form-data`.
+    #[must_use]
+    pub fn to_element(&self) -> Element<'a> {
+        let mut form = element("form")
+            .with_key_value("action", self.action.clone())
+            .with_key_value("method", self.method.clone());
+        if let Some(id) = &self.id {
+            form.add_key_value("id", id.clone());
+        }
+        if self.fields.iter().any(
+            |field| matches!(&field.kind, FieldKind::Input(input_type) if input_type == "file"),
+        ) {
+            form.add_key_value("enctype", "multipart/form-data");
+        }
+        if !self.errors.is_empty() {
+            let id = match &self.id {
+                Some(id) => format!("{id}-errors"),
+                None => "form-errors".to_string(),
+            };
+            form.add_child(error_list(id, &self.errors));
+        }
+        for field in &self.fields {
+            let id = match (&self.id, &field.id) {
+                (Some(form), None) => Cow::Owned(format!("{form}-{}", field.control_id())),
+                _ => field.control_id(),
+            };
+            form.add_child(field.build(id));
+        }
+        form.with_child(
+            element("button")
+                .with_key_value("type", "submit")
+                .with_child(Node::text(self.submit.clone())),
+        )
+    }
+}
+
+impl<'a> From<Form<'a>> for Node<'a> {
+    fn from(form: Form<'a>) -> Self {
+        form.to_element().into()
+    }
+}
+
+impl<'a> From<Field<'a>> for Node<'a> {
+    fn from(field: Field<'a>) -> Self {
+        field.to_element().into()
+    }
+}
+
+/// A value edited through a form field, used by `#[derive(Form)]`
+///
+/// Strings are text inputs, numbers number inputs and `bool`s checkboxes. An
+/// `Option` is edited like its content, without being required.
+pub trait FormValue {
+    /// Whether the field must be filled in
+    const REQUIRED: bool = true;
+
+    /// The field the value is edited with unless the derive names another kind
+    fn field(name: &'static str) -> Field<'static>;
+
+    /// Sets the field's value to this one
+    fn fill(&self, field: Field<'static>) -> Field<'static>;
+}
+
+impl FormValue for String {
+    fn field(name: &'static str) -> Field<'static> {
+        Field::text(name)
+    }
+
+    fn fill(&self, field: Field<'static>) -> Field<'static> {
+        field.with_value(self.clone())
+    }
+}
+
+impl FormValue for &str {
+    fn field(name: &'static str) -> Field<'static> {
+        Field::text(name)
+    }
+
+    fn fill(&self, field: Field<'static>) -> Field<'static> {
+        field.with_value(self.to_string())
+    }
+}
+
+impl FormValue for bool {
+    // An unchecked checkbox is a valid answer
+    const REQUIRED: bool = false;
+
+    fn field(name: &'static str) -> Field<'static> {
+        Field::checkbox(name)
+    }
+
+    fn fill(&self, field: Field<'static>) -> Field<'static> {
+        field.with_checked(*self)
+    }
+}
+
+macro_rules! impl_form_value_number {
+    ($($number:ty),*) => {
+        $(
+            impl FormValue for $number {
+                fn field(name: &'static str) -> Field<'static> {
+                    Field::number(name)
+                }
+
+                fn fill(&self, field: Field<'static>) -> Field<'static> {
+                    field.with_value(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_form_value_number!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+impl<T: FormValue> FormValue for Option<T> {
+    const REQUIRED: bool = false;
+
+    fn field(name: &'static str) -> Field<'static> {
+        T::field(name)
+    }
+
+    fn fill(&self, field: Field<'static>) -> Field<'static> {
+        match self {
+            Some(value) => value.fill(field),
+            None => field,
+        }
+    }
+}
+
+/// A struct edited through a form, usually derived with `#[derive(Form)]` from
+/// `rs-tml-macro`
+///
+/// The derive adds a field per struct field, labelled with its name and filled
+/// with its value, see [`FormValue`]. Struct fields take a `#[form(...)]` attribute
+/// with `label = "..."`, `placeholder = "..."`, `kind = "email"` (any input type,
+/// or `textarea`) and `skip`.
+pub trait FormFields {
+    fn fields(&self) -> Vec<Field<'static>>;
+
+    /// A form posting the fields to the action
+    fn to_form(&self, action: impl Into<Cow<'static, str>>) -> Form<'static> {
+        Form::new(action).with_fields(self.fields())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Field, Form, FormValue, SelectOption};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_form() {
+        let form = Form::new("/signup")
+            .with_id("signup")
+            .with_field(
+                Field::email("email")
+                    .with_label("Email")
+                    .with_value("a\"b@example.com")
+                    .with_required(true),
+            )
+            .with_field(
+                Field::password("password")
+                    .with_label("Password")
+                    .with_value("secret"),
+            )
+            .with_field(
+                Field::select(
+                    "plan",
+                    vec![
+                        SelectOption::new("free", "Free"),
+                        SelectOption::new("pro", "Pro"),
+                    ],
+                )
+                .with_label("Plan")
+                .with_value("pro"),
+            )
+            .with_field(Field::checkbox("terms").with_label("I agree"))
+            .with_field(Field::hidden("token").with_value("t<1>"))
+            .with_error("email", "Already taken")
+            .with_error("other", "Try again later");
+        assert!(form.has_errors());
+        assert_eq!(
+            form.to_element().render(),
+            concat!(
+                r#"<form action="/signup" method="post" id="signup">"#,
+                r#"<ul class="errors" id="signup-errors"><li>Try again later</li></ul>"#,
+                r#"<div class="field has-errors"><label for="signup-email">Email</label>"#,
+                r#"<input type="email" value="a&quot;b@example.com" name="email" id="signup-email" required aria-invalid="true" aria-describedby="signup-email-errors">"#,
+                r#"<ul class="errors" id="signup-email-errors"><li>Already taken</li></ul></div>"#,
+                r#"<div class="field"><label for="signup-password">Password</label>"#,
+                r#"<input type="password" name="password" id="signup-password"></div>"#,
+                r#"<div class="field"><label for="signup-plan">Plan</label><select name="plan" id="signup-plan">"#,
+                r#"<option value="free">Free</option><option value="pro" selected>Pro</option></select></div>"#,
+                r#"<div class="field"><input type="checkbox" name="terms" id="signup-terms">"#,
+                r#"<label for="signup-terms">I agree</label></div>"#,
+                r#"<input type="hidden" value="t&lt;1&gt;" name="token">"#,
+                r#"<button type="submit">Submit</button></form>"#,
+            )
+        );
+
+        let mut form = Form::new("/note").with_field(Field::textarea("body").with_label("Body"));
+        form.fill([("body", "<b>hi</b>")]);
+        assert_eq!(
+            form.field("body").unwrap().to_element().render(),
+            r#"<div class="field"><label for="body">Body</label><textarea name="body" id="body">&lt;b&gt;hi&lt;/b&gt;</textarea></div>"#
+        );
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let mut form = Form::new("/signup")
+            .with_field(Field::text("name").with_label("Name"))
+            .with_field(Field::hidden("token"));
+        assert!(!form.has_errors());
+        form.add_error("name", "Too short");
+        form.add_error("name", "Must start with a letter");
+        assert!(form.has_errors());
+        assert_eq!(
+            form.field("name").unwrap().to_element().render(),
+            concat!(
+                r#"<div class="field has-errors"><label for="name">Name</label>"#,
+                r#"<input type="text" name="name" id="name" aria-invalid="true" aria-describedby="name-errors">"#,
+                r#"<ul class="errors" id="name-errors"><li>Too short</li><li>Must start with a letter</li></ul></div>"#,
+            )
+        );
+
+        // Hidden inputs have no place to show errors, and unknown fields report to the form
+        let form = Form::new("/signup")
+            .with_field(Field::hidden("token"))
+            .with_error("token", "Expired")
+            .with_error("missing", "Try again");
+        assert!(form.has_errors());
+        assert_eq!(
+            form.to_element().render(),
+            concat!(
+                r#"<form action="/signup" method="post">"#,
+                r#"<ul class="errors" id="form-errors"><li>Try again</li></ul>"#,
+                r#"<input type="hidden" name="token"><button type="submit">Submit</button></form>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_optional_fields() {
+        const {
+            assert!(String::REQUIRED);
+            assert!(!Option::<String>::REQUIRED);
+            assert!(!bool::REQUIRED);
+            assert!(!Option::<i32>::REQUIRED);
+        }
+        assert_eq!(Option::<u8>::field("age"), Field::number("age"));
+
+        let empty = None::<String>.fill(Field::text("nickname"));
+        assert_eq!(empty, Field::text("nickname"));
+        assert_eq!(
+            empty.control().render(),
+            r#"<input type="text" name="nickname" id="nickname">"#
+        );
+        assert_eq!(
+            Some(7).fill(Field::number("age")).control().render(),
+            r#"<input type="number" value="7" name="age" id="age">"#
+        );
+
+        // Filling a form only sets the submitted fields, and unchecks missing checkboxes
+        let mut form = Form::new("/profile")
+            .with_field(Field::text("nickname"))
+            .with_field(Field::text("bio").with_value("Old"))
+            .with_field(Field::checkbox("newsletter").with_checked(true));
+        form.fill([("nickname", "Ann"), ("unknown", "x")]);
+        assert_eq!(
+            form.field("nickname").unwrap().value.as_deref(),
+            Some("Ann")
+        );
+        assert_eq!(form.field("bio").unwrap().value.as_deref(), Some("Old"));
+        assert_eq!(
+            form.field("newsletter").unwrap().control().render(),
+            r#"<input type="checkbox" name="newsletter" id="newsletter">"#
+        );
+    }
+
+    #[test]
+    fn test_value_escaping() {
+        let value = r#"" onfocus="steal()" x='<&>'"#;
+        assert_eq!(
+            Field::text("q")
+                .with_value(value)
+                .with_placeholder("<search>")
+                .control()
+                .render(),
+            concat!(
+                r#"<input type="text" value="&quot; onfocus=&quot;steal()&quot; x=&#39;&lt;&amp;&gt;&#39;" "#,
+                r#"name="q" id="q" placeholder="&lt;search&gt;">"#,
+            )
+        );
+        assert_eq!(
+            Field::textarea("body")
+                .with_value("</textarea><script>steal()</script>")
+                .control()
+                .render(),
+            r#"<textarea name="body" id="body">&lt;/textarea&gt;&lt;script&gt;steal()&lt;/script&gt;</textarea>"#
+        );
+        assert_eq!(
+            Field::select("plan", vec![SelectOption::new("a\"b", "<Pro>")])
+                .with_value("a\"b")
+                .control()
+                .render(),
+            r#"<select name="plan" id="plan"><option value="a&quot;b" selected>&lt;Pro&gt;</option></select>"#
+        );
+        // Names with characters not allowed in ids get a cleaned up id
+        assert_eq!(
+            Field::text("user[\"name\"]")
+                .with_label("<Name>")
+                .with_error("<b>bad</b>")
+                .to_element()
+                .render(),
+            concat!(
+                r#"<div class="field has-errors"><label for="user--name--">&lt;Name&gt;</label>"#,
+                r#"<input type="text" name="user[&quot;name&quot;]" id="user--name--" aria-invalid="true" aria-describedby="user--name---errors">"#,
+                r#"<ul class="errors" id="user--name---errors"><li>&lt;b&gt;bad&lt;/b&gt;</li></ul></div>"#,
+            )
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::borrow::Cow;
+
+use crate::prelude::*;
+
+fn position(nodes: &[Node], tag: &str) -> Option<usize> {
+    nodes
+        .iter()
+        .position(|node| node.as_element().is_some_and(|e| e.name.as_str() == tag))
+}
+
+// Index of the child element matching the predicate, or of a new one built by `create`
+pub(crate) fn find_or_add<'e, 'a>(
+    parent: &'e mut Element<'a>,
+    matches: impl Fn(&Element) -> bool,
+    create: impl FnOnce() -> Element<'a>,
+) -> &'e mut Element<'a> {
+    let index = match parent
+        .children
+        .iter()
+        .position(|node| node.as_element().is_some_and(&matches))
+    {
+        Some(index) => index,
+        None => {
+            parent.add_child(create());
+            parent.children.len() - 1
+        }
+    };
+    parent.children[index]
+        .as_element_mut()
+        .expect("matched or added an element")
+}
+
+impl<'a> Block<'a> {
+    // Nodes that `head` and `body` belong to, the `html` element's children if there is one
+    fn page_nodes(&self) -> &[Node<'a>] {
+        position(&self.children, "html")
+            .and_then(|i| self.children[i].as_element())
+            .map_or(&self.children, |html| &html.children)
+    }
+
+    fn page_nodes_mut(&mut self) -> &mut Vec<Node<'a>> {
+        match position(&self.children, "html") {
+            Some(i) => {
+                &mut self.children[i]
+                    .as_element_mut()
+                    .expect("found an element")
+                    .children
+            }
+            None => &mut self.children,
+        }
+    }
+
+    fn section(&self, tag: &str) -> Option<&Element<'a>> {
+        let nodes = self.page_nodes();
+        nodes[position(nodes, tag)?].as_element()
+    }
+
+    // Finds the `head` or `body`, adding it first or last if it is missing
+    fn section_mut(&mut self, tag: &'static str, first: bool) -> &mut Element<'a> {
+        let nodes = self.page_nodes_mut();
+        let index = position(nodes, tag).unwrap_or_else(|| {
+            let index = if first { 0 } else { nodes.len() };
+            nodes.insert(index, element(tag).into());
+            index
+        });
+        nodes[index].as_element_mut().expect("found an element")
+    }
+
+    /// Returns the `head` element, at the top level or inside `html`
+    #[must_use]
+    pub fn head(&self) -> Option<&Element<'a>> {
+        self.section("head")
+    }
+
+    /// Returns the `body` element, at the top level or inside `html`
+    #[must_use]
+    pub fn body(&self) -> Option<&Element<'a>> {
+        self.section("body")
+    }
+
+    /// Returns the `head` element, inserting an empty one before any other content if missing
+    pub fn head_mut(&mut self) -> &mut Element<'a> {
+        self.section_mut("head", true)
+    }
+
+    /// Returns the `body` element, appending an empty one after any other content if missing
+    pub fn body_mut(&mut self) -> &mut Element<'a> {
+        self.section_mut("body", false)
+    }
+
+    /// Sets the text of the `title` in `head`, adding it if missing
+    pub fn set_title(&mut self, title: impl Into<Cow<'a, str>>) -> &mut Element<'a> {
+        let title_element = find_or_add(
+            self.head_mut(),
+            |e| e.name.as_str() == "title",
+            || element("title"),
+        );
+        title_element.children = vec![Node::text(title)];
+        title_element
+    }
+
+    /// Sets the `content` of the `meta` in `head` with the `name`, adding it if missing
+    pub fn add_meta(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        content: impl Into<Cow<'a, str>>,
+    ) -> &mut Element<'a> {
+        let name = name.into();
+        let meta = find_or_add(
+            self.head_mut(),
+            |e| e.name.as_str() == "meta" && e.attr("name") == Some(&name),
+            || element("meta").with_key_value("name", name.clone()),
+        );
+        meta.set_attr("content", content);
+        meta
+    }
+
+    /// Links a stylesheet in `head`, unless a stylesheet with the same `href` is already linked
+    pub fn add_stylesheet(&mut self, href: impl Into<Cow<'a, str>>) -> &mut Element<'a> {
+        let href = href.into();
+        find_or_add(
+            self.head_mut(),
+            |e| {
+                e.name.as_str() == "link"
+                    && e.attr("rel") == Some("stylesheet")
+                    && e.attr("href") == Some(&href)
+            },
+            || {
+                element("link")
+                    .with_key_value("rel", "stylesheet")
+                    .with_key_value("href", href.clone())
+            },
+        )
+    }
+
+    /// Adds a script to `head`, unless a script with the same `src` is already present
+    ///
+    /// Returns the script, e.g. to add `defer` or `type="module"` to it.
+    pub fn add_script(&mut self, src: impl Into<Cow<'a, str>>) -> &mut Element<'a> {
+        let src = src.into();
+        find_or_add(
+            self.head_mut(),
+            |e| e.name.as_str() == "script" && e.attr("src") == Some(&src),
+            || element("script").with_key_value("src", src.clone()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_head_helpers() {
+        let mut page = Document::new().with_child(
+            element("html").with_child(element("body").with_child(element("h1").with_child("Hi"))),
+        );
+        assert!(page.head().is_none());
+        page.set_title("Draft");
+        page.set_title("Home");
+        page.add_meta("description", "Old");
+        page.add_meta("description", "Welcome");
+        page.add_stylesheet("/app.css");
+        page.add_stylesheet("/app.css");
+        page.add_script("/app.js")
+            .add_attribute(Attribute::boolean("defer"));
+        page.add_script("/app.js");
+
+        assert_eq!(
+            page.render(),
+            concat!(
+                "<html><head><title>Home</title>",
+                r#"<meta name="description" content="Welcome">"#,
+                r#"<link rel="stylesheet" href="/app.css">"#,
+                r#"<script src="/app.js" defer></script></head>"#,
+                "<body><h1>Hi</h1></body></html>",
+            )
+        );
+        assert_eq!(page.body().unwrap().inner_text(), "Hi");
+    }
+
+    #[test]
+    fn test_missing_sections_are_added() {
+        let mut page = Document::new().with_child(Node::comment("generated"));
+        page.body_mut().add_child("Content");
+        page.set_title("Title");
+        assert_eq!(
+            page.render(),
+            "<head><title>Title</title></head><!--generated--><body>Content</body>"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use std::fmt::Write;
+
+use crate::render::escape_text;
+
+/// Kind of a highlighted range of RSTML source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum TokenKind {
+    /// Element name, e.g. `div`
+    Tag,
+    /// `.key` of an attribute, or the `#id` and `.class` shorthands
+    AttributeKey,
+    /// A quoted string, excluding its placeholders
+    String,
+    /// A `{name}` placeholder inside a string, see [`crate::template::Context`]
+    Interpolation,
+    /// A `//` line comment or a `/* */` block comment, which may nest
+    Comment,
+    /// An `@` directive, e.g. `@slot`
+    Keyword,
+    /// Any other name, e.g. a slot name
+    Identifier,
+    /// Braces, parentheses and `=`
+    Punctuation,
+}
+
+impl TokenKind {
+    /// Name of the kind, e.g. `attribute-key`, usable as a CSS class
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            TokenKind::Tag => "tag",
+            TokenKind::AttributeKey => "attribute-key",
+            TokenKind::String => "string",
+            TokenKind::Interpolation => "interpolation",
+            TokenKind::Comment => "comment",
+            TokenKind::Keyword => "keyword",
+            TokenKind::Identifier => "identifier",
+            TokenKind::Punctuation => "punctuation",
+        }
+    }
+}
+
+/// A classified range of the source, as byte offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    /// The text of the token in the source it was lexed from
+    #[must_use]
+    pub fn text<'s>(&self, source: &'s str) -> &'s str {
+        &source[self.start..self.end]
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+struct Lexer<'s> {
+    source: &'s str,
+    position: usize,
+    tokens: Vec<Token>,
+}
+
+impl Lexer<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    fn push(&mut self, kind: TokenKind, len: usize) {
+        self.tokens.push(Token {
+            kind,
+            start: self.position,
+            end: self.position + len,
+        });
+        self.position += len;
+    }
+
+    fn name_len(&self, skip: usize) -> usize {
+        let rest = &self.rest()[skip..];
+        skip + rest.find(|c| !is_name_char(c)).unwrap_or(rest.len())
+    }
+
+    // Length of a block comment, which may nest, or of the rest if it is not closed
+    fn block_comment_len(&self) -> usize {
+        let rest = self.rest();
+        let mut depth = 0;
+        let mut i = 0;
+        while i < rest.len() {
+            if rest[i..].starts_with("/*") {
+                depth += 1;
+                i += 2;
+            } else if rest[i..].starts_with("*/") {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            } else {
+                i += rest[i..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        rest.len()
+    }
+
+    // Splits a string into its text and placeholders, `{{` and `}}` being text
+    fn string(&mut self) {
+        let rest = self.rest();
+        let mut end = rest.len();
+        let mut escaped = false;
+        for (i, c) in rest.char_indices().skip(1) {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    end = i + 1;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let string = &rest[..end];
+        let mut text_start = 0;
+        let mut i = 0;
+        let mut pieces = Vec::new();
+        while let Some(offset) = string[i..].find(['{', '}']) {
+            let brace = i + offset;
+            if string[brace..].starts_with("{{") || string[brace..].starts_with("}}") {
+                i = brace + 2;
+                continue;
+            }
+            let close = string[brace..]
+                .find('}')
+                .filter(|_| string[brace..].starts_with('{'));
+            let Some(close) = close else {
+                i = brace + 1;
+                continue;
+            };
+            pieces.push((TokenKind::String, brace - text_start));
+            pieces.push((TokenKind::Interpolation, close + 1));
+            i = brace + close + 1;
+            text_start = i;
+        }
+        pieces.push((TokenKind::String, end - text_start));
+        for (kind, len) in pieces.into_iter().filter(|(_, len)| *len > 0) {
+            self.push(kind, len);
+        }
+    }
+
+    fn lex(mut self) -> Vec<Token> {
+        while let Some(c) = self.rest().chars().next() {
+            let rest = self.rest();
+            if c.is_whitespace() {
+                self.position += c.len_utf8();
+            } else if rest.starts_with("//") {
+                self.push(TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()));
+            } else if rest.starts_with("/*") {
+                self.push(TokenKind::Comment, self.block_comment_len());
+            } else if c == '"' {
+                self.string();
+            } else if c == '.' || c == '#' {
+                self.push(TokenKind::AttributeKey, self.name_len(1));
+            } else if c == '@' {
+                self.push(TokenKind::Keyword, self.name_len(1));
+            } else if matches!(c, '{' | '}' | '(' | ')' | '=') {
+                self.push(TokenKind::Punctuation, 1);
+            } else if is_name_char(c) {
+                let len = self.name_len(0);
+                let next = crate::parse::consume_comments(&rest[len..]);
+                let kind = if next.starts_with('{') {
+                    TokenKind::Tag
+                } else {
+                    TokenKind::Identifier
+                };
+                self.push(kind, len);
+            } else {
+                self.position += c.len_utf8();
+            }
+        }
+        self.tokens
+    }
+}
+
+/// Classifies the ranges of RSTML source for syntax highlighting, in source order
+///
+/// Lexing never fails, so incomplete input such as an unclosed string or comment
+/// still highlights up to the end. Whitespace and unrecognized characters are not
+/// covered by any token.
+#[must_use]
+pub fn tokens(input: &str) -> Vec<Token> {
+    Lexer {
+        source: input,
+        position: 0,
+        tokens: Vec::new(),
+    }
+    .lex()
+}
+
+/// Highlights RSTML source as HTML, wrapping each token in a `<span>` whose class
+/// is its [`TokenKind::as_str`]
+///
+/// All text, including whitespace and unrecognized characters between tokens, is
+/// escaped, so the result can be placed inside a `<pre>` as is.
+#[must_use]
+pub fn to_html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut position = 0;
+    for token in tokens(input) {
+        out.push_str(&escape_text(&input[position..token.start]));
+        write!(
+            out,
+            r#"<span class="{}">{}</span>"#,
+            token.kind.as_str(),
+            escape_text(token.text(input))
+        )
+        .expect("writing to a String cannot fail");
+        position = token.end;
+    }
+    out.push_str(&escape_text(&input[position..]));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TokenKind, to_html, tokens};
+
+    fn kinds(source: &str) -> Vec<(TokenKind, &str)> {
+        tokens(source)
+            .iter()
+            .map(|token| (token.kind, token.text(source)))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokens() {
+        let source = "div { // note\n    #main .title = \"Hi {name}!\" @slot(body) }";
+        assert_eq!(
+            kinds(source),
+            [
+                (TokenKind::Tag, "div"),
+                (TokenKind::Punctuation, "{"),
+                (TokenKind::Comment, "// note"),
+                (TokenKind::AttributeKey, "#main"),
+                (TokenKind::AttributeKey, ".title"),
+                (TokenKind::Punctuation, "="),
+                (TokenKind::String, "\"Hi "),
+                (TokenKind::Interpolation, "{name}"),
+                (TokenKind::String, "!\""),
+                (TokenKind::Keyword, "@slot"),
+                (TokenKind::Punctuation, "("),
+                (TokenKind::Identifier, "body"),
+                (TokenKind::Punctuation, ")"),
+                (TokenKind::Punctuation, "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_token_kinds() {
+        // Names are tags only when a block follows, even past a comment
+        assert_eq!(
+            kinds("p /* c */ { br }"),
+            [
+                (TokenKind::Tag, "p"),
+                (TokenKind::Comment, "/* c */"),
+                (TokenKind::Punctuation, "{"),
+                (TokenKind::Identifier, "br"),
+                (TokenKind::Punctuation, "}"),
+            ]
+        );
+        assert_eq!(
+            kinds(".data-x=\"1\" #a_b"),
+            [
+                (TokenKind::AttributeKey, ".data-x"),
+                (TokenKind::Punctuation, "="),
+                (TokenKind::String, "\"1\""),
+                (TokenKind::AttributeKey, "#a_b"),
+            ]
+        );
+        // Escaped quotes and doubled braces stay in the string
+        assert_eq!(
+            kinds(r#""a \" {{b}} {c}""#),
+            [
+                (TokenKind::String, r#""a \" {{b}} "#),
+                (TokenKind::Interpolation, "{c}"),
+                (TokenKind::String, "\""),
+            ]
+        );
+        assert_eq!(
+            kinds("/* a /* b */ c */ // d"),
+            [
+                (TokenKind::Comment, "/* a /* b */ c */"),
+                (TokenKind::Comment, "// d"),
+            ]
+        );
+        assert_eq!(
+            kinds("@if(x)"),
+            [
+                (TokenKind::Keyword, "@if"),
+                (TokenKind::Punctuation, "("),
+                (TokenKind::Identifier, "x"),
+                (TokenKind::Punctuation, ")"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_incomplete_input() {
+        assert_eq!(kinds("\"open {x"), [(TokenKind::String, "\"open {x")]);
+        assert_eq!(kinds("/* open"), [(TokenKind::Comment, "/* open")]);
+        assert_eq!(
+            kinds("a ; b"),
+            [(TokenKind::Identifier, "a"), (TokenKind::Identifier, "b")]
+        );
+        assert!(tokens("").is_empty());
+    }
+
+    #[test]
+    fn test_to_html() {
+        assert_eq!(
+            to_html("p { \"<b>&{x}\" }"),
+            concat!(
+                r#"<span class="tag">p</span> <span class="punctuation">{</span> "#,
+                r#"<span class="string">"&lt;b&gt;&amp;</span>"#,
+                r#"<span class="interpolation">{x}</span>"#,
+                r#"<span class="string">"</span> <span class="punctuation">}</span>"#,
+            )
+        );
+        // Text outside tokens is escaped too
+        assert_eq!(
+            to_html("a <script>"),
+            r#"<span class="identifier">a</span> &lt;<span class="identifier">script</span>&gt;"#
+        );
+        assert_eq!(
+            to_html("// </pre>"),
+            r#"<span class="comment">// &lt;/pre&gt;</span>"#
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use std::borrow::Cow;
+
+use crate::prelude::*;
+
+/// Names of the attributes defined by htmx 2, other than `hx-on` handlers
+pub const HTMX_ATTRIBUTES: &[&str] = &[
+    "hx-boost",
+    "hx-confirm",
+    "hx-delete",
+    "hx-disable",
+    "hx-disabled-elt",
+    "hx-disinherit",
+    "hx-encoding",
+    "hx-ext",
+    "hx-get",
+    "hx-headers",
+    "hx-history",
+    "hx-history-elt",
+    "hx-include",
+    "hx-indicator",
+    "hx-inherit",
+    "hx-params",
+    "hx-patch",
+    "hx-post",
+    "hx-preserve",
+    "hx-prompt",
+    "hx-push-url",
+    "hx-put",
+    "hx-replace-url",
+    "hx-request",
+    "hx-select",
+    "hx-select-oob",
+    "hx-swap",
+    "hx-swap-oob",
+    "hx-sync",
+    "hx-target",
+    "hx-trigger",
+    "hx-validate",
+    "hx-vals",
+    "hx-vars",
+];
+
+/// How htmx swaps a response into the target, the value of `hx-swap`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Swap {
+    /// Replaces the target's children, the htmx default
+    #[default]
+    InnerHtml,
+    /// Replaces the target itself
+    OuterHtml,
+    /// Replaces the target's text content, without parsing the response
+    TextContent,
+    BeforeBegin,
+    AfterBegin,
+    BeforeEnd,
+    AfterEnd,
+    /// Deletes the target regardless of the response
+    Delete,
+    /// Does not swap, out of band swaps still happen
+    None,
+}
+
+impl Swap {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Swap::InnerHtml => "innerHTML",
+            Swap::OuterHtml => "outerHTML",
+            Swap::TextContent => "textContent",
+            Swap::BeforeBegin => "beforebegin",
+            Swap::AfterBegin => "afterbegin",
+            Swap::BeforeEnd => "beforeend",
+            Swap::AfterEnd => "afterend",
+            Swap::Delete => "delete",
+            Swap::None => "none",
+        }
+    }
+}
+
+impl std::fmt::Display for Swap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Issues a `GET` to the URL, e.g. `hx_get("/items")` becomes `hx-get="/items"`
+pub fn hx_get<'a>(url: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-get", url)
+}
+pub fn hx_post<'a>(url: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-post", url)
+}
+pub fn hx_put<'a>(url: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-put", url)
+}
+pub fn hx_patch<'a>(url: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-patch", url)
+}
+pub fn hx_delete<'a>(url: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-delete", url)
+}
+
+/// Element to swap the response into, a CSS selector or an extended one such as
+/// `this` or `closest tr`
+pub fn hx_target<'a>(target: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-target", target)
+}
+
+/// Selects part of the response to swap in, by CSS selector
+pub fn hx_select<'a>(selector: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-select", selector)
+}
+
+#[must_use]
+pub fn hx_swap(swap: Swap) -> Attribute<'static> {
+    Attribute::new("hx-swap", swap.as_str())
+}
+
+/// Events that issue the request, e.g. `click`, `keyup changed delay:500ms` or `every 2s`
+pub fn hx_trigger<'a>(trigger: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-trigger", trigger)
+}
+
+/// Asks the user to confirm with the message before issuing the request
+pub fn hx_confirm<'a>(message: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-confirm", message)
+}
+
+/// Element to show while the request is in flight, by CSS selector
+pub fn hx_indicator<'a>(selector: impl Into<Cow<'a, str>>) -> Attribute<'a> {
+    Attribute::new("hx-indicator", selector)
+}
+
+/// Whether to push the request URL into the browser history
+#[must_use]
+pub fn hx_push_url(push: bool) -> Attribute<'static> {
+    Attribute::new("hx-push-url", if push { "true" } else { "false" })
+}
+
+/// Whether links and forms in the element are boosted to use AJAX
+#[must_use]
+pub fn hx_boost(boost: bool) -> Attribute<'static> {
+    Attribute::new("hx-boost", if boost { "true" } else { "false" })
+}
+
+impl Attribute<'_> {
+    /// Returns true if this is an `hx-*` attribute, including `hx-on` handlers
+    #[must_use]
+    pub fn is_htmx(&self) -> bool {
+        self.key.starts_with("hx-")
+    }
+
+    /// Returns true if this is an `hx-*` attribute that is not defined by htmx
+    ///
+    /// `hx-on:*` and `hx-on-*` event handlers are always known.
+    #[must_use]
+    pub fn is_unknown_htmx(&self) -> bool {
+        self.is_htmx()
+            && !HTMX_ATTRIBUTES.contains(&self.key.as_ref())
+            && !self.key.starts_with("hx-on:")
+            && !self.key.starts_with("hx-on-")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        HTMX_ATTRIBUTES, Swap, hx_boost, hx_confirm, hx_delete, hx_get, hx_indicator, hx_patch,
+        hx_post, hx_push_url, hx_put, hx_select, hx_swap, hx_target, hx_trigger,
+    };
+    use crate::prelude::*;
+
+    #[test]
+    fn test_htmx_builders() {
+        let button = element("button")
+            .with_attribute(hx_get("/items"))
+            .with_attribute(hx_target("#list"))
+            .with_attribute(hx_swap(Swap::OuterHtml))
+            .with_attribute(hx_trigger("click once"))
+            .with_child("Load");
+        assert_eq!(
+            button.render(),
+            r##"<button hx-get="/items" hx-target="#list" hx-swap="outerHTML" hx-trigger="click once">Load</button>"##
+        );
+        assert!(button.attributes.iter().all(|a| !a.is_unknown_htmx()));
+        assert!(Attribute::new("hx-gett", "/items").is_unknown_htmx());
+        assert!(!Attribute::new("hx-on:click", "go()").is_unknown_htmx());
+    }
+
+    #[test]
+    fn test_each_builder() {
+        let cases = [
+            (hx_get("/items"), "hx-get", "/items"),
+            (hx_post("/items"), "hx-post", "/items"),
+            (hx_put("/items/1"), "hx-put", "/items/1"),
+            (hx_patch("/items/1"), "hx-patch", "/items/1"),
+            (hx_delete("/items/1"), "hx-delete", "/items/1"),
+            (hx_target("closest tr"), "hx-target", "closest tr"),
+            (hx_select("#content"), "hx-select", "#content"),
+            (hx_swap(Swap::default()), "hx-swap", "innerHTML"),
+            (hx_trigger("every 2s"), "hx-trigger", "every 2s"),
+            (hx_confirm("Sure?"), "hx-confirm", "Sure?"),
+            (hx_indicator("#spinner"), "hx-indicator", "#spinner"),
+            (hx_push_url(true), "hx-push-url", "true"),
+            (hx_push_url(false), "hx-push-url", "false"),
+            (hx_boost(true), "hx-boost", "true"),
+            (hx_boost(false), "hx-boost", "false"),
+        ];
+        for (attribute, key, value) in cases {
+            assert_eq!(attribute.key, key);
+            assert_eq!(attribute.value, value);
+            assert!(HTMX_ATTRIBUTES.contains(&key));
+            assert!(attribute.is_htmx() && !attribute.is_unknown_htmx());
+        }
+    }
+
+    #[test]
+    fn test_swap_values() {
+        let swaps = [
+            (Swap::InnerHtml, "innerHTML"),
+            (Swap::OuterHtml, "outerHTML"),
+            (Swap::TextContent, "textContent"),
+            (Swap::BeforeBegin, "beforebegin"),
+            (Swap::AfterBegin, "afterbegin"),
+            (Swap::BeforeEnd, "beforeend"),
+            (Swap::AfterEnd, "afterend"),
+            (Swap::Delete, "delete"),
+            (Swap::None, "none"),
+        ];
+        for (swap, value) in swaps {
+            assert_eq!(swap.to_string(), value);
+            assert_eq!(hx_swap(swap).value, value);
+        }
+    }
+
+    #[test]
+    fn test_values_are_escaped() {
+        let form = element("form")
+            .with_attribute(hx_post("/search?q=a&b=<c>"))
+            .with_attribute(hx_confirm(r#"Delete "it"? It's gone"#))
+            .with_attribute(hx_trigger("keyup[key=='Enter']"));
+        assert_eq!(
+            form.render(),
+            "<form hx-post=\"/search?q=a&amp;b=&lt;c&gt;\" \
+             hx-confirm=\"Delete &quot;it&quot;? It&#39;s gone\" \
+             hx-trigger=\"keyup[key==&#39;Enter&#39;]\"></form>"
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use std::borrow::{Borrow, Cow};
+
+use fluent_bundle::{
+    FluentArgs, FluentResource, FluentValue, bundle::FluentBundle, memoizer::MemoizerKind,
+};
+
+use crate::{prelude::*, util::quote_nested};
+
+/// Text translated when the document is rendered, written `@t("key", name = "value")`
+///
+/// Without a [`Translator`], e.g. with [`Render::render`], the key is rendered.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Translation<'a> {
+    /// Id of the message to look up
+    pub key: Cow<'a, str>,
+    /// Named arguments of the message
+    pub args: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> Translation<'a> {
+    pub fn new(key: impl Into<Cow<'a, str>>) -> Self {
+        Translation {
+            key: key.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn add_arg(&mut self, name: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) {
+        self.args.push((name.into(), value.into()));
+    }
+    #[must_use]
+    pub fn with_arg(
+        mut self,
+        name: impl Into<Cow<'a, str>>,
+        value: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        self.add_arg(name, value);
+        self
+    }
+
+    /// Returns the value of the named argument
+    #[must_use]
+    pub fn arg(&self, name: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_ref())
+    }
+
+    // Parses the parenthesized arguments of `@t`, e.g. `("key", name = "value")`
+    pub(crate) fn parse_args(input: &'a str) -> ParseResult<'a, Self> {
+        let expected = |found: &'a str, context: &str| {
+            ParseError::invalid_input(found, Some(context.to_string().into()))
+        };
+        let rest = input
+            .trim_start()
+            .strip_prefix('(')
+            .ok_or_else(|| expected(input, "Expected `(` after `@t`"))?;
+        let (mut rest, key) = quote_nested(rest)
+            .map_err(|_| expected(rest, "Expected the message key, e.g. `@t(\"greeting\")`"))?;
+        let mut translation = Translation::new(key);
+        loop {
+            rest = rest.trim_start();
+            if let Some(rest) = rest.strip_prefix(')') {
+                return Ok((rest, translation));
+            }
+            let after_comma = rest
+                .strip_prefix(',')
+                .ok_or_else(|| expected(rest, "Expected `,` or `)` in `@t`"))?
+                .trim_start();
+            if let Some(rest) = after_comma.strip_prefix(')') {
+                return Ok((rest, translation));
+            }
+            let (after_name, name) = crate::tag::parse_name(after_comma)
+                .map_err(|_| expected(after_comma, "Expected an argument name"))?;
+            let after_equals = after_name
+                .trim_start()
+                .strip_prefix('=')
+                .ok_or_else(|| expected(after_name, "Expected `=` after the argument name"))?;
+            let (after_value, value) = quote_nested(after_equals)
+                .map_err(|_| expected(after_equals, "Expected a quoted argument value"))?;
+            translation.add_arg(name, value);
+            rest = after_value;
+        }
+    }
+
+    #[must_use]
+    pub fn into_owned(self) -> Translation<'static> {
+        Translation {
+            key: self.key.into_owned().into(),
+            args: self
+                .args
+                .into_iter()
+                .map(|(name, value)| (name.into_owned().into(), value.into_owned().into()))
+                .collect(),
+        }
+    }
+}
+
+/// Looks up translated messages, see [`Block::localize`]
+pub trait Translator {
+    /// Formats the message for the translation, `None` if the key is unknown
+    fn translate(&self, translation: &Translation) -> Option<String>;
+}
+
+impl<F: Fn(&Translation) -> Option<String>> Translator for F {
+    fn translate(&self, translation: &Translation) -> Option<String> {
+        self(translation)
+    }
+}
+
+/// Formats messages of a Fluent bundle
+///
+/// Arguments that parse as numbers are passed as Fluent numbers, so they select
+/// plural variants. Formatting errors, such as a missing argument, are ignored
+/// and leave Fluent's fallback text in the message.
+impl<R: Borrow<FluentResource>, M: MemoizerKind> Translator for FluentBundle<R, M> {
+    fn translate(&self, translation: &Translation) -> Option<String> {
+        let pattern = self.get_message(&translation.key)?.value()?;
+        let mut args = FluentArgs::new();
+        for (name, value) in &translation.args {
+            let value = match value.parse::<f64>() {
+                Ok(number) => FluentValue::from(number),
+                Err(_) => FluentValue::from(value.as_ref()),
+            };
+            args.set(name.as_ref(), value);
+        }
+        let mut errors = Vec::new();
+        Some(
+            self.format_pattern(pattern, Some(&args), &mut errors)
+                .into_owned(),
+        )
+    }
+}
+
+fn localize_nodes<'a, T: Translator + ?Sized>(nodes: &[Node<'a>], translator: &T) -> Vec<Node<'a>> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Translation(translation) => Node::text(
+                translator
+                    .translate(translation)
+                    .unwrap_or_else(|| translation.key.to_string()),
+            ),
+            node => match node.as_element() {
+                Some(element) => {
+                    let mut localized = element.clone();
+                    localized.children = localize_nodes(&element.children, translator);
+                    Node::Element(localized)
+                }
+                None => node.clone(),
+            },
+        })
+        .collect()
+}
+
+impl<'a> Block<'a> {
+    /// Returns a copy of the document with its translations replaced by text
+    ///
+    /// Keys the translator does not know are kept as the text.
+    #[must_use]
+    pub fn localize<T: Translator + ?Sized>(&self, translator: &T) -> Self {
+        Block {
+            children: localize_nodes(&self.children, translator),
+        }
+    }
+
+    /// Renders the document with its translations looked up, see [`Block::localize`]
+    #[must_use]
+    pub fn render_localized<T: Translator + ?Sized>(&self, translator: &T) -> String {
+        self.localize(translator).render()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fluent_bundle::{FluentBundle, FluentResource};
+
+    use super::{Translation, Translator};
+    use crate::{prelude::*, template::Context};
+
+    fn bundle(locale: &str, source: &str) -> FluentBundle<FluentResource> {
+        let mut bundle = FluentBundle::new(vec![locale.parse().unwrap()]);
+        bundle.set_use_isolating(false);
+        bundle
+            .add_resource(FluentResource::try_new(source.to_string()).unwrap())
+            .unwrap();
+        bundle
+    }
+
+    #[test]
+    fn test_fluent_translator() {
+        let resource = FluentResource::try_new(
+            "greeting = Hello, { $name }!\nitems = { $count ->\n [one] One item\n *[other] { $count } items\n}"
+                .to_string(),
+        )
+        .unwrap();
+        let mut bundle = FluentBundle::new(vec!["en-US".parse().unwrap()]);
+        bundle.set_use_isolating(false);
+        bundle.add_resource(resource).unwrap();
+
+        let (_, document) = Document::parse(
+            r#"p { @t("greeting", name = "Ada") } p { @t("items", count = "1") } p { @t("missing") }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            document.children[0].as_element().unwrap().children[0],
+            Node::translation(Translation::new("greeting").with_arg("name", "Ada"))
+        );
+        assert_eq!(
+            document.render_localized(&bundle),
+            "<p>Hello, Ada!</p><p>One item</p><p>missing</p>"
+        );
+        assert_eq!(
+            document.render(),
+            "<p>greeting</p><p>items</p><p>missing</p>"
+        );
+    }
+
+    #[test]
+    fn test_missing_key() {
+        let bundle = bundle("en-US", "greeting = Hello!");
+        let missing = Translation::new("farewell").with_arg("name", "Ada");
+        assert_eq!(bundle.translate(&missing), None);
+
+        let document = Document::new().with_child(Node::translation(missing));
+        assert_eq!(document.render_localized(&bundle), "farewell");
+        // Keys are text, so they are escaped like it
+        let document = Document::new().with_child(Node::translation(Translation::new("<b>")));
+        assert_eq!(document.render_localized(&bundle), "&lt;b&gt;");
+    }
+
+    #[test]
+    fn test_arguments() {
+        let bundle = bundle(
+            "en-US",
+            "welcome = Welcome, { $name }, you have { $count } messages\nplain = No arguments",
+        );
+        let welcome = Translation::new("welcome")
+            .with_arg("name", "<Ada>")
+            .with_arg("count", "3");
+        assert_eq!(welcome.arg("name"), Some("<Ada>"));
+        assert_eq!(welcome.arg("missing"), None);
+        let document = Document::new()
+            .with_child(element("p").with_child(Node::translation(welcome)))
+            .with_child(Node::translation(
+                Translation::new("plain").with_arg("unused", "x"),
+            ));
+        assert_eq!(
+            document.render_localized(&bundle),
+            "<p>Welcome, &lt;Ada&gt;, you have 3 messages</p>No arguments"
+        );
+
+        let (_, parsed) = Document::parse(r#"@t("welcome", name = "Ada", count = "2",)"#).unwrap();
+        assert_eq!(
+            parsed.children[0],
+            Node::translation(
+                Translation::new("welcome")
+                    .with_arg("name", "Ada")
+                    .with_arg("count", "2")
+            )
+        );
+        assert!(Node::parse(r#"@t("welcome", name)"#).is_err());
+    }
+
+    #[test]
+    fn test_locale_fallback() {
+        let english = bundle("en-US", "greeting = Hello\nfarewell = Goodbye");
+        let french = bundle("fr-FR", "greeting = Bonjour");
+        let translator = |translation: &Translation| {
+            french
+                .translate(translation)
+                .or_else(|| english.translate(translation))
+        };
+        let (_, document) =
+            Document::parse(r#"p { @t("greeting") } p { @t("farewell") } p { @t("unknown") }"#)
+                .unwrap();
+        assert_eq!(
+            document.render_localized(&translator),
+            "<p>Bonjour</p><p>Goodbye</p><p>unknown</p>"
+        );
+    }
+
+    #[test]
+    fn test_runtime_templates() {
+        let bundle = bundle("en-US", "title = Your cart");
+        let (_, document) = Document::parse(r#"h1 { @t("title") } p { "{count} items" }"#).unwrap();
+        let context = Context::new().with_value("count", 2);
+        assert_eq!(
+            document.render_with(&context).unwrap(),
+            "<h1>title</h1><p>2 items</p>"
+        );
+        assert_eq!(
+            document.localize(&bundle).render_with(&context).unwrap(),
+            "<h1>Your cart</h1><p>2 items</p>"
+        );
+    }
+}
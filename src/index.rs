@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use crate::prelude::*;
+
+/// Index from element ids to their position in a document, for repeated lookups
+///
+/// The index stores child positions rather than references, so the document can
+/// still be mutated. Lookups check that the element found still has the id and
+/// return `None` otherwise, after structural changes call [`IdIndex::rebuild`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdIndex {
+    paths: HashMap<String, NodePath>,
+}
+
+impl IdIndex {
+    #[must_use]
+    pub fn new(document: &Document) -> Self {
+        let mut index = IdIndex::default();
+        index.rebuild(document);
+        index
+    }
+
+    /// Re-indexes the document, the first element with an id wins when it is duplicated
+    pub fn rebuild(&mut self, document: &Document) {
+        self.paths.clear();
+        self.collect(&document.children, &mut NodePath::root());
+    }
+
+    fn collect(&mut self, nodes: &[Node], path: &mut NodePath) {
+        for (i, node) in nodes.iter().enumerate() {
+            let Some(element) = node.as_element() else {
+                continue;
+            };
+            path.push(i);
+            if let Some(id) = element.id() {
+                self.paths
+                    .entry(id.to_string())
+                    .or_insert_with(|| path.clone());
+            }
+            self.collect(&element.children, path);
+            path.pop();
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Returns the path of the element with the id, as it was when the index was built
+    #[must_use]
+    pub fn path(&self, id: &str) -> Option<&NodePath> {
+        self.paths.get(id)
+    }
+
+    /// Returns the element with the id, if the index is still up to date for it
+    #[must_use]
+    pub fn get<'d, 'a>(&self, document: &'d Document<'a>, id: &str) -> Option<&'d Element<'a>> {
+        document
+            .get_path(self.path(id)?)?
+            .as_element()
+            .filter(|element| element.id() == Some(id))
+    }
+
+    /// Returns the element with the id mutably, if the index is still up to date for it
+    ///
+    /// Shared elements on the way are copied into the document first, see [`Node::as_element_mut`].
+    #[must_use]
+    pub fn get_mut<'d, 'a>(
+        &self,
+        document: &'d mut Document<'a>,
+        id: &str,
+    ) -> Option<&'d mut Element<'a>> {
+        // Checked before borrowing mutably, so a stale entry does not copy shared elements
+        self.get(document, id)?;
+        document.get_path_mut(self.path(id)?)?.as_element_mut()
+    }
+}
+
+impl<'a> Element<'a> {
+    /// Finds the first descendant with the id, searching depth-first
+    #[must_use]
+    pub fn element_by_id(&self, id: &str) -> Option<&Element<'a>> {
+        by_id(self.descendants(), id)
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Finds the first element with the id, searching depth-first
+    ///
+    /// For many lookups on the same document, build an [`IdIndex`] instead.
+    #[must_use]
+    pub fn element_by_id(&self, id: &str) -> Option<&Element<'a>> {
+        by_id(self.iter(), id)
+    }
+}
+
+fn by_id<'s, 'a>(
+    mut nodes: impl Iterator<Item = &'s Node<'a>>,
+    id: &str,
+) -> Option<&'s Element<'a>> {
+    nodes.find_map(|node| node.as_element().filter(|element| element.id() == Some(id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdIndex;
+    use crate::prelude::*;
+
+    fn document() -> Document<'static> {
+        Document::new()
+            .with_child(element("header").with_attribute(Attribute::id("top")))
+            .with_child(
+                element("main")
+                    .with_attribute(Attribute::id("main"))
+                    .with_child(element("p").with_attribute(Attribute::id("intro"))),
+            )
+    }
+
+    #[test]
+    fn test_element_by_id() {
+        let document = document();
+        assert_eq!(
+            document.element_by_id("intro").map(|e| e.name.clone()),
+            Some(Tag::P)
+        );
+        assert!(document.element_by_id("missing").is_none());
+        let main = document.element_by_id("main").unwrap();
+        assert!(main.element_by_id("intro").is_some());
+        assert!(main.element_by_id("top").is_none());
+    }
+
+    #[test]
+    fn test_id_index() {
+        let mut document = document();
+        let mut index = IdIndex::new(&document);
+        assert_eq!(index.len(), 3);
+        assert_eq!(
+            index.get(&document, "main").map(|e| e.name.clone()),
+            Some(Tag::MAIN)
+        );
+
+        index
+            .get_mut(&mut document, "intro")
+            .unwrap()
+            .add_class("lead");
+        assert!(document.element_by_id("intro").unwrap().has_class("lead"));
+
+        // Stale entries are not returned until the index is rebuilt
+        document.children.remove(0);
+        assert!(index.get(&document, "main").is_none());
+        index.rebuild(&document);
+        assert_eq!(index.len(), 2);
+        assert!(index.get(&document, "main").is_some());
+    }
+}
@@ -1,16 +1,72 @@
+pub mod a11y;
+#[cfg(feature = "actix")]
+pub mod actix;
+pub mod arena;
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "body")]
+pub mod body;
+pub mod cache;
+pub mod components;
+pub mod convert;
+#[cfg(feature = "dev-server")]
+pub mod dev;
+pub mod diagnostics;
+#[cfg(feature = "dioxus")]
+pub mod dioxus;
+#[cfg(feature = "wasm")]
+pub mod dom;
+pub mod edit;
+pub mod environment;
 pub mod error;
+mod expr;
+pub mod feed;
+mod fingerprint;
+pub mod forms;
+pub mod head;
+pub mod highlight;
+pub mod htmx;
+#[cfg(feature = "i18n")]
+pub mod i18n;
+pub mod index;
+pub mod links;
+pub mod lint;
+#[cfg(feature = "live")]
+pub mod live;
+pub mod meta;
 mod models;
 pub use models::*;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 pub mod parse;
+pub mod patch;
+pub mod path;
+pub mod render;
+pub mod sanitize;
+pub mod select;
+pub mod style;
+pub mod table;
+pub mod template;
+pub mod templates;
+pub mod testing;
+pub mod theme;
+pub mod transform;
+pub mod traverse;
 mod util;
+pub mod validate;
+pub mod value;
+pub mod visit;
 #[cfg(test)]
 pub(crate) use util::test_util;
 
 pub mod prelude {
-    use super::{error, models, parse};
+    use super::{error, models, parse, path, render, select};
     pub use error::{ParseError, ParseResult};
     pub use models::prelude::*;
     pub use parse::{RSTMLParse, RSTMLParseExt};
+    pub use path::NodePath;
+    pub use render::Render;
+    pub use select::Selector;
 }
 
 #[cfg(test)]
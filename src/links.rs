@@ -0,0 +1,186 @@
+use crate::prelude::*;
+
+/// A URL found in an attribute, with the element and attribute it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlRef<'s, 'a> {
+    pub path: NodePath,
+    pub element: &'s Element<'a>,
+    pub attribute: &'s Attribute<'a>,
+    /// The URL itself, a single candidate for `srcset` attributes
+    pub url: &'s str,
+}
+
+/// Attributes holding the URL of a resource loaded with the page
+const ASSET_ATTRIBUTES: &[&str] = &["src", "srcset", "poster"];
+
+// Whether an attribute of the element holds a link to navigate to, or an asset to load
+fn is_link(element: &Element, attribute: &Attribute) -> bool {
+    attribute.key == "href" && element.name.as_str() != "link"
+}
+
+pub(crate) fn is_asset(element: &Element, attribute: &Attribute) -> bool {
+    ASSET_ATTRIBUTES.contains(&attribute.key.as_ref())
+        || (attribute.key == "href" && element.name.as_str() == "link")
+}
+
+// Splits a `srcset` into its URLs, e.g. `a.png 1x, b.png 2x`
+fn srcset_urls(srcset: &str) -> impl Iterator<Item = &str> {
+    srcset
+        .split(',')
+        .filter_map(|candidate| candidate.split_whitespace().next())
+}
+
+fn collect<'s, 'a>(
+    nodes: &'s [Node<'a>],
+    filter: fn(&Element, &Attribute) -> bool,
+    path: &mut NodePath,
+    found: &mut Vec<UrlRef<'s, 'a>>,
+) {
+    for (i, node) in nodes.iter().enumerate() {
+        let Some(element) = node.as_element() else {
+            continue;
+        };
+        path.push(i);
+        for attribute in &element.attributes {
+            if !filter(element, attribute) {
+                continue;
+            }
+            let url_ref = |url| UrlRef {
+                path: path.clone(),
+                element,
+                attribute,
+                url,
+            };
+            if attribute.key == "srcset" {
+                found.extend(srcset_urls(&attribute.value).map(url_ref));
+            } else if !attribute.value.trim().is_empty() {
+                found.push(url_ref(attribute.value.trim()));
+            }
+        }
+        collect(&element.children, filter, path, found);
+        path.pop();
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Returns the `href` values of links in the document, e.g. from `a` and `area`
+    ///
+    /// Stylesheets and icons referenced by `link` elements are assets, see [`Block::assets`].
+    #[must_use]
+    pub fn links(&self) -> Vec<UrlRef<'_, 'a>> {
+        let mut found = Vec::new();
+        collect(&self.children, is_link, &mut NodePath::root(), &mut found);
+        found
+    }
+
+    /// Returns the URLs of resources loaded by the document, from `src`, `srcset`,
+    /// `poster` and the `href` of `link` elements
+    ///
+    /// Each candidate of a `srcset` is returned on its own.
+    #[must_use]
+    pub fn assets(&self) -> Vec<UrlRef<'_, 'a>> {
+        let mut found = Vec::new();
+        collect(&self.children, is_asset, &mut NodePath::root(), &mut found);
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_links_and_assets() {
+        let document = Document::new()
+            .with_child(
+                element("head")
+                    .with_child(
+                        element("link")
+                            .with_key_values([("rel", "stylesheet"), ("href", "/app.css")]),
+                    )
+                    .with_child(element("script").with_key_value("src", "/app.js")),
+            )
+            .with_child(
+                element("body")
+                    .with_child(element("a").with_key_value("href", "/about"))
+                    .with_child(
+                        element("img")
+                            .with_key_value("src", "/logo.png")
+                            .with_key_value("srcset", "/logo.png 1x, /logo@2x.png 2x"),
+                    ),
+            );
+
+        let links = document.links();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].url, "/about");
+        assert_eq!(links[0].path, NodePath::from([1, 0]));
+        assert_eq!(links[0].element.name, Tag::A);
+
+        let assets: Vec<_> = document
+            .assets()
+            .iter()
+            .map(|asset| (asset.attribute.key.to_string(), asset.url))
+            .collect();
+        assert_eq!(
+            assets,
+            [
+                ("href".into(), "/app.css"),
+                ("src".into(), "/app.js"),
+                ("src".into(), "/logo.png"),
+                ("srcset".into(), "/logo.png"),
+                ("srcset".into(), "/logo@2x.png"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relative_and_absolute_urls() {
+        let hrefs = [
+            "https://example.com/a?b=c#d",
+            "//cdn.example.com/lib.js",
+            "/about",
+            "../docs/index.html",
+            "page.html",
+            "#top",
+            "?page=2",
+            "mailto:ann@example.com",
+        ];
+        let document = Document::new()
+            .with_children(hrefs.map(|href| element("a").with_key_value("href", href)))
+            .with_child(element("a").with_key_value("href", "  /padded  "))
+            .with_child(element("a").with_key_value("href", " "));
+        let urls: Vec<_> = document.links().iter().map(|link| link.url).collect();
+        // URLs are returned as written, only trimmed, and blank ones are skipped
+        assert_eq!(urls[..hrefs.len()], hrefs);
+        assert_eq!(urls[hrefs.len()..], ["/padded"]);
+
+        let image = Document::new().with_child(
+            element("img")
+                .with_key_value("srcset", "https://example.com/a.png 1x,  b.png 2x , /c.png"),
+        );
+        let urls: Vec<_> = image.assets().iter().map(|asset| asset.url).collect();
+        assert_eq!(urls, ["https://example.com/a.png", "b.png", "/c.png"]);
+    }
+
+    #[test]
+    fn test_escaped_attributes() {
+        let (_, document) = Document::parse(
+            r#"a { .href = "/search?q='x'&page=<2>" } link { .rel = "icon" .href = "/icon.png?v=1&amp;x" }"#,
+        )
+        .unwrap();
+        let document = document.with_child(element("a").with_key_value("href", r#"/say?"hi""#));
+        let links = document.links();
+        assert_eq!(links[0].url, "/search?q='x'&page=<2>");
+        assert_eq!(links[1].url, r#"/say?"hi""#);
+        assert_eq!(document.assets()[0].url, "/icon.png?v=1&amp;x");
+        // The values are kept as written, rendering escapes them
+        assert_eq!(
+            document.render(),
+            concat!(
+                r#"<a href="/search?q=&#39;x&#39;&amp;page=&lt;2&gt;"></a>"#,
+                r#"<link rel="icon" href="/icon.png?v=1&amp;amp;x">"#,
+                r#"<a href="/say?&quot;hi&quot;"></a>"#,
+            )
+        );
+    }
+}
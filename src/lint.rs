@@ -0,0 +1,832 @@
+use std::ops::ControlFlow;
+
+use crate::{
+    parse::ParseOptions,
+    prelude::*,
+    visit::{Visitor, Walk, walk},
+};
+
+/// Elements defined by the HTML living standard
+pub const HTML_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "datalist",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "map",
+    "mark",
+    "math",
+    "menu",
+    "meta",
+    "meter",
+    "nav",
+    "noscript",
+    "object",
+    "ol",
+    "optgroup",
+    "option",
+    "output",
+    "p",
+    "param",
+    "picture",
+    "pre",
+    "progress",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "script",
+    "search",
+    "section",
+    "select",
+    "slot",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "svg",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+];
+
+/// Elements whose start tag implicitly closes an open `<p>`
+pub const P_CLOSING_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "search",
+    "section",
+    "table",
+    "ul",
+];
+
+// Elements that may not contain another element of the same kind
+const NON_NESTING_TAGS: &[&str] = &["a", "button", "form", "label"];
+
+// Hyphenated names used by SVG and MathML, which custom elements may not take
+const RESERVED_CUSTOM_ELEMENT_NAMES: &[&str] = &[
+    "annotation-xml",
+    "color-profile",
+    "font-face",
+    "font-face-src",
+    "font-face-uri",
+    "font-face-format",
+    "font-face-name",
+    "missing-glyph",
+];
+
+/// Returns true for standard HTML tags and valid custom elements, e.g. `my-widget`
+#[must_use]
+pub fn is_known_tag(tag: &str) -> bool {
+    HTML_TAGS.contains(&tag) || custom_element_name_error(tag).is_none()
+}
+
+/// Returns why the name is not a valid custom element name, if it is not
+///
+/// Custom element names start with a lowercase ASCII letter, contain a hyphen and
+/// no uppercase ASCII letters, and are not one of the names reserved by SVG and
+/// MathML. Browsers treat any other unknown tag as an inert `HTMLUnknownElement`.
+#[must_use]
+pub fn custom_element_name_error(name: &str) -> Option<String> {
+    if !name.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return Some("custom element names must start with a lowercase letter".into());
+    }
+    if let Some(c) = name.chars().find(|&c| {
+        !(c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.' | '_'))
+            && c.is_ascii()
+    }) {
+        return Some(format!("custom element names cannot contain `{c}`"));
+    }
+    if !name.contains('-') {
+        return Some("custom element names must contain a hyphen".into());
+    }
+    RESERVED_CUSTOM_ELEMENT_NAMES
+        .contains(&name)
+        .then(|| format!("`{name}` is reserved and cannot name a custom element"))
+}
+
+/// The warning for a tag that is neither standard HTML nor a valid custom element
+#[must_use]
+pub fn unknown_tag_message(tag: &str) -> Option<String> {
+    if HTML_TAGS.contains(&tag) {
+        return None;
+    }
+    custom_element_name_error(tag).map(|reason| format!("Unknown HTML tag `<{tag}>`, {reason}"))
+}
+
+// Elements that are only valid inside one of the listed parents
+const REQUIRED_PARENTS: &[(&str, &[&str])] = &[
+    ("caption", &["table"]),
+    ("col", &["colgroup"]),
+    ("colgroup", &["table"]),
+    ("dd", &["dl", "div"]),
+    ("dt", &["dl", "div"]),
+    ("figcaption", &["figure"]),
+    ("legend", &["fieldset"]),
+    ("li", &["ul", "ol", "menu"]),
+    ("optgroup", &["select"]),
+    ("option", &["select", "datalist", "optgroup"]),
+    ("summary", &["details"]),
+    ("tbody", &["table"]),
+    ("td", &["tr"]),
+    ("tfoot", &["table"]),
+    ("th", &["tr"]),
+    ("thead", &["table"]),
+    ("tr", &["table", "thead", "tbody", "tfoot"]),
+];
+
+// Elements that may only contain the listed children, and script-supporting elements
+const ALLOWED_CHILDREN: &[(&str, &[&str])] = &[
+    ("colgroup", &["col"]),
+    ("dl", &["dt", "dd", "div"]),
+    ("menu", &["li"]),
+    ("ol", &["li"]),
+    ("optgroup", &["option"]),
+    ("select", &["option", "optgroup", "hr"]),
+    (
+        "table",
+        &["caption", "colgroup", "thead", "tbody", "tfoot", "tr"],
+    ),
+    ("tbody", &["tr"]),
+    ("tfoot", &["tr"]),
+    ("thead", &["tr"]),
+    ("tr", &["td", "th"]),
+    ("ul", &["li"]),
+];
+
+// Elements allowed wherever any content model restricts the children
+const SCRIPT_SUPPORTING_TAGS: &[&str] = &["script", "template"];
+
+fn lookup<'t>(table: &[(&str, &'t [&'t str])], tag: &str) -> Option<&'t [&'t str]> {
+    table
+        .iter()
+        .find(|(name, _)| *name == tag)
+        .map(|(_, tags)| *tags)
+}
+
+/// Returns true if `child` may appear directly inside `parent`
+///
+/// This covers the mistakes browsers silently repair, such as a `<div>` inside a
+/// `<p>`, an `<li>` outside a list or a `<div>` directly inside a `<table>`,
+/// rather than the full HTML content model.
+#[must_use]
+pub fn can_contain(parent: &str, child: &str) -> bool {
+    if parent == "p" && P_CLOSING_TAGS.contains(&child) {
+        return false;
+    }
+    if parent == child && NON_NESTING_TAGS.contains(&child) {
+        return false;
+    }
+    if let Some(parents) = lookup(REQUIRED_PARENTS, child)
+        && !parents.contains(&parent)
+    {
+        return false;
+    }
+    lookup(ALLOWED_CHILDREN, parent)
+        .is_none_or(|children| children.contains(&child) || SCRIPT_SUPPORTING_TAGS.contains(&child))
+}
+
+/// Attributes valid on every HTML element
+pub const GLOBAL_ATTRIBUTES: &[&str] = &[
+    "accesskey",
+    "autocapitalize",
+    "autocorrect",
+    "autofocus",
+    "class",
+    "contenteditable",
+    "dir",
+    "draggable",
+    "enterkeyhint",
+    "exportparts",
+    "hidden",
+    "id",
+    "inert",
+    "inputmode",
+    "is",
+    "itemid",
+    "itemprop",
+    "itemref",
+    "itemscope",
+    "itemtype",
+    "lang",
+    "nonce",
+    "part",
+    "popover",
+    "role",
+    "slot",
+    "spellcheck",
+    "style",
+    "tabindex",
+    "title",
+    "translate",
+    "writingsuggestions",
+    "xmlns",
+];
+
+// Attributes valid on specific elements, besides the global ones
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    (
+        "a",
+        &[
+            "href",
+            "target",
+            "download",
+            "ping",
+            "rel",
+            "hreflang",
+            "type",
+            "referrerpolicy",
+        ],
+    ),
+    (
+        "area",
+        &[
+            "alt",
+            "coords",
+            "shape",
+            "href",
+            "target",
+            "download",
+            "ping",
+            "rel",
+            "referrerpolicy",
+        ],
+    ),
+    (
+        "audio",
+        &[
+            "src",
+            "crossorigin",
+            "preload",
+            "autoplay",
+            "loop",
+            "muted",
+            "controls",
+        ],
+    ),
+    ("base", &["href", "target"]),
+    ("blockquote", &["cite"]),
+    (
+        "button",
+        &[
+            "command",
+            "commandfor",
+            "disabled",
+            "form",
+            "formaction",
+            "formenctype",
+            "formmethod",
+            "formnovalidate",
+            "formtarget",
+            "name",
+            "popovertarget",
+            "popovertargetaction",
+            "type",
+            "value",
+        ],
+    ),
+    ("canvas", &["width", "height"]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+    ("data", &["value"]),
+    ("del", &["cite", "datetime"]),
+    ("details", &["open", "name"]),
+    ("dialog", &["open"]),
+    ("embed", &["src", "type", "width", "height"]),
+    ("fieldset", &["disabled", "form", "name"]),
+    (
+        "form",
+        &[
+            "accept-charset",
+            "action",
+            "autocomplete",
+            "enctype",
+            "method",
+            "name",
+            "novalidate",
+            "rel",
+            "target",
+        ],
+    ),
+    ("html", &["manifest"]),
+    (
+        "iframe",
+        &[
+            "src",
+            "srcdoc",
+            "name",
+            "sandbox",
+            "allow",
+            "allowfullscreen",
+            "width",
+            "height",
+            "referrerpolicy",
+            "loading",
+        ],
+    ),
+    (
+        "img",
+        &[
+            "alt",
+            "src",
+            "srcset",
+            "sizes",
+            "crossorigin",
+            "usemap",
+            "ismap",
+            "width",
+            "height",
+            "referrerpolicy",
+            "decoding",
+            "loading",
+            "fetchpriority",
+        ],
+    ),
+    (
+        "input",
+        &[
+            "accept",
+            "alt",
+            "autocomplete",
+            "capture",
+            "checked",
+            "dirname",
+            "disabled",
+            "form",
+            "formaction",
+            "formenctype",
+            "formmethod",
+            "formnovalidate",
+            "formtarget",
+            "height",
+            "list",
+            "max",
+            "maxlength",
+            "min",
+            "minlength",
+            "multiple",
+            "name",
+            "pattern",
+            "placeholder",
+            "popovertarget",
+            "popovertargetaction",
+            "readonly",
+            "required",
+            "size",
+            "src",
+            "step",
+            "type",
+            "value",
+            "width",
+        ],
+    ),
+    ("ins", &["cite", "datetime"]),
+    ("label", &["for"]),
+    ("li", &["value"]),
+    (
+        "link",
+        &[
+            "href",
+            "crossorigin",
+            "rel",
+            "as",
+            "media",
+            "integrity",
+            "hreflang",
+            "type",
+            "referrerpolicy",
+            "sizes",
+            "imagesrcset",
+            "imagesizes",
+            "blocking",
+            "color",
+            "disabled",
+            "fetchpriority",
+        ],
+    ),
+    ("map", &["name"]),
+    (
+        "meta",
+        &["name", "http-equiv", "content", "charset", "media"],
+    ),
+    ("meter", &["value", "min", "max", "low", "high", "optimum"]),
+    (
+        "object",
+        &["data", "type", "name", "form", "width", "height"],
+    ),
+    ("ol", &["reversed", "start", "type"]),
+    ("optgroup", &["disabled", "label"]),
+    ("option", &["disabled", "label", "selected", "value"]),
+    ("output", &["for", "form", "name"]),
+    ("progress", &["value", "max"]),
+    ("q", &["cite"]),
+    (
+        "script",
+        &[
+            "src",
+            "type",
+            "nomodule",
+            "async",
+            "defer",
+            "crossorigin",
+            "integrity",
+            "referrerpolicy",
+            "blocking",
+            "fetchpriority",
+        ],
+    ),
+    (
+        "select",
+        &[
+            "autocomplete",
+            "disabled",
+            "form",
+            "multiple",
+            "name",
+            "required",
+            "size",
+        ],
+    ),
+    ("slot", &["name"]),
+    (
+        "source",
+        &["type", "media", "src", "srcset", "sizes", "width", "height"],
+    ),
+    ("style", &["media", "blocking"]),
+    ("td", &["colspan", "rowspan", "headers"]),
+    (
+        "template",
+        &[
+            "shadowrootmode",
+            "shadowrootdelegatesfocus",
+            "shadowrootclonable",
+            "shadowrootserializable",
+        ],
+    ),
+    (
+        "textarea",
+        &[
+            "autocomplete",
+            "cols",
+            "dirname",
+            "disabled",
+            "form",
+            "maxlength",
+            "minlength",
+            "name",
+            "placeholder",
+            "readonly",
+            "required",
+            "rows",
+            "wrap",
+        ],
+    ),
+    ("th", &["colspan", "rowspan", "headers", "scope", "abbr"]),
+    ("time", &["datetime"]),
+    ("track", &["default", "kind", "label", "src", "srclang"]),
+    (
+        "video",
+        &[
+            "src",
+            "crossorigin",
+            "poster",
+            "preload",
+            "autoplay",
+            "playsinline",
+            "loop",
+            "muted",
+            "controls",
+            "width",
+            "height",
+        ],
+    ),
+];
+
+// Attribute prefixes passed through, as they are open-ended or checked on their own
+const PASSTHROUGH_PREFIXES: &[&str] = &["data-", "aria-", "hx-", "on"];
+
+/// Returns true if the attribute is valid on the tag
+///
+/// Besides the global and tag-specific attributes, `data-*`, `aria-*`, `hx-*`,
+/// event handlers such as `onclick` and namespaced attributes such as `xml:lang`
+/// are accepted. Custom elements, e.g. `my-widget`, and unknown tags accept any
+/// attribute.
+#[must_use]
+pub fn is_known_attribute(tag: &str, key: &str) -> bool {
+    if !HTML_TAGS.contains(&tag) {
+        return true;
+    }
+    GLOBAL_ATTRIBUTES.contains(&key)
+        || lookup(TAG_ATTRIBUTES, tag).is_some_and(|attributes| attributes.contains(&key))
+        || PASSTHROUGH_PREFIXES
+            .iter()
+            .any(|prefix| key.starts_with(prefix))
+        || key.contains(':')
+}
+
+// Edit distance between two short strings, counting single byte changes
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, &x) in a.as_bytes().iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &y) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The attribute of the tag closest to a misspelled one, e.g. `href` for `herf`
+#[must_use]
+pub fn suggest_attribute(tag: &str, key: &str) -> Option<&'static str> {
+    GLOBAL_ATTRIBUTES
+        .iter()
+        .chain(lookup(TAG_ATTRIBUTES, tag).unwrap_or_default())
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|&(distance, _)| distance <= 2 && distance < key.len())
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// A warning about markup that parses correctly but is likely a mistake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// Tag of the element the lint was raised on
+    pub element: String,
+    pub message: String,
+}
+
+impl Lint {
+    pub fn new(element: impl Into<String>, message: impl Into<String>) -> Self {
+        Lint {
+            element: element.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}>: {}", self.element, self.message)
+    }
+}
+
+/// Reports every `aria-*` attribute that is not defined by WAI-ARIA
+#[must_use]
+pub fn unknown_aria_attributes(nodes: &[Node]) -> Vec<Lint> {
+    unknown_attributes(nodes, |a| a.is_unknown_aria(), "ARIA")
+}
+
+/// Reports every `hx-*` attribute that is not defined by htmx
+#[must_use]
+pub fn unknown_htmx_attributes(nodes: &[Node]) -> Vec<Lint> {
+    unknown_attributes(nodes, |a| a.is_unknown_htmx(), "htmx")
+}
+
+/// Reports every attribute that is not valid on its element, see [`is_known_attribute`]
+///
+/// Attributes matching the options' allowlist are accepted, as are the declarations
+/// of `style {}` blocks. `svg` and `math` subtrees are not checked.
+#[must_use]
+pub fn unknown_html_attributes(nodes: &[Node], options: &ParseOptions) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    check_html_attributes(nodes, None, options, &mut lints);
+    lints
+}
+
+fn check_html_attributes(
+    nodes: &[Node],
+    parent: Option<&str>,
+    options: &ParseOptions,
+    lints: &mut Vec<Lint>,
+) {
+    for element in nodes.iter().filter_map(Node::as_element) {
+        let tag = element.name.as_str();
+        // The attributes of a style block are CSS declarations
+        if matches!(tag, "svg" | "math") || tag == "style" && parent != Some("head") {
+            continue;
+        }
+        for attribute in &element.attributes {
+            if !is_known_attribute(tag, &attribute.key)
+                && !options.is_allowed_attribute(&attribute.key)
+            {
+                let key = &attribute.key;
+                let message = match suggest_attribute(tag, key) {
+                    Some(suggestion) => {
+                        format!(
+                            "Unknown attribute '{key}' on <{tag}>, did you mean '{suggestion}'?"
+                        )
+                    }
+                    None => format!("Unknown attribute '{key}' on <{tag}>"),
+                };
+                lints.push(Lint::new(tag, message));
+            }
+        }
+        check_html_attributes(&element.children, Some(tag), options, lints);
+    }
+}
+
+fn unknown_attributes(
+    nodes: &[Node],
+    is_unknown: fn(&Attribute) -> bool,
+    kind: &'static str,
+) -> Vec<Lint> {
+    let mut visitor = UnknownAttributes {
+        lints: Vec::new(),
+        is_unknown,
+        kind,
+    };
+    let _ = walk(nodes, &mut visitor);
+    visitor.lints
+}
+
+struct UnknownAttributes {
+    lints: Vec<Lint>,
+    is_unknown: fn(&Attribute) -> bool,
+    kind: &'static str,
+}
+
+impl Visitor<'_> for UnknownAttributes {
+    fn visit_element(&mut self, element: &Element) -> ControlFlow<(), Walk> {
+        for attribute in element.attributes.iter().filter(|a| (self.is_unknown)(a)) {
+            self.lints.push(Lint::new(
+                element.name.as_str(),
+                format!("Unknown {} attribute '{}'", self.kind, attribute.key),
+            ));
+        }
+        ControlFlow::Continue(Walk::Children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Lint, can_contain, is_known_attribute, is_known_tag, unknown_aria_attributes,
+        unknown_html_attributes, unknown_tag_message,
+    };
+    use crate::{parse::ParseOptions, prelude::*};
+
+    #[test]
+    fn test_unknown_aria_attributes() {
+        let block = Block::new().with_child(
+            element("nav")
+                .with_attribute(Attribute::aria("label", "Main"))
+                .with_child(element("button").with_attribute(Attribute::aria("expaned", "false"))),
+        );
+        assert_eq!(
+            unknown_aria_attributes(block.as_nodes()),
+            vec![Lint::new("button", "Unknown ARIA attribute 'aria-expaned'")]
+        );
+    }
+
+    #[test]
+    fn test_unknown_html_attributes() {
+        let (_, document) = Document::parse(
+            r#"a { .herf = "/" .data-x = "1" .x-data = "{}" } p { .clas = "a" .onclick = "go()" }
+            my-widget { .anything = "ok" } div { style { .padding = "1rem" } }
+            svg { .viewBox = "0 0 1 1" } input { .placeholder = "Name" .autofocus }"#,
+        )
+        .unwrap();
+        let options = ParseOptions::new().with_allowed_attribute("x-*");
+        assert_eq!(
+            unknown_html_attributes(document.as_nodes(), &options),
+            [
+                Lint::new("a", "Unknown attribute 'herf' on <a>, did you mean 'href'?"),
+                Lint::new(
+                    "p",
+                    "Unknown attribute 'clas' on <p>, did you mean 'class'?"
+                ),
+            ]
+        );
+        assert!(is_known_attribute("label", "for"));
+        assert!(!is_known_attribute("div", "for"));
+    }
+
+    #[test]
+    fn test_tags_and_nesting() {
+        assert!(is_known_tag("section"));
+        assert!(is_known_tag("my-widget"));
+        assert!(is_known_tag("math-α"));
+        assert!(!is_known_tag("dvi"));
+        assert!(!is_known_tag("My-widget"));
+        assert!(!is_known_tag("font-face"));
+        assert_eq!(
+            unknown_tag_message("my_Widget").as_deref(),
+            Some("Unknown HTML tag `<my_Widget>`, custom element names cannot contain `W`")
+        );
+        assert!(can_contain("div", "p"));
+        assert!(can_contain("p", "span"));
+        assert!(!can_contain("p", "div"));
+        assert!(!can_contain("a", "a"));
+        assert!(can_contain("ul", "li"));
+        assert!(can_contain("table", "template"));
+        assert!(!can_contain("div", "li"));
+        assert!(!can_contain("table", "div"));
+        assert!(!can_contain("tbody", "td"));
+    }
+}
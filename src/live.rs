@@ -0,0 +1,676 @@
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+use crate::{patch::Patch, prelude::*, render::escape_script_json, value::Value};
+
+// Appended to the key of a handshake before hashing, see RFC 6455
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// Largest message accepted from a page
+const MAX_MESSAGE_LENGTH: usize = 1 << 20;
+
+/// The message a page sends when it cannot apply patches, answered with the whole
+/// document
+pub const RESET_MESSAGE: &str = "reset";
+
+/// Creates the script that connects the page to a live socket at the URL and
+/// applies the messages it receives
+///
+/// The live content is the children of the first element with a `data-live`
+/// attribute, which should hold the first document pushed. Patches address nodes
+/// by their position among the element's `childNodes`, and if one does not resolve
+/// the page sends [`RESET_MESSAGE`] to get the whole document instead.
+#[must_use]
+pub fn live_script(url: &str) -> Node<'static> {
+    let url = escape_script_json(&Value::from(url).to_json()).into_owned();
+    Node::script(format!(
+        r#"(() => {{
+  const root = document.querySelector("[data-live]");
+  const socket = new WebSocket(new URL({url}, location.href.replace(/^http/, "ws")));
+  const at = path => path.reduce((node, index) => node.childNodes[index], root);
+  const parse = html => {{
+    const template = document.createElement("template");
+    template.innerHTML = html;
+    return template.content;
+  }};
+  const apply = patch => {{
+    const node = at(patch.path);
+    switch (patch.op) {{
+      case "replace": node.replaceWith(parse(patch.html)); break;
+      case "insert": {{
+        const parent = at(patch.path.slice(0, -1));
+        parent.insertBefore(parse(patch.html), parent.childNodes[patch.path.at(-1)] ?? null);
+        break;
+      }}
+      case "remove": node.remove(); break;
+      case "set_attribute": node.setAttribute(patch.key, patch.value); break;
+      case "remove_attribute": node.removeAttribute(patch.key); break;
+      case "set_text": node.data = patch.text; break;
+    }}
+  }};
+  socket.onmessage = ({{ data }}) => {{
+    const message = JSON.parse(data);
+    if ("html" in message) {{
+      root.innerHTML = message.html;
+      return;
+    }}
+    try {{
+      message.patches.forEach(apply);
+    }} catch {{
+      socket.send("{RESET_MESSAGE}");
+    }}
+  }};
+}})();"#
+    ))
+}
+
+// The patch as the JSON object the live script applies, new nodes as HTML
+fn patch_value(patch: &Patch) -> Value<'static> {
+    let mut fields: BTreeMap<_, Value<'static>> = BTreeMap::new();
+    let path = patch.path().indices().iter().map(|&index| index as i64);
+    fields.insert("path".into(), Value::List(path.map(Value::Int).collect()));
+    let mut field = |key: &'static str, value: &str| {
+        fields.insert(key.into(), Value::from(value.to_string()));
+    };
+    let op = match patch {
+        Patch::Replace { node, .. } => {
+            field("html", &node.render());
+            "replace"
+        }
+        Patch::Insert { node, .. } => {
+            field("html", &node.render());
+            "insert"
+        }
+        Patch::Remove { .. } => "remove",
+        Patch::SetAttribute { key, value, .. } => {
+            field("key", key);
+            field("value", value);
+            "set_attribute"
+        }
+        Patch::RemoveAttribute { key, .. } => {
+            field("key", key);
+            "remove_attribute"
+        }
+        Patch::SetText { text, .. } => {
+            field("text", text);
+            "set_text"
+        }
+    };
+    fields.insert("op".into(), Value::from(op));
+    Value::Map(fields)
+}
+
+/// Encodes patches as the JSON message applied by [`live_script`], e.g.
+/// `{"patches":[{"op":"set_text","path":[0,1,0],"text":"2"}]}`
+#[must_use]
+pub fn patches_to_json(patches: &[Patch]) -> String {
+    let patches = Value::List(patches.iter().map(patch_value).collect());
+    Value::Map(BTreeMap::from([("patches".into(), patches)])).to_json()
+}
+
+// The message replacing the live content with the whole document
+fn html_message(document: &Document) -> String {
+    let html = Value::from(document.render());
+    Value::Map(BTreeMap::from([("html".into(), html)])).to_json()
+}
+
+// Whether the nodes become the same DOM nodes when parsed, so patch paths resolve
+//
+// Adjacent or empty text, raw markup, slots, directives, translations and `template`
+// contents render into a different number of nodes or none.
+fn mirrors_dom(nodes: &[Node]) -> bool {
+    let mut previous_text = false;
+    nodes.iter().all(|node| {
+        let text = matches!(node, Node::Text(_));
+        let mirrored = match node {
+            Node::Text(text) => !previous_text && !text.content.is_empty(),
+            Node::Comment(_) => true,
+            node => node.as_element().is_some_and(|element| {
+                element.name.as_str() != "template" && mirrors_dom(&element.children)
+            }),
+        };
+        previous_text = text;
+        mirrored
+    })
+}
+
+/// The document last sent to a page, turning each new render into the message
+/// that brings the page up to date
+///
+/// Independent of the transport, e.g. for a websocket of a web framework; see
+/// [`LiveConnection`] for a socket served with the standard library.
+#[derive(Debug, Default)]
+pub struct LiveSession {
+    last: Option<Document<'static>>,
+}
+
+impl LiveSession {
+    #[must_use]
+    pub fn new() -> Self {
+        LiveSession::default()
+    }
+
+    /// The document the page was last brought up to date with
+    #[must_use]
+    pub fn last(&self) -> Option<&Document<'static>> {
+        self.last.as_ref()
+    }
+
+    /// Returns the message turning the last document into this one, `None` if
+    /// they are the same
+    ///
+    /// The message holds the patches from [`Block::diff`], or the whole document
+    /// when it is the first or either document does not map one to one onto the
+    /// nodes browsers parse from it, e.g. when it has adjacent text nodes.
+    pub fn update(&mut self, document: Document<'static>) -> Option<String> {
+        let message = match &self.last {
+            Some(last) if mirrors_dom(&last.children) && mirrors_dom(&document.children) => {
+                let patches = last.diff(&document);
+                (!patches.is_empty()).then(|| patches_to_json(&patches))
+            }
+            _ => Some(html_message(&document)),
+        };
+        self.last = Some(document);
+        message
+    }
+
+    /// Returns the message replacing the page's content with the last document,
+    /// e.g. to answer [`RESET_MESSAGE`]
+    #[must_use]
+    pub fn reset(&self) -> String {
+        html_message(self.last.as_ref().unwrap_or(&Document::new()))
+    }
+}
+
+// SHA-1 digest, only used for the websocket handshake
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [
+        0x6745_2301,
+        0xEFCD_AB89,
+        0x98BA_DCFE,
+        0x1032_5476,
+        0xC3D2_E1F0,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+    for block in message.chunks_exact(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().expect("chunks of four bytes"));
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.into_iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let next = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, next);
+        }
+        for (state, value) in state.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+    let mut digest = [0; 20];
+    for (bytes, value) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// A websocket connection to a page running [`live_script`], pushing each new
+/// render of its document as patches
+///
+/// Works on any stream, e.g. a `TcpStream` accepted for the socket's URL, and
+/// handles the websocket protocol itself, so it needs no other dependency.
+#[derive(Debug)]
+pub struct LiveConnection<S> {
+    stream: S,
+    session: LiveSession,
+}
+
+impl<S: Read + Write> LiveConnection<S> {
+    /// Reads the page's websocket handshake from the stream and accepts it
+    ///
+    /// # Errors
+    /// Errors if the stream fails or the request is not a websocket handshake,
+    /// which is answered with `400 Bad Request`
+    pub fn accept(mut stream: S) -> std::io::Result<Self> {
+        // Read byte by byte, so no frame is buffered past the request
+        let mut request = Vec::new();
+        let mut byte = [0];
+        while !request.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte)?;
+            request.push(byte[0]);
+            if request.len() > MAX_MESSAGE_LENGTH {
+                return Err(invalid("Handshake request is too long"));
+            }
+        }
+        let request = String::from_utf8_lossy(&request);
+        let key = request.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("sec-websocket-key")
+                .then(|| value.trim())
+        });
+        let Some(key) = key else {
+            stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")?;
+            return Err(invalid("Request is not a websocket handshake"));
+        };
+        let accept = base64(&sha1(format!("{key}{WEBSOCKET_GUID}").as_bytes()));
+        write!(
+            stream,
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+             Connection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n"
+        )?;
+        Ok(LiveConnection {
+            stream,
+            session: LiveSession::new(),
+        })
+    }
+
+    #[must_use]
+    pub fn session(&self) -> &LiveSession {
+        &self.session
+    }
+
+    /// Sends what changed since the last document pushed, nothing if it is the same
+    ///
+    /// # Errors
+    /// Errors if writing to the stream fails
+    pub fn push(&mut self, document: Document<'static>) -> std::io::Result<()> {
+        match self.session.update(document) {
+            Some(message) => self.send(&message),
+            None => Ok(()),
+        }
+    }
+
+    /// Waits for the next text message from the page, `None` once it closes
+    ///
+    /// Pings are answered, and so are [`RESET_MESSAGE`]s, with the whole document.
+    ///
+    /// # Errors
+    /// Errors if the stream fails or the page breaks the websocket protocol
+    pub fn receive(&mut self) -> std::io::Result<Option<String>> {
+        let mut message = Vec::new();
+        loop {
+            let (fin, opcode, payload) = self.read_frame()?;
+            match opcode {
+                0x0..=0x2 => {
+                    message.extend_from_slice(&payload);
+                    if message.len() > MAX_MESSAGE_LENGTH {
+                        return Err(invalid("Message is too long"));
+                    }
+                    if !fin {
+                        continue;
+                    }
+                    let text = String::from_utf8(std::mem::take(&mut message))
+                        .map_err(|_| invalid("Message is not UTF-8"))?;
+                    if text == RESET_MESSAGE {
+                        self.send(&self.session.reset())?;
+                    } else {
+                        return Ok(Some(text));
+                    }
+                }
+                0x8 => {
+                    self.write_frame(0x8, &payload)?;
+                    return Ok(None);
+                }
+                0x9 => self.write_frame(0xA, &payload)?,
+                _ => {}
+            }
+        }
+    }
+
+    fn send(&mut self, message: &str) -> std::io::Result<()> {
+        self.write_frame(0x1, message.as_bytes())
+    }
+
+    // Writes an unmasked final frame, as servers do
+    fn write_frame(&mut self, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+        let mut header = vec![0x80 | opcode];
+        match payload.len() {
+            length @ 0..=125 => header.push(length as u8),
+            length @ 126..=0xFFFF => {
+                header.push(126);
+                header.extend_from_slice(&(length as u16).to_be_bytes());
+            }
+            length => {
+                header.push(127);
+                header.extend_from_slice(&(length as u64).to_be_bytes());
+            }
+        }
+        self.stream.write_all(&header)?;
+        self.stream.write_all(payload)?;
+        self.stream.flush()
+    }
+
+    // Reads a frame, unmasking its payload
+    fn read_frame(&mut self) -> std::io::Result<(bool, u8, Vec<u8>)> {
+        let mut header = [0; 2];
+        self.stream.read_exact(&mut header)?;
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0F;
+        let length = match header[1] & 0x7F {
+            126 => {
+                let mut length = [0; 2];
+                self.stream.read_exact(&mut length)?;
+                u64::from(u16::from_be_bytes(length))
+            }
+            127 => {
+                let mut length = [0; 8];
+                self.stream.read_exact(&mut length)?;
+                u64::from_be_bytes(length)
+            }
+            length => u64::from(length),
+        };
+        let length = usize::try_from(length)
+            .ok()
+            .filter(|&length| length <= MAX_MESSAGE_LENGTH)
+            .ok_or_else(|| invalid("Message is too long"))?;
+        let mut mask = [0; 4];
+        if header[1] & 0x80 != 0 {
+            self.stream.read_exact(&mut mask)?;
+        }
+        let mut payload = vec![0; length];
+        self.stream.read_exact(&mut payload)?;
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+        Ok((fin, opcode, payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Write};
+
+    use super::{LiveConnection, LiveSession, RESET_MESSAGE, base64, sha1};
+    use crate::prelude::*;
+
+    // A stream reading what the page sent and recording what the server writes
+    struct Page {
+        sent: Cursor<Vec<u8>>,
+        received: Vec<u8>,
+    }
+
+    impl Read for Page {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.sent.read(buf)
+        }
+    }
+
+    impl Write for Page {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.received.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // A masked frame, as pages send them
+    fn client_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [1, 2, 3, 4];
+        let mut frame = vec![0x80 | opcode, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(
+            payload
+                .iter()
+                .enumerate()
+                .map(|(i, byte)| byte ^ mask[i % 4]),
+        );
+        frame
+    }
+
+    // Splits the server's output into its text frames
+    fn text_frames(mut output: &[u8]) -> Vec<String> {
+        let mut frames = Vec::new();
+        while let [first, length, rest @ ..] = output {
+            let (length, rest) = match length {
+                126 => (
+                    usize::from(u16::from_be_bytes([rest[0], rest[1]])),
+                    &rest[2..],
+                ),
+                length => (usize::from(*length), rest),
+            };
+            if first & 0x0F == 0x1 {
+                frames.push(String::from_utf8(rest[..length].to_vec()).unwrap());
+            }
+            output = &rest[length..];
+        }
+        frames
+    }
+
+    fn counter(count: u32) -> Document<'static> {
+        Document::new().with_child(
+            element("p")
+                .with_key_value("class", if count > 1 { "many" } else { "one" })
+                .with_child(Node::text(count.to_string())),
+        )
+    }
+
+    #[test]
+    fn test_live_session() {
+        assert_eq!(
+            base64(&sha1(
+                b"dGhlIHNhbXBsZSBub25jZQ==258EAFA5-E914-47DA-95CA-C5AB0DC85B11"
+            )),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+
+        let mut session = LiveSession::new();
+        assert_eq!(
+            session.update(counter(1)).unwrap(),
+            r#"{"html":"<p class=\"one\">1</p>"}"#
+        );
+        assert_eq!(session.update(counter(1)), None);
+        assert_eq!(
+            session.update(counter(2)).unwrap(),
+            concat!(
+                r#"{"patches":[{"key":"class","op":"set_attribute","path":[0],"value":"many"},"#,
+                r#"{"op":"set_text","path":[0,0],"text":"2"}]}"#,
+            )
+        );
+        // Adjacent text merges into one DOM node, so paths would not resolve
+        let merged = Document::new().with_child("a").with_child("b");
+        assert_eq!(session.update(merged).unwrap(), r#"{"html":"ab"}"#);
+    }
+
+    #[test]
+    fn test_live_connection() {
+        let mut sent = concat!(
+            "GET /live HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n",
+            "Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+        )
+        .as_bytes()
+        .to_vec();
+        sent.extend(client_frame(0x1, RESET_MESSAGE.as_bytes()));
+        sent.extend(client_frame(0x1, b"clicked"));
+        sent.extend(client_frame(0x8, b""));
+        let page = Page {
+            sent: Cursor::new(sent),
+            received: Vec::new(),
+        };
+
+        let mut connection = LiveConnection::accept(page).unwrap();
+        connection.push(counter(1)).unwrap();
+        connection.push(counter(2)).unwrap();
+        assert_eq!(connection.receive().unwrap().as_deref(), Some("clicked"));
+        assert_eq!(connection.receive().unwrap(), None);
+
+        let output = &connection.stream.received;
+        let handshake_end = output.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        let handshake = String::from_utf8_lossy(&output[..handshake_end]);
+        assert!(handshake.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+        assert!(handshake.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        let frames = text_frames(&output[handshake_end..]);
+        assert_eq!(frames.len(), 3);
+        assert!(frames[1].starts_with(r#"{"patches":"#));
+        // The reset is answered with the whole latest document
+        assert_eq!(frames[2], r#"{"html":"<p class=\"many\">2</p>"}"#);
+    }
+
+    // A list whose items are keyed by their label
+    fn list(items: &[&str]) -> Document<'static> {
+        Document::new().with_child(element("ul").with_children(items.iter().map(|item| {
+            Node::from(element("li").with_child(Node::text(item.to_string())))
+                .with_key(item.to_string())
+        })))
+    }
+
+    #[test]
+    fn test_diff_ordering() {
+        let mut session = LiveSession::new();
+        session.update(list(&["a", "b", "c", "d"]));
+        // Removals run from the last child, so earlier paths stay valid
+        assert_eq!(
+            session.update(list(&["a", "b"])).unwrap(),
+            r#"{"patches":[{"op":"remove","path":[0,3]},{"op":"remove","path":[0,2]}]}"#
+        );
+        // Insertions run from the first new child, each one after the previous
+        assert_eq!(
+            session.update(list(&["a", "b", "c", "d"])).unwrap(),
+            concat!(
+                r#"{"patches":[{"html":"<li data-key=\"c\">c</li>","op":"insert","path":[0,2]},"#,
+                r#"{"html":"<li data-key=\"d\">d</li>","op":"insert","path":[0,3]}]}"#,
+            )
+        );
+        // An element's attributes are patched before its children
+        let old = counter(1).with_child(element("footer"));
+        let new = counter(2).with_child("End");
+        let patches = old.diff(&new);
+        assert_eq!(
+            patches
+                .iter()
+                .map(|patch| patch.path().indices().to_vec())
+                .collect::<Vec<_>>(),
+            [vec![0], vec![0, 0], vec![1]]
+        );
+        let mut applied = old.clone();
+        applied.apply(&patches).unwrap();
+        assert_eq!(applied, new);
+    }
+
+    #[test]
+    fn test_keyed_moves() {
+        let old = list(&["a", "b", "c"]);
+        let new = list(&["c", "a", "b"]);
+        let mut session = LiveSession::new();
+        session.update(old.clone());
+        let message = session.update(new.clone()).unwrap();
+        // Items are matched by position, a move rewrites the key and text of each
+        assert_eq!(
+            message,
+            concat!(
+                r#"{"patches":[{"key":"data-key","op":"set_attribute","path":[0,0],"value":"c"},"#,
+                r#"{"op":"set_text","path":[0,0,0],"text":"c"},"#,
+                r#"{"key":"data-key","op":"set_attribute","path":[0,1],"value":"a"},"#,
+                r#"{"op":"set_text","path":[0,1,0],"text":"a"},"#,
+                r#"{"key":"data-key","op":"set_attribute","path":[0,2],"value":"b"},"#,
+                r#"{"op":"set_text","path":[0,2,0],"text":"b"}]}"#,
+            )
+        );
+        let mut applied = old.clone();
+        applied.apply(&old.diff(&new)).unwrap();
+        assert_eq!(applied.render(), new.render());
+        assert_eq!(session.last(), Some(&new));
+
+        // Moving one item to the end of a shorter list still ends up in order
+        let shorter = list(&["b", "c"]);
+        let mut applied = new.clone();
+        applied.apply(&new.diff(&shorter)).unwrap();
+        assert_eq!(applied.render(), shorter.render());
+    }
+
+    #[test]
+    fn test_resync() {
+        // A reset before anything was pushed empties the live content
+        let mut session = LiveSession::new();
+        assert_eq!(session.reset(), r#"{"html":""}"#);
+        session.update(counter(1));
+        session.update(counter(2));
+        assert_eq!(session.reset(), r#"{"html":"<p class=\"many\">2</p>"}"#);
+        // Resetting does not forget the last document, later renders are still patches
+        assert_eq!(
+            session.update(counter(3)).unwrap(),
+            r#"{"patches":[{"op":"set_text","path":[0,0],"text":"3"}]}"#
+        );
+
+        // A page that reconnects gets a new connection, which starts with the whole document
+        let handshake = concat!(
+            "GET /live HTTP/1.1\r\nUpgrade: websocket\r\n",
+            "Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+        );
+        let mut sent = handshake.as_bytes().to_vec();
+        sent.extend(client_frame(0x9, b"ping"));
+        sent.extend(client_frame(0x1, RESET_MESSAGE.as_bytes()));
+        sent.extend(client_frame(0x8, b""));
+        let page = Page {
+            sent: Cursor::new(sent),
+            received: Vec::new(),
+        };
+        let mut connection = LiveConnection::accept(page).unwrap();
+        assert!(connection.session().last().is_none());
+        connection.push(counter(3)).unwrap();
+        connection.push(counter(3)).unwrap();
+        assert_eq!(connection.receive().unwrap(), None);
+        let output = &connection.stream.received;
+        let handshake_end = output.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        assert_eq!(
+            text_frames(&output[handshake_end..]),
+            [
+                r#"{"html":"<p class=\"many\">3</p>"}"#,
+                r#"{"html":"<p class=\"many\">3</p>"}"#,
+            ]
+        );
+        // The ping is answered with a pong carrying its payload
+        assert!(output.windows(6).any(|w| w == b"\x8a\x04ping"));
+
+        // Requests without a websocket key are turned away
+        let page = Page {
+            sent: Cursor::new(b"GET /live HTTP/1.1\r\n\r\n".to_vec()),
+            received: Vec::new(),
+        };
+        assert!(LiveConnection::accept(page).is_err());
+    }
+}
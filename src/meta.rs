@@ -0,0 +1,293 @@
+use std::borrow::Cow;
+
+use crate::{
+    head::find_or_add,
+    known::{Url, UrlError},
+    lint::Lint,
+    prelude::*,
+};
+
+// Lengths beyond which search results and link previews cut the text off
+const TITLE_LENGTH: usize = 60;
+const DESCRIPTION_LENGTH: usize = 160;
+
+/// The `title`, description, canonical link, Open Graph and Twitter card tags of a
+/// page, built with [`Meta::to_nodes`] or set on a document's `head` with
+/// [`Meta::apply`]
+///
+/// URLs are checked as they are set. [`Meta::warnings`] reports the mistakes that
+/// still render, like a title or description too long to be shown in full.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Meta<'a> {
+    pub title: Option<Cow<'a, str>>,
+    pub description: Option<Cow<'a, str>>,
+    pub canonical: Option<Url<'a>>,
+    pub og_image: Option<Url<'a>>,
+    /// `og:type`, `website` unless set
+    pub og_type: Option<Cow<'a, str>>,
+    pub site_name: Option<Cow<'a, str>>,
+    /// `twitter:site`, the site's handle, e.g. `@example`
+    pub twitter_site: Option<Cow<'a, str>>,
+}
+
+impl<'a> Meta<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        Meta::default()
+    }
+
+    pub fn set_title(&mut self, title: impl Into<Cow<'a, str>>) {
+        self.title = Some(title.into());
+    }
+    #[must_use]
+    pub fn with_title(mut self, title: impl Into<Cow<'a, str>>) -> Self {
+        self.set_title(title);
+        self
+    }
+
+    pub fn set_description(&mut self, description: impl Into<Cow<'a, str>>) {
+        self.description = Some(description.into());
+    }
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.set_description(description);
+        self
+    }
+
+    /// Sets the canonical URL of the page, also used as its `og:url`
+    ///
+    /// # Errors
+    /// Errors if the value is not a valid URL, see [`Url::new`]
+    pub fn set_canonical(&mut self, url: impl Into<Cow<'a, str>>) -> Result<(), UrlError> {
+        self.canonical = Some(Url::new(url)?);
+        Ok(())
+    }
+    /// # Errors
+    /// Errors if the value is not a valid URL, see [`Url::new`]
+    pub fn with_canonical(mut self, url: impl Into<Cow<'a, str>>) -> Result<Self, UrlError> {
+        self.set_canonical(url)?;
+        Ok(self)
+    }
+
+    /// Sets the image shown in link previews
+    ///
+    /// # Errors
+    /// Errors if the value is not a valid URL, see [`Url::new`]
+    pub fn set_og_image(&mut self, url: impl Into<Cow<'a, str>>) -> Result<(), UrlError> {
+        self.og_image = Some(Url::new(url)?);
+        Ok(())
+    }
+    /// # Errors
+    /// Errors if the value is not a valid URL, see [`Url::new`]
+    pub fn with_og_image(mut self, url: impl Into<Cow<'a, str>>) -> Result<Self, UrlError> {
+        self.set_og_image(url)?;
+        Ok(self)
+    }
+
+    pub fn set_og_type(&mut self, og_type: impl Into<Cow<'a, str>>) {
+        self.og_type = Some(og_type.into());
+    }
+    #[must_use]
+    pub fn with_og_type(mut self, og_type: impl Into<Cow<'a, str>>) -> Self {
+        self.set_og_type(og_type);
+        self
+    }
+
+    pub fn set_site_name(&mut self, site_name: impl Into<Cow<'a, str>>) {
+        self.site_name = Some(site_name.into());
+    }
+    #[must_use]
+    pub fn with_site_name(mut self, site_name: impl Into<Cow<'a, str>>) -> Self {
+        self.set_site_name(site_name);
+        self
+    }
+
+    pub fn set_twitter_site(&mut self, handle: impl Into<Cow<'a, str>>) {
+        self.twitter_site = Some(handle.into());
+    }
+    #[must_use]
+    pub fn with_twitter_site(mut self, handle: impl Into<Cow<'a, str>>) -> Self {
+        self.set_twitter_site(handle);
+        self
+    }
+
+    /// Reports a title or description longer than search results show, and canonical
+    /// or image URLs that are relative, which crawlers do not resolve
+    #[must_use]
+    pub fn warnings(&self) -> Vec<Lint> {
+        let mut warnings = Vec::new();
+        let lengths = [
+            ("title", &self.title, TITLE_LENGTH),
+            ("meta", &self.description, DESCRIPTION_LENGTH),
+        ];
+        for (element, text, limit) in lengths {
+            let length = text.as_deref().map_or(0, |text| text.chars().count());
+            if length > limit {
+                let what = if element == "title" {
+                    "Title"
+                } else {
+                    "Description"
+                };
+                warnings.push(Lint::new(
+                    element,
+                    format!("{what} is {length} characters, only about {limit} are shown"),
+                ));
+            }
+        }
+        let urls = [
+            ("link", "Canonical URL", &self.canonical),
+            ("meta", "og:image", &self.og_image),
+        ];
+        for (element, what, url) in urls {
+            if let Some(url) = url
+                && !url.is_absolute()
+            {
+                warnings.push(Lint::new(
+                    element,
+                    format!("{what} `{url}` should be an absolute URL"),
+                ));
+            }
+        }
+        warnings
+    }
+
+    // The tags in order, each with the attribute identifying it and its value
+    fn tags(&self) -> Vec<(&'static str, &'static str, &'static str, Cow<'a, str>)> {
+        let og_type = self.og_type.clone().unwrap_or(Cow::Borrowed("website"));
+        let card = if self.og_image.is_some() {
+            "summary_large_image"
+        } else {
+            "summary"
+        };
+        let url = |url: &Option<Url<'a>>| url.as_ref().map(|url| Cow::Owned(url.to_string()));
+        let tags = [
+            ("meta", "name", "description", self.description.clone()),
+            ("link", "rel", "canonical", url(&self.canonical)),
+            ("meta", "property", "og:type", Some(og_type)),
+            ("meta", "property", "og:title", self.title.clone()),
+            (
+                "meta",
+                "property",
+                "og:description",
+                self.description.clone(),
+            ),
+            ("meta", "property", "og:url", url(&self.canonical)),
+            ("meta", "property", "og:image", url(&self.og_image)),
+            ("meta", "property", "og:site_name", self.site_name.clone()),
+            ("meta", "name", "twitter:card", Some(Cow::Borrowed(card))),
+            ("meta", "name", "twitter:site", self.twitter_site.clone()),
+        ];
+        tags.into_iter()
+            .filter_map(|(tag, key, name, value)| Some((tag, key, name, value?)))
+            .collect()
+    }
+
+    /// Builds the `title`, then the `meta` and `link` elements of the values set
+    #[must_use]
+    pub fn to_nodes(&self) -> Vec<Node<'a>> {
+        let title = self
+            .title
+            .clone()
+            .map(|title| element("title").with_child(Node::text(title)).into());
+        let tags = self.tags().into_iter().map(|(tag, key, name, value)| {
+            let value_key = if tag == "link" { "href" } else { "content" };
+            element(tag)
+                .with_key_value(key, name)
+                .with_key_value(value_key, value)
+                .into()
+        });
+        title.into_iter().chain(tags).collect()
+    }
+
+    /// Sets the tags in the document's `head`, replacing those already present
+    pub fn apply(&self, document: &mut Block<'a>) {
+        if let Some(title) = &self.title {
+            document.set_title(title.clone());
+        }
+        let head = document.head_mut();
+        for (tag, key, name, value) in self.tags() {
+            let value_key = if tag == "link" { "href" } else { "content" };
+            find_or_add(
+                head,
+                |e| e.name.as_str() == tag && e.attr(key) == Some(name),
+                || element(tag).with_key_value(key, name),
+            )
+            .set_attr(value_key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Meta;
+    use crate::{known::UrlError, lint::Lint, prelude::*};
+
+    #[test]
+    fn test_meta() {
+        let meta = Meta::new()
+            .with_title("Rust & HTML")
+            .with_description("Templates in Rust")
+            .with_canonical("https://example.com/posts/1")
+            .and_then(|meta| meta.with_og_image("https://example.com/cover.png"))
+            .unwrap()
+            .with_twitter_site("@example");
+        assert!(meta.warnings().is_empty());
+        assert_eq!(
+            meta.to_nodes().into_iter().collect::<Document>().render(),
+            concat!(
+                "<title>Rust &amp; HTML</title>",
+                r#"<meta name="description" content="Templates in Rust">"#,
+                r#"<link rel="canonical" href="https://example.com/posts/1">"#,
+                r#"<meta property="og:type" content="website">"#,
+                r#"<meta property="og:title" content="Rust &amp; HTML">"#,
+                r#"<meta property="og:description" content="Templates in Rust">"#,
+                r#"<meta property="og:url" content="https://example.com/posts/1">"#,
+                r#"<meta property="og:image" content="https://example.com/cover.png">"#,
+                r#"<meta name="twitter:card" content="summary_large_image">"#,
+                r#"<meta name="twitter:site" content="@example">"#,
+            )
+        );
+
+        let mut page = Document::new().with_child(
+            element("head").with_child(
+                element("meta")
+                    .with_key_value("name", "description")
+                    .with_key_value("content", "Old"),
+            ),
+        );
+        Meta::new().with_description("New").apply(&mut page);
+        assert_eq!(
+            page.render(),
+            concat!(
+                r#"<head><meta name="description" content="New">"#,
+                r#"<meta property="og:type" content="website">"#,
+                r#"<meta property="og:description" content="New">"#,
+                r#"<meta name="twitter:card" content="summary"></head>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_meta_warnings() {
+        assert_eq!(
+            Meta::new().with_canonical("https://exa mple.com"),
+            Err(UrlError::InvalidCharacter(' '))
+        );
+        let meta = Meta::new()
+            .with_title("a".repeat(61))
+            .with_description("b".repeat(170))
+            .with_og_image("/cover.png")
+            .unwrap();
+        assert_eq!(
+            meta.warnings(),
+            vec![
+                Lint::new("title", "Title is 61 characters, only about 60 are shown"),
+                Lint::new(
+                    "meta",
+                    "Description is 170 characters, only about 160 are shown"
+                ),
+                Lint::new("meta", "og:image `/cover.png` should be an absolute URL"),
+            ]
+        );
+    }
+}
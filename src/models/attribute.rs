@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use crate::prelude::*;
+use crate::{prelude::*, tag::parse_name};
 use pastey::paste;
 
 /// Represents an RSTML attribute
@@ -12,12 +12,79 @@ use pastey::paste;
 /// Values are usually enclosed in double quotes.
 ///
 /// Keys without values are treated as class attributes with the value of the key name.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// Boolean attributes, e.g. `disabled`, are made with [`Attribute::boolean`] and
+/// render as their key alone, while an empty value still renders as `key=""`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attribute<'a> {
     pub key: Cow<'a, str>,
     pub value: Cow<'a, str>,
+    /// Whether only the key is written, in which case the value is ignored
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub boolean: bool,
 }
 
+/// Names of the states and properties defined by WAI-ARIA 1.2
+pub const ARIA_ATTRIBUTES: &[&str] = &[
+    "aria-activedescendant",
+    "aria-atomic",
+    "aria-autocomplete",
+    "aria-braillelabel",
+    "aria-brailleroledescription",
+    "aria-busy",
+    "aria-checked",
+    "aria-colcount",
+    "aria-colindex",
+    "aria-colindextext",
+    "aria-colspan",
+    "aria-controls",
+    "aria-current",
+    "aria-describedby",
+    "aria-description",
+    "aria-details",
+    "aria-disabled",
+    "aria-dropeffect",
+    "aria-errormessage",
+    "aria-expanded",
+    "aria-flowto",
+    "aria-grabbed",
+    "aria-haspopup",
+    "aria-hidden",
+    "aria-invalid",
+    "aria-keyshortcuts",
+    "aria-label",
+    "aria-labelledby",
+    "aria-level",
+    "aria-live",
+    "aria-modal",
+    "aria-multiline",
+    "aria-multiselectable",
+    "aria-orientation",
+    "aria-owns",
+    "aria-placeholder",
+    "aria-posinset",
+    "aria-pressed",
+    "aria-readonly",
+    "aria-relevant",
+    "aria-required",
+    "aria-roledescription",
+    "aria-rowcount",
+    "aria-rowindex",
+    "aria-rowindextext",
+    "aria-rowspan",
+    "aria-selected",
+    "aria-setsize",
+    "aria-sort",
+    "aria-valuemax",
+    "aria-valuemin",
+    "aria-valuenow",
+    "aria-valuetext",
+];
+
 macro_rules! attribute {
     ($($attribute:ident)*) => {
         $(
@@ -36,20 +103,139 @@ macro_rules! attribute {
 impl<'a> Attribute<'a> {
     #[must_use]
     pub const fn new_const(key: Cow<'a, str>, value: Cow<'a, str>) -> Self {
-        Attribute { key, value }
+        Attribute {
+            key,
+            value,
+            boolean: false,
+        }
     }
     pub fn new(key: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) -> Self {
+        Self::new_const(key.into(), value.into())
+    }
+
+    /// Creates a boolean attribute, e.g. `disabled` or `checked`,
+    /// whose presence alone is meaningful.
+    pub fn boolean(key: impl Into<Cow<'a, str>>) -> Self {
         Attribute {
-            key: key.into(),
-            value: value.into(),
+            boolean: true,
+            ..Self::new(key, "")
         }
     }
 
+    /// Creates a custom data attribute, e.g. `data("id", "7")` becomes `data-id="7"`.
+    pub fn data(name: &str, value: impl Into<Cow<'a, str>>) -> Self {
+        Self::new(format!("data-{name}"), value)
+    }
+
+    /// Creates an accessibility attribute, e.g. `aria("label", "Close")` becomes `aria-label="Close"`.
+    pub fn aria(name: &str, value: impl Into<Cow<'a, str>>) -> Self {
+        Self::new(format!("aria-{name}"), value)
+    }
+
+    #[must_use]
+    pub fn is_data(&self) -> bool {
+        self.key.starts_with("data-")
+    }
+
+    #[must_use]
+    pub fn is_aria(&self) -> bool {
+        self.key.starts_with("aria-")
+    }
+
+    /// Copies any borrowed key or value, detaching the attribute from its source
+    #[must_use]
+    pub fn into_owned(self) -> Attribute<'static> {
+        Attribute {
+            key: self.key.into_owned().into(),
+            value: self.value.into_owned().into(),
+            boolean: self.boolean,
+        }
+    }
+
+    /// Returns true if this is an `aria-*` attribute that is not defined by WAI-ARIA.
+    #[must_use]
+    pub fn is_unknown_aria(&self) -> bool {
+        self.is_aria() && !ARIA_ATTRIBUTES.contains(&self.key.as_ref())
+    }
+
+    /// Whether the key can be written as an attribute name, i.e. it is not empty and
+    /// holds no whitespace, control characters, quotes, `>`, `/` or `=`
+    ///
+    /// Attributes with other keys are left out when rendering, as they would
+    /// change the markup around them.
+    #[must_use]
+    pub fn has_valid_key(&self) -> bool {
+        !self.key.is_empty()
+            && !self.key.chars().any(|c| {
+                c.is_whitespace()
+                    || c.is_control()
+                    || matches!(c, '"' | '\'' | '>' | '/' | '=' | '<')
+            })
+    }
+
+    /// Returns true if this is a boolean attribute, see [`Attribute::boolean`]
+    #[must_use]
+    pub fn is_boolean(&self) -> bool {
+        self.boolean
+    }
+
     // TODO : add type attribute, but it's a reserved keyword
     attribute!(id class href src alt title style name value placeholder disabled checked readonly);
 }
+/// Which value is kept when both sides of a merge set the same key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Conflict {
+    /// Keep the value that was already set
+    FirstWins,
+    /// Replace it with the incoming value
+    #[default]
+    LastWins,
+}
+
+/// How attributes set on both sides are resolved by [`Element::merge_attributes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MergePolicy {
+    /// Join `class` values into one space separated list
+    pub join_classes: bool,
+    /// Join `style` declarations with `;`
+    pub join_styles: bool,
+    /// Resolves every other key, and `class`/`style` when they are not joined
+    pub conflict: Conflict,
+}
+
+impl MergePolicy {
+    /// Joins classes and styles, the incoming value wins for other keys
+    pub const DEFAULT: Self = MergePolicy {
+        join_classes: true,
+        join_styles: true,
+        conflict: Conflict::LastWins,
+    };
+
+    /// Joins classes, the incoming value wins for other keys, as `rstml!` resolves
+    /// attributes set more than once
+    pub const MARKUP: Self = MergePolicy {
+        join_styles: false,
+        ..Self::DEFAULT
+    };
+
+    #[must_use]
+    pub const fn with_conflict(mut self, conflict: Conflict) -> Self {
+        self.conflict = conflict;
+        self
+    }
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
 impl std::fmt::Display for Attribute<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.boolean {
+            return f.write_str(&self.key);
+        }
         write!(f, "{}=\"{}\"", self.key, self.value)
     }
 }
@@ -79,13 +265,13 @@ fn get_attribute_key(key: &str) -> ParseResult<'_, &str> {
         ));
     }
     let key = &key[1..]; // Remove the leading period
-    let Ok((rest, key)) = Tag::parse_no_whitespace(key) else {
+    let Ok((rest, key)) = parse_name(key) else {
         return Err(ParseError::invalid_input(
             key,
             Some("Invalid attribute key format".into()),
         ));
     };
-    Ok((rest, key.name))
+    Ok((rest, key))
 }
 
 impl<'a> RSTMLParse<'a> for Attribute<'a> {
@@ -93,13 +279,13 @@ impl<'a> RSTMLParse<'a> for Attribute<'a> {
         // Handle #id shorthand syntax
         if let Some(id_value) = input.strip_prefix('#') {
             // Remove the leading #
-            let Ok((rest, id)) = Tag::parse_no_whitespace(id_value) else {
+            let Ok((rest, id)) = parse_name(id_value) else {
                 return Err(ParseError::invalid_input(
                     input,
                     Some("Invalid id format".into()),
                 ));
             };
-            return Ok((rest, Attribute::id(id.name)));
+            return Ok((rest, Attribute::id(id)));
         }
 
         let Some((key, rest)) = input.split_once('=') else {
@@ -159,6 +345,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_boolean_attribute() {
+        let attribute = Attribute::boolean("disabled");
+        assert!(attribute.is_boolean());
+        assert_eq!(attribute.key, "disabled");
+        assert!(!Attribute::class("btn").is_boolean());
+        assert!(!Attribute::new("value", "").is_boolean());
+        assert_eq!(attribute.to_string(), "disabled");
+        assert!(attribute.into_owned().is_boolean());
+        // Joining a value into a boolean attribute gives it that value
+        let merged = element("p")
+            .with_attribute(Attribute::boolean("class"))
+            .with_merged_attributes([Attribute::class("a")], super::MergePolicy::DEFAULT);
+        assert_eq!(merged.attributes, [Attribute::class("a")]);
+    }
+
+    #[test]
+    fn test_data_and_aria_attributes() {
+        let data = Attribute::data("user-id", "7");
+        assert_eq!(data, Attribute::new("data-user-id", "7"));
+        assert!(data.is_data());
+        let aria = Attribute::aria("label", "Close");
+        assert_eq!(aria, Attribute::new("aria-label", "Close"));
+        assert!(aria.is_aria());
+        assert!(!aria.is_unknown_aria());
+        assert!(Attribute::aria("lable", "Close").is_unknown_aria());
+    }
+
     #[test]
     fn test_attribute_parse_invalid() {
         let input = r#"class=my-class"#;
@@ -1,11 +1,15 @@
 use crate::prelude::*;
 
 /// Represents a block of RSTML nodes.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block<'a> {
     pub children: Vec<Node<'a>>,
 }
 
+/// A full RSTML document, i.e. the root block of nodes
+pub type Document<'a> = Block<'a>;
+
 impl Default for Block<'_> {
     fn default() -> Self {
         Self::new()
@@ -58,6 +62,12 @@ impl<'a> Block<'a> {
     pub fn into_nodes(self) -> Vec<Node<'a>> {
         self.children
     }
+
+    /// Copies all borrowed data, see [`Element::into_owned`]
+    #[must_use]
+    pub fn into_owned(self) -> Block<'static> {
+        self.children.into_iter().map(Node::into_owned).collect()
+    }
     pub fn iter_nodes(&self) -> impl Iterator<Item = &Node<'a>> {
         self.children.iter()
     }
@@ -72,6 +82,18 @@ impl<'a> IntoIterator for Block<'a> {
     }
 }
 
+impl<'a, N: Into<Node<'a>>> FromIterator<N> for Block<'a> {
+    fn from_iter<I: IntoIterator<Item = N>>(iter: I) -> Self {
+        Block::new().with_children(iter)
+    }
+}
+
+impl<'a, N: Into<Node<'a>>> Extend<N> for Block<'a> {
+    fn extend<I: IntoIterator<Item = N>>(&mut self, iter: I) {
+        self.add_children(iter);
+    }
+}
+
 impl<'a> RSTMLParse<'a> for Block<'a> {
     fn parse_no_whitespace(mut input: &'a str) -> ParseResult<'a, Self>
     where
@@ -80,7 +102,8 @@ impl<'a> RSTMLParse<'a> for Block<'a> {
         let mut children = Vec::new();
 
         while !input.is_empty() {
-            let Ok((rest, node)) = Node::parse_no_whitespace(input) else {
+            // Slots and directives do not skip the whitespace before them
+            let Ok((rest, node)) = Node::parse_ignoring_comments(input) else {
                 break;
             };
             children.push(node);
@@ -114,4 +137,39 @@ mod tests {
             "",
         );
     }
+
+    #[test]
+    fn test_collect_and_extend() {
+        let items = ["one", "two"];
+        let mut list: Document = items
+            .iter()
+            .map(|item| element("li").with_child(*item))
+            .collect();
+        list.extend([Node::comment("end")]);
+        assert_eq!(
+            list,
+            Document::new()
+                .with_child(element("li").with_child("one"))
+                .with_child(element("li").with_child("two"))
+                .with_child(Node::comment("end"))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let document = Document::new()
+            .with_child(Node::comment(" cached "))
+            .with_child(
+                element("main")
+                    .with_attribute(Attribute::id("main"))
+                    .with_child(element("p").with_child("Fish & Chips"))
+                    .with_child(element("svg").with_child(element("circle"))),
+            );
+        let json = serde_json::to_string(&document).unwrap();
+        assert!(json.contains(r#"{"Text":"Fish & Chips"}"#));
+        // Deserializes into owned data, so the document outlives the input
+        let restored: Document<'static> = serde_json::from_str(&json.clone()).unwrap();
+        assert_eq!(restored, document);
+    }
 }
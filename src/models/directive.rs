@@ -0,0 +1,407 @@
+use std::borrow::Cow;
+
+use crate::{
+    expr::Expr,
+    parse::{TRAILING_CONTENT, consume_comments},
+    prelude::*,
+    util::{nested, quote_nested},
+};
+
+/// A template instruction in a runtime template, written with an `@`
+///
+/// Layout directives are resolved when rendering through a
+/// [`crate::templates::Registry`], conditions and loops when rendering with a
+/// [`crate::template::Context`]. Rendered on their own, directives render their
+/// children, so a layout shows the default content of its blocks.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Directive<'a> {
+    /// `@extends("base.rstml")`, names the layout whose blocks the template replaces
+    Extends(Cow<'a, str>),
+    /// `@block(name) { ... }`, a region with default content that a template extending
+    /// this one can replace
+    Block {
+        name: Cow<'a, str>,
+        children: Vec<Node<'a>>,
+    },
+    /// `@include("partials/nav.rstml")`, replaced by the named template
+    Include(Cow<'a, str>),
+    /// `@if condition { ... }`, rendered when the condition holds, e.g.
+    /// `@if user.admin && page != "home"`
+    If {
+        condition: Cow<'a, str>,
+        children: Vec<Node<'a>>,
+    },
+    /// `@else { ... }` or `@else if condition { ... }` right after an `@if`, rendered
+    /// when no earlier branch was
+    Else {
+        condition: Option<Cow<'a, str>>,
+        children: Vec<Node<'a>>,
+    },
+    /// `@for item in path { ... }`, rendered once for each item of the list at the
+    /// path, with the item bound to its name
+    For {
+        item: Cow<'a, str>,
+        path: Cow<'a, str>,
+        children: Vec<Node<'a>>,
+    },
+}
+
+impl<'a> Directive<'a> {
+    /// The nodes inside the directive, rendered in its place
+    #[must_use]
+    pub fn children(&self) -> &[Node<'a>] {
+        match self {
+            Directive::Extends(_) | Directive::Include(_) => &[],
+            Directive::Block { children, .. }
+            | Directive::If { children, .. }
+            | Directive::Else { children, .. }
+            | Directive::For { children, .. } => children,
+        }
+    }
+
+    /// The nodes inside the directive mutably, `None` for directives without a body
+    pub fn children_mut(&mut self) -> Option<&mut Vec<Node<'a>>> {
+        match self {
+            Directive::Extends(_) | Directive::Include(_) => None,
+            Directive::Block { children, .. }
+            | Directive::If { children, .. }
+            | Directive::Else { children, .. }
+            | Directive::For { children, .. } => Some(children),
+        }
+    }
+
+    /// Takes the nodes inside the directive
+    #[must_use]
+    pub fn into_children(self) -> Vec<Node<'a>> {
+        match self {
+            Directive::Extends(_) | Directive::Include(_) => Vec::new(),
+            Directive::Block { children, .. }
+            | Directive::If { children, .. }
+            | Directive::Else { children, .. }
+            | Directive::For { children, .. } => children,
+        }
+    }
+
+    /// Copies all borrowed data, see [`Element::into_owned`]
+    #[must_use]
+    pub fn into_owned(self) -> Directive<'static> {
+        match self {
+            Directive::Extends(name) => Directive::Extends(name.into_owned().into()),
+            Directive::Include(name) => Directive::Include(name.into_owned().into()),
+            Directive::Block { name, children } => Directive::Block {
+                name: name.into_owned().into(),
+                children: children.into_iter().map(Node::into_owned).collect(),
+            },
+            Directive::If {
+                condition,
+                children,
+            } => Directive::If {
+                condition: condition.into_owned().into(),
+                children: children.into_iter().map(Node::into_owned).collect(),
+            },
+            Directive::Else {
+                condition,
+                children,
+            } => Directive::Else {
+                condition: condition.map(|condition| condition.into_owned().into()),
+                children: children.into_iter().map(Node::into_owned).collect(),
+            },
+            Directive::For {
+                item,
+                path,
+                children,
+            } => Directive::For {
+                item: item.into_owned().into(),
+                path: path.into_owned().into(),
+                children: children.into_iter().map(Node::into_owned).collect(),
+            },
+        }
+    }
+
+    // Parses a directive, `None` if the input does not start with one
+    pub(crate) fn parse(input: &'a str) -> Option<ParseResult<'a, Self>> {
+        if let Some(rest) = keyword(input, "@extends") {
+            let example = "@extends(\"base.rstml\")";
+            return Some(
+                parse_template_name(rest, example)
+                    .map(|(rest, name)| (rest, Directive::Extends(name.into()))),
+            );
+        }
+        if let Some(rest) = keyword(input, "@include") {
+            let example = "@include(\"partials/nav.rstml\")";
+            return Some(
+                parse_template_name(rest, example)
+                    .map(|(rest, name)| (rest, Directive::Include(name.into()))),
+            );
+        }
+        if let Some(rest) = keyword(input, "@block") {
+            return Some(parse_block(rest));
+        }
+        if let Some(rest) = statement(input, "@if") {
+            return Some(parse_if(rest));
+        }
+        if let Some(rest) = input.strip_prefix("@else")
+            && rest.trim_start().starts_with(['{', 'i'])
+        {
+            return Some(parse_else(rest));
+        }
+        if let Some(rest) = statement(input, "@for") {
+            return Some(parse_for(rest));
+        }
+        None
+    }
+}
+
+// The input after the directive keyword, if its arguments follow
+fn keyword<'a>(input: &'a str, keyword: &str) -> Option<&'a str> {
+    input
+        .strip_prefix(keyword)
+        .filter(|rest| rest.trim_start().starts_with('('))
+}
+
+// The input after a directive keyword followed by whitespace, e.g. `@if`
+fn statement<'a>(input: &'a str, keyword: &str) -> Option<&'a str> {
+    input
+        .strip_prefix(keyword)
+        .filter(|rest| rest.starts_with(char::is_whitespace))
+}
+
+/// Parses the parenthesized name of a slot or block, e.g. `(content)`
+pub(crate) fn parse_name<'a>(input: &'a str, example: &str) -> ParseResult<'a, &'a str> {
+    let (rest, name) = nested(input, "(", ")")?;
+    let name = name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(ParseError::invalid_input(
+            name,
+            Some(format!("Expected a name, e.g. `{example}`").into()),
+        ));
+    }
+    Ok((rest, name))
+}
+
+// Parses the parenthesized template name of an `@extends` or `@include`
+fn parse_template_name<'a>(input: &'a str, example: &str) -> ParseResult<'a, &'a str> {
+    let (rest, arguments) = nested(input, "(", ")")?;
+    let invalid = || {
+        ParseError::invalid_input(
+            arguments,
+            Some(format!("Expected a template name, e.g. `{example}`").into()),
+        )
+    };
+    let (after, name) = quote_nested(arguments).map_err(|_| invalid())?;
+    if !after.trim().is_empty() || name.is_empty() {
+        return Err(invalid());
+    }
+    Ok((rest, name))
+}
+
+// Parses the braced nodes of a directive, where `{-` strips the whitespace at the
+// start of the first text and `-}` the whitespace at the end of the last one
+fn parse_body(input: &str) -> ParseResult<'_, Vec<Node<'_>>> {
+    let (rest, content) = nested(consume_comments(input), "{", "}")?;
+    let (trim_start, content) = match content.strip_prefix('-') {
+        Some(content) => (true, content),
+        None => (false, content),
+    };
+    let (trim_end, content) = match content.strip_suffix('-') {
+        Some(content) => (true, content),
+        None => (false, content),
+    };
+    let (trailing, mut children) = Node::parse_many_ignoring_comments(consume_comments(content));
+    if !consume_comments(trailing).is_empty() {
+        return Err(ParseError::invalid_input(
+            trailing,
+            Some(TRAILING_CONTENT.into()),
+        ));
+    }
+    if trim_start && let Some(Node::Text(text)) = children.first_mut() {
+        text.content = trim_cow(&text.content, str::trim_start);
+        if text.content.is_empty() {
+            children.remove(0);
+        }
+    }
+    if trim_end && let Some(Node::Text(text)) = children.last_mut() {
+        text.content = trim_cow(&text.content, str::trim_end);
+        if text.content.is_empty() {
+            children.pop();
+        }
+    }
+    Ok((rest, children))
+}
+
+// Trims text, still borrowing from the input if it did
+fn trim_cow<'a>(text: &Cow<'a, str>, trim: fn(&str) -> &str) -> Cow<'a, str> {
+    match text {
+        Cow::Borrowed(text) => Cow::Borrowed(trim(text)),
+        Cow::Owned(text) => Cow::Owned(trim(text).to_string()),
+    }
+}
+
+fn parse_block(input: &str) -> ParseResult<'_, Directive<'_>> {
+    let (rest, name) = parse_name(input, "@block(content) { ... }")?;
+    let (rest, children) = parse_body(rest)?;
+    Ok((
+        rest,
+        Directive::Block {
+            name: name.into(),
+            children,
+        },
+    ))
+}
+
+// Splits off a condition, which runs up to the first `{` outside a string
+fn parse_condition(input: &str) -> ParseResult<'_, &str> {
+    let mut quote = None;
+    let end = input.char_indices().find_map(|(i, c)| {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '{' => return Some(i),
+            None => {}
+        }
+        None
+    });
+    let Some(end) = end else {
+        return Err(ParseError::invalid_input(
+            input,
+            Some("Expected `{` after the condition".into()),
+        ));
+    };
+    let condition = input[..end].trim();
+    if let Err(message) = Expr::parse(condition) {
+        return Err(ParseError::invalid_input(condition, Some(message.into())));
+    }
+    Ok((&input[end..], condition))
+}
+
+fn parse_if(input: &str) -> ParseResult<'_, Directive<'_>> {
+    let (rest, condition) = parse_condition(input)?;
+    let (rest, children) = parse_body(rest)?;
+    Ok((
+        rest,
+        Directive::If {
+            condition: condition.into(),
+            children,
+        },
+    ))
+}
+
+fn parse_else(input: &str) -> ParseResult<'_, Directive<'_>> {
+    let (rest, condition) = match statement(input.trim_start(), "if") {
+        Some(rest) => {
+            let (rest, condition) = parse_condition(rest)?;
+            (rest, Some(condition.into()))
+        }
+        None => (input, None),
+    };
+    let (rest, children) = parse_body(rest)?;
+    Ok((
+        rest,
+        Directive::Else {
+            condition,
+            children,
+        },
+    ))
+}
+
+fn parse_for(input: &str) -> ParseResult<'_, Directive<'_>> {
+    let invalid = || {
+        ParseError::invalid_input(
+            input,
+            Some("Expected a loop, e.g. `@for item in items { ... }`".into()),
+        )
+    };
+    let header = &input[..input.find('{').ok_or_else(invalid)?];
+    let mut words = header.split_whitespace();
+    let (Some(item), Some("in"), Some(path), None) =
+        (words.next(), words.next(), words.next(), words.next())
+    else {
+        return Err(invalid());
+    };
+    if !item.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(invalid());
+    }
+    let (rest, children) = parse_body(&input[header.len()..])?;
+    Ok((
+        rest,
+        Directive::For {
+            item: item.into(),
+            path: path.into(),
+            children,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prelude::*, test_util::*};
+
+    #[test]
+    fn test_directive_parse() {
+        assert_parse_eq(
+            Node::parse_no_whitespace(r#"@extends("layouts/base.rstml") p {}"#),
+            Node::Directive(Directive::Extends("layouts/base.rstml".into())),
+            " p {}",
+        );
+        assert_parse_eq(
+            Node::parse_no_whitespace(
+                r#"@block(content) { h1 { "Title" } // heading
+            }"#,
+            ),
+            Node::Directive(Directive::Block {
+                name: "content".into(),
+                children: vec![element("h1").with_child("Title").into()],
+            }),
+            "",
+        );
+        assert_parse_eq(
+            Node::parse_no_whitespace(r#"@include( "nav.rstml" )"#),
+            Node::Directive(Directive::Include("nav.rstml".into())),
+            "",
+        );
+        assert_parse_eq(
+            Node::parse_no_whitespace(r#"@if user && page != "{home}" { p {} } @else {}"#),
+            Node::Directive(Directive::If {
+                condition: r#"user && page != "{home}""#.into(),
+                children: vec![element("p").into()],
+            }),
+            " @else {}",
+        );
+        assert_parse_eq(
+            Node::parse_no_whitespace(r#"@else if !admin { "Guest" }"#),
+            Node::Directive(Directive::Else {
+                condition: Some("!admin".into()),
+                children: vec!["Guest".into()],
+            }),
+            "",
+        );
+        assert_parse_eq(
+            Node::parse_no_whitespace(r#"@for tag in tags { li { "{tag}" } }"#),
+            Node::Directive(Directive::For {
+                item: "tag".into(),
+                path: "tags".into(),
+                children: vec![element("li").with_child("{tag}").into()],
+            }),
+            "",
+        );
+        assert_parse_eq(
+            Node::parse_no_whitespace("@for tag in tags {- \"\n  {tag}\n\" br {} \"\n\" -}"),
+            Node::Directive(Directive::For {
+                item: "tag".into(),
+                path: "tags".into(),
+                children: vec!["{tag}\n".into(), element("br").into()],
+            }),
+            "",
+        );
+        assert!(Node::parse_no_whitespace("@if a == { p {} }").is_err());
+        assert!(Node::parse_no_whitespace("@for tag of tags {}").is_err());
+        assert!(Node::parse_no_whitespace("@extends(base)").is_err());
+        assert!(Node::parse_no_whitespace("@block(a b) {}").is_err());
+    }
+}
@@ -1,24 +1,29 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc};
 
-use crate::{parse::consume_comments, prelude::*};
+use crate::{
+    attribute::{Conflict, MergePolicy},
+    parse::consume_comments,
+    prelude::*,
+};
 
 // Generic Element struct that can hold different types of children
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Element<'a> {
     pub name: Tag<'a>,
+    pub namespace: Namespace,
     pub attributes: Vec<Attribute<'a>>,
     pub children: Vec<Node<'a>>,
+    #[cfg(feature = "events")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub handlers: Vec<EventHandler<'a>>,
 }
 
 impl<'a> Element<'a> {
     pub const EMPTY: Self = Self::empty();
     #[must_use]
     pub const fn empty() -> Element<'a> {
-        Element {
-            name: Tag::DIV,
-            attributes: Vec::new(),
-            children: Vec::new(),
-        }
+        Self::new_const(Tag::DIV)
     }
     #[must_use]
     pub const fn is_empty(&self) -> bool {
@@ -26,10 +31,14 @@ impl<'a> Element<'a> {
     }
     #[must_use]
     pub const fn new_const(name: Tag<'a>) -> Self {
+        let namespace = Namespace::for_tag(name.as_str());
         Element {
             name,
+            namespace,
             attributes: Vec::new(),
             children: Vec::new(),
+            #[cfg(feature = "events")]
+            handlers: Vec::new(),
         }
     }
     pub fn new(name: impl Into<Tag<'a>>) -> Self {
@@ -38,9 +47,56 @@ impl<'a> Element<'a> {
 
     /// Adds a child node to the element.
     ///
-    /// If the child is a Block, its children are flattened into the element's children.
+    /// Child elements inherit the namespace of a foreign (e.g. SVG) parent.
     pub fn add_child(&mut self, child: impl Into<Node<'a>>) {
-        self.children.push(child.into());
+        let mut child = child.into();
+        if let Node::Element(element) = &mut child {
+            Self::inherit_namespace(self.namespace, &self.name, element);
+        }
+        self.children.push(child);
+    }
+
+    /// Sets the namespace of the element.
+    ///
+    /// Foreign namespaces also apply to descendants that are still in HTML,
+    /// except for the children of a `foreignObject`. Only those descendants are
+    /// visited, so each element is moved out of HTML once however deep the tree
+    /// is built.
+    pub fn set_namespace(&mut self, namespace: Namespace) {
+        self.namespace = namespace;
+        if namespace.is_html() {
+            return;
+        }
+        // Walked with a stack, as trees built in code may be deeper than the call stack
+        let mut pending = vec![&mut *self];
+        while let Some(parent) = pending.pop() {
+            if parent.name.as_str() == "foreignObject" {
+                continue;
+            }
+            for child in &mut parent.children {
+                if let Node::Element(child) = child
+                    && child.namespace.is_html()
+                {
+                    child.namespace = namespace;
+                    pending.push(child);
+                }
+            }
+        }
+    }
+    #[must_use]
+    pub fn with_namespace(mut self, namespace: Namespace) -> Self {
+        self.set_namespace(namespace);
+        self
+    }
+
+    pub(crate) fn inherit_namespace(
+        namespace: Namespace,
+        parent: &Tag<'a>,
+        child: &mut Element<'a>,
+    ) {
+        if !namespace.is_html() && child.namespace.is_html() && parent.as_str() != "foreignObject" {
+            child.set_namespace(namespace);
+        }
     }
     #[must_use]
     pub fn with_child(mut self, child: impl Into<Node<'a>>) -> Self {
@@ -65,9 +121,78 @@ impl<'a> Element<'a> {
         self
     }
 
+    /// Adds each item as an attribute or a child, depending on its type
+    ///
+    /// Used by the `..items` spread of `rstml!` before an element's first child,
+    /// which may spread either attributes or children.
+    pub fn add_spread<I>(&mut self, items: I)
+    where
+        I: IntoIterator<Item: SpreadItem<'a>>,
+    {
+        for item in items {
+            item.spread_into(self);
+        }
+    }
+    #[must_use]
+    pub fn with_spread<I>(mut self, items: I) -> Self
+    where
+        I: IntoIterator<Item: SpreadItem<'a>>,
+    {
+        self.add_spread(items);
+        self
+    }
+
+    /// Adds an attribute to the element, even if its key is already set
+    ///
+    /// Use [`Element::merge_attributes`] to resolve keys that are set more than once.
     pub fn add_attribute(&mut self, attribute: Attribute<'a>) {
         self.attributes.push(attribute);
     }
+
+    /// Merges attributes into the element, resolving keys that are already set with the policy.
+    ///
+    /// Useful for component wrappers applying caller-supplied attributes to their root.
+    pub fn merge_attributes<I>(&mut self, attributes: I, policy: MergePolicy)
+    where
+        I: IntoIterator<Item = Attribute<'a>>,
+    {
+        for attribute in attributes {
+            self.merge_attribute(attribute, policy);
+        }
+    }
+    #[must_use]
+    pub fn with_merged_attributes<I>(mut self, attributes: I, policy: MergePolicy) -> Self
+    where
+        I: IntoIterator<Item = Attribute<'a>>,
+    {
+        self.merge_attributes(attributes, policy);
+        self
+    }
+
+    fn merge_attribute(&mut self, attribute: Attribute<'a>, policy: MergePolicy) {
+        let Some(existing) = self.attributes.iter_mut().find(|a| a.key == attribute.key) else {
+            self.attributes.push(attribute);
+            return;
+        };
+        let separator = match attribute.key.as_ref() {
+            "class" if policy.join_classes => " ",
+            "style" if policy.join_styles => "; ",
+            _ => {
+                if policy.conflict == Conflict::LastWins {
+                    *existing = attribute;
+                }
+                return;
+            }
+        };
+        let current = existing.value.trim_end_matches([' ', ';']);
+        let incoming = attribute.value.trim_start_matches([' ', ';']);
+        existing.boolean &= attribute.boolean;
+        existing.value = match (current.is_empty(), incoming.is_empty()) {
+            (_, true) => current.to_string().into(),
+            (true, false) => attribute.value,
+            (false, false) => format!("{current}{separator}{incoming}").into(),
+        };
+    }
     #[must_use]
     pub fn with_attribute(mut self, attribute: Attribute<'a>) -> Self {
         self.add_attribute(attribute);
@@ -124,10 +249,225 @@ impl<'a> Element<'a> {
         self
     }
 
+    /// Returns the value of an attribute, if it is set.
+    #[must_use]
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.key == key)
+            .map(|a| a.value.as_ref())
+    }
+    #[must_use]
+    pub fn has_attr(&self, key: &str) -> bool {
+        self.attributes.iter().any(|a| a.key == key)
+    }
+
+    /// Sets an attribute, replacing any existing value rather than merging with it.
+    ///
+    /// The attribute takes the place of the first one with the same key, any later
+    /// duplicates are removed.
+    pub fn set_attr(&mut self, key: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) {
+        let attribute = Attribute::new(key, value);
+        let index = self
+            .attributes
+            .iter()
+            .position(|a| a.key == attribute.key)
+            .unwrap_or(self.attributes.len());
+        self.attributes.retain(|a| a.key != attribute.key);
+        self.attributes.insert(index, attribute);
+    }
+
+    /// Removes every attribute with the key, returning the value of the first one
+    /// if it was set.
+    pub fn remove_attr(&mut self, key: &str) -> Option<Cow<'a, str>> {
+        let index = self.attributes.iter().position(|a| a.key == key)?;
+        let value = self.attributes.remove(index).value;
+        self.attributes.retain(|a| a.key != key);
+        Some(value)
+    }
+
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        self.attr("id")
+    }
+    pub fn set_id(&mut self, id: impl Into<Cow<'a, str>>) {
+        self.set_attr("id", id);
+    }
+    /// Returns the raw `class` attribute, i.e. all classes separated by spaces.
+    #[must_use]
+    pub fn class(&self) -> Option<&str> {
+        self.attr("class")
+    }
+    pub fn set_class(&mut self, class: impl Into<Cow<'a, str>>) {
+        self.set_attr("class", class);
+    }
+
+    /// Iterates over the classes in the `class` attribute.
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.class().unwrap_or_default().split_whitespace()
+    }
+    #[must_use]
+    pub fn has_class(&self, class: &str) -> bool {
+        self.classes().any(|c| c == class)
+    }
+
+    /// Adds a class if it is not already present.
+    pub fn add_class(&mut self, class: &str) {
+        let mut classes = self.class_list();
+        if !classes.contains(&class) {
+            classes.push(class);
+        }
+        let classes = classes.join(" ");
+        self.write_classes(classes);
+    }
+    #[must_use]
+    pub fn with_class(mut self, class: &str) -> Self {
+        self.add_class(class);
+        self
+    }
+
+    pub fn remove_class(&mut self, class: &str) {
+        let mut classes = self.class_list();
+        classes.retain(|c| *c != class);
+        let classes = classes.join(" ");
+        self.write_classes(classes);
+    }
+
+    /// Adds the class if it is missing and removes it otherwise, returning true if it is now present.
+    pub fn toggle_class(&mut self, class: &str) -> bool {
+        let present = self.has_class(class);
+        if present {
+            self.remove_class(class);
+        } else {
+            self.add_class(class);
+        }
+        !present
+    }
+
+    // Classes without duplicates, in their original order
+    fn class_list(&self) -> Vec<&str> {
+        let mut classes = Vec::new();
+        for class in self.classes() {
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+        classes
+    }
+
+    // Stores a normalized class list in place, removing the attribute once it is empty
+    fn write_classes(&mut self, classes: String) {
+        if classes.is_empty() {
+            self.remove_attr("class");
+        } else {
+            self.set_attr("class", classes);
+        }
+    }
+
+    /// Attaches an event handler to the element.
+    #[cfg(feature = "events")]
+    pub fn add_handler(&mut self, handler: EventHandler<'a>) {
+        self.handlers.push(handler);
+    }
+    #[cfg(feature = "events")]
+    #[must_use]
+    pub fn with_handler(mut self, handler: EventHandler<'a>) -> Self {
+        self.add_handler(handler);
+        self
+    }
+
     #[must_use]
     pub fn into_node(self) -> Node<'a> {
         Node::Element(self)
     }
+
+    /// Copies all borrowed data, detaching the element from the input it was parsed from
+    ///
+    /// Event handlers may borrow their environment and are dropped.
+    #[must_use]
+    pub fn into_owned(self) -> Element<'static> {
+        Element {
+            name: self.name.into_owned(),
+            namespace: self.namespace,
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(Attribute::into_owned)
+                .collect(),
+            children: self.children.into_iter().map(Node::into_owned).collect(),
+            #[cfg(feature = "events")]
+            handlers: Vec::new(),
+        }
+    }
+}
+
+impl<'a, N: Into<Node<'a>>> Extend<N> for Element<'a> {
+    fn extend<I: IntoIterator<Item = N>>(&mut self, iter: I) {
+        self.add_children(iter);
+    }
+}
+
+/// An item of [`Element::add_spread`], added to the element as an attribute or a child
+///
+/// Attributes are merged like those set in `rstml!`, with [`MergePolicy::MARKUP`].
+pub trait SpreadItem<'a> {
+    fn spread_into(self, element: &mut Element<'a>);
+}
+
+macro_rules! spread_attributes {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> SpreadItem<'a> for $ty {
+                fn spread_into(self, element: &mut Element<'a>) {
+                    element.merge_attributes([Attribute::from(self)], MergePolicy::MARKUP);
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! spread_children {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> SpreadItem<'a> for $ty {
+                fn spread_into(self, element: &mut Element<'a>) {
+                    element.add_child(self);
+                }
+            }
+        )*
+    };
+}
+
+spread_attributes!(
+    Attribute<'a>,
+    crate::known::KnownAttribute<'a>,
+    crate::style::Style
+);
+spread_children!(
+    Node<'a>,
+    Element<'a>,
+    Arc<Element<'a>>,
+    Text<'a>,
+    String,
+    &'a str
+);
+
+impl<'a, T, U> SpreadItem<'a> for (T, U)
+where
+    T: Into<Cow<'a, str>>,
+    U: Into<Cow<'a, str>>,
+{
+    fn spread_into(self, element: &mut Element<'a>) {
+        Attribute::from(self).spread_into(element);
+    }
+}
+
+impl<'a, S: SpreadItem<'a>> SpreadItem<'a> for Option<S> {
+    fn spread_into(self, element: &mut Element<'a>) {
+        if let Some(item) = self {
+            item.spread_into(element);
+        }
+    }
 }
 
 impl<'a> RSTMLParse<'a> for Element<'a> {
@@ -144,17 +484,15 @@ impl<'a> RSTMLParse<'a> for Element<'a> {
         if !consume_comments(rest).is_empty() {
             return Err(ParseError::invalid_input(
                 rest,
-                Some("Unexpected content after element children".into()),
+                Some(crate::parse::TRAILING_CONTENT.into()),
             ));
         }
 
         Ok((
             rest_out,
-            Element {
-                name,
-                attributes,
-                children,
-            },
+            Element::new_const(name)
+                .with_attributes(attributes)
+                .with_children(children),
         ))
     }
 }
@@ -165,6 +503,7 @@ pub fn element<'a>(name: impl Into<Tag<'a>>) -> Element<'a> {
 
 #[cfg(test)]
 mod tests {
+    use crate::attribute::{Conflict, MergePolicy};
     use crate::prelude::*;
     use crate::util::test_util::assert_parse_eq;
 
@@ -224,6 +563,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_svg_namespace_parse() {
+        let input = r#"svg { .viewBox="0 0 10 10" g { circle { .cx="5" } } }"#;
+        let (_, svg) = Element::parse_no_whitespace(input).unwrap();
+        assert_eq!(svg.namespace, Namespace::Svg);
+        assert_eq!(svg.attributes, vec![Attribute::new("viewBox", "0 0 10 10")]);
+        let Node::Element(group) = &svg.children[0] else {
+            panic!("Expected an element");
+        };
+        assert_eq!(group.namespace, Namespace::Svg);
+        let Node::Element(circle) = &group.children[0] else {
+            panic!("Expected an element");
+        };
+        assert_eq!(circle.namespace, Namespace::Svg);
+    }
+
+    #[test]
+    fn test_deep_namespace_inheritance() {
+        let mut chain = element("circle");
+        for _ in 0..2_000 {
+            chain = element("g").with_child(chain);
+        }
+        let html = element(Tag::DIV).with_child(element(Tag::P));
+        let svg = element("svg")
+            .with_child(chain)
+            .with_child(element("foreignObject").with_child(html));
+        let mut depth = 0;
+        let mut node = &svg.children[0];
+        while let Some(element) = node.as_element() {
+            assert_eq!(element.namespace, Namespace::Svg);
+            depth += 1;
+            let Some(child) = element.children.first() else {
+                break;
+            };
+            node = child;
+        }
+        assert_eq!(depth, 2_001);
+        // Children of a `foreignObject` stay in HTML
+        let Node::Element(object) = &svg.children[1] else {
+            panic!("Expected an element");
+        };
+        assert_eq!(object.namespace, Namespace::Svg);
+        assert_eq!(
+            object.children[0].as_element().unwrap().namespace,
+            Namespace::Html
+        );
+    }
+
     #[test]
     fn test_nested_element_parse() {
         let input = r#"div
@@ -243,4 +630,139 @@ mod tests {
             "",
         );
     }
+
+    #[test]
+    fn test_duplicate_attributes_merge() {
+        let attributes = [
+            Attribute::class("card"),
+            Attribute::title("first"),
+            Attribute::class("active"),
+            Attribute::title("second"),
+        ];
+        // Adding keeps every attribute, merging resolves the keys set twice
+        let added = element(Tag::DIV).with_attributes(attributes.clone());
+        assert_eq!(added.attributes, attributes);
+        let merged = element(Tag::DIV).with_merged_attributes(attributes, MergePolicy::MARKUP);
+        assert_eq!(
+            merged.attributes,
+            vec![Attribute::class("card active"), Attribute::title("second")]
+        );
+    }
+
+    #[test]
+    fn test_attribute_accessors() {
+        let mut link = element(Tag::A)
+            .with_key_value("href", "/home")
+            .with_attribute(Attribute::class("nav"));
+        assert_eq!(link.attr("href"), Some("/home"));
+        assert!(!link.has_attr("title"));
+
+        link.set_attr("href", "/about");
+        link.set_class("nav active");
+        link.set_id("about");
+        assert_eq!(link.attr("href"), Some("/about"));
+        assert_eq!(link.class(), Some("nav active"));
+        assert_eq!(link.id(), Some("about"));
+
+        assert_eq!(link.remove_attr("href"), Some("/about".into()));
+        assert_eq!(link.remove_attr("href"), None);
+        assert_eq!(
+            link.attributes,
+            vec![Attribute::class("nav active"), Attribute::id("about")]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_attribute_keys() {
+        let mut link = element(Tag::A)
+            .with_attribute(Attribute::new("href", "/old1"))
+            .with_attribute(Attribute::id("x"))
+            .with_attribute(Attribute::new("href", "/old2"));
+        assert_eq!(link.attributes.len(), 3);
+        link.set_attr("href", "/new");
+        assert_eq!(link.attr("href"), Some("/new"));
+        assert_eq!(link.render(), r#"<a href="/new" id="x"></a>"#);
+
+        link.add_attribute(Attribute::new("href", "/again"));
+        link.attributes.push(Attribute::new("href", "/pushed"));
+        assert_eq!(link.remove_attr("href"), Some("/new".into()));
+        assert!(!link.has_attr("href"));
+        assert_eq!(link.attributes, vec![Attribute::id("x")]);
+    }
+
+    #[test]
+    fn test_class_list() {
+        let mut button =
+            element(Tag::BUTTON).with_attribute(Attribute::class(" btn  btn btn-primary "));
+        assert_eq!(
+            button.classes().collect::<Vec<_>>(),
+            ["btn", "btn", "btn-primary"]
+        );
+
+        button.add_class("active");
+        assert_eq!(button.class(), Some("btn btn-primary active"));
+        assert!(!button.toggle_class("btn-primary"));
+        assert!(button.toggle_class("large"));
+        assert!(button.has_class("large"));
+        assert_eq!(button.class(), Some("btn active large"));
+
+        for class in ["btn", "active", "large"] {
+            button.remove_class(class);
+        }
+        assert!(!button.has_attr("class"));
+    }
+
+    #[test]
+    fn test_merge_attributes() {
+        let caller = || {
+            vec![
+                Attribute::class("wide"),
+                Attribute::style("margin: 0"),
+                Attribute::id("caller"),
+            ]
+        };
+        let root = || {
+            element(Tag::DIV)
+                .with_attribute(Attribute::class("card"))
+                .with_attribute(Attribute::style("color: red;"))
+                .with_attribute(Attribute::id("root"))
+        };
+
+        let mut merged = root();
+        merged.merge_attributes(caller(), MergePolicy::default());
+        assert_eq!(
+            merged.attributes,
+            vec![
+                Attribute::class("card wide"),
+                Attribute::style("color: red; margin: 0"),
+                Attribute::id("caller"),
+            ]
+        );
+
+        let mut merged = root();
+        let policy = MergePolicy {
+            join_classes: false,
+            ..MergePolicy::DEFAULT.with_conflict(Conflict::FirstWins)
+        };
+        merged.merge_attributes(caller(), policy);
+        assert_eq!(
+            merged.attributes,
+            vec![
+                Attribute::class("card"),
+                Attribute::style("color: red; margin: 0"),
+                Attribute::id("root"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extend_children() {
+        let mut svg = element("svg");
+        svg.extend(["a", "b"].map(|id| element("circle").with_attribute(Attribute::id(id))));
+        assert_eq!(svg.children.len(), 2);
+        assert!(
+            svg.children_elements()
+                .all(|circle| circle.namespace == Namespace::Svg)
+        );
+    }
 }
@@ -0,0 +1,141 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+type Callback<'a> = Arc<dyn Fn(&dyn Any) + Send + Sync + 'a>;
+
+/// Represents a callback attached to an element for a given event
+///
+/// Handlers are not rendered to static markup, they exist so the same tree
+/// can back an interactive renderer which dispatches its native events to them.
+///
+/// The event payload is type erased, handlers created with [`EventHandler::typed`]
+/// are only invoked when the payload has the expected type. Callbacks are `Send`
+/// and `Sync`, so trees with handlers can be shared between threads.
+#[derive(Clone)]
+pub struct EventHandler<'a> {
+    pub event: Cow<'a, str>,
+    callback: Callback<'a>,
+}
+
+impl<'a> EventHandler<'a> {
+    pub fn new(
+        event: impl Into<Cow<'a, str>>,
+        callback: impl Fn(&dyn Any) + Send + Sync + 'a,
+    ) -> Self {
+        EventHandler {
+            event: event.into(),
+            callback: Arc::new(callback),
+        }
+    }
+
+    /// Creates a handler that is only called with payloads of type `E`
+    pub fn typed<E: Any>(
+        event: impl Into<Cow<'a, str>>,
+        callback: impl Fn(&E) + Send + Sync + 'a,
+    ) -> Self {
+        Self::new(event, move |payload: &dyn Any| {
+            if let Some(payload) = payload.downcast_ref::<E>() {
+                callback(payload);
+            }
+        })
+    }
+
+    /// Invokes the handler with the given event payload
+    pub fn call(&self, payload: &dyn Any) {
+        (self.callback)(payload);
+    }
+}
+
+impl std::fmt::Debug for EventHandler<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "on:{}", self.event)
+    }
+}
+
+// Handlers are equal when they listen to the same event with the same callback
+impl PartialEq for EventHandler<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.event == other.event && Arc::ptr_eq(&self.callback, &other.callback)
+    }
+}
+
+impl Eq for EventHandler<'_> {}
+
+impl EventHandler<'_> {
+    // Identity of the callback, only meaningful while the handler is alive
+    fn address(&self) -> usize {
+        Arc::as_ptr(&self.callback).cast::<()>() as usize
+    }
+}
+
+impl Hash for EventHandler<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.event.hash(state);
+        self.address().hash(state);
+    }
+}
+
+// Ordered by event, then by callback address so the order agrees with equality
+impl Ord for EventHandler<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.event
+            .cmp(&other.event)
+            .then_with(|| self.address().cmp(&other.address()))
+    }
+}
+
+impl PartialOrd for EventHandler<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use super::EventHandler;
+
+    #[test]
+    fn test_handler_call() {
+        let clicks = AtomicI32::new(0);
+        let handler = EventHandler::new("click", |_| {
+            clicks.fetch_add(1, Ordering::Relaxed);
+        });
+        handler.call(&());
+        handler.call(&());
+        assert_eq!(clicks.load(Ordering::Relaxed), 2);
+        assert_eq!(handler, handler.clone());
+    }
+
+    #[test]
+    fn test_typed_handler_call() {
+        let total = AtomicI32::new(0);
+        let handler = EventHandler::typed("input", |value: &i32| {
+            total.fetch_add(*value, Ordering::Relaxed);
+        });
+        handler.call(&5);
+        handler.call(&"ignored");
+        assert_eq!(total.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn test_handler_across_threads() {
+        let total = AtomicI32::new(0);
+        let handler = EventHandler::typed("input", |value: &i32| {
+            total.fetch_add(*value, Ordering::Relaxed);
+        });
+        std::thread::scope(|scope| {
+            for value in 1..=4 {
+                let handler = handler.clone();
+                scope.spawn(move || handler.call(&value));
+            }
+        });
+        assert_eq!(total.load(Ordering::Relaxed), 10);
+    }
+}
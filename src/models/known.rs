@@ -0,0 +1,271 @@
+use std::borrow::Cow;
+
+use crate::prelude::*;
+
+/// A URL attribute value, checked to contain no whitespace or control characters
+/// and, if it has a scheme, a well-formed one
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Url<'a>(Cow<'a, str>);
+
+/// Error returned for a value that cannot be used as a [`Url`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlError {
+    /// The value contains whitespace or a control character
+    InvalidCharacter(char),
+    /// The part before the first `:` is not a valid scheme, e.g. `1http:`
+    InvalidScheme,
+}
+
+impl std::fmt::Display for UrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UrlError::InvalidCharacter(c) => write!(f, "Invalid character {c:?} in URL"),
+            UrlError::InvalidScheme => write!(f, "Invalid URL scheme"),
+        }
+    }
+}
+
+impl std::error::Error for UrlError {}
+
+impl<'a> Url<'a> {
+    /// Checks the value, surrounding whitespace is trimmed as browsers do
+    ///
+    /// # Errors
+    /// Errors if the value contains whitespace or control characters, or has a malformed scheme
+    pub fn new(value: impl Into<Cow<'a, str>>) -> Result<Self, UrlError> {
+        let value = match value.into() {
+            Cow::Borrowed(value) => Cow::Borrowed(value.trim()),
+            Cow::Owned(value) => Cow::Owned(value.trim().to_string()),
+        };
+        if let Some(c) = value.chars().find(|c| c.is_whitespace() || c.is_control()) {
+            return Err(UrlError::InvalidCharacter(c));
+        }
+        let url = Url(value);
+        // A colon after a slash, `?` or `#` is part of a relative URL, not a scheme
+        let before_path = url.0.split(['/', '?', '#']).next().unwrap_or_default();
+        if let Some((scheme, _)) = before_path.split_once(':')
+            && !is_scheme(scheme)
+        {
+            return Err(UrlError::InvalidScheme);
+        }
+        Ok(url)
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the scheme in lowercase, e.g. `https`, or `None` for a relative URL
+    #[must_use]
+    pub fn scheme(&self) -> Option<String> {
+        let before_path = self.0.split(['/', '?', '#']).next()?;
+        let (scheme, _) = before_path.split_once(':')?;
+        Some(scheme.to_ascii_lowercase())
+    }
+
+    #[must_use]
+    pub fn is_absolute(&self) -> bool {
+        self.scheme().is_some() || self.0.starts_with("//")
+    }
+}
+
+fn is_scheme(scheme: &str) -> bool {
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+}
+
+impl std::fmt::Display for Url<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The classes of a `class` attribute, without duplicates
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ClassList<'a>(Vec<Cow<'a, str>>);
+
+impl<'a> ClassList<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        ClassList::default()
+    }
+
+    /// Splits a `class` attribute value on whitespace
+    #[must_use]
+    pub fn parse(value: &'a str) -> Self {
+        value.split_whitespace().collect()
+    }
+
+    #[must_use]
+    pub fn contains(&self, class: &str) -> bool {
+        self.0.iter().any(|c| c == class)
+    }
+
+    /// Adds a class if it is not already present, returning true if it was added
+    pub fn add(&mut self, class: impl Into<Cow<'a, str>>) -> bool {
+        let class = class.into();
+        if self.contains(&class) {
+            return false;
+        }
+        self.0.push(class);
+        true
+    }
+
+    /// Removes a class, returning true if it was present
+    pub fn remove(&mut self, class: &str) -> bool {
+        let before = self.0.len();
+        self.0.retain(|c| c != class);
+        self.0.len() != before
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(AsRef::as_ref)
+    }
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a, C: Into<Cow<'a, str>>> FromIterator<C> for ClassList<'a> {
+    fn from_iter<I: IntoIterator<Item = C>>(iter: I) -> Self {
+        let mut list = ClassList::new();
+        for class in iter {
+            list.add(class);
+        }
+        list
+    }
+}
+
+impl std::fmt::Display for ClassList<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, class) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(class)?;
+        }
+        Ok(())
+    }
+}
+
+/// Typed view of the attributes an application cares about
+///
+/// Converting an [`Attribute`] never fails, anything that is not recognized or
+/// whose value does not parse is kept untouched as [`KnownAttribute::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KnownAttribute<'a> {
+    Id(Cow<'a, str>),
+    Class(ClassList<'a>),
+    Href(Url<'a>),
+    Src(Url<'a>),
+    /// A `data-*` attribute, with the name after `data-`
+    Data(Cow<'a, str>, Cow<'a, str>),
+    /// An `aria-*` attribute, with the name after `aria-`
+    Aria(Cow<'a, str>, Cow<'a, str>),
+    Other(Attribute<'a>),
+}
+
+// Drops a prefix from a possibly borrowed key
+fn strip_prefix<'a>(key: &Cow<'a, str>, prefix: &str) -> Option<Cow<'a, str>> {
+    match key {
+        Cow::Borrowed(key) => key.strip_prefix(prefix).map(Cow::Borrowed),
+        Cow::Owned(key) => key
+            .strip_prefix(prefix)
+            .map(|name| Cow::Owned(name.to_string())),
+    }
+}
+
+impl<'a> From<Attribute<'a>> for KnownAttribute<'a> {
+    fn from(attribute: Attribute<'a>) -> Self {
+        let url = |value: &Cow<'a, str>| Url::new(value.clone()).ok();
+        match attribute.key.as_ref() {
+            "id" => KnownAttribute::Id(attribute.value),
+            "class" => KnownAttribute::Class(
+                attribute
+                    .value
+                    .split_whitespace()
+                    .map(|class| Cow::Owned(class.to_string()))
+                    .collect(),
+            ),
+            "href" if let Some(url) = url(&attribute.value) => KnownAttribute::Href(url),
+            "src" if let Some(url) = url(&attribute.value) => KnownAttribute::Src(url),
+            _ if let Some(name) = strip_prefix(&attribute.key, "data-") => {
+                KnownAttribute::Data(name, attribute.value)
+            }
+            _ if let Some(name) = strip_prefix(&attribute.key, "aria-") => {
+                KnownAttribute::Aria(name, attribute.value)
+            }
+            _ => KnownAttribute::Other(attribute),
+        }
+    }
+}
+
+impl<'a> From<KnownAttribute<'a>> for Attribute<'a> {
+    fn from(attribute: KnownAttribute<'a>) -> Self {
+        match attribute {
+            KnownAttribute::Id(id) => Attribute::new("id", id),
+            KnownAttribute::Class(classes) => Attribute::new("class", classes.to_string()),
+            KnownAttribute::Href(url) => Attribute::new("href", url.0),
+            KnownAttribute::Src(url) => Attribute::new("src", url.0),
+            KnownAttribute::Data(name, value) => Attribute::data(&name, value),
+            KnownAttribute::Aria(name, value) => Attribute::aria(&name, value),
+            KnownAttribute::Other(attribute) => attribute,
+        }
+    }
+}
+
+impl<'a> Element<'a> {
+    /// Iterates over the attributes as [`KnownAttribute`]s
+    pub fn known_attributes(&self) -> impl Iterator<Item = KnownAttribute<'a>> {
+        self.attributes.iter().cloned().map(KnownAttribute::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClassList, KnownAttribute, Url, UrlError};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_url() {
+        let url = Url::new(" https://example.com/a?b#c ").unwrap();
+        assert_eq!(url.as_str(), "https://example.com/a?b#c");
+        assert_eq!(url.scheme().as_deref(), Some("https"));
+        assert!(url.is_absolute());
+        assert!(!Url::new("/docs/a:b").unwrap().is_absolute());
+        assert_eq!(Url::new("/a b"), Err(UrlError::InvalidCharacter(' ')));
+        assert_eq!(Url::new("1http://x"), Err(UrlError::InvalidScheme));
+    }
+
+    #[test]
+    fn test_known_attribute_roundtrip() {
+        let link = element("a")
+            .with_attribute(Attribute::class("nav nav active"))
+            .with_key_value("href", "/home")
+            .with_attribute(Attribute::data("page", "1"))
+            .with_key_value("target", "_blank")
+            .with_key_value("src", "bad url");
+        let known: Vec<_> = link.known_attributes().collect();
+        assert_eq!(
+            known,
+            [
+                KnownAttribute::Class(ClassList::parse("nav active")),
+                KnownAttribute::Href(Url::new("/home").unwrap()),
+                KnownAttribute::Data("page".into(), "1".into()),
+                KnownAttribute::Other(Attribute::new("target", "_blank")),
+                KnownAttribute::Other(Attribute::new("src", "bad url")),
+            ]
+        );
+        let attributes: Vec<Attribute> = known.into_iter().map(Attribute::from).collect();
+        assert_eq!(attributes[0], Attribute::class("nav active"));
+        assert_eq!(attributes[1..], link.attributes[1..]);
+    }
+}
@@ -1,16 +1,26 @@
 pub mod attribute;
 pub mod block;
+pub mod directive;
 pub mod element;
+#[cfg(feature = "events")]
+pub mod event;
+pub mod known;
+pub mod namespace;
 pub mod node;
 pub mod tag;
 pub mod text;
 
 pub mod prelude {
-    use super::{attribute, block, element, node, tag, text};
+    #[cfg(feature = "events")]
+    pub use super::event::EventHandler;
+    use super::{attribute, block, directive, element, known, namespace, node, tag, text};
     pub use attribute::Attribute;
-    pub use block::Block;
-    pub use element::{Element, element};
-    pub use node::Node;
+    pub use block::{Block, Document};
+    pub use directive::Directive;
+    pub use element::{Element, SpreadItem, element};
+    pub use known::KnownAttribute;
+    pub use namespace::Namespace;
+    pub use node::{Node, ScriptLoading};
     pub use tag::Tag;
     pub use text::Text;
 }
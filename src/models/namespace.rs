@@ -0,0 +1,69 @@
+/// Represents the XML namespace an element belongs to
+///
+/// Elements are in the HTML namespace unless their tag starts a foreign
+/// subtree, such as `svg` or `math`, in which case descendants inherit it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Namespace {
+    #[default]
+    Html,
+    Svg,
+    MathMl,
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+impl Namespace {
+    /// Returns the namespace started by the given tag, defaulting to HTML
+    #[must_use]
+    pub const fn for_tag(tag: &str) -> Self {
+        if str_eq(tag, "svg") {
+            Namespace::Svg
+        } else if str_eq(tag, "math") {
+            Namespace::MathMl
+        } else {
+            Namespace::Html
+        }
+    }
+
+    /// Returns the namespace URI, as emitted in `xmlns` attributes
+    #[must_use]
+    pub const fn uri(self) -> &'static str {
+        match self {
+            Namespace::Html => "http://www.w3.org/1999/xhtml",
+            Namespace::Svg => "http://www.w3.org/2000/svg",
+            Namespace::MathMl => "http://www.w3.org/1998/Math/MathML",
+        }
+    }
+
+    #[must_use]
+    pub const fn is_html(self) -> bool {
+        matches!(self, Namespace::Html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Namespace;
+
+    #[test]
+    fn test_namespace_for_tag() {
+        assert_eq!(Namespace::for_tag("svg"), Namespace::Svg);
+        assert_eq!(Namespace::for_tag("math"), Namespace::MathMl);
+        assert_eq!(Namespace::for_tag("div"), Namespace::Html);
+        assert_eq!(Namespace::for_tag("svgs"), Namespace::Html);
+    }
+}
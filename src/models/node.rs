@@ -1,12 +1,45 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc};
 
-use crate::prelude::*;
+use crate::{models::directive::parse_name, prelude::*};
 
-/// Generic Node enum that can represent either a Text, Element, or Block node.
-#[derive(PartialEq, Clone)]
+/// Generic Node enum that can represent either a Text, Element, Raw, Comment, Slot, Shared or
+/// Directive node.
+///
+/// Raw nodes hold trusted markup that is emitted verbatim, without escaping.
+/// Comment nodes are rendered as HTML comments.
+/// Slot nodes are named holes, written `@slot(name)`, that are filled in later.
+/// Translation nodes, written `@t("key")` with the `i18n` feature, are looked up
+/// when rendering, see [`crate::i18n`].
+/// Shared nodes reference an element that many trees can hold without cloning it,
+/// they are rendered, traversed and queried like the element itself.
+/// Directive nodes, e.g. `@block(content) { ... }`, are template instructions resolved
+/// by a [`crate::templates::Registry`], see [`Directive`].
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Node<'a> {
     Text(Text<'a>),
     Element(Element<'a>),
+    Raw(Cow<'a, str>),
+    Comment(Cow<'a, str>),
+    Slot(Cow<'a, str>),
+    Shared(Arc<Element<'a>>),
+    Directive(Directive<'a>),
+    #[cfg(feature = "i18n")]
+    Translation(crate::i18n::Translation<'a>),
+}
+
+/// When a script loaded with [`Node::script_src`] runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ScriptLoading {
+    /// As soon as it loads, pausing parsing until then
+    Blocking,
+    /// Once the document is parsed, in document order
+    #[default]
+    Defer,
+    /// As soon as it loads, without pausing parsing
+    Async,
+    /// As a JavaScript module, which is deferred
+    Module,
 }
 
 impl std::fmt::Debug for Node<'_> {
@@ -14,6 +47,13 @@ impl std::fmt::Debug for Node<'_> {
         match self {
             Node::Text(text) => write!(f, "{text:?}"),
             Node::Element(element) => write!(f, "{element:?}"),
+            Node::Raw(raw) => write!(f, "raw!({raw:?})"),
+            Node::Comment(comment) => write!(f, "comment!({comment:?})"),
+            Node::Slot(name) => write!(f, "@slot({name})"),
+            Node::Shared(element) => write!(f, "shared!({element:?})"),
+            Node::Directive(directive) => write!(f, "{directive:?}"),
+            #[cfg(feature = "i18n")]
+            Node::Translation(translation) => write!(f, "t!({translation:?})"),
         }
     }
 }
@@ -29,6 +69,57 @@ impl<'a> Node<'a> {
         matches!(self, Node::Element(_))
     }
 
+    #[must_use]
+    pub const fn is_raw(&self) -> bool {
+        matches!(self, Node::Raw(_))
+    }
+
+    #[must_use]
+    pub const fn is_comment(&self) -> bool {
+        matches!(self, Node::Comment(_))
+    }
+
+    #[must_use]
+    pub const fn is_slot(&self) -> bool {
+        matches!(self, Node::Slot(_))
+    }
+
+    #[must_use]
+    pub const fn is_shared(&self) -> bool {
+        matches!(self, Node::Shared(_))
+    }
+
+    /// Returns the element of an element or shared node
+    #[must_use]
+    pub fn as_element(&self) -> Option<&Element<'a>> {
+        match self {
+            Node::Element(element) => Some(element),
+            Node::Shared(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Returns the element of an element or shared node mutably
+    ///
+    /// A shared element is copied into this node first if other trees still hold it.
+    pub fn as_element_mut(&mut self) -> Option<&mut Element<'a>> {
+        match self {
+            Node::Element(element) => Some(element),
+            Node::Shared(element) => Some(Arc::make_mut(element)),
+            _ => None,
+        }
+    }
+
+    /// Turns a shared node into an owned element node, cloning the element only if
+    /// other trees still hold it
+    #[must_use]
+    pub fn unshare(self) -> Self {
+        match self {
+            Node::Shared(element) => Node::Element(Arc::unwrap_or_clone(element)),
+            node => node,
+        }
+    }
+
     #[must_use]
     pub const fn text_const(value: Cow<'a, str>) -> Self {
         Node::Text(Text::new_const(value))
@@ -38,6 +129,79 @@ impl<'a> Node<'a> {
         Self::text_const(value.into())
     }
 
+    /// Creates a raw node from trusted markup, which is never escaped.
+    #[must_use]
+    pub fn raw(value: impl Into<Cow<'a, str>>) -> Self {
+        Node::Raw(value.into())
+    }
+
+    /// Creates a `script` element running trusted code, escaped so it cannot close
+    /// the element early, see [`crate::render::escape_script`]
+    #[must_use]
+    pub fn script(code: impl Into<Cow<'a, str>>) -> Self {
+        let code = code.into();
+        let code = match crate::render::escape_script(&code) {
+            Cow::Borrowed(_) => code,
+            Cow::Owned(escaped) => Cow::Owned(escaped),
+        };
+        element("script").with_child(Node::Raw(code)).into()
+    }
+
+    /// Creates a `script` element loading the code at the URL, run as set by `loading`
+    #[must_use]
+    pub fn script_src(src: impl Into<Cow<'a, str>>, loading: ScriptLoading) -> Self {
+        let script = element("script").with_key_value("src", src);
+        match loading {
+            ScriptLoading::Blocking => script,
+            ScriptLoading::Defer => script.with_attribute(Attribute::boolean("defer")),
+            ScriptLoading::Async => script.with_attribute(Attribute::boolean("async")),
+            ScriptLoading::Module => script.with_key_value("type", "module"),
+        }
+        .into()
+    }
+
+    /// Creates a `script type="application/ld+json"` element holding the value as
+    /// structured data, escaped so its strings cannot close the script
+    ///
+    /// # Errors
+    /// Errors if the value cannot be serialized, see [`crate::value::to_value`]
+    #[cfg(feature = "serde")]
+    pub fn json_ld<T: serde::Serialize + ?Sized>(
+        value: &T,
+    ) -> Result<Node<'static>, crate::value::SerializeError> {
+        let json = crate::value::to_value(value)?.to_json();
+        let json = crate::render::escape_script_json(&json).into_owned();
+        Ok(element("script")
+            .with_key_value("type", "application/ld+json")
+            .with_child(Node::Raw(json.into()))
+            .into())
+    }
+
+    /// Creates a comment node, rendered as `<!-- value -->`.
+    #[must_use]
+    pub fn comment(value: impl Into<Cow<'a, str>>) -> Self {
+        Node::Comment(value.into())
+    }
+
+    /// Creates a named slot, to be replaced with `Block::fill_slots`.
+    #[must_use]
+    pub fn slot(name: impl Into<Cow<'a, str>>) -> Self {
+        Node::Slot(name.into())
+    }
+
+    /// Creates a node translated when rendering, see [`crate::i18n::Translator`]
+    #[cfg(feature = "i18n")]
+    #[must_use]
+    pub fn translation(translation: crate::i18n::Translation<'a>) -> Self {
+        Node::Translation(translation)
+    }
+
+    /// Creates a node referencing a shared element, see [`Node::Shared`]
+    #[must_use]
+    pub fn shared(element: impl Into<Arc<Element<'a>>>) -> Self {
+        Node::Shared(element.into())
+    }
+
     #[must_use]
     pub fn element(element: impl Into<Element<'a>>) -> Self {
         Self::element_const(element.into())
@@ -48,13 +212,19 @@ impl<'a> Node<'a> {
     }
 
     /// Check if the node is empty,
-    /// i.e., if it is a Text node with empty content,
-    /// an Element node with no attributes and no children,
+    /// i.e., if it is a Text, Raw or Comment node with empty content,
+    /// an Element node with no attributes and no children.
+    /// Slots, directives and translations are never empty, since they stand for content.
     #[must_use]
     pub fn is_empty(&self) -> bool {
         match self {
             Node::Text(text) => text.content.is_empty(),
             Node::Element(element) => element.is_empty(),
+            Node::Shared(element) => element.is_empty(),
+            Node::Raw(raw) | Node::Comment(raw) => raw.is_empty(),
+            Node::Slot(_) | Node::Directive(_) => false,
+            #[cfg(feature = "i18n")]
+            Node::Translation(_) => false,
         }
     }
 
@@ -62,6 +232,53 @@ impl<'a> Node<'a> {
     pub fn into_node(self) -> Self {
         self
     }
+
+    /// Copies all borrowed data, see [`Element::into_owned`]
+    ///
+    /// Shared elements are copied into a new shared element.
+    #[must_use]
+    pub fn into_owned(self) -> Node<'static> {
+        match self {
+            Node::Text(text) => Node::Text(text.into_owned()),
+            Node::Element(element) => Node::Element(element.into_owned()),
+            Node::Shared(element) => {
+                Node::Shared(Arc::from(Arc::unwrap_or_clone(element).into_owned()))
+            }
+            Node::Raw(raw) => Node::Raw(raw.into_owned().into()),
+            Node::Comment(comment) => Node::Comment(comment.into_owned().into()),
+            Node::Slot(name) => Node::Slot(name.into_owned().into()),
+            Node::Directive(directive) => Node::Directive(directive.into_owned()),
+            #[cfg(feature = "i18n")]
+            Node::Translation(translation) => Node::Translation(translation.into_owned()),
+        }
+    }
+
+    /// Attaches a stable key to the node, stored as a `data-key` attribute.
+    ///
+    /// Keys identify nodes produced by loops across renders. Only element and
+    /// shared nodes can carry a key, shared nodes being turned into owned elements,
+    /// see [`Node::unshare`].
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if the node is not an element, as text, comments and the
+    /// other nodes have no attributes to hold the key. Release builds return them
+    /// unchanged.
+    #[must_use]
+    pub fn with_key(self, key: impl Into<Cow<'a, str>>) -> Self {
+        match self.unshare() {
+            Node::Element(element) => {
+                Node::Element(element.with_attribute(Attribute::new(Self::KEY_ATTRIBUTE, key)))
+            }
+            node => {
+                debug_assert!(false, "only elements can carry a key, got {node:?}");
+                node
+            }
+        }
+    }
+
+    /// Name of the attribute used to store node keys
+    pub const KEY_ATTRIBUTE: &'static str = "data-key";
 }
 
 impl From<String> for Node<'_> {
@@ -88,8 +305,74 @@ impl<'a> From<Element<'a>> for Node<'a> {
     }
 }
 
+impl<'a> From<Arc<Element<'a>>> for Node<'a> {
+    fn from(value: Arc<Element<'a>>) -> Self {
+        Node::Shared(value)
+    }
+}
+
+// Used by `rstml!` for `{ expr }` children, which may be a node or an iterator of
+// nodes. The two cases would overlap as trait impls, so the one applying is picked
+// by method resolution: `InlineNode` on `Inline<T>` is found before autoref reaches
+// `InlineNodes` on `&Inline<T>`.
+#[doc(hidden)]
+pub struct Inline<T>(std::cell::Cell<Option<T>>);
+
+impl<T> Inline<T> {
+    pub fn new(value: T) -> Self {
+        Inline(std::cell::Cell::new(Some(value)))
+    }
+
+    fn take(&self) -> T {
+        self.0.take().expect("inline children are expanded once")
+    }
+}
+
+#[doc(hidden)]
+pub trait InlineNode<'a> {
+    fn expand_nodes(&self) -> std::iter::Once<Node<'a>>;
+}
+
+impl<'a, T: Into<Node<'a>>> InlineNode<'a> for Inline<T> {
+    fn expand_nodes(&self) -> std::iter::Once<Node<'a>> {
+        std::iter::once(self.take().into())
+    }
+}
+
+#[doc(hidden)]
+pub trait InlineNodes<'a> {
+    type Nodes: Iterator<Item = Node<'a>>;
+    fn expand_nodes(&self) -> Self::Nodes;
+}
+
+impl<'a, I> InlineNodes<'a> for &Inline<I>
+where
+    I: IntoIterator<Item: Into<Node<'a>>>,
+{
+    type Nodes = std::iter::Map<I::IntoIter, fn(I::Item) -> Node<'a>>;
+
+    fn expand_nodes(&self) -> Self::Nodes {
+        self.take().into_iter().map(Into::into)
+    }
+}
+
 impl<'a> RSTMLParse<'a> for Node<'a> {
     fn parse_no_whitespace(input: &'a str) -> ParseResult<'a, Self> {
+        #[cfg(feature = "i18n")]
+        if let Some(rest) = input.strip_prefix("@t")
+            && rest.trim_start().starts_with('(')
+        {
+            let (rest, translation) = crate::i18n::Translation::parse_args(rest)?;
+            return Ok((rest, Node::Translation(translation)));
+        }
+        if let Some(rest) = input.strip_prefix("@slot") {
+            let (rest, name) = parse_name(rest, "@slot(content)")?;
+            return Ok((rest, Node::Slot(name.into())));
+        }
+        if let Some(result) = Directive::parse(input) {
+            let (rest, directive) = result?;
+            return Ok((rest, Node::Directive(directive)));
+        }
         if let Ok((rest, text)) = Text::parse_ignoring_comments(input) {
             return Ok((rest, Node::Text(text)));
         }
@@ -102,3 +385,25 @@ impl<'a> RSTMLParse<'a> for Node<'a> {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn test_with_key() {
+        let keyed = Node::from(element("li")).with_key("a");
+        assert_eq!(keyed.render(), r#"<li data-key="a"></li>"#);
+        let shared = Node::Shared(Arc::new(element("li"))).with_key("b");
+        assert!(matches!(&shared, Node::Element(li) if li.attr("data-key") == Some("b")));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "only elements can carry a key")]
+    fn test_with_key_on_text() {
+        let _ = Node::text("a").with_key("a");
+    }
+}
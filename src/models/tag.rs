@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use crate::{error::ParseResult, parse::RSTMLParse};
 use pastey::paste;
 
@@ -5,9 +7,9 @@ use pastey::paste;
 //
 // RSTML tags are structured like 'lower-camel-case' strings.
 // They can contain alphanumeric characters and hyphens.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Tag<'a> {
-    pub(crate) name: &'a str,
+    pub(crate) name: Cow<'a, str>,
 }
 
 macro_rules! tag {
@@ -26,9 +28,33 @@ impl<'a> From<&'a str> for Tag<'a> {
     }
 }
 
-impl<'a> From<Tag<'a>> for &'a str {
-    fn from(tag: Tag<'a>) -> Self {
-        tag.as_str()
+impl From<String> for Tag<'_> {
+    fn from(name: String) -> Self {
+        Tag {
+            name: Cow::Owned(name),
+        }
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Tag<'a> {
+    fn from(name: Cow<'a, str>) -> Self {
+        Tag { name }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Tag<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name)
+    }
+}
+
+// Names are owned, so documents deserialize from any source, e.g. `Document<'static>`
+// from a `String`
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Tag<'_> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Tag::from)
     }
 }
 
@@ -40,12 +66,25 @@ impl std::fmt::Display for Tag<'_> {
 
 impl<'a> Tag<'a> {
     pub(crate) const fn new(name: &'a str) -> Self {
-        Tag { name }
+        Tag {
+            name: Cow::Borrowed(name),
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        match &self.name {
+            Cow::Borrowed(name) => name,
+            Cow::Owned(name) => name.as_str(),
+        }
     }
 
+    /// Copies a borrowed name, detaching the tag from its source
     #[must_use]
-    pub const fn as_str(&self) -> &'a str {
-        self.name
+    pub fn into_owned(self) -> Tag<'static> {
+        Tag {
+            name: Cow::Owned(self.name.into_owned()),
+        }
     }
 
     tag!(div span p a img ul li table tr td th header footer nav section article main aside form input button label select option textarea style);
@@ -60,14 +99,19 @@ fn split_exclusive_once(input: &str, predicate: impl Fn(char) -> bool) -> Option
     None
 }
 
+// Parses a tag name, borrowed from the input, e.g. for attribute keys
+pub(crate) fn parse_name(input: &str) -> ParseResult<'_, &str> {
+    let (name, rest) =
+        split_exclusive_once(input, |c| !(c.is_alphanumeric() || c == '-')).unwrap_or((input, ""));
+    if name.is_empty() {
+        return Err(crate::error::ParseError::EmptyInput);
+    }
+    Ok((rest, name))
+}
+
 impl<'a> RSTMLParse<'a> for Tag<'a> {
     fn parse_no_whitespace(input: &'a str) -> ParseResult<'a, Self> {
-        let (name, rest) = split_exclusive_once(input, |c| !(c.is_alphanumeric() || c == '-'))
-            .unwrap_or((input, ""));
-        if name.is_empty() {
-            return Err(crate::error::ParseError::EmptyInput);
-        }
-        Ok((rest, Tag::new(name)))
+        parse_name(input).map(|(rest, name)| (rest, Tag::new(name)))
     }
 }
 
@@ -103,4 +147,29 @@ mod tests {
             ".class#id{content}",
         );
     }
+
+    #[test]
+    fn test_tag_into_owned() {
+        let name = String::from("my-widget");
+        let owned = Tag::from(name.as_str()).into_owned();
+        drop(name);
+        assert_eq!(owned.as_str(), "my-widget");
+        assert_eq!(Tag::from(String::from("div")), Tag::DIV);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deserialize_owns_names() {
+        use std::borrow::Cow;
+        // Each distinct name is owned by its tag and freed with it, not kept globally
+        for i in 0..10_000 {
+            let tag: Tag<'static> = serde_json::from_str(&format!(r#""x-tag-{i}""#)).unwrap();
+            assert!(matches!(&tag.name, Cow::Owned(name) if *name == format!("x-tag-{i}")));
+        }
+        let element: crate::prelude::Element<'static> = serde_json::from_str(
+            &serde_json::to_string(&crate::prelude::element("my-widget")).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(element.name.name, Cow::Owned(_)));
+    }
 }
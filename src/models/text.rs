@@ -5,7 +5,9 @@ use crate::{error::ParseResult, parse::RSTMLParse};
 // Represents plain text content within RSTML
 //
 // Text content is any sequence of characters that is surrounded by quotes
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Text<'a> {
     pub content: Cow<'a, str>,
 }
@@ -18,6 +20,11 @@ impl<'a> Text<'a> {
     pub fn new(content: impl Into<Cow<'a, str>>) -> Self {
         Self::new_const(content.into())
     }
+
+    #[must_use]
+    pub fn into_owned(self) -> Text<'static> {
+        Text::new(self.content.into_owned())
+    }
 }
 
 impl From<String> for Text<'_> {
@@ -0,0 +1,136 @@
+use rayon::prelude::*;
+
+use crate::{
+    prelude::*,
+    render::{close_tag, is_raw_text, open_tag, render_element, render_node},
+};
+
+/// Fewest siblings rendered in parallel, smaller groups render on the current thread
+pub const MIN_PARALLEL_SIBLINGS: usize = 64;
+
+// Fewest siblings rendered into one buffer, so tiny rows are not split per thread
+const MIN_CHUNK: usize = 16;
+
+fn render_nodes(nodes: &[Node], parent: Namespace, out: &mut String) -> std::fmt::Result
+where
+    for<'x> Node<'x>: Sync,
+{
+    if nodes.len() < MIN_PARALLEL_SIBLINGS {
+        return nodes
+            .iter()
+            .try_for_each(|node| render_node_par(node, parent, out));
+    }
+    let chunk = nodes
+        .len()
+        .div_ceil(rayon::current_num_threads() * 4)
+        .max(MIN_CHUNK);
+    let buffers = nodes
+        .par_chunks(chunk)
+        .map(|chunk| {
+            let mut buffer = String::new();
+            for node in chunk {
+                render_node_par(node, parent, &mut buffer)?;
+            }
+            Ok(buffer)
+        })
+        .collect::<Result<Vec<_>, std::fmt::Error>>()?;
+    buffers.iter().for_each(|buffer| out.push_str(buffer));
+    Ok(())
+}
+
+fn render_node_par(node: &Node, parent: Namespace, out: &mut String) -> std::fmt::Result
+where
+    for<'x> Node<'x>: Sync,
+{
+    match node.as_element() {
+        Some(element) => render_element_par(element, parent, out),
+        None => render_node(node, parent, None, out),
+    }
+}
+
+fn render_element_par(element: &Element, parent: Namespace, out: &mut String) -> std::fmt::Result
+where
+    for<'x> Node<'x>: Sync,
+{
+    // Text holding code is not escaped like other text, see `raw_text`
+    if is_raw_text(element) {
+        return render_element(element, parent, None, out);
+    }
+    if open_tag(element, parent, out)? {
+        render_nodes(&element.children, element.namespace, out)?;
+        close_tag(element.name.as_str(), out)?;
+    }
+    Ok(())
+}
+
+impl Element<'_> {
+    /// Renders the element like [`Render::render`], rendering each group of at
+    /// least [`MIN_PARALLEL_SIBLINGS`] siblings in parallel
+    ///
+    /// Siblings are split into chunks rendered into their own buffers, which are
+    /// joined in order.
+    #[must_use]
+    pub fn render_par(&self) -> String {
+        let mut out = String::new();
+        render_element_par(self, Namespace::Html, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+}
+
+impl Block<'_> {
+    /// Renders the document in parallel, see [`Element::render_par`]
+    #[must_use]
+    pub fn render_par(&self) -> String {
+        let mut out = String::new();
+        render_nodes(&self.children, Namespace::Html, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_render_par() {
+        let rows = (0..1_000).map(|i| {
+            element("tr")
+                .with_child(element("td").with_child(i.to_string()))
+                .with_child(element("td").with_child(format!("Item <{i}>")))
+        });
+        let table = element("table").with_children(rows);
+        let document = Block::new()
+            .with_child(element("h1").with_child("Report"))
+            .with_child(element("svg").with_child(element("circle")))
+            .with_child(table.clone());
+        assert_eq!(document.render_par(), document.render());
+        assert_eq!(table.render_par(), table.render());
+    }
+
+    #[cfg(feature = "events")]
+    #[test]
+    fn test_render_par_with_handlers() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use rayon::prelude::*;
+
+        let clicks = AtomicUsize::new(0);
+        let rows = (0..1_000).map(|i| {
+            element("li")
+                .with_handler(EventHandler::typed("click", |row: &usize| {
+                    clicks.fetch_add(*row, Ordering::Relaxed);
+                }))
+                .with_child(i.to_string())
+        });
+        let list = element("ul").with_children(rows);
+        assert_eq!(list.render_par(), list.render());
+        list.children.par_iter().enumerate().for_each(|(i, row)| {
+            for handler in &row.as_element().unwrap().handlers {
+                handler.call(&i);
+            }
+        });
+        assert_eq!(clicks.load(Ordering::Relaxed), (0..1_000).sum::<usize>());
+    }
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use crate::prelude::*;
 use crate::util::nested;
 
@@ -37,8 +39,8 @@ impl<'a> RSTMLParse<'a> for Comment<'a> {
 
 #[cfg(test)]
 mod tests {
-    use super::{Comment, RSTMLParse};
-    use crate::test_util::*;
+    use super::{Comment, RSTMLParse, parse_document};
+    use crate::{error::line_column, test_util::*};
 
     #[test]
     fn test_comment_parse() {
@@ -59,6 +61,50 @@ mod tests {
             "",
         );
     }
+
+    #[test]
+    fn test_parse_document_error_location() {
+        let input = "div {\n    p { \"ok\" }\n    span { .class = }\n}";
+        let error = parse_document(input).unwrap_err();
+        let offset = error.offset(input).unwrap();
+        assert_eq!(line_column(input, offset), (3, 12));
+        assert!(parse_document("div { \"ok\" } // done").is_ok());
+    }
+}
+
+/// Options for checking parsed RSTML, see [`crate::diagnostics::diagnostics_with`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Attributes accepted on every element by the unknown attribute lint, where a
+    /// trailing `*` matches any suffix, e.g. `x-*` for a framework's attributes
+    pub allowed_attributes: Vec<String>,
+}
+
+impl ParseOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        ParseOptions::default()
+    }
+
+    pub fn add_allowed_attribute(&mut self, pattern: impl Into<String>) {
+        self.allowed_attributes.push(pattern.into());
+    }
+    #[must_use]
+    pub fn with_allowed_attribute(mut self, pattern: impl Into<String>) -> Self {
+        self.add_allowed_attribute(pattern);
+        self
+    }
+
+    /// Returns true if the attribute matches one of the allowed patterns
+    #[must_use]
+    pub fn is_allowed_attribute(&self, key: &str) -> bool {
+        self.allowed_attributes
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => key == pattern,
+            })
+    }
 }
 
 /// Trait for parsing RSTML items from a string input
@@ -180,3 +226,52 @@ pub trait RSTMLParseExt<'a>: RSTMLParse<'a> {
 }
 
 impl<'a, T: RSTMLParse<'a>> RSTMLParseExt<'a> for T {}
+
+// Context of the error for input left inside an element after its children
+pub(crate) const TRAILING_CONTENT: &str = "Unexpected content after element children";
+
+/// Parses a whole document, erroring if any input is left after the last node
+///
+/// The error points at the innermost node that failed to parse, use
+/// [`ParseError::offset`] and [`crate::error::line_column`] to locate it.
+///
+/// # Errors
+/// Errors if the input is not a valid RSTML document
+pub fn parse_document(input: &str) -> Result<Block<'_>, ParseError<'_>> {
+    let (rest, document) = Block::parse_ignoring_comments(input)?;
+    let rest = consume_comments(rest);
+    if rest.is_empty() {
+        return Ok(document);
+    }
+    Err(node_error(rest).first_line())
+}
+
+// The most specific error for input that does not start with a valid node
+pub(crate) fn node_error(input: &str) -> ParseError<'_> {
+    let fallback =
+        |context: &str| ParseError::invalid_input(input, Some(context.to_string().into()));
+    if input.starts_with(['.', '#']) {
+        return match Attribute::parse_no_whitespace(input) {
+            Err(error) if error.offset(input).is_some() => error,
+            Err(_) => fallback("Invalid attribute"),
+            Ok(_) => fallback("Attributes must come before children"),
+        };
+    }
+    if input.starts_with('@') {
+        return match Node::parse_no_whitespace(input) {
+            Err(error) if error.offset(input).is_some() => error,
+            _ => fallback("Invalid slot or directive"),
+        };
+    }
+    if input.starts_with('"') {
+        return fallback("Unterminated string");
+    }
+    match Element::parse_no_whitespace(input) {
+        Err(ParseError::InvalidInput {
+            found: Cow::Borrowed(found),
+            context: Some(context),
+        }) if context == TRAILING_CONTENT => node_error(consume_comments(found)),
+        Err(error) if error.offset(input).is_some() => error,
+        _ => fallback("Expected an element, text or attribute"),
+    }
+}
@@ -0,0 +1,337 @@
+use std::{borrow::Cow, sync::Arc};
+
+use crate::{
+    path::{children_at_mut, node_at_mut},
+    prelude::*,
+};
+
+/// A single change to a document, addressed by the [`NodePath`] of a node
+///
+/// Patches are applied in order, so each path refers to the tree as left by the
+/// patches before it. A list produced by [`Block::diff`] can be serialized with the
+/// `serde` feature and applied to a mirrored tree with [`Block::apply`].
+///
+/// Shared elements on a patched path are copied into the tree first, other trees
+/// holding them are not changed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Patch<'a> {
+    /// Replaces the node at the path
+    Replace { path: NodePath, node: Node<'a> },
+    /// Inserts a node so that it ends up at the path, the index may be one past the last child
+    Insert { path: NodePath, node: Node<'a> },
+    /// Removes the node at the path
+    Remove { path: NodePath },
+    /// Sets an attribute on the element at the path, replacing any existing value
+    SetAttribute {
+        path: NodePath,
+        key: Cow<'a, str>,
+        value: Cow<'a, str>,
+    },
+    /// Removes an attribute from the element at the path
+    RemoveAttribute { path: NodePath, key: Cow<'a, str> },
+    /// Replaces the content of the text node at the path
+    SetText { path: NodePath, text: Cow<'a, str> },
+}
+
+impl Patch<'_> {
+    #[must_use]
+    pub fn path(&self) -> &NodePath {
+        match self {
+            Patch::Replace { path, .. }
+            | Patch::Insert { path, .. }
+            | Patch::Remove { path }
+            | Patch::SetAttribute { path, .. }
+            | Patch::RemoveAttribute { path, .. }
+            | Patch::SetText { path, .. } => path,
+        }
+    }
+}
+
+/// Error returned when a patch does not fit the tree it is applied to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// No node exists at the path
+    InvalidPath(NodePath),
+    /// The patch changes attributes, but the node at the path is not an element
+    NotAnElement(NodePath),
+    /// The patch changes text, but the node at the path is not a text node
+    NotText(NodePath),
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatchError::InvalidPath(path) => write!(f, "No node at path {path}"),
+            PatchError::NotAnElement(path) => write!(f, "Node at path {path} is not an element"),
+            PatchError::NotText(path) => write!(f, "Node at path {path} is not text"),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+fn element_mut<'d, 'a>(
+    nodes: &'d mut Vec<Node<'a>>,
+    path: &NodePath,
+) -> Result<&'d mut Element<'a>, PatchError> {
+    node_at_mut(nodes, path.indices())
+        .ok_or_else(|| PatchError::InvalidPath(path.clone()))?
+        .as_element_mut()
+        .ok_or_else(|| PatchError::NotAnElement(path.clone()))
+}
+
+fn apply_patch<'a>(nodes: &mut Vec<Node<'a>>, patch: &Patch<'a>) -> Result<(), PatchError> {
+    let path = patch.path();
+    let invalid = || PatchError::InvalidPath(path.clone());
+    match patch {
+        Patch::Replace { node, .. } => {
+            *node_at_mut(nodes, path.indices()).ok_or_else(invalid)? = node.clone();
+        }
+        Patch::Insert { node, .. } => {
+            let (last, parent) = path.indices().split_last().ok_or_else(invalid)?;
+            let children = children_at_mut(nodes, parent).ok_or_else(invalid)?;
+            if *last > children.len() {
+                return Err(invalid());
+            }
+            children.insert(*last, node.clone());
+        }
+        Patch::Remove { .. } => {
+            let (last, parent) = path.indices().split_last().ok_or_else(invalid)?;
+            let children = children_at_mut(nodes, parent).ok_or_else(invalid)?;
+            if *last >= children.len() {
+                return Err(invalid());
+            }
+            children.remove(*last);
+        }
+        Patch::SetAttribute { key, value, .. } => {
+            element_mut(nodes, path)?.set_attr(key.clone(), value.clone());
+        }
+        Patch::RemoveAttribute { key, .. } => {
+            element_mut(nodes, path)?.remove_attr(key);
+        }
+        Patch::SetText { text, .. } => match node_at_mut(nodes, path.indices()) {
+            Some(Node::Text(node)) => node.content = text.clone(),
+            Some(_) => return Err(PatchError::NotText(path.clone())),
+            None => return Err(invalid()),
+        },
+    }
+    Ok(())
+}
+
+// Diffs two lists of siblings by position, pushing patches that turn `old` into `new`
+fn diff_nodes<'n>(
+    old: &[Node],
+    new: &[Node<'n>],
+    path: &mut NodePath,
+    patches: &mut Vec<Patch<'n>>,
+) {
+    for (i, (old, new)) in old.iter().zip(new).enumerate() {
+        path.push(i);
+        match (old, new) {
+            (Node::Text(old), Node::Text(new)) if old.content != new.content => {
+                patches.push(Patch::SetText {
+                    path: path.clone(),
+                    text: new.content.clone(),
+                });
+            }
+            (Node::Text(_), Node::Text(_)) => {}
+            (Node::Shared(old), Node::Shared(new)) if Arc::ptr_eq(old, new) => {}
+            _ => match (old.as_element(), new.as_element()) {
+                (Some(old), Some(new))
+                    if old.name == new.name && old.namespace == new.namespace =>
+                {
+                    diff_attributes(old, new, path, patches);
+                    diff_nodes(&old.children, &new.children, path, patches);
+                }
+                _ if old == new => {}
+                _ => patches.push(Patch::Replace {
+                    path: path.clone(),
+                    node: new.clone(),
+                }),
+            },
+        }
+        path.pop();
+    }
+    // Remove from the end first so the remaining indices stay valid
+    for i in (new.len()..old.len()).rev() {
+        path.push(i);
+        patches.push(Patch::Remove { path: path.clone() });
+        path.pop();
+    }
+    for (i, node) in new.iter().enumerate().skip(old.len()) {
+        path.push(i);
+        patches.push(Patch::Insert {
+            path: path.clone(),
+            node: node.clone(),
+        });
+        path.pop();
+    }
+}
+
+fn diff_attributes<'n>(
+    old: &Element,
+    new: &Element<'n>,
+    path: &NodePath,
+    patches: &mut Vec<Patch<'n>>,
+) {
+    for attribute in &old.attributes {
+        if !new.has_attr(&attribute.key) {
+            patches.push(Patch::RemoveAttribute {
+                path: path.clone(),
+                key: Cow::Owned(attribute.key.to_string()),
+            });
+        }
+    }
+    for attribute in &new.attributes {
+        if old.attr(&attribute.key) != Some(attribute.value.as_ref()) {
+            patches.push(Patch::SetAttribute {
+                path: path.clone(),
+                key: attribute.key.clone(),
+                value: attribute.value.clone(),
+            });
+        }
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Computes the patches that turn this document into `new`
+    ///
+    /// Children are compared by position, elements with the same tag are updated
+    /// in place and anything else is replaced.
+    #[must_use]
+    pub fn diff<'n>(&self, new: &Block<'n>) -> Vec<Patch<'n>> {
+        let mut patches = Vec::new();
+        diff_nodes(
+            &self.children,
+            &new.children,
+            &mut NodePath::root(),
+            &mut patches,
+        );
+        patches
+    }
+
+    /// Applies the patches in order
+    ///
+    /// # Errors
+    /// Errors if a patch's path does not resolve to a suitable node. Patches before
+    /// the failing one remain applied.
+    pub fn apply(&mut self, patches: &[Patch<'a>]) -> Result<(), PatchError> {
+        patches
+            .iter()
+            .try_for_each(|patch| apply_patch(&mut self.children, patch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{Patch, PatchError};
+    use crate::prelude::*;
+
+    fn old() -> Document<'static> {
+        Document::new().with_child(
+            element("ul")
+                .with_attribute(Attribute::class("list"))
+                .with_child(element("li").with_child("one"))
+                .with_child(element("li").with_child("two"))
+                .with_child(element("li").with_child("three")),
+        )
+    }
+
+    fn new() -> Document<'static> {
+        Document::new()
+            .with_child(
+                element("ul")
+                    .with_attribute(Attribute::id("items"))
+                    .with_child(element("li").with_child("one"))
+                    .with_child(element("p").with_child("2")),
+            )
+            .with_child("done")
+    }
+
+    #[test]
+    fn test_diff_and_apply() {
+        let mut document = old();
+        let patches = document.diff(&new());
+        assert_eq!(
+            patches,
+            vec![
+                Patch::RemoveAttribute {
+                    path: [0].into(),
+                    key: "class".into()
+                },
+                Patch::SetAttribute {
+                    path: [0].into(),
+                    key: "id".into(),
+                    value: "items".into()
+                },
+                Patch::Replace {
+                    path: [0, 1].into(),
+                    node: element("p").with_child("2").into()
+                },
+                Patch::Remove {
+                    path: [0, 2].into()
+                },
+                Patch::Insert {
+                    path: [1].into(),
+                    node: "done".into()
+                },
+            ]
+        );
+        document.apply(&patches).unwrap();
+        assert_eq!(document, new());
+        assert!(document.diff(&new()).is_empty());
+    }
+
+    #[test]
+    fn test_apply_validates_paths() {
+        let mut document = old();
+        let set_text = |path: NodePath| Patch::SetText {
+            path,
+            text: "1".into(),
+        };
+        assert_eq!(document.apply(&[set_text([0, 0, 0].into())]), Ok(()));
+        assert_eq!(
+            document.apply(&[set_text([0, 5, 0].into())]),
+            Err(PatchError::InvalidPath([0, 5, 0].into()))
+        );
+        assert_eq!(
+            document.apply(&[set_text([0, 0].into())]),
+            Err(PatchError::NotText([0, 0].into()))
+        );
+        assert_eq!(
+            document.apply(&[Patch::Insert {
+                path: [3].into(),
+                node: "late".into()
+            }]),
+            Err(PatchError::InvalidPath([3].into()))
+        );
+    }
+
+    #[test]
+    fn test_apply_copies_shared() {
+        let nav = Arc::from(element("nav").with_child("Home"));
+        let mut document = Document::new().with_child(Node::shared(nav.clone()));
+        let patches = [Patch::SetText {
+            path: [0, 0].into(),
+            text: "Start".into(),
+        }];
+        document.apply(&patches).unwrap();
+        assert_eq!(nav.text_content(), "Home");
+        assert_eq!(document.text_content(), "Start");
+        assert!(document.diff(&document.clone()).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_patch_serde() {
+        let patches = old().diff(&new());
+        let json = serde_json::to_string(&patches).unwrap();
+        let restored: Vec<Patch<'static>> = serde_json::from_str(&json).unwrap();
+        let mut document = old();
+        document.apply(&restored).unwrap();
+        assert_eq!(document, new());
+    }
+}
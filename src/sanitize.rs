@@ -0,0 +1,595 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crate::{convert::from_html, prelude::*};
+
+/// Tags allowed by [`Policy::default`]
+pub const DEFAULT_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "acronym",
+    "area",
+    "article",
+    "aside",
+    "b",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "br",
+    "caption",
+    "center",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "map",
+    "mark",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "rp",
+    "rt",
+    "rtc",
+    "ruby",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strike",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "tt",
+    "u",
+    "ul",
+    "var",
+    "wbr",
+];
+
+/// Attributes allowed on specific tags by [`Policy::default`]
+pub const DEFAULT_TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href", "hreflang"]),
+    ("bdo", &["dir"]),
+    ("blockquote", &["cite"]),
+    ("col", &["align", "char", "charoff", "span"]),
+    ("colgroup", &["align", "char", "charoff", "span"]),
+    ("del", &["cite", "datetime"]),
+    ("hr", &["align", "size", "width"]),
+    ("img", &["align", "alt", "height", "src", "width"]),
+    ("ins", &["cite", "datetime"]),
+    ("ol", &["start"]),
+    ("q", &["cite"]),
+    ("table", &["align", "char", "charoff", "summary"]),
+    ("tbody", &["align", "char", "charoff"]),
+    (
+        "td",
+        &["align", "char", "charoff", "colspan", "headers", "rowspan"],
+    ),
+    ("tfoot", &["align", "char", "charoff"]),
+    (
+        "th",
+        &[
+            "align", "char", "charoff", "colspan", "headers", "rowspan", "scope",
+        ],
+    ),
+    ("thead", &["align", "char", "charoff"]),
+    ("tr", &["align", "char", "charoff"]),
+];
+
+/// URL schemes allowed by [`Policy::default`]
+pub const DEFAULT_URL_SCHEMES: &[&str] = &[
+    "bitcoin",
+    "ftp",
+    "ftps",
+    "geo",
+    "http",
+    "https",
+    "im",
+    "irc",
+    "ircs",
+    "magnet",
+    "mailto",
+    "mms",
+    "mx",
+    "news",
+    "nntp",
+    "openpgp4fpr",
+    "sip",
+    "sms",
+    "smsto",
+    "ssh",
+    "tel",
+    "url",
+    "webcal",
+    "wtai",
+    "xmpp",
+];
+
+/// Attributes whose values are URLs, checked against [`Policy::url_schemes`]
+pub const URL_ATTRIBUTES: &[&str] = &[
+    "action",
+    "cite",
+    "formaction",
+    "href",
+    "poster",
+    "src",
+    "xlink:href",
+];
+
+/// What [`Block::sanitize`] keeps, everything else is removed
+///
+/// The default policy follows ammonia's: common formatting tags, a few attributes
+/// per tag, no `style`, `class` or event handler attributes and only well known
+/// URL schemes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    /// Tags to keep, other elements are replaced by their sanitized children
+    pub tags: HashSet<Cow<'static, str>>,
+    /// Tags removed together with their content, e.g. `script`
+    pub clean_content_tags: HashSet<Cow<'static, str>>,
+    /// Attributes allowed on every kept tag
+    pub generic_attributes: HashSet<Cow<'static, str>>,
+    /// Attributes allowed on specific tags
+    pub tag_attributes: HashMap<Cow<'static, str>, HashSet<Cow<'static, str>>>,
+    /// Schemes allowed in [`URL_ATTRIBUTES`], attributes with other schemes are removed
+    pub url_schemes: HashSet<Cow<'static, str>>,
+    /// Whether URLs without a scheme, e.g. `/about` or `#top`, are allowed
+    pub relative_urls: bool,
+    /// `rel` set on every kept `a` element, `None` to leave links unchanged
+    pub link_rel: Option<Cow<'static, str>>,
+    /// Whether comment nodes are kept
+    pub comments: bool,
+}
+
+fn set(names: &[&'static str]) -> HashSet<Cow<'static, str>> {
+    names.iter().map(|name| Cow::Borrowed(*name)).collect()
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            tags: set(DEFAULT_TAGS),
+            clean_content_tags: set(&["script", "style"]),
+            generic_attributes: set(&["lang", "title"]),
+            tag_attributes: DEFAULT_TAG_ATTRIBUTES
+                .iter()
+                .map(|(tag, attributes)| (Cow::Borrowed(*tag), set(attributes)))
+                .collect(),
+            url_schemes: set(DEFAULT_URL_SCHEMES),
+            relative_urls: true,
+            link_rel: Some("noopener noreferrer".into()),
+            comments: false,
+        }
+    }
+}
+
+impl Policy {
+    /// A policy that keeps only text
+    #[must_use]
+    pub fn empty() -> Self {
+        Policy {
+            tags: HashSet::new(),
+            clean_content_tags: set(&["script", "style"]),
+            generic_attributes: HashSet::new(),
+            tag_attributes: HashMap::new(),
+            url_schemes: HashSet::new(),
+            relative_urls: false,
+            link_rel: None,
+            comments: false,
+        }
+    }
+
+    pub fn add_tags<I>(&mut self, tags: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.tags.extend(tags.into_iter().map(Into::into));
+    }
+    #[must_use]
+    pub fn with_tags<I>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.add_tags(tags);
+        self
+    }
+
+    pub fn remove_tags<'t>(&mut self, tags: impl IntoIterator<Item = &'t str>) {
+        for tag in tags {
+            self.tags.remove(tag);
+        }
+    }
+    #[must_use]
+    pub fn without_tags<'t>(mut self, tags: impl IntoIterator<Item = &'t str>) -> Self {
+        self.remove_tags(tags);
+        self
+    }
+
+    pub fn add_tag_attributes<I>(&mut self, tag: impl Into<Cow<'static, str>>, attributes: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.tag_attributes
+            .entry(tag.into())
+            .or_default()
+            .extend(attributes.into_iter().map(Into::into));
+    }
+    #[must_use]
+    pub fn with_tag_attributes<I>(
+        mut self,
+        tag: impl Into<Cow<'static, str>>,
+        attributes: I,
+    ) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.add_tag_attributes(tag, attributes);
+        self
+    }
+
+    pub fn add_generic_attributes<I>(&mut self, attributes: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.generic_attributes
+            .extend(attributes.into_iter().map(Into::into));
+    }
+    #[must_use]
+    pub fn with_generic_attributes<I>(mut self, attributes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.add_generic_attributes(attributes);
+        self
+    }
+
+    pub fn add_url_schemes<I>(&mut self, schemes: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.url_schemes.extend(schemes.into_iter().map(Into::into));
+    }
+    #[must_use]
+    pub fn with_url_schemes<I>(mut self, schemes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.add_url_schemes(schemes);
+        self
+    }
+
+    /// Returns true if the attribute may be kept on the tag, including its URL check
+    #[must_use]
+    pub fn allows_attribute(&self, tag: &str, attribute: &Attribute) -> bool {
+        let key = attribute.key.to_ascii_lowercase();
+        let allowed = self.generic_attributes.contains(key.as_str())
+            || self
+                .tag_attributes
+                .get(tag)
+                .is_some_and(|attributes| attributes.contains(key.as_str()));
+        allowed && (!URL_ATTRIBUTES.contains(&key.as_str()) || self.allows_url(&attribute.value))
+    }
+
+    /// Returns true if the URL has an allowed scheme, or is relative and those are allowed
+    #[must_use]
+    pub fn allows_url(&self, url: &str) -> bool {
+        // Browsers ignore leading controls and spaces and tabs and newlines anywhere
+        let url: String = url
+            .trim_start_matches(|c: char| c.is_ascii_control() || c == ' ')
+            .chars()
+            .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+            .collect();
+        let scheme_end = url.find([':', '/', '?', '#']);
+        match scheme_end.map(|end| (end, &url[end..end + 1])) {
+            Some((end, ":")) => self
+                .url_schemes
+                .contains(url[..end].to_ascii_lowercase().as_str()),
+            _ => self.relative_urls,
+        }
+    }
+
+    fn sanitize_nodes<'a>(&self, nodes: Vec<Node<'a>>, out: &mut Vec<Node<'a>>) {
+        for node in nodes {
+            match node {
+                Node::Text(_) | Node::Slot(_) => out.push(node),
+                #[cfg(feature = "i18n")]
+                Node::Translation(_) => out.push(node),
+                Node::Comment(_) => {
+                    if self.comments {
+                        out.push(node);
+                    }
+                }
+                // Raw markup is parsed so that it goes through the same checks, only
+                // what is kept being copied out of the borrowed parse
+                Node::Raw(raw) => {
+                    let mut kept = Vec::new();
+                    self.sanitize_nodes(from_html(&raw).into_nodes(), &mut kept);
+                    out.extend(kept.into_iter().map(|node| node.into_owned()));
+                }
+                Node::Element(element) => self.sanitize_element(element, out),
+                Node::Shared(element) => self.sanitize_element(Arc::unwrap_or_clone(element), out),
+                // Directives could pull in other templates, only their content is kept
+                Node::Directive(directive) => self.sanitize_nodes(directive.into_children(), out),
+            }
+        }
+    }
+
+    fn sanitize_element<'a>(&self, mut element: Element<'a>, out: &mut Vec<Node<'a>>) {
+        let tag = element.name.as_str().to_ascii_lowercase();
+        if self.clean_content_tags.contains(tag.as_str()) {
+            return;
+        }
+        let children = std::mem::take(&mut element.children);
+        if !self.tags.contains(tag.as_str()) {
+            self.sanitize_nodes(children, out);
+            return;
+        }
+        element
+            .attributes
+            .retain(|attribute| self.allows_attribute(&tag, attribute));
+        if tag == "a"
+            && let Some(rel) = &self.link_rel
+        {
+            element.set_attr("rel", rel.clone());
+        }
+        self.sanitize_nodes(children, &mut element.children);
+        out.push(element.into());
+    }
+}
+
+impl Block<'_> {
+    /// Removes the tags, attributes and URLs the policy does not allow
+    ///
+    /// Disallowed elements are replaced by their sanitized children, except for
+    /// [`Policy::clean_content_tags`], which are removed entirely. Raw markup is
+    /// parsed with [`from_html`] and sanitized like the rest of the tree.
+    pub fn sanitize(&mut self, policy: &Policy) {
+        let nodes = std::mem::take(&mut self.children);
+        policy.sanitize_nodes(nodes, &mut self.children);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Policy;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_sanitize_default_policy() {
+        let mut document = Document::new()
+            .with_child(
+                element("div")
+                    .with_key_value("onclick", "steal()")
+                    .with_key_value("title", "Note")
+                    .with_child(element("script").with_child("steal()"))
+                    .with_child(element("a").with_key_value("href", " java\tscript:steal()"))
+                    .with_child(element("a").with_key_value("href", "/about"))
+                    .with_child(element("blink").with_child("Hi")),
+            )
+            .with_child(Node::raw(
+                r#"<img src="x.png" onerror="steal()"><!-- c -->"#,
+            ));
+        document.sanitize(&Policy::default());
+        assert_eq!(
+            document.render(),
+            concat!(
+                r#"<div title="Note"><a rel="noopener noreferrer"></a>"#,
+                r#"<a href="/about" rel="noopener noreferrer"></a>Hi</div><img src="x.png">"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        let policy = Policy::empty()
+            .with_tags(["p"])
+            .with_generic_attributes(["class"])
+            .with_url_schemes(["https"]);
+        assert!(policy.allows_url("HTTPS://example.com"));
+        assert!(!policy.allows_url("/relative"));
+        let mut document = Document::new().with_child(
+            element("p")
+                .with_class("lead")
+                .with_child(element("em").with_child("Hi")),
+        );
+        document.sanitize(&policy);
+        assert_eq!(document.render(), r#"<p class="lead">Hi</p>"#);
+    }
+
+    #[test]
+    fn test_disallowed_attributes_and_urls() {
+        let policy = Policy::default();
+        let link = element("a").with_key_value("href", "x");
+        assert!(!policy.allows_attribute("a", &Attribute::new("class", "x")));
+        assert!(!policy.allows_attribute("p", &Attribute::new("href", "/about")));
+        assert!(policy.allows_attribute("a", &link.attributes[0]));
+        for url in [
+            "javascript:alert(1)",
+            "JavaScript:alert(1)",
+            "\u{1}  javascript:alert(1)",
+            "java\nscript:alert(1)",
+            "data:text/html;base64,PHNjcmlwdD4=",
+            "vbscript:msgbox(1)",
+        ] {
+            assert!(!policy.allows_url(url), "{url}");
+        }
+        for url in [
+            "https://example.com",
+            "mailto:a@example.com",
+            "#top",
+            "?q=1",
+        ] {
+            assert!(policy.allows_url(url), "{url}");
+        }
+
+        let mut document = Document::new().with_child(
+            element("p")
+                .with_class("lead")
+                .with_key_value("style", "color: red")
+                .with_key_value("lang", "en")
+                .with_child(element("img").with_key_value("src", "data:image/png;base64,AAAA"))
+                .with_child(element("q").with_key_value("cite", "javascript:alert(1)")),
+        );
+        document.sanitize(&policy);
+        assert_eq!(document.render(), r#"<p lang="en"><img><q></q></p>"#);
+    }
+
+    #[test]
+    fn test_raw_markup_is_reparsed() {
+        let mut document = Document::new().with_child(element("p").with_child(Node::raw(concat!(
+            r#"<b onclick="steal()">Bold</b><script>steal()</script>"#,
+            r#"<a href="javascript:steal()">Link</a>"#,
+        ))));
+        document.sanitize(&Policy::default());
+        assert_eq!(
+            document.render(),
+            r#"<p><b>Bold</b><a rel="noopener noreferrer">Link</a></p>"#
+        );
+        assert!(document.children[0].as_element().is_some_and(|p| {
+            p.children
+                .iter()
+                .all(|child| !matches!(child, Node::Raw(_)))
+        }));
+    }
+
+    #[test]
+    fn test_raw_markup_with_unknown_tags() {
+        let markup: String = (0..1000)
+            .map(|i| format!("<x-{i} data-i=\"{i}\">{i}</x-{i}>"))
+            .collect();
+        let mut document = Document::new().with_child(Node::raw(markup));
+        document.sanitize(&Policy::default());
+        assert_eq!(document.children.len(), 1000);
+        assert!(
+            document
+                .children
+                .iter()
+                .all(|node| matches!(node, Node::Text(_)))
+        );
+        assert_eq!(
+            document.render(),
+            (0..1000).map(|i| i.to_string()).collect::<String>()
+        );
+    }
+
+    #[test]
+    fn test_link_rel_replaces_duplicates() {
+        let policy = Policy::default().with_tag_attributes("a", ["href", "rel"]);
+        let mut link = element("a").with_key_value("href", "/");
+        link.attributes.push(Attribute::new("rel", "opener"));
+        link.attributes.push(Attribute::new("rel", "external"));
+        let mut document = Document::new()
+            .with_child(link)
+            .with_child(Node::raw(r#"<a rel="opener" href="/x" rel="x">X</a>"#));
+        document.sanitize(&policy);
+        assert_eq!(
+            document.render(),
+            concat!(
+                r#"<a href="/" rel="noopener noreferrer"></a>"#,
+                r#"<a rel="noopener noreferrer" href="/x">X</a>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_directive_children() {
+        let mut document = Document::new().with_child(Node::Directive(Directive::Block {
+            name: "content".into(),
+            children: vec![
+                element("em").with_child("Kept").into(),
+                element("script").with_child("steal()").into(),
+                Node::Directive(Directive::Include("nav.rstml".into())),
+            ],
+        }));
+        document.sanitize(&Policy::default());
+        assert_eq!(
+            document.children,
+            vec![element("em").with_child("Kept").into()]
+        );
+    }
+
+    #[test]
+    fn test_nested_disallowed_elements() {
+        let mut document = Document::new().with_child(
+            element("form").with_child(
+                element("div")
+                    .with_key_value("onclick", "steal()")
+                    .with_child(element("i").with_child("Hi"))
+                    .with_child(
+                        element("button")
+                            .with_child(element("iframe").with_child("Frame"))
+                            .with_child(element("style").with_child("p {}")),
+                    ),
+            ),
+        );
+        document.sanitize(&Policy::default());
+        assert_eq!(document.render(), "<div><i>Hi</i>Frame</div>");
+    }
+
+    #[test]
+    fn test_comments() {
+        let document = Document::new().with_child(
+            element("p")
+                .with_child(Node::comment(" note "))
+                .with_child("Text"),
+        );
+        let mut stripped = document.clone();
+        stripped.sanitize(&Policy::default());
+        assert_eq!(stripped.render(), "<p>Text</p>");
+
+        let mut kept = document.clone();
+        kept.sanitize(&Policy {
+            comments: true,
+            ..Policy::default()
+        });
+        assert_eq!(kept.render(), "<p><!-- note -->Text</p>");
+    }
+}
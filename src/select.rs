@@ -0,0 +1,384 @@
+use crate::prelude::*;
+
+/// How a compound selector relates to the one before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combinator {
+    /// `a b`, matches any ancestor
+    Descendant,
+    /// `a > b`, matches the direct parent
+    Child,
+}
+
+/// Condition on a single attribute, e.g. `[href]` or `[type="text"]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeSelector<'a> {
+    pub key: &'a str,
+    /// Required value, `None` only requires the attribute to be present
+    pub value: Option<&'a str>,
+}
+
+impl AttributeSelector<'_> {
+    #[must_use]
+    pub fn matches(&self, element: &Element) -> bool {
+        element
+            .attr(self.key)
+            .is_some_and(|found| self.value.is_none_or(|value| found == value))
+    }
+}
+
+/// Selector for a single element, e.g. `a.external[href]`
+///
+/// `#id` is stored as an `[id="..."]` attribute selector.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Compound<'a> {
+    /// Tag name, `None` for `*` or when omitted
+    pub tag: Option<&'a str>,
+    pub classes: Vec<&'a str>,
+    pub attributes: Vec<AttributeSelector<'a>>,
+}
+
+impl Compound<'_> {
+    #[must_use]
+    pub fn matches(&self, element: &Element) -> bool {
+        self.tag
+            .is_none_or(|tag| element.name.as_str().eq_ignore_ascii_case(tag))
+            && self.classes.iter().all(|class| element.has_class(class))
+            && self.attributes.iter().all(|a| a.matches(element))
+    }
+}
+
+/// A CSS selector supporting tag, class, id and attribute selectors joined by
+/// descendant and child combinators, e.g. `div.card > a[href]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector<'a> {
+    // Compounds from left to right, each with the combinator joining it to the previous one
+    parts: Vec<(Combinator, Compound<'a>)>,
+}
+
+impl<'a> Selector<'a> {
+    /// Parses a complete selector
+    ///
+    /// # Errors
+    /// Errors if the selector is invalid or followed by any other input
+    pub fn new(input: &'a str) -> Result<Self, ParseError<'a>> {
+        let (rest, selector) = Self::parse(input)?;
+        if !rest.trim().is_empty() {
+            return Err(ParseError::invalid_input(
+                rest,
+                Some("Unexpected input after selector".into()),
+            ));
+        }
+        Ok(selector)
+    }
+
+    /// Returns true if the element matches, given its ancestors from the outermost to its parent
+    #[must_use]
+    pub fn matches(&self, element: &Element, ancestors: &[&Element]) -> bool {
+        let Some(((combinator, last), rest)) = self.parts.split_last() else {
+            return false;
+        };
+        last.matches(element) && matches_ancestors(rest, *combinator, ancestors)
+    }
+
+    /// Collects the elements in `nodes` and their descendants that match, in document order
+    #[must_use]
+    pub fn select<'s, 'n>(&self, nodes: &'s [Node<'n>]) -> Vec<&'s Element<'n>> {
+        let mut found = Vec::new();
+        self.collect(
+            nodes,
+            &mut Vec::new(),
+            &mut NodePath::root(),
+            &mut |_, element| {
+                found.push(element);
+            },
+        );
+        found
+    }
+
+    /// Collects the paths of the elements in `nodes` and their descendants that match,
+    /// in document order
+    #[must_use]
+    pub fn select_paths(&self, nodes: &[Node]) -> Vec<NodePath> {
+        let mut found = Vec::new();
+        self.collect(
+            nodes,
+            &mut Vec::new(),
+            &mut NodePath::root(),
+            &mut |path, _| {
+                found.push(path.clone());
+            },
+        );
+        found
+    }
+
+    fn collect<'s, 'n>(
+        &self,
+        nodes: &'s [Node<'n>],
+        ancestors: &mut Vec<&'s Element<'n>>,
+        path: &mut NodePath,
+        on_match: &mut impl FnMut(&NodePath, &'s Element<'n>),
+    ) {
+        for (i, node) in nodes.iter().enumerate() {
+            let Some(element) = node.as_element() else {
+                continue;
+            };
+            path.push(i);
+            if self.matches(element, ancestors) {
+                on_match(path, element);
+            }
+            ancestors.push(element);
+            self.collect(&element.children, ancestors, path, on_match);
+            ancestors.pop();
+            path.pop();
+        }
+    }
+}
+
+// Matches the remaining compounds against the ancestors, backtracking over descendant combinators
+fn matches_ancestors(
+    parts: &[(Combinator, Compound)],
+    combinator: Combinator,
+    ancestors: &[&Element],
+) -> bool {
+    let Some(((next, compound), rest)) = parts.split_last() else {
+        return true;
+    };
+    match combinator {
+        Combinator::Child => ancestors.split_last().is_some_and(|(parent, above)| {
+            compound.matches(parent) && matches_ancestors(rest, *next, above)
+        }),
+        Combinator::Descendant => (0..ancestors.len()).rev().any(|i| {
+            compound.matches(ancestors[i]) && matches_ancestors(rest, *next, &ancestors[..i])
+        }),
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+fn ident<'a>(input: &'a str, allowed: fn(char) -> bool) -> ParseResult<'a, &'a str> {
+    let end = input.find(|c| !allowed(c)).unwrap_or(input.len());
+    if end == 0 {
+        return Err(ParseError::invalid_input(
+            input,
+            Some("Expected a name in selector".into()),
+        ));
+    }
+    Ok((&input[end..], &input[..end]))
+}
+
+impl<'a> RSTMLParse<'a> for AttributeSelector<'a> {
+    fn parse_no_whitespace(input: &'a str) -> ParseResult<'a, Self> {
+        let Some(input) = input.strip_prefix('[') else {
+            return Err(ParseError::missing_token("[", input, None));
+        };
+        // Attribute names may be namespaced, e.g. `xlink:href`
+        let (input, key) = ident(input.trim_start(), |c| is_ident_char(c) || c == ':')?;
+        let mut input = input.trim_start();
+        let mut value = None;
+        if let Some(rest) = input.strip_prefix('=') {
+            let rest = rest.trim_start();
+            let (rest, parsed) = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let Some(end) = rest[1..].find(quote) else {
+                        return Err(ParseError::missing_delimiter(quote.to_string(), rest));
+                    };
+                    (&rest[end + 2..], &rest[1..=end])
+                }
+                _ => ident(rest, is_ident_char)?,
+            };
+            value = Some(parsed);
+            input = rest.trim_start();
+        }
+        let Some(input) = input.strip_prefix(']') else {
+            return Err(ParseError::missing_token(
+                "]",
+                input,
+                Some("attribute selector".into()),
+            ));
+        };
+        Ok((input, AttributeSelector { key, value }))
+    }
+}
+
+impl<'a> RSTMLParse<'a> for Compound<'a> {
+    fn parse_no_whitespace(mut input: &'a str) -> ParseResult<'a, Self> {
+        let mut compound = Compound::default();
+        let mut empty = true;
+        if let Some(rest) = input.strip_prefix('*') {
+            input = rest;
+            empty = false;
+        } else if input.starts_with(is_ident_char) {
+            let (rest, tag) = ident(input, is_ident_char)?;
+            compound.tag = Some(tag);
+            input = rest;
+            empty = false;
+        }
+        loop {
+            if let Some(rest) = input.strip_prefix('.') {
+                let (rest, class) = ident(rest, is_ident_char)?;
+                compound.classes.push(class);
+                input = rest;
+            } else if let Some(rest) = input.strip_prefix('#') {
+                let (rest, id) = ident(rest, is_ident_char)?;
+                compound.attributes.push(AttributeSelector {
+                    key: "id",
+                    value: Some(id),
+                });
+                input = rest;
+            } else if input.starts_with('[') {
+                let (rest, attribute) = AttributeSelector::parse_no_whitespace(input)?;
+                compound.attributes.push(attribute);
+                input = rest;
+            } else {
+                break;
+            }
+            empty = false;
+        }
+        if empty {
+            return Err(if input.is_empty() {
+                ParseError::EmptyInput
+            } else {
+                ParseError::invalid_input(input, Some("Expected a selector".into()))
+            });
+        }
+        Ok((input, compound))
+    }
+}
+
+impl<'a> RSTMLParse<'a> for Selector<'a> {
+    fn parse_no_whitespace(input: &'a str) -> ParseResult<'a, Self> {
+        let (mut input, first) = Compound::parse_no_whitespace(input)?;
+        let mut parts = vec![(Combinator::Descendant, first)];
+        loop {
+            let trimmed = input.trim_start();
+            let (rest, combinator) = if let Some(rest) = trimmed.strip_prefix('>') {
+                (rest.trim_start(), Combinator::Child)
+            } else if trimmed.len() < input.len()
+                && trimmed.starts_with(|c| is_ident_char(c) || "*.#[".contains(c))
+            {
+                (trimmed, Combinator::Descendant)
+            } else {
+                break;
+            };
+            let (rest, compound) = Compound::parse_no_whitespace(rest)?;
+            parts.push((combinator, compound));
+            input = rest;
+        }
+        Ok((input, Selector { parts }))
+    }
+}
+
+impl<'a> Element<'a> {
+    /// Returns the descendants matching a CSS selector, in document order
+    ///
+    /// The element itself is not included, but takes part in matching ancestors,
+    /// e.g. `card.select("div.card > a")` finds the links directly inside `card`.
+    ///
+    /// # Errors
+    /// Errors if the selector is invalid
+    pub fn select<'s>(
+        &'s self,
+        selector: &'s str,
+    ) -> Result<impl Iterator<Item = &'s Element<'a>>, ParseError<'s>> {
+        let selector = Selector::new(selector)?;
+        let mut found = Vec::new();
+        selector.collect(
+            &self.children,
+            &mut vec![self],
+            &mut NodePath::root(),
+            &mut |_, element| found.push(element),
+        );
+        Ok(found.into_iter())
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Returns the elements matching a CSS selector, in document order
+    ///
+    /// # Errors
+    /// Errors if the selector is invalid
+    pub fn select<'s>(
+        &'s self,
+        selector: &'s str,
+    ) -> Result<impl Iterator<Item = &'s Element<'a>>, ParseError<'s>> {
+        Ok(Selector::new(selector)?.select(&self.children).into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AttributeSelector, Selector};
+    use crate::prelude::*;
+    use crate::test_util::*;
+
+    fn page() -> Document<'static> {
+        Document::new().with_child(
+            element("main")
+                .with_child(
+                    element("div")
+                        .with_attribute(Attribute::class("card featured"))
+                        .with_child(element("a").with_key_value("href", "/one"))
+                        .with_child(
+                            element("p").with_child(element("a").with_key_value("href", "/two")),
+                        ),
+                )
+                .with_child(
+                    element("div")
+                        .with_attribute(Attribute::id("footer"))
+                        .with_child(element("a").with_key_value("name", "top")),
+                ),
+        )
+    }
+
+    fn hrefs<'s>(elements: impl Iterator<Item = &'s Element<'s>>) -> Vec<&'s str> {
+        elements
+            .map(|e| e.attr("href").or(e.attr("name")).unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_attribute_selector_parse() {
+        assert_parse_eq(
+            AttributeSelector::parse_no_whitespace(r#"[ type = "text" ] a"#),
+            AttributeSelector {
+                key: "type",
+                value: Some("text"),
+            },
+            " a",
+        );
+        assert_parse_err(
+            AttributeSelector::parse_no_whitespace("[href"),
+            ParseError::missing_token("]", "", Some("attribute selector".into())),
+        );
+    }
+
+    #[test]
+    fn test_select_combinators() {
+        let page = page();
+        assert_eq!(hrefs(page.select("a").unwrap()), ["/one", "/two", "top"]);
+        assert_eq!(hrefs(page.select("div.card > a").unwrap()), ["/one"]);
+        assert_eq!(
+            hrefs(page.select("main .card a[href]").unwrap()),
+            ["/one", "/two"]
+        );
+        assert_eq!(hrefs(page.select("#footer a").unwrap()), ["top"]);
+        assert_eq!(hrefs(page.select(r#"a[href="/two"]"#).unwrap()), ["/two"]);
+        assert_eq!(page.select("main > a").unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_element_select() {
+        let page = page();
+        let card = page.select(".featured").unwrap().next().unwrap();
+        assert_eq!(hrefs(card.select("div > a").unwrap()), ["/one"]);
+        assert_eq!(hrefs(card.select("p a").unwrap()), ["/two"]);
+    }
+
+    #[test]
+    fn test_invalid_selector() {
+        assert!(Selector::new("div >").is_err());
+        assert!(Selector::new("div, p").is_err());
+    }
+}
@@ -0,0 +1,951 @@
+use std::fmt::Display;
+
+use crate::prelude::*;
+
+/// Prefix of the classes generated for scoped style blocks, see [`Block::scope_styles`]
+pub const SCOPE_PREFIX: &str = "rstml-";
+
+/// A CSS rule, e.g. `.card{padding:1rem}`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Rule {
+    pub selector: String,
+    /// Media query the rule is nested in, e.g. `(max-width: 600px)`
+    pub media: Option<String>,
+    /// Property and value pairs, in order
+    pub declarations: Vec<(String, String)>,
+}
+
+impl Rule {
+    pub fn new(selector: impl Into<String>) -> Self {
+        Rule {
+            selector: selector.into(),
+            media: None,
+            declarations: Vec::new(),
+        }
+    }
+
+    pub fn add_declaration(&mut self, property: impl Into<String>, value: impl Into<String>) {
+        self.declarations.push((property.into(), value.into()));
+    }
+    #[must_use]
+    pub fn with_declaration(
+        mut self,
+        property: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.add_declaration(property, value);
+        self
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_rules(std::slice::from_ref(self), f)
+    }
+}
+
+// Writes the rules, sharing one `@media` block between neighbours with the same query
+fn write_rules(rules: &[Rule], out: &mut impl std::fmt::Write) -> std::fmt::Result {
+    let mut media = None;
+    for rule in rules {
+        if rule.media.as_deref() != media {
+            if media.is_some() {
+                out.write_char('}')?;
+            }
+            media = rule.media.as_deref();
+            if let Some(media) = media {
+                write!(out, "@media {media}{{")?;
+            }
+        }
+        write!(out, "{}{{", rule.selector)?;
+        write_declarations(&rule.declarations, out)?;
+        out.write_char('}')?;
+    }
+    if media.is_some() {
+        out.write_char('}')?;
+    }
+    Ok(())
+}
+
+/// Standard CSS properties, see [`is_known_property`]
+pub const CSS_PROPERTIES: &[&str] = &[
+    "accent-color",
+    "align-content",
+    "align-items",
+    "align-self",
+    "all",
+    "animation",
+    "animation-delay",
+    "animation-direction",
+    "animation-duration",
+    "animation-fill-mode",
+    "animation-iteration-count",
+    "animation-name",
+    "animation-play-state",
+    "animation-timing-function",
+    "appearance",
+    "aspect-ratio",
+    "backdrop-filter",
+    "backface-visibility",
+    "background",
+    "background-attachment",
+    "background-blend-mode",
+    "background-clip",
+    "background-color",
+    "background-image",
+    "background-origin",
+    "background-position",
+    "background-repeat",
+    "background-size",
+    "block-size",
+    "border",
+    "border-block",
+    "border-block-end",
+    "border-block-start",
+    "border-bottom",
+    "border-bottom-color",
+    "border-bottom-left-radius",
+    "border-bottom-right-radius",
+    "border-bottom-style",
+    "border-bottom-width",
+    "border-collapse",
+    "border-color",
+    "border-image",
+    "border-inline",
+    "border-inline-end",
+    "border-inline-start",
+    "border-left",
+    "border-left-color",
+    "border-left-style",
+    "border-left-width",
+    "border-radius",
+    "border-right",
+    "border-right-color",
+    "border-right-style",
+    "border-right-width",
+    "border-spacing",
+    "border-style",
+    "border-top",
+    "border-top-color",
+    "border-top-left-radius",
+    "border-top-right-radius",
+    "border-top-style",
+    "border-top-width",
+    "border-width",
+    "bottom",
+    "box-shadow",
+    "box-sizing",
+    "break-after",
+    "break-before",
+    "break-inside",
+    "caption-side",
+    "caret-color",
+    "clear",
+    "clip-path",
+    "color",
+    "column-count",
+    "column-gap",
+    "column-rule",
+    "column-span",
+    "column-width",
+    "columns",
+    "contain",
+    "container",
+    "container-name",
+    "container-type",
+    "content",
+    "content-visibility",
+    "counter-increment",
+    "counter-reset",
+    "cursor",
+    "direction",
+    "display",
+    "empty-cells",
+    "fill",
+    "filter",
+    "flex",
+    "flex-basis",
+    "flex-direction",
+    "flex-flow",
+    "flex-grow",
+    "flex-shrink",
+    "flex-wrap",
+    "float",
+    "font",
+    "font-family",
+    "font-feature-settings",
+    "font-size",
+    "font-style",
+    "font-variant",
+    "font-variant-numeric",
+    "font-weight",
+    "gap",
+    "grid",
+    "grid-area",
+    "grid-auto-columns",
+    "grid-auto-flow",
+    "grid-auto-rows",
+    "grid-column",
+    "grid-column-end",
+    "grid-column-start",
+    "grid-row",
+    "grid-row-end",
+    "grid-row-start",
+    "grid-template",
+    "grid-template-areas",
+    "grid-template-columns",
+    "grid-template-rows",
+    "height",
+    "hyphens",
+    "image-rendering",
+    "inline-size",
+    "inset",
+    "inset-block",
+    "inset-inline",
+    "isolation",
+    "justify-content",
+    "justify-items",
+    "justify-self",
+    "left",
+    "letter-spacing",
+    "line-height",
+    "list-style",
+    "list-style-image",
+    "list-style-position",
+    "list-style-type",
+    "margin",
+    "margin-block",
+    "margin-block-end",
+    "margin-block-start",
+    "margin-bottom",
+    "margin-inline",
+    "margin-inline-end",
+    "margin-inline-start",
+    "margin-left",
+    "margin-right",
+    "margin-top",
+    "mask",
+    "max-block-size",
+    "max-height",
+    "max-inline-size",
+    "max-width",
+    "min-block-size",
+    "min-height",
+    "min-inline-size",
+    "min-width",
+    "mix-blend-mode",
+    "object-fit",
+    "object-position",
+    "opacity",
+    "order",
+    "outline",
+    "outline-color",
+    "outline-offset",
+    "outline-style",
+    "outline-width",
+    "overflow",
+    "overflow-wrap",
+    "overflow-x",
+    "overflow-y",
+    "overscroll-behavior",
+    "padding",
+    "padding-block",
+    "padding-block-end",
+    "padding-block-start",
+    "padding-bottom",
+    "padding-inline",
+    "padding-inline-end",
+    "padding-inline-start",
+    "padding-left",
+    "padding-right",
+    "padding-top",
+    "page-break-after",
+    "page-break-before",
+    "page-break-inside",
+    "perspective",
+    "place-content",
+    "place-items",
+    "place-self",
+    "pointer-events",
+    "position",
+    "quotes",
+    "resize",
+    "right",
+    "rotate",
+    "row-gap",
+    "scale",
+    "scroll-behavior",
+    "scroll-margin",
+    "scroll-padding",
+    "scroll-snap-align",
+    "scroll-snap-type",
+    "scrollbar-color",
+    "scrollbar-gutter",
+    "scrollbar-width",
+    "stroke",
+    "stroke-width",
+    "tab-size",
+    "table-layout",
+    "text-align",
+    "text-align-last",
+    "text-decoration",
+    "text-decoration-color",
+    "text-decoration-line",
+    "text-decoration-style",
+    "text-decoration-thickness",
+    "text-indent",
+    "text-overflow",
+    "text-shadow",
+    "text-transform",
+    "text-underline-offset",
+    "text-wrap",
+    "top",
+    "touch-action",
+    "transform",
+    "transform-origin",
+    "transition",
+    "transition-delay",
+    "transition-duration",
+    "transition-property",
+    "transition-timing-function",
+    "translate",
+    "unicode-bidi",
+    "user-select",
+    "vertical-align",
+    "visibility",
+    "white-space",
+    "width",
+    "will-change",
+    "word-break",
+    "word-spacing",
+    "writing-mode",
+    "z-index",
+    "zoom",
+];
+
+/// Returns true for standard CSS properties, custom properties, e.g. `--accent`,
+/// and vendor-prefixed ones, e.g. `-webkit-line-clamp`
+#[must_use]
+pub fn is_known_property(property: &str) -> bool {
+    property.starts_with('-') || CSS_PROPERTIES.binary_search(&property).is_ok()
+}
+
+/// Inline declarations for a `style` attribute, e.g.
+/// `Style::new().padding("1rem").background_color("blue")`
+///
+/// Displays as the attribute's value, `padding:1rem;background-color:blue`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Style {
+    /// Property and value pairs, in order
+    pub declarations: Vec<(String, String)>,
+}
+
+macro_rules! properties {
+    ($($method:ident)+) => {
+        $(
+            #[must_use]
+            pub fn $method(self, value: impl Into<String>) -> Self {
+                self.with_property(stringify!($method).replace('_', "-"), value)
+            }
+        )+
+    };
+}
+
+impl Style {
+    #[must_use]
+    pub fn new() -> Self {
+        Style::default()
+    }
+
+    /// Sets a property, replacing its value if it is already set
+    pub fn add_property(&mut self, property: impl Into<String>, value: impl Into<String>) {
+        let (property, value) = (property.into(), value.into());
+        match self.declarations.iter_mut().find(|(p, _)| *p == property) {
+            Some((_, existing)) => *existing = value,
+            None => self.declarations.push((property, value)),
+        }
+    }
+    #[must_use]
+    pub fn with_property(mut self, property: impl Into<String>, value: impl Into<String>) -> Self {
+        self.add_property(property, value);
+        self
+    }
+
+    /// Returns the value of the property
+    #[must_use]
+    pub fn get(&self, property: &str) -> Option<&str> {
+        self.declarations
+            .iter()
+            .find(|(p, _)| p == property)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Iterates over the properties that are not known, see [`is_known_property`]
+    pub fn unknown_properties(&self) -> impl Iterator<Item = &str> {
+        self.declarations
+            .iter()
+            .map(|(property, _)| property.as_str())
+            .filter(|property| !is_known_property(property))
+    }
+
+    properties!(
+        align_items background background_color border border_radius bottom box_shadow
+        color cursor display flex flex_direction flex_wrap font_family font_size font_weight
+        gap grid_template_columns height justify_content left letter_spacing line_height
+        margin margin_bottom margin_left margin_right margin_top max_width min_height
+        min_width opacity overflow padding padding_bottom padding_left padding_right
+        padding_top position right text_align text_decoration top transform transition
+        visibility white_space width z_index
+    );
+}
+
+fn write_declarations(
+    declarations: &[(String, String)],
+    out: &mut impl std::fmt::Write,
+) -> std::fmt::Result {
+    for (i, (property, value)) in declarations.iter().enumerate() {
+        if i > 0 {
+            out.write_char(';')?;
+        }
+        write!(out, "{property}:{value}")?;
+    }
+    Ok(())
+}
+
+impl Display for Style {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_declarations(&self.declarations, f)
+    }
+}
+
+impl From<Style> for Attribute<'_> {
+    fn from(style: Style) -> Self {
+        Attribute::new("style", style.to_string())
+    }
+}
+
+/// Parses CSS declarations, e.g. `padding: 1rem; color: blue`
+///
+/// Declarations without a `:` are skipped.
+#[must_use]
+pub fn parse_declarations(css: &str) -> Vec<(String, String)> {
+    css.split(';')
+        .filter_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            let (property, value) = (property.trim(), value.trim());
+            (!property.is_empty() && !value.is_empty())
+                .then(|| (property.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut uncommented = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        uncommented.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .find("*/")
+            .map_or("", |end| &rest[start + end + 4..]);
+    }
+    uncommented.push_str(rest);
+    uncommented
+}
+
+// Index of the `}` closing a block that was just opened
+fn closing_brace(input: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+// Selector of a rule nested in `parent`, where `&` stands for the parent, e.g.
+// `&:hover`, and other selectors match descendants, e.g. `a` is `parent a`
+fn nest_selector(parent: &str, nested: &str) -> String {
+    let mut selectors = Vec::new();
+    for parent in parent.split(',').map(str::trim) {
+        for nested in nested.split(',').map(str::trim) {
+            selectors.push(if nested.contains('&') {
+                nested.replace('&', parent)
+            } else {
+                format!("{parent} {nested}")
+            });
+        }
+    }
+    selectors.join(",")
+}
+
+// Parses declarations, nested rules and `@media` blocks into flat rules, the
+// parent's own declarations first. Without a parent only rules are allowed, as at
+// the top level of a stylesheet. Fails on other at-rules.
+fn parse_nested(
+    css: &str,
+    parent: Option<&str>,
+    media: Option<&str>,
+    rules: &mut Vec<Rule>,
+) -> Option<()> {
+    let index = rules.len();
+    let mut declarations = Vec::new();
+    let mut rest = css.trim_start();
+    while !rest.is_empty() {
+        match rest.find([';', '{']) {
+            Some(open) if rest[open..].starts_with('{') => {
+                let prelude = rest[..open].trim();
+                let after = &rest[open + 1..];
+                let close = closing_brace(after)?;
+                let body = &after[..close];
+                if let Some(query) = prelude.strip_prefix("@media") {
+                    let query = match media {
+                        Some(outer) => format!("{outer} and {}", query.trim()),
+                        None => query.trim().to_string(),
+                    };
+                    parse_nested(body, parent, Some(&query), rules)?;
+                } else if prelude.starts_with('@') {
+                    return None;
+                } else {
+                    let selector = parent.map_or_else(
+                        || prelude.to_string(),
+                        |parent| nest_selector(parent, prelude),
+                    );
+                    parse_nested(body, Some(&selector), media, rules)?;
+                }
+                rest = after[close + 1..].trim_start();
+            }
+            end => {
+                let end = end.unwrap_or(rest.len());
+                if parent.is_none() && !rest[..end].trim().is_empty() {
+                    return None;
+                }
+                declarations.extend(parse_declarations(&rest[..end]));
+                rest = rest[(end + 1).min(rest.len())..].trim_start();
+            }
+        }
+    }
+    if let Some(parent) = parent
+        && !declarations.is_empty()
+    {
+        let rule = Rule {
+            selector: parent.to_string(),
+            media: media.map(str::to_string),
+            declarations,
+        };
+        rules.insert(index, rule);
+    }
+    Some(())
+}
+
+/// Parses a stylesheet, e.g. `a { color: red } @media print { p { margin: 0 } }`
+///
+/// Nested rules, e.g. `a { &:hover { color: red } }`, and `@media` blocks are
+/// flattened into separate rules, and comments are dropped. Returns `None` for CSS
+/// that rules cannot hold, such as other at-rules.
+#[must_use]
+pub fn parse_rules(css: &str) -> Option<Vec<Rule>> {
+    let mut rules = Vec::new();
+    parse_nested(&strip_comments(css), None, None, &mut rules)?;
+    Some(rules)
+}
+
+/// Drops repeated rules, and merges rules with the same declarations into the
+/// first of them by joining their selectors
+///
+/// Merging moves a rule earlier, which only changes the cascade if a rule in
+/// between sets the same properties on the same elements.
+#[must_use]
+pub fn dedupe_rules(rules: Vec<Rule>) -> Vec<Rule> {
+    let mut deduped: Vec<Rule> = Vec::with_capacity(rules.len());
+    for rule in rules {
+        match deduped.iter_mut().find(|existing| {
+            existing.media == rule.media && existing.declarations == rule.declarations
+        }) {
+            Some(existing) => {
+                for selector in rule.selector.split(',').map(str::trim) {
+                    if !existing.selector.split(',').any(|s| s.trim() == selector) {
+                        existing.selector = format!("{},{selector}", existing.selector);
+                    }
+                }
+            }
+            None => deduped.push(rule),
+        }
+    }
+    deduped
+}
+
+// A `style` element holding the rules, written raw since escaping would break
+// selectors such as `a > b`
+pub(crate) fn stylesheet<'a>(rules: &[Rule]) -> Element<'a> {
+    let mut css = String::new();
+    write_rules(rules, &mut css).expect("writing to a String cannot fail");
+    element("style").with_child(Node::raw(css.replace("</", "<\\/")))
+}
+
+// Rules of a `style` element without attributes, if `parse_rules` can read them
+fn stylesheet_rules(style: &Element) -> Option<Vec<Rule>> {
+    if style.name.as_str() != "style" || !style.attributes.is_empty() {
+        return None;
+    }
+    let css: String = style
+        .children
+        .iter()
+        .filter_map(|node| match node {
+            Node::Text(text) => Some(text.content.as_ref()),
+            Node::Raw(raw) => Some(raw.as_ref()),
+            _ => None,
+        })
+        .collect();
+    parse_rules(&css.replace("<\\/", "</"))
+}
+
+fn is_style_block(node: &Node) -> bool {
+    node.as_element()
+        .is_some_and(|element| element.name.as_str() == "style")
+}
+
+impl Element<'_> {
+    /// Rules of the element's `style {}` block for the selector, `None` if it has none
+    ///
+    /// The block's attributes are declarations, e.g. `.padding = "1rem"`, followed by
+    /// its text, which may also hold nested rules, e.g. `"&:hover { color: red }"`,
+    /// and `@media` blocks. Parsing stops at any other at-rule.
+    #[must_use]
+    pub fn style_rules(&self, selector: &str) -> Option<Vec<Rule>> {
+        let block = self
+            .children
+            .iter()
+            .find(|node| is_style_block(node))?
+            .as_element()?;
+        let mut css: String = block
+            .attributes
+            .iter()
+            .filter(|attribute| !attribute.is_boolean())
+            .map(|attribute| format!("{}:{};", attribute.key, attribute.value))
+            .collect();
+        css.push_str(&strip_comments(&block.text_content()));
+        let mut rules = Vec::new();
+        let _ = parse_nested(&css, Some(selector), None, &mut rules);
+        Some(rules)
+    }
+
+    /// Replaces the element's `style {}` block with a generated class, returning
+    /// the rules for that class, see [`Element::style_rules`]
+    ///
+    /// The class is a hash of the element, so equal components share their class.
+    pub fn scope_style(&mut self) -> Vec<Rule> {
+        let class = format!("{SCOPE_PREFIX}{:06x}", self.fingerprint() & 0xff_ffff);
+        let Some(rules) = self.style_rules(&format!(".{class}")) else {
+            return Vec::new();
+        };
+        self.children.retain(|node| !is_style_block(node));
+        self.add_class(&class);
+        rules
+    }
+}
+
+fn scope_nodes(nodes: &mut [Node], rules: &mut Vec<Rule>) {
+    for element in nodes.iter_mut().filter_map(Node::as_element_mut) {
+        if element.name.as_str() == "head" {
+            continue;
+        }
+        for rule in element.scope_style() {
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+        }
+        scope_nodes(&mut element.children, rules);
+    }
+}
+
+impl Block<'_> {
+    /// Scopes the `style {}` block of every element, see [`Element::scope_style`],
+    /// and hoists their rules into a `style` element in `head`
+    ///
+    /// Style elements inside `head` are left as they are. Returns the hoisted rules.
+    pub fn scope_styles(&mut self) -> Vec<Rule> {
+        let mut rules = Vec::new();
+        scope_nodes(&mut self.children, &mut rules);
+        if rules.is_empty() {
+            return rules;
+        }
+        self.head_mut().add_child(stylesheet(&rules));
+        rules
+    }
+
+    /// Scopes every `style {}` block like [`Block::scope_styles`], and consolidates
+    /// their rules and those of the `style` elements already in `head` into a single
+    /// `style` element, see [`dedupe_rules`]
+    ///
+    /// Style elements with attributes, e.g. `media`, or with CSS that [`parse_rules`]
+    /// cannot read are left as they are. Returns the consolidated rules.
+    pub fn hoist_styles(&mut self) -> Vec<Rule> {
+        let mut scoped = Vec::new();
+        scope_nodes(&mut self.children, &mut scoped);
+        let mut rules = Vec::new();
+        let mut hoisted = Vec::new();
+        if let Some(head) = self.head() {
+            for (i, node) in head.children.iter().enumerate() {
+                if let Some(parsed) = node.as_element().and_then(stylesheet_rules) {
+                    rules.extend(parsed);
+                    hoisted.push(i);
+                }
+            }
+        }
+        rules.extend(scoped);
+        if rules.is_empty() {
+            return rules;
+        }
+        let rules = dedupe_rules(rules);
+        let head = self.head_mut();
+        // Last first, so that removing a style does not shift the ones still to go
+        for &i in hoisted.iter().rev() {
+            head.children.remove(i);
+        }
+        let position = hoisted.first().copied().unwrap_or(head.children.len());
+        head.children.insert(position, stylesheet(&rules).into());
+        rules
+    }
+}
+
+// Removes pseudo-classes and pseudo-elements, e.g. `a:hover::after` becomes `a`,
+// since whether they apply is only known in the browser
+fn strip_pseudo(selector: &str) -> String {
+    let mut out = String::with_capacity(selector.len());
+    let mut chars = selector.chars().peekable();
+    let mut in_attribute = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => in_attribute = true,
+            ']' => in_attribute = false,
+            _ => {}
+        }
+        if c != ':' || in_attribute {
+            out.push(c);
+            continue;
+        }
+        while chars
+            .next_if(|c| *c == ':' || c.is_alphanumeric() || *c == '-' || *c == '_')
+            .is_some()
+        {}
+        // Arguments, e.g. `:not(.hidden)`
+        if chars.next_if_eq(&'(').is_some() {
+            let mut depth = 1;
+            for c in chars.by_ref() {
+                match c {
+                    '(' => depth += 1,
+                    ')' if depth == 1 => break,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+    out
+}
+
+// Whether the selector may match an element in the nodes, true when the selector
+// engine cannot tell, e.g. for sibling combinators or `:root`
+fn may_match(nodes: &[Node], selector: &str) -> bool {
+    let stripped = strip_pseudo(selector);
+    if stripped.trim().is_empty() {
+        return true;
+    }
+    Selector::new(&stripped).map_or(true, |selector| !selector.select(nodes).is_empty())
+}
+
+impl Block<'_> {
+    /// The rules that apply to elements in the document, keeping only the
+    /// selectors of each rule that match
+    ///
+    /// Pseudo-classes and pseudo-elements are ignored, so `a:hover` is kept when
+    /// the document has a link. Selectors the selector engine cannot read, e.g.
+    /// `a + b`, are kept as well.
+    #[must_use]
+    pub fn critical_rules(&self, rules: &[Rule]) -> Vec<Rule> {
+        rules
+            .iter()
+            .filter_map(|rule| {
+                let selectors: Vec<_> = rule
+                    .selector
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|selector| may_match(&self.children, selector))
+                    .collect();
+                (!selectors.is_empty()).then(|| Rule {
+                    selector: selectors.join(","),
+                    ..rule.clone()
+                })
+            })
+            .collect()
+    }
+
+    /// The critical CSS of a stylesheet for the document, see [`Block::critical_rules`]
+    ///
+    /// Returns `None` if [`parse_rules`] cannot read the stylesheet.
+    #[must_use]
+    pub fn critical_css(&self, css: &str) -> Option<String> {
+        let rules = self.critical_rules(&parse_rules(css)?);
+        let mut critical = String::new();
+        write_rules(&rules, &mut critical).expect("writing to a String cannot fail");
+        Some(critical)
+    }
+
+    /// Inlines the critical CSS of a stylesheet into a `style` element in `head`,
+    /// returning the inlined rules
+    ///
+    /// Returns `None`, leaving the document as it is, if [`parse_rules`] cannot
+    /// read the stylesheet.
+    pub fn inline_critical_css(&mut self, css: &str) -> Option<Vec<Rule>> {
+        let rules = self.critical_rules(&parse_rules(css)?);
+        if !rules.is_empty() {
+            self.head_mut().add_child(stylesheet(&rules));
+        }
+        Some(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CSS_PROPERTIES, Style, parse_declarations, parse_rules};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_scope_styles() {
+        let (_, mut document) = Document::parse(
+            r#"html { body {
+                div { style { .padding = "1rem" "color: blue" } "A" }
+                div { style { .padding = "1rem" "color: blue" } "A" }
+            } }"#,
+        )
+        .unwrap();
+        let rules = document.scope_styles();
+        assert_eq!(rules.len(), 1);
+        let class = &rules[0].selector[1..];
+        assert!(class.starts_with("rstml-") && class.len() == 12);
+        assert_eq!(
+            document.render(),
+            format!(
+                r#"<html><head><style>.{class}{{padding:1rem;color:blue}}</style></head><body><div class="{class}">A</div><div class="{class}">A</div></body></html>"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_hoist_styles() {
+        let (_, mut document) = Document::parse(
+            r#"head { style { "p { margin: 0 } /* reset */ a { color: red }" } }
+            body {
+                ul { style { .padding = "0" } li { span { style { .color = "red" } "x" } } }
+                p { style { .padding = "0" } "y" }
+            }"#,
+        )
+        .unwrap();
+        let rules = document.hoist_styles();
+        let selectors: Vec<Vec<_>> = rules
+            .iter()
+            .map(|rule| rule.selector.split(',').collect())
+            .collect();
+        assert_eq!(selectors.len(), 3);
+        assert_eq!(selectors[0], ["p"]);
+        assert_eq!(selectors[1][0], "a");
+        assert_eq!(selectors[1].len(), 2);
+        assert_eq!(selectors[2].len(), 2);
+        assert!(selectors[2].iter().all(|s| s.starts_with(".rstml-")));
+        let head = document.head().unwrap();
+        assert_eq!(head.children.len(), 1);
+        assert!(
+            document
+                .render()
+                .starts_with("<head><style>p{margin:0}a,.rstml-")
+        );
+        assert_eq!(
+            document
+                .iter()
+                .filter(|node| node
+                    .as_element()
+                    .is_some_and(|e| e.name.as_str() == "style"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_nested_style_blocks() {
+        let (_, mut document) = Document::parse(
+            r#"body { div {
+                style {
+                    .padding = "1rem"
+                    "&:hover { color: red } a, b { margin: 0 }
+                    @media (max-width: 600px) { padding: 0; & > a { color: blue } }"
+                }
+            } }"#,
+        )
+        .unwrap();
+        let rules = document.hoist_styles();
+        let class = &rules[0].selector;
+        let css = format!(
+            "{class}{{padding:1rem}}{class}:hover{{color:red}}{class} a,{class} b{{margin:0}}\
+            @media (max-width: 600px){{{class}{{padding:0}}{class} > a{{color:blue}}}}"
+        );
+        assert!(
+            document
+                .render()
+                .starts_with(&format!("<head><style>{css}</style></head>"))
+        );
+        assert_eq!(parse_rules(&css), Some(rules));
+        assert_eq!(parse_rules("@font-face { font-family: x }"), None);
+    }
+
+    #[test]
+    fn test_critical_css() {
+        let (_, mut document) =
+            Document::parse(r#"body { main { p { .intro "x" } a { .href = "/" } } }"#).unwrap();
+        let stylesheet = r#"p.intro, h1 { margin: 0 } a:hover::after { color: red }
+            .missing { color: red } @media print { main > p, ul { color: black } }
+            ul li { margin: 0 } :root { --x: 1 } a[href^="/"] { color: blue }"#;
+        let css = r#"p.intro{margin:0}a:hover::after{color:red}@media print{main > p{color:black}}:root{--x:1}a[href^="/"]{color:blue}"#;
+        assert_eq!(document.critical_css(stylesheet).as_deref(), Some(css));
+        assert_eq!(document.inline_critical_css(stylesheet).unwrap().len(), 5);
+        assert!(
+            document
+                .render()
+                .starts_with(&format!("<head><style>{css}</style></head>"))
+        );
+    }
+
+    #[test]
+    fn test_style_builder() {
+        let style = Style::new()
+            .padding("1rem")
+            .background_color("blue")
+            .with_property("--accent", "red")
+            .with_property("colour", "red")
+            .padding("2rem");
+        assert_eq!(
+            style.to_string(),
+            "padding:2rem;background-color:blue;--accent:red;colour:red"
+        );
+        assert_eq!(style.unknown_properties().collect::<Vec<_>>(), ["colour"]);
+        assert!(CSS_PROPERTIES.is_sorted());
+        assert_eq!(
+            element("p").with_attribute(style.into()).render(),
+            r#"<p style="padding:2rem;background-color:blue;--accent:red;colour:red"></p>"#
+        );
+    }
+
+    #[test]
+    fn test_parse_declarations() {
+        assert_eq!(
+            parse_declarations(" margin: 0 auto ;color:red; ;broken"),
+            [
+                ("margin".to_string(), "0 auto".to_string()),
+                ("color".to_string(), "red".to_string())
+            ]
+        );
+    }
+}
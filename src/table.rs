@@ -0,0 +1,182 @@
+use std::borrow::Cow;
+
+use crate::prelude::*;
+
+/// A struct shown as a row of a table, usually derived with `#[derive(TableRow)]`
+/// from `rs-tml-macro`
+///
+/// The derive makes a column of each struct field, headed by its name, e.g.
+/// `First name` for `first_name`, with cells from [`TableCell`]. Struct fields take
+/// a `#[table(...)]` attribute with `header = "..."`, `order = 1` to move the
+/// column, `format = path::to_fn` to build the cell with a function of the value
+/// returning anything that converts into a [`Node`], and `skip`.
+pub trait TableRow {
+    /// The header of each column
+    fn headers() -> Vec<Cow<'static, str>>;
+
+    /// The cell of each column, in the order of the headers
+    fn cells(&self) -> Vec<Node<'static>>;
+}
+
+impl<T: TableRow> TableRow for &T {
+    fn headers() -> Vec<Cow<'static, str>> {
+        T::headers()
+    }
+
+    fn cells(&self) -> Vec<Node<'static>> {
+        T::cells(self)
+    }
+}
+
+/// A value shown in a table cell, as text unless it is a node
+pub trait TableCell {
+    fn to_cell(&self) -> Node<'static>;
+}
+
+impl TableCell for String {
+    fn to_cell(&self) -> Node<'static> {
+        Node::text(self.clone())
+    }
+}
+
+impl TableCell for &str {
+    fn to_cell(&self) -> Node<'static> {
+        Node::text(self.to_string())
+    }
+}
+
+impl TableCell for Cow<'_, str> {
+    fn to_cell(&self) -> Node<'static> {
+        Node::text(self.to_string())
+    }
+}
+
+impl TableCell for Node<'_> {
+    fn to_cell(&self) -> Node<'static> {
+        self.clone().into_owned()
+    }
+}
+
+impl TableCell for Element<'_> {
+    fn to_cell(&self) -> Node<'static> {
+        self.clone().into_owned().into()
+    }
+}
+
+impl TableCell for bool {
+    fn to_cell(&self) -> Node<'static> {
+        Node::text(if *self { "Yes" } else { "No" })
+    }
+}
+
+macro_rules! impl_table_cell_display {
+    ($($ty:ty),*) => {
+        $(
+            impl TableCell for $ty {
+                fn to_cell(&self) -> Node<'static> {
+                    Node::text(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_table_cell_display!(
+    char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+/// An empty cell for `None`
+impl<T: TableCell> TableCell for Option<T> {
+    fn to_cell(&self) -> Node<'static> {
+        match self {
+            Some(value) => value.to_cell(),
+            None => Node::text(""),
+        }
+    }
+}
+
+/// Builds a `table` with a `thead` row of column headers and a `tbody` row per item
+///
+/// Headers are `th` elements with `scope="col"`. Items may be rows or references to
+/// them, and the body is empty if there are none.
+pub fn from_rows<I>(rows: I) -> Element<'static>
+where
+    I: IntoIterator,
+    I::Item: TableRow,
+{
+    let headers = <I::Item as TableRow>::headers().into_iter().map(|header| {
+        element("th")
+            .with_key_value("scope", "col")
+            .with_child(Node::text(header))
+    });
+    let body = rows.into_iter().map(|row| {
+        element("tr").with_children(
+            row.cells()
+                .into_iter()
+                .map(|cell| element("td").with_child(cell)),
+        )
+    });
+    element("table")
+        .with_child(element("thead").with_child(element("tr").with_children(headers)))
+        .with_child(element("tbody").with_children(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{TableCell, TableRow, from_rows};
+    use crate::prelude::*;
+
+    struct User {
+        name: &'static str,
+        admin: bool,
+        score: Option<f64>,
+    }
+
+    impl TableRow for User {
+        fn headers() -> Vec<Cow<'static, str>> {
+            vec!["Name".into(), "Admin".into(), "Score".into()]
+        }
+
+        fn cells(&self) -> Vec<Node<'static>> {
+            vec![
+                self.name.to_cell(),
+                self.admin.to_cell(),
+                self.score.to_cell(),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let users = [
+            User {
+                name: "<Ann>",
+                admin: true,
+                score: Some(9.5),
+            },
+            User {
+                name: "Bob",
+                admin: false,
+                score: None,
+            },
+        ];
+        assert_eq!(
+            from_rows(&users).render(),
+            concat!(
+                r#"<table><thead><tr><th scope="col">Name</th><th scope="col">Admin</th>"#,
+                r#"<th scope="col">Score</th></tr></thead><tbody>"#,
+                "<tr><td>&lt;Ann&gt;</td><td>Yes</td><td>9.5</td></tr>",
+                "<tr><td>Bob</td><td>No</td><td></td></tr></tbody></table>",
+            )
+        );
+        assert_eq!(
+            from_rows(Vec::<User>::new()).render(),
+            concat!(
+                r#"<table><thead><tr><th scope="col">Name</th><th scope="col">Admin</th>"#,
+                r#"<th scope="col">Score</th></tr></thead><tbody></tbody></table>"#,
+            )
+        );
+    }
+}
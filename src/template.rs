@@ -0,0 +1,1466 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{
+    diagnostics::Span,
+    environment::Environment,
+    expr::{Expr, FilterCall, first_bar, parse_pipeline},
+    prelude::*,
+    render::{RawText, close_tag, open_tag_with, render_node, write_attribute, write_escaped_text},
+    value::Value,
+};
+
+/// What to do with a `{name}` placeholder whose key or path is not in the [`Context`]
+///
+/// Conditions are lenient whatever the setting, a missing value counts as `null`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Missing {
+    /// Fail with [`RenderError::MissingKey`], the strict mode
+    #[default]
+    Error,
+    /// Replace the placeholder with nothing, the lenient mode
+    Empty,
+    /// Leave the placeholder in the output as written
+    Keep,
+}
+
+/// Values for the `{name}` placeholders, `@if` conditions and `@for` loops of
+/// templates loaded at runtime, see [`Block::render_with`]
+///
+/// Placeholders may name a path into the values, e.g. `{order.customer.name}` or
+/// `{items.0.price}`, see [`Value::lookup`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Context<'c> {
+    values: HashMap<Cow<'c, str>, Value<'c>>,
+    missing: Missing,
+}
+
+impl<'c> Context<'c> {
+    #[must_use]
+    pub fn new() -> Self {
+        Context::default()
+    }
+
+    /// Builds a context from the fields of a JSON object, other values give an empty context
+    ///
+    /// In placeholders strings are used without quotes, `null` as an empty string and
+    /// anything else as JSON, see [`Value::to_text`].
+    #[cfg(feature = "json")]
+    #[must_use]
+    pub fn from_json(value: &serde_json::Value) -> Context<'static> {
+        Context::from_value(Value::from(value))
+    }
+
+    /// Builds a context from the entries of a map, other values give an empty context
+    #[must_use]
+    pub fn from_value(value: Value<'c>) -> Self {
+        match value {
+            Value::Map(entries) => entries.into_iter().collect(),
+            _ => Context::default(),
+        }
+    }
+
+    /// Builds a context from the fields of any `Serialize` struct or map, see
+    /// [`crate::value::to_value`]
+    ///
+    /// # Errors
+    /// Errors if the value does not serialize into a map
+    #[cfg(feature = "serde")]
+    pub fn from_serialize<T: serde::Serialize + ?Sized>(
+        value: &T,
+    ) -> Result<Context<'static>, crate::value::SerializeError> {
+        match crate::value::to_value(value)? {
+            value @ Value::Map(_) => Ok(Context::from_value(value)),
+            _ => Err(serde::ser::Error::custom("expected a struct or a map")),
+        }
+    }
+
+    pub fn add_value(&mut self, key: impl Into<Cow<'c, str>>, value: impl Into<Value<'c>>) {
+        self.values.insert(key.into(), value.into());
+    }
+    #[must_use]
+    pub fn with_value(mut self, key: impl Into<Cow<'c, str>>, value: impl Into<Value<'c>>) -> Self {
+        self.add_value(key, value);
+        self
+    }
+
+    pub fn set_missing(&mut self, missing: Missing) {
+        self.missing = missing;
+    }
+    #[must_use]
+    pub fn with_missing(mut self, missing: Missing) -> Self {
+        self.set_missing(missing);
+        self
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value<'c>> {
+        self.values.get(key)
+    }
+
+    /// Replaces the placeholders in a string, `{{` and `}}` are literal braces
+    ///
+    /// # Errors
+    /// Errors if a placeholder is not closed, or its key is missing and the context
+    /// is set to [`Missing::Error`]
+    pub fn interpolate<'s>(&self, input: &'s str) -> Result<Cow<'s, str>, RenderError> {
+        Scope::new(Environment::builtin(), self).interpolate(input)
+    }
+}
+
+// The context with the items bound by the enclosing `@for` loops
+pub(crate) struct Scope<'s> {
+    environment: &'s Environment,
+    context: &'s Context<'s>,
+    // Loop variables by name, innermost last
+    locals: Vec<(&'s str, &'s Value<'s>)>,
+    // The element holding code the text is in, values inside a `script` must be safe
+    raw: Option<RawText>,
+}
+
+impl<'s> Scope<'s> {
+    pub(crate) fn new(environment: &'s Environment, context: &'s Context<'s>) -> Self {
+        Scope {
+            environment,
+            context,
+            locals: Vec::new(),
+            raw: None,
+        }
+    }
+
+    // The value of a key, or of a path starting at a key
+    pub(crate) fn lookup(&self, key: &str) -> Option<&'s Value<'s>> {
+        if let Some(value) = self.variable(key) {
+            return Some(value);
+        }
+        let (first, path) = key.split_once('.')?;
+        self.variable(first)?.lookup(path)
+    }
+
+    fn variable(&self, key: &str) -> Option<&'s Value<'s>> {
+        self.locals
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| *value)
+            .or_else(|| self.context.values.get(key))
+    }
+
+    // The value of a placeholder piped through its filters, as the context's
+    // `Missing` setting decides for missing keys
+    fn resolve<'r>(&'r self, key: &str, source: &'r str) -> Result<Resolved<'r>, RenderError> {
+        let (key, filters) =
+            parse_pipeline(key).map_err(|message| RenderError::InvalidExpression {
+                expression: key.to_string(),
+                message,
+            })?;
+        let escaped = |text| Ok(Resolved { text, safe: false });
+        let mut value = if key.contains('(') {
+            Cow::Owned(self.evaluate(&parse_condition(key)?)?)
+        } else {
+            match (self.lookup(key), self.context.missing) {
+                (Some(value), _) if filters.is_empty() => return escaped(value.to_text()),
+                (Some(value), _) => Cow::Borrowed(value),
+                (None, Missing::Error) => return Err(RenderError::MissingKey(key.to_string())),
+                (None, Missing::Empty) => return escaped(Cow::Borrowed("")),
+                (None, Missing::Keep) => return escaped(Cow::Borrowed(source)),
+            }
+        };
+        let mut safe = false;
+        for filter in &filters {
+            if filter.name == SAFE {
+                safe = true;
+            } else {
+                value = Cow::Owned(self.apply(filter, &value)?);
+            }
+        }
+        Ok(Resolved {
+            text: Cow::Owned(value.to_text().into_owned()),
+            safe,
+        })
+    }
+
+    fn apply(&self, filter: &FilterCall, value: &Value) -> Result<Value<'static>, RenderError> {
+        let Some(function) = self.environment.filter(filter.name) else {
+            return Err(RenderError::UnknownFilter(filter.name.to_string()));
+        };
+        let args = filter
+            .args
+            .iter()
+            .map(|arg| Ok(arg.eval(self)?.into_owned()))
+            .collect::<Result<Vec<_>, RenderError>>()?;
+        function(value, &args).map_err(|message| RenderError::Filter {
+            name: filter.name.to_string(),
+            message,
+        })
+    }
+
+    // Calls a function of the environment, `source` being the call as written
+    pub(crate) fn call(
+        &self,
+        name: &str,
+        args: &[Value],
+        source: &str,
+    ) -> Result<Value<'static>, RenderError> {
+        let Some(function) = self.environment.function(name) else {
+            return Err(RenderError::UnknownFunction(name.to_string()));
+        };
+        function(args).map_err(|message| RenderError::Function {
+            call: source.to_string(),
+            message,
+        })
+    }
+
+    // Evaluates an expression into a value detached from the scope
+    fn evaluate(&self, expr: &Expr) -> Result<Value<'static>, RenderError> {
+        Ok(expr.eval(self)?.into_owned().into_owned())
+    }
+
+    // Replaces the placeholders of an attribute value or a string, where `safe`
+    // makes no difference as the result is escaped as a whole
+    fn interpolate<'i>(&self, input: &'i str) -> Result<Cow<'i, str>, RenderError> {
+        if !input.contains(['{', '}']) {
+            return Ok(Cow::Borrowed(input));
+        }
+        let mut out = String::with_capacity(input.len());
+        for part in parts(input)? {
+            match part {
+                Part::Literal(literal) => out.push_str(literal),
+                Part::Placeholder { key, source } => {
+                    out.push_str(&self.resolve(key, source)?.text);
+                }
+            }
+        }
+        Ok(Cow::Owned(out))
+    }
+
+    // Replaces the placeholders of text content, values piped through `safe`
+    // becoming raw nodes and the rest text that is escaped when rendered
+    //
+    // Inside a `script` or `style` the text is code, which stays text as it is
+    // escaped so it cannot end the element, and values inside it must be safe.
+    fn interpolate_text<'a>(
+        &self,
+        input: &str,
+        out: &mut Vec<Node<'a>>,
+    ) -> Result<(), RenderError> {
+        let start = out.len();
+        let mut text = String::new();
+        for part in parts(input)? {
+            match part {
+                Part::Literal(literal) => text.push_str(literal),
+                Part::Placeholder { key, source } => {
+                    let resolved = self.resolve(key, source)?;
+                    match (self.raw, resolved.safe) {
+                        (Some(raw), false) => return Err(unsafe_value(raw, source)),
+                        (None, true) => {
+                            if !text.is_empty() {
+                                out.push(Node::Text(Text::new(std::mem::take(&mut text))));
+                            }
+                            out.push(Node::Raw(owned(resolved.text)));
+                        }
+                        _ => text.push_str(&resolved.text),
+                    }
+                }
+            }
+        }
+        if !text.is_empty() || out.len() == start {
+            out.push(Node::Text(Text::new(text)));
+        }
+        Ok(())
+    }
+
+    fn condition(&self, condition: &Expr) -> Result<bool, RenderError> {
+        Ok(condition.eval(self)?.is_truthy())
+    }
+
+    // A copy of the scope for a loop, whose variables may live shorter
+    fn nested(&self) -> Scope<'s> {
+        Scope {
+            environment: self.environment,
+            context: self.context,
+            locals: self.locals.clone(),
+            raw: self.raw,
+        }
+    }
+
+    // The items a `@for` loop iterates, none for `null` and for a missing list
+    // unless the context is set to `Missing::Error`
+    fn items(&self, path: &str) -> Result<&'s [Value<'s>], RenderError> {
+        match self.lookup(path) {
+            Some(Value::List(items)) => Ok(items),
+            Some(Value::Null) => Ok(&[]),
+            None if self.context.missing != Missing::Error => Ok(&[]),
+            None => Err(RenderError::MissingKey(path.to_string())),
+            Some(_) => Err(RenderError::NotIterable(path.to_string())),
+        }
+    }
+}
+
+// The filter that marks a value as trusted markup, applied by `Scope::resolve`
+// itself so that it cannot be replaced
+const SAFE: &str = "safe";
+
+// The text of a placeholder, written into text content unescaped if it is `safe`
+struct Resolved<'r> {
+    text: Cow<'r, str>,
+    safe: bool,
+}
+
+// The `loop` variable of each iteration of a loop over `length` items, holding the
+// 1-based `index`, the 0-based `index0`, `first`, `last` and `length`
+fn loop_metadata(length: usize) -> Vec<Value<'static>> {
+    let length = i64::try_from(length).unwrap_or(i64::MAX);
+    (0..length)
+        .map(|index| {
+            [
+                ("index", Value::Int(index + 1)),
+                ("index0", Value::Int(index)),
+                ("first", Value::Bool(index == 0)),
+                ("last", Value::Bool(index == length - 1)),
+                ("length", Value::Int(length)),
+            ]
+            .into_iter()
+            .collect()
+        })
+        .collect()
+}
+
+// Parses the condition of an `@if` or `@else if`
+fn parse_condition(condition: &str) -> Result<Expr<'_>, RenderError> {
+    Expr::parse(condition).map_err(|message| RenderError::InvalidExpression {
+        expression: condition.to_string(),
+        message,
+    })
+}
+
+// A run of literal text or a placeholder, as written in a template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part<S> {
+    Literal(S),
+    // `source` is the placeholder as written, braces included
+    Placeholder { key: S, source: S },
+}
+
+impl Part<&str> {
+    fn into_owned(self) -> Part<String> {
+        match self {
+            Part::Literal(literal) => Part::Literal(literal.to_string()),
+            Part::Placeholder { key, source } => Part::Placeholder {
+                key: key.to_string(),
+                source: source.to_string(),
+            },
+        }
+    }
+}
+
+// Splits the input at its placeholders, `{{` and `}}` being literal braces
+//
+// A placeholder written `{- key }` strips the whitespace before it and one written
+// `{ key -}` the whitespace after it.
+fn parts(input: &str) -> Result<Vec<Part<&str>>, RenderError> {
+    let mut parts = Vec::new();
+    let mut rest = input;
+    let mut trim_next = false;
+    while let Some(start) = rest.find(['{', '}']) {
+        if trim_next {
+            let trimmed = rest[..start].trim_start();
+            rest = &rest[start - trimmed.len()..];
+            trim_next = false;
+            continue;
+        }
+        if start > 0 {
+            parts.push(Part::Literal(&rest[..start]));
+        }
+        let brace = &rest[start..];
+        if let Some(after) = brace
+            .strip_prefix("{{")
+            .or_else(|| brace.strip_prefix("}}"))
+        {
+            parts.push(Part::Literal(&brace[..1]));
+            rest = after;
+            continue;
+        }
+        let Some(end) = brace.find('}').filter(|_| brace.starts_with('{')) else {
+            return Err(RenderError::UnclosedPlaceholder(brace.to_string()));
+        };
+        let mut key = brace[1..end].trim();
+        if let Some(trimmed) = trim_marker(key, str::strip_prefix, str::trim_start) {
+            key = trimmed;
+            trim_end_of_literals(&mut parts);
+        }
+        if let Some(trimmed) = trim_marker(key, str::strip_suffix, str::trim_end) {
+            key = trimmed;
+            trim_next = true;
+        }
+        parts.push(Part::Placeholder {
+            key,
+            source: &brace[..=end],
+        });
+        rest = &brace[end + 1..];
+    }
+    if trim_next {
+        rest = rest.trim_start();
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Literal(rest));
+    }
+    Ok(parts)
+}
+
+// The key without a `-` trim marker on one side, which is set apart by whitespace
+fn trim_marker<'k>(
+    key: &'k str,
+    strip: fn(&'k str, char) -> Option<&'k str>,
+    trim: fn(&'k str) -> &'k str,
+) -> Option<&'k str> {
+    strip(key, '-')
+        .filter(|rest| trim(rest).len() < rest.len())
+        .map(trim)
+}
+
+// Strips the whitespace at the end of the literal text before a placeholder
+fn trim_end_of_literals(parts: &mut Vec<Part<&str>>) {
+    while let Some(Part::Literal(literal)) = parts.last_mut() {
+        *literal = literal.trim_end();
+        if !literal.is_empty() {
+            return;
+        }
+        parts.pop();
+    }
+}
+
+impl<'c, K, V> FromIterator<(K, V)> for Context<'c>
+where
+    K: Into<Cow<'c, str>>,
+    V: Into<Value<'c>>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Context {
+            values: iter
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+            missing: Missing::default(),
+        }
+    }
+}
+
+/// Error returned when rendering a template with a [`Context`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderError {
+    /// A placeholder names a key that is not in the context
+    MissingKey(String),
+    /// A `{` without a matching `}`, or a lone `}`, with the input from that brace on
+    UnclosedPlaceholder(String),
+    /// An `@if` condition that does not parse
+    InvalidExpression { expression: String, message: String },
+    /// A `@for` loop over a value that is not a list
+    NotIterable(String),
+    /// An `@else` that does not follow an `@if` or `@else if`
+    UnexpectedElse,
+    /// A placeholder pipes its value through a filter the environment does not have
+    UnknownFilter(String),
+    /// A filter rejected its value or arguments
+    Filter { name: String, message: String },
+    /// A call names a function the environment does not have
+    UnknownFunction(String),
+    /// A placeholder inside a `script` whose value is not marked `safe`, which would
+    /// run as code, with the placeholder as written
+    UnsafeScriptValue(String),
+    /// A placeholder inside a `style` whose value is not marked `safe`, which would
+    /// be written as CSS, with the placeholder as written
+    UnsafeStyleValue(String),
+    /// A function rejected its arguments or failed, `call` being the call as written
+    Function { call: String, message: String },
+    /// An error at a placeholder, condition or loop of a named template, see
+    /// [`crate::environment::NamedTemplate::render`]
+    InTemplate {
+        location: Box<ErrorLocation>,
+        error: Box<RenderError>,
+    },
+}
+
+impl RenderError {
+    /// The error without its location
+    #[must_use]
+    pub fn root(&self) -> &RenderError {
+        match self {
+            RenderError::InTemplate { error, .. } => error.root(),
+            error => error,
+        }
+    }
+
+    /// Where in a named template the error happened, if known
+    #[must_use]
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        match self {
+            RenderError::InTemplate { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+}
+
+/// The text of a named template that failed to render, see [`RenderError::InTemplate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorLocation {
+    /// Name of the template the text is written in, which may be a layout or
+    /// partial of the rendered one
+    pub template: String,
+    pub span: Span,
+    /// The line of the text with the text underlined, e.g.
+    ///
+    /// ```text
+    /// 3 | p { "Hello, {user.nmae}" }
+    ///   |            ^^^^^^^^^^^
+    /// ```
+    pub excerpt: String,
+}
+
+impl ErrorLocation {
+    /// Locates the first occurrence of `written` in the source, `None` if it does
+    /// not occur
+    #[must_use]
+    pub fn find(template: &str, source: &str, written: &str) -> Option<Self> {
+        if written.is_empty() {
+            return None;
+        }
+        let start = source.find(written)?;
+        let span = Span::new(source, start, start + written.len());
+        let line = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let number = span.line.to_string();
+        let underline = source[start..end]
+            .chars()
+            .count()
+            .min(written.chars().count())
+            .max(1);
+        let excerpt = format!(
+            "{number} | {}\n{} | {}{}",
+            &source[line..end],
+            " ".repeat(number.len()),
+            " ".repeat(span.column - 1),
+            "^".repeat(underline),
+        );
+        Some(ErrorLocation {
+            template: template.to_string(),
+            span,
+            excerpt,
+        })
+    }
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::MissingKey(key) => write!(f, "No value for placeholder `{{{key}}}`"),
+            RenderError::UnclosedPlaceholder(found) => {
+                write!(f, "Unclosed placeholder at '{found}'")
+            }
+            RenderError::InvalidExpression {
+                expression,
+                message,
+            } => write!(f, "Invalid expression `{expression}`: {message}"),
+            RenderError::NotIterable(path) => write!(f, "Cannot loop over `{path}`, not a list"),
+            RenderError::UnexpectedElse => f.write_str("`@else` without a preceding `@if`"),
+            RenderError::UnknownFilter(name) => write!(f, "No filter named `{name}`"),
+            RenderError::Filter { name, message } => write!(f, "Filter `{name}` failed: {message}"),
+            RenderError::UnknownFunction(name) => write!(f, "No function named `{name}`"),
+            RenderError::UnsafeScriptValue(source) => write!(
+                f,
+                "Placeholder `{source}` inside `<script>` runs as code, pipe it through `safe` if it is trusted"
+            ),
+            RenderError::UnsafeStyleValue(source) => write!(
+                f,
+                "Placeholder `{source}` inside `<style>` is written as CSS, pipe it through `safe` if it is trusted"
+            ),
+            RenderError::Function { call, message } => write!(f, "Cannot call `{call}`: {message}"),
+            RenderError::InTemplate { location, error } => write!(
+                f,
+                "{}:{}:{}: {error}\n{}",
+                location.template, location.span.line, location.span.column, location.excerpt
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+// Clones the nodes with the placeholders in text and attribute values replaced,
+// the `@if` branches that hold kept and the `@for` loops repeated
+fn bind_nodes<'a, 's>(
+    nodes: &'s [Node<'a>],
+    scope: &mut Scope<'s>,
+    out: &mut Vec<Node<'a>>,
+) -> Result<(), RenderError> {
+    // Whether a branch of the `@if` before the current node was taken, `None`
+    // outside of a chain of branches
+    let mut taken = None;
+    for node in nodes {
+        match node {
+            Node::Directive(Directive::If {
+                condition,
+                children,
+            }) => {
+                let holds = scope.condition(&parse_condition(condition)?)?;
+                if holds {
+                    bind_nodes(children, scope, out)?;
+                }
+                taken = Some(holds);
+                continue;
+            }
+            Node::Directive(Directive::Else {
+                condition,
+                children,
+            }) => {
+                let Some(done) = taken else {
+                    return Err(RenderError::UnexpectedElse);
+                };
+                let holds = !done
+                    && match condition {
+                        Some(condition) => scope.condition(&parse_condition(condition)?)?,
+                        None => true,
+                    };
+                if holds {
+                    bind_nodes(children, scope, out)?;
+                }
+                taken = condition.as_ref().map(|_| done || holds);
+                continue;
+            }
+            Node::Directive(Directive::For {
+                item,
+                path,
+                children,
+            }) => {
+                let items = scope.items(path)?;
+                let metadata = loop_metadata(items.len());
+                let mut scope = scope.nested();
+                for (value, metadata) in items.iter().zip(&metadata) {
+                    scope.locals.extend([("loop", metadata), (item, value)]);
+                    bind_nodes(children, &mut scope, out)?;
+                    scope.locals.truncate(scope.locals.len() - 2);
+                }
+            }
+            Node::Text(text) if text.content.contains(['{', '}']) => {
+                scope.interpolate_text(&text.content, out)?;
+            }
+            Node::Directive(directive) => {
+                let mut bound = directive.clone();
+                if let Some(children) = bound.children_mut() {
+                    children.clear();
+                    bind_nodes(directive.children(), scope, children)?;
+                }
+                out.push(Node::Directive(bound));
+            }
+            // Shared elements may hold placeholders as well, so they are copied
+            node => out.push(match node.as_element() {
+                Some(element) => Node::Element(bind_element(element, scope)?),
+                None => node.clone(),
+            }),
+        }
+        taken = None;
+    }
+    Ok(())
+}
+
+// Binds the document with the filters of the environment, see `Block::bind`
+pub(crate) fn bind<'a>(
+    block: &Block<'a>,
+    environment: &Environment,
+    context: &Context,
+) -> Result<Block<'a>, RenderError> {
+    let mut children = Vec::with_capacity(block.children.len());
+    bind_nodes(
+        &block.children,
+        &mut Scope::new(environment, context),
+        &mut children,
+    )?;
+    Ok(Block { children })
+}
+
+fn bind_element<'a, 's>(
+    element: &'s Element<'a>,
+    scope: &mut Scope<'s>,
+) -> Result<Element<'a>, RenderError> {
+    let mut bound = element.clone();
+    for attribute in &mut bound.attributes {
+        attribute.value = owned(scope.interpolate(&attribute.value)?);
+    }
+    bound.children = Vec::with_capacity(element.children.len());
+    let raw = std::mem::replace(&mut scope.raw, RawText::of(element));
+    let bound_children = bind_nodes(&element.children, scope, &mut bound.children);
+    scope.raw = raw;
+    bound_children?;
+    Ok(bound)
+}
+
+// Detaches an interpolated value from the input it was borrowed from
+fn owned<'a>(value: Cow<'_, str>) -> Cow<'a, str> {
+    Cow::Owned(value.into_owned())
+}
+
+// Clones the nodes, replacing every slot that has content in `slots`
+fn fill_nodes<'a>(nodes: &[Node<'a>], slots: &HashMap<&str, Node<'a>>) -> Vec<Node<'a>> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Slot(name) => slots.get(name.as_ref()).unwrap_or(node).clone(),
+            Node::Element(element) => Node::Element(fill_element(element, slots)),
+            Node::Directive(directive) => {
+                let mut filled = directive.clone();
+                if let Some(children) = filled.children_mut() {
+                    *children = fill_nodes(directive.children(), slots);
+                }
+                Node::Directive(filled)
+            }
+            // Shared elements are only copied when they contain a slot
+            Node::Shared(element) if element.descendants().any(Node::is_slot) => {
+                Node::Element(fill_element(element, slots))
+            }
+            node => node.clone(),
+        })
+        .collect()
+}
+
+fn fill_element<'a>(element: &Element<'a>, slots: &HashMap<&str, Node<'a>>) -> Element<'a> {
+    let mut filled = element.clone();
+    filled.children = fill_nodes(&element.children, slots);
+    filled
+}
+
+/// A document compiled for rendering many times with a [`Context`]
+///
+/// Compiling renders everything that does not depend on the context once, leaving
+/// runs of finished markup and holes for the `{name}` placeholders in text and
+/// attribute values, `@if` branches and `@for` loops. [`CompiledTemplate::render_with`]
+/// then writes the runs and the escaped values in turn instead of walking the tree,
+/// with the same output as [`Block::render_with`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledTemplate {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    // Markup written as is
+    Static(String),
+    // A placeholder in text content, which must be safe inside a `script`
+    Text {
+        key: String,
+        source: String,
+        raw: Option<RawText>,
+    },
+    // An attribute whose value holds placeholders
+    Attribute {
+        key: String,
+        foreign: bool,
+        parts: Vec<Part<String>>,
+    },
+    // An `@if` with its `@else` branches, the first whose condition holds rendering
+    Branches(Vec<(Option<Condition>, Vec<Segment>)>),
+    // A `@for` loop
+    Loop {
+        item: String,
+        path: String,
+        body: Vec<Segment>,
+    },
+}
+
+// The condition of a branch, with its source to locate errors
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    source: String,
+    expr: Expr<'static>,
+}
+
+impl Condition {
+    fn parse(source: &str) -> Result<Self, RenderError> {
+        Ok(Condition {
+            source: source.to_string(),
+            expr: parse_condition(source)?.into_owned(),
+        })
+    }
+}
+
+// Collects the segments, buffering static markup until the next hole
+#[derive(Default)]
+struct Compiler {
+    segments: Vec<Segment>,
+    run: String,
+    // The element holding code the nodes are in, see `Scope::interpolate_text`
+    raw: Option<RawText>,
+}
+
+impl std::fmt::Write for Compiler {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.run.push_str(s);
+        Ok(())
+    }
+}
+
+impl Compiler {
+    fn push(&mut self, segment: Segment) {
+        if !self.run.is_empty() {
+            self.segments
+                .push(Segment::Static(std::mem::take(&mut self.run)));
+        }
+        self.segments.push(segment);
+    }
+
+    fn finish(mut self) -> Vec<Segment> {
+        if !self.run.is_empty() {
+            self.segments.push(Segment::Static(self.run));
+        }
+        self.segments
+    }
+
+    // Compiles nodes into their own segments, e.g. the body of a loop
+    fn compile(
+        nodes: &[Node],
+        parent: Namespace,
+        raw: Option<RawText>,
+    ) -> Result<Vec<Segment>, RenderError> {
+        let mut compiler = Compiler {
+            raw,
+            ..Compiler::default()
+        };
+        compiler.nodes(nodes, parent)?;
+        Ok(compiler.finish())
+    }
+
+    fn nodes(&mut self, nodes: &[Node], parent: Namespace) -> Result<(), RenderError> {
+        let mut nodes = nodes.iter().peekable();
+        while let Some(node) = nodes.next() {
+            match node {
+                Node::Directive(Directive::If {
+                    condition,
+                    children,
+                }) => {
+                    let condition = Condition::parse(condition)?;
+                    let mut branches =
+                        vec![(Some(condition), Self::compile(children, parent, self.raw)?)];
+                    while let Some(Node::Directive(Directive::Else {
+                        condition,
+                        children,
+                    })) = nodes.peek()
+                    {
+                        nodes.next();
+                        let condition = match condition {
+                            Some(condition) => Some(Condition::parse(condition)?),
+                            None => None,
+                        };
+                        let last = condition.is_none();
+                        branches.push((condition, Self::compile(children, parent, self.raw)?));
+                        if last {
+                            break;
+                        }
+                    }
+                    self.push(Segment::Branches(branches));
+                }
+                Node::Directive(Directive::Else { .. }) => return Err(RenderError::UnexpectedElse),
+                Node::Directive(Directive::For {
+                    item,
+                    path,
+                    children,
+                }) => self.push(Segment::Loop {
+                    item: item.to_string(),
+                    path: path.to_string(),
+                    body: Self::compile(children, parent, self.raw)?,
+                }),
+                node => self.node(node, parent)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn node(&mut self, node: &Node, parent: Namespace) -> Result<(), RenderError> {
+        match node {
+            Node::Text(text) => {
+                for part in parts(&text.content)? {
+                    match part {
+                        // Literals inside a script or style are code, see `raw_text`
+                        Part::Literal(literal) if let Some(raw) = self.raw => {
+                            self.run.push_str(&raw.escape(literal));
+                        }
+                        Part::Literal(literal) => write_escaped_text(literal, self)
+                            .expect("writing to a String cannot fail"),
+                        Part::Placeholder { key, source } => self.push(Segment::Text {
+                            key: key.to_string(),
+                            source: source.to_string(),
+                            raw: self.raw,
+                        }),
+                    }
+                }
+                Ok(())
+            }
+            Node::Directive(directive) => self.nodes(directive.children(), parent),
+            node => match node.as_element() {
+                Some(element) => self.element(element, parent),
+                None => {
+                    render_node(node, parent, None, self).expect("writing to a String cannot fail");
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    fn element(&mut self, element: &Element, parent: Namespace) -> Result<(), RenderError> {
+        let mut attribute_parts = element
+            .attributes
+            .iter()
+            .map(|attribute| parts(&attribute.value))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+        let open = open_tag_with(
+            element,
+            &element.attributes,
+            parent,
+            self,
+            |attribute, foreign, compiler| {
+                let parts = attribute_parts.next().unwrap_or_default();
+                if !parts
+                    .iter()
+                    .any(|part| matches!(part, Part::Placeholder { .. }))
+                {
+                    return write_attribute(attribute, foreign, compiler);
+                }
+                compiler.push(Segment::Attribute {
+                    key: attribute.key.to_string(),
+                    foreign,
+                    parts: parts.into_iter().map(Part::into_owned).collect(),
+                });
+                Ok(())
+            },
+        )
+        .expect("writing to a String cannot fail");
+        if open {
+            let raw = std::mem::replace(&mut self.raw, RawText::of(element));
+            self.nodes(&element.children, element.namespace)?;
+            self.raw = raw;
+            close_tag(element.name.as_str(), self).expect("writing to a String cannot fail");
+        }
+        Ok(())
+    }
+}
+
+impl CompiledTemplate {
+    /// Compiles the document, see [`Block::compile`]
+    ///
+    /// # Errors
+    /// Errors if a placeholder is not closed
+    pub fn new(block: &Block) -> Result<Self, RenderError> {
+        Ok(CompiledTemplate {
+            segments: Compiler::compile(&block.children, Namespace::Html, None)?,
+        })
+    }
+
+    /// Names of the placeholders and of the lists looped over, in the order they
+    /// appear, with repeats
+    #[must_use]
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys = Vec::new();
+        collect_keys(&self.segments, &mut keys);
+        keys
+    }
+
+    /// Renders the template with its placeholders replaced from the context
+    ///
+    /// # Errors
+    /// Errors if a placeholder's key is missing and the context is set to
+    /// [`Missing::Error`]
+    pub fn render_with(&self, context: &Context) -> Result<String, RenderError> {
+        let mut out = String::new();
+        self.render_into(context, &mut out)?;
+        Ok(out)
+    }
+
+    /// Appends the rendered template to a string, see [`CompiledTemplate::render_with`]
+    ///
+    /// # Errors
+    /// Errors if a placeholder's key is missing and the context is set to
+    /// [`Missing::Error`]. The output written before the error is kept.
+    pub fn render_into(&self, context: &Context, out: &mut String) -> Result<(), RenderError> {
+        self.render_in(Environment::builtin(), context, out)
+    }
+
+    // Appends the rendered template with the filters of the environment
+    pub(crate) fn render_in(
+        &self,
+        environment: &Environment,
+        context: &Context,
+        out: &mut String,
+    ) -> Result<(), RenderError> {
+        self.render_traced(environment, context, out)
+            .map_err(|(error, _)| error)
+    }
+
+    // Like `render_in`, failing with the source of the placeholder, condition or
+    // loop that failed as well
+    pub(crate) fn render_traced(
+        &self,
+        environment: &Environment,
+        context: &Context,
+        out: &mut String,
+    ) -> Result<(), (RenderError, &str)> {
+        let mut at = "";
+        render_segments(
+            &self.segments,
+            &mut Scope::new(environment, context),
+            out,
+            &mut at,
+        )
+        .map_err(|error| (error, at))
+    }
+}
+
+// The key of a placeholder without the filters its value is piped through
+fn without_filters(key: &str) -> &str {
+    first_bar(key).map_or(key, |bar| key[..bar].trim_end())
+}
+
+fn collect_keys<'s>(segments: &'s [Segment], keys: &mut Vec<&'s str>) {
+    for segment in segments {
+        match segment {
+            Segment::Static(_) => {}
+            Segment::Text { key, .. } => keys.push(without_filters(key)),
+            Segment::Attribute { parts, .. } => {
+                keys.extend(parts.iter().filter_map(|part| match part {
+                    Part::Placeholder { key, .. } => Some(without_filters(key)),
+                    Part::Literal(_) => None,
+                }));
+            }
+            Segment::Branches(branches) => {
+                for (_, body) in branches {
+                    collect_keys(body, keys);
+                }
+            }
+            Segment::Loop { path, body, .. } => {
+                keys.push(path);
+                collect_keys(body, keys);
+            }
+        }
+    }
+}
+
+// Renders the segments, setting `at` to the source of each placeholder, condition
+// and loop before evaluating it, so that an error can be located
+fn render_segments<'s, 'w: 's>(
+    segments: &'w [Segment],
+    scope: &mut Scope<'s>,
+    out: &mut String,
+    at: &mut &'w str,
+) -> Result<(), RenderError> {
+    for segment in segments {
+        match segment {
+            Segment::Static(markup) => out.push_str(markup),
+            Segment::Text { key, source, raw } => {
+                *at = source;
+                let resolved = scope.resolve(key, source)?;
+                match (raw, resolved.safe) {
+                    (Some(raw), false) => return Err(unsafe_value(*raw, source)),
+                    (Some(raw), _) => out.push_str(&raw.escape(&resolved.text)),
+                    (None, true) => out.push_str(&resolved.text),
+                    (None, false) => write_escaped_text(&resolved.text, out)
+                        .expect("writing to a String cannot fail"),
+                }
+            }
+            Segment::Attribute {
+                key,
+                foreign,
+                parts,
+            } => {
+                let mut value = String::new();
+                for part in parts {
+                    match part {
+                        Part::Literal(literal) => value.push_str(literal),
+                        Part::Placeholder { key, source } => {
+                            *at = source;
+                            value.push_str(&scope.resolve(key, source)?.text);
+                        }
+                    }
+                }
+                write_attribute(&Attribute::new(key.as_str(), value), *foreign, out)
+                    .expect("writing to a String cannot fail");
+            }
+            Segment::Branches(branches) => {
+                for (condition, body) in branches {
+                    let holds = match condition {
+                        Some(condition) => {
+                            *at = &condition.source;
+                            scope.condition(&condition.expr)?
+                        }
+                        None => true,
+                    };
+                    if holds {
+                        render_segments(body, scope, out, at)?;
+                        break;
+                    }
+                }
+            }
+            Segment::Loop { item, path, body } => {
+                *at = path;
+                let items = scope.items(path)?;
+                let metadata = loop_metadata(items.len());
+                let mut scope = scope.nested();
+                for (value, metadata) in items.iter().zip(&metadata) {
+                    scope
+                        .locals
+                        .extend([("loop", metadata), (item.as_str(), value)]);
+                    render_segments(body, &mut scope, out, at)?;
+                    scope.locals.truncate(scope.locals.len() - 2);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<'a> Block<'a> {
+    /// Returns a copy of the document with its `@slot(name)` holes replaced
+    ///
+    /// Slots without an entry in the map are kept, so a layout can be filled in stages.
+    /// A `Block` can be converted into a single node with `Element::from` or by wrapping it
+    /// in an element, e.g. to fill one slot with several nodes.
+    #[must_use]
+    pub fn fill_slots(&self, slots: &HashMap<&str, Node<'a>>) -> Self {
+        Block {
+            children: fill_nodes(&self.children, slots),
+        }
+    }
+
+    /// Returns a copy of the document with the `{name}` placeholders in text and
+    /// attribute values replaced from the context
+    ///
+    /// # Errors
+    /// Errors if a placeholder is malformed or its key is missing, see [`Context::interpolate`]
+    pub fn bind(&self, context: &Context) -> Result<Self, RenderError> {
+        bind(self, Environment::builtin(), context)
+    }
+
+    /// Renders the document with its placeholders replaced from the context
+    ///
+    /// Values are escaped like any other text, raw nodes and comments are left as is.
+    ///
+    /// # Errors
+    /// Errors if a placeholder is malformed or its key is missing, see [`Context::interpolate`]
+    pub fn render_with(&self, context: &Context) -> Result<String, RenderError> {
+        Ok(self.bind(context)?.render())
+    }
+
+    /// Compiles the document for rendering many times, see [`CompiledTemplate`]
+    ///
+    /// # Errors
+    /// Errors if a placeholder is not closed
+    pub fn compile(&self) -> Result<CompiledTemplate, RenderError> {
+        CompiledTemplate::new(self)
+    }
+}
+
+// The error for a placeholder inside code whose value is not marked `safe`
+fn unsafe_value(raw: RawText, source: &str) -> RenderError {
+    match raw {
+        RawText::Script => RenderError::UnsafeScriptValue(source.to_string()),
+        RawText::Style => RenderError::UnsafeStyleValue(source.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Context, Missing, RenderError};
+    use crate::test_util::*;
+    use crate::{prelude::*, value::Value};
+
+    #[test]
+    fn test_slot_parse() {
+        assert_parse_eq(
+            Node::parse_no_whitespace("@slot( content ) p {}"),
+            Node::slot("content"),
+            " p {}",
+        );
+        assert!(Node::parse_no_whitespace("@slot(two words)").is_err());
+    }
+
+    #[test]
+    fn test_fill_slots() {
+        let (_, layout) = Block::parse_ignoring_comments(
+            r#"
+            header { @slot(title) }
+            main { @slot(content) }
+            footer { @slot(footer) }"#,
+        )
+        .unwrap();
+
+        let slots = HashMap::from([
+            ("title", element("h1").with_child("Home").into()),
+            ("content", Node::text("Welcome")),
+        ]);
+        let page = layout.fill_slots(&slots);
+        assert_eq!(
+            page,
+            Block::new()
+                .with_child(element("header").with_child(element("h1").with_child("Home")))
+                .with_child(element("main").with_child("Welcome"))
+                .with_child(element("footer").with_child(Node::slot("footer")))
+        );
+        assert_eq!(
+            page.render(),
+            "<header><h1>Home</h1></header><main>Welcome</main><footer></footer>"
+        );
+    }
+
+    #[test]
+    fn test_render_with() {
+        let (_, page) = Block::parse_ignoring_comments(
+            r#"a { .href = "/users/{ id }" "Hello {name}, {{literal}}" }"#,
+        )
+        .unwrap();
+        let context = Context::new()
+            .with_value("id", "7")
+            .with_value("name", "<Ann>");
+        assert_eq!(
+            page.render_with(&context).unwrap(),
+            r#"<a href="/users/7">Hello &lt;Ann&gt;, {literal}</a>"#
+        );
+    }
+
+    #[test]
+    fn test_compiled_template() {
+        let (_, page) = Block::parse_ignoring_comments(
+            r#"div { .class = "card {kind}" .data-id = "{id}" /* note */
+                h1 { "{title} & co" } br {} svg { g { .fill = "{color}" } }
+                p { "{{kept}} {missing}" } }"#,
+        )
+        .unwrap();
+        let compiled = page.compile().unwrap();
+        assert_eq!(compiled.keys(), ["kind", "id", "title", "color", "missing"]);
+        let context = Context::new()
+            .with_value("kind", "wide")
+            .with_value("id", "")
+            .with_value("title", "<Tea>")
+            .with_value("color", "")
+            .with_missing(Missing::Keep);
+        assert_eq!(compiled.render_with(&context), page.render_with(&context));
+        let context = context.with_missing(Missing::Error);
+        assert_eq!(
+            compiled.render_with(&context),
+            Err(RenderError::MissingKey("missing".into()))
+        );
+    }
+
+    #[test]
+    fn test_missing_keys() {
+        let (_, page) = Block::parse_ignoring_comments(r#"p { "{greeting} {name}" }"#).unwrap();
+        let context = Context::from_iter([("greeting", "Hi")]);
+        assert_eq!(
+            page.render_with(&context),
+            Err(RenderError::MissingKey("name".into()))
+        );
+        let render = |missing| page.render_with(&context.clone().with_missing(missing));
+        assert_eq!(render(Missing::Empty).unwrap(), "<p>Hi </p>");
+        assert_eq!(render(Missing::Keep).unwrap(), "<p>Hi {name}</p>");
+        assert_eq!(
+            context.interpolate("{open"),
+            Err(RenderError::UnclosedPlaceholder("{open".into()))
+        );
+    }
+
+    #[test]
+    fn test_conditions_and_loops() {
+        let (_, page) = Block::parse_ignoring_comments(
+            r#"@if user { p { "Hi {user}" } } @else if guest == true { p { "Guest" } } @else { a { "Log in" } }
+            ul { @for tag in tags { li { .class = "{tag}" "{tag}" } } }
+            @for row in rows { @for cell in row { "{cell};" } }
+            @if !tags { "No tags" }"#,
+        )
+        .unwrap();
+        let compiled = page.compile().unwrap();
+        let render = |context: &Context| {
+            let rendered = page.render_with(context);
+            assert_eq!(compiled.render_with(context), rendered);
+            rendered
+        };
+        let context = Context::new()
+            .with_value("user", "Ann")
+            .with_value("tags", vec!["a", "b"])
+            .with_value("rows", vec![vec![1, 2], vec![3]]);
+        assert_eq!(
+            render(&context).unwrap(),
+            r#"<p>Hi Ann</p><ul><li class="a">a</li><li class="b">b</li></ul>1;2;3;"#
+        );
+        let context = Context::new()
+            .with_value("guest", true)
+            .with_value("tags", Vec::<&str>::new())
+            .with_value("rows", Value::Null);
+        assert_eq!(render(&context).unwrap(), "<p>Guest</p><ul></ul>No tags");
+        assert_eq!(
+            render(&Context::new().with_value("tags", "a")),
+            Err(RenderError::NotIterable("tags".into()))
+        );
+        let (_, stray) = Block::parse_ignoring_comments(r#"p {} @else { "x" }"#).unwrap();
+        assert_eq!(
+            stray.render_with(&Context::new()),
+            Err(RenderError::UnexpectedElse)
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let (_, page) = Block::parse_ignoring_comments(
+            r#"p { "{order.customer.name}: {order.items.0.price}" }
+            @for item in order.items { i { "{item.name}" } }
+            @if order.customer.vip { "VIP" }"#,
+        )
+        .unwrap();
+        let order: Value = [
+            ("customer", [("name", "Ann")].into_iter().collect::<Value>()),
+            (
+                "items",
+                vec![
+                    [("name", Value::from("Tea")), ("price", 3.into())]
+                        .into_iter()
+                        .collect::<Value>(),
+                    [("name", "Cake")].into_iter().collect(),
+                ]
+                .into(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let context = Context::new().with_value("order", order);
+        assert_eq!(
+            page.render_with(&context).unwrap(),
+            "<p>Ann: 3</p><i>Tea</i><i>Cake</i>"
+        );
+        let (_, page) = Block::parse_ignoring_comments(r#"p { "{order.items.5.price}" }"#).unwrap();
+        assert_eq!(
+            page.render_with(&context),
+            Err(RenderError::MissingKey("order.items.5.price".into()))
+        );
+        let lenient = context.with_missing(Missing::Empty);
+        assert_eq!(page.render_with(&lenient).unwrap(), "<p></p>");
+    }
+
+    #[test]
+    fn test_loop_metadata() {
+        let (_, page) = Block::parse_ignoring_comments(
+            r#"ul { @for name in names {
+                li { .class = "row-{loop.index0}" "{loop.index}/{loop.length} {name}"
+                    @for letter in letters { "{loop.index}{letter}" }
+                }
+                @if !loop.last { "," }
+            } }
+            @for name in names { @if loop.first { "First: {name}" } }"#,
+        )
+        .unwrap();
+        let context = Context::new()
+            .with_value("names", vec!["Ann", "Bob"])
+            .with_value("letters", vec!["a", "b"]);
+        let expected = r#"<ul><li class="row-0">1/2 Ann1a2b</li>,<li class="row-1">2/2 Bob1a2b</li></ul>First: Ann"#;
+        assert_eq!(page.render_with(&context).unwrap(), expected);
+        assert_eq!(
+            page.compile().unwrap().render_with(&context).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_trim_markers() {
+        let (_, page) = Block::parse_ignoring_comments(
+            "ul {
+                @for name in names {-
+                    \"
+                    \"
+                    li { \"  {- name -}  !\" }
+                    \"
+                    \"
+                -}
+            }
+            p { \"Total:   {- count } items,  {{ {- count -} }}\" }",
+        )
+        .unwrap();
+        let context = Context::new()
+            .with_value("names", vec!["Ann", "Bob"])
+            .with_value("count", 2);
+        let expected = "<ul><li>Ann!</li><li>Bob!</li></ul><p>Total:2 items,  {2}</p>";
+        assert_eq!(page.render_with(&context).unwrap(), expected);
+        assert_eq!(
+            page.compile().unwrap().render_with(&context).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_safe_filter() {
+        let (_, page) = Block::parse_ignoring_comments(
+            r#"p { .title = "{html | safe}" "{html} {html | safe} {name | safe | upper}!" }"#,
+        )
+        .unwrap();
+        let context = Context::new()
+            .with_value("html", "<b title=\"x\">&amp;</b>")
+            .with_value("name", "<i>");
+        let expected = concat!(
+            r#"<p title="&lt;b title=&quot;x&quot;&gt;&amp;amp;&lt;/b&gt;">"#,
+            r#"&lt;b title="x"&gt;&amp;amp;&lt;/b&gt; <b title="x">&amp;</b> <I>!</p>"#,
+        );
+        assert_eq!(page.render_with(&context).unwrap(), expected);
+        assert_eq!(
+            page.compile().unwrap().render_with(&context).unwrap(),
+            expected
+        );
+
+        // Inside a script text is code, so values must be safe
+        let (_, page) = Block::parse(r#"script { "if (a < b) {code | safe}" }"#).unwrap();
+        let context = Context::new()
+            .with_value("name", "</script>")
+            .with_value("code", "start('</script>');");
+        let expected = r#"<script>if (a < b) start('<\/script>');</script>"#;
+        assert_eq!(page.render_with(&context).unwrap(), expected);
+        assert_eq!(
+            page.compile().unwrap().render_with(&context).unwrap(),
+            expected
+        );
+        let (_, page) = Block::parse(r#"script { "alert({name});" }"#).unwrap();
+        let unsafe_value = RenderError::UnsafeScriptValue("{name}".into());
+        assert_eq!(page.render_with(&context), Err(unsafe_value.clone()));
+        assert_eq!(
+            page.compile().unwrap().render_with(&context),
+            Err(unsafe_value)
+        );
+
+        // Style text is CSS, which is not escaped like other text, so values must
+        // be safe too
+        let (_, page) = Block::parse(r#"style { "a > b {{ color: {color} }}" }"#).unwrap();
+        let context = Context::new().with_value("color", "}body{background:url(//evil)}");
+        let unsafe_value = RenderError::UnsafeStyleValue("{color}".into());
+        assert_eq!(page.render_with(&context), Err(unsafe_value.clone()));
+        assert_eq!(
+            page.compile().unwrap().render_with(&context),
+            Err(unsafe_value)
+        );
+        let (_, page) = Block::parse(r#"style { "a > b {{ color: {color | safe} }}" }"#).unwrap();
+        let context = Context::new().with_value("color", "red");
+        let expected = "<style>a > b { color: red }</style>";
+        assert_eq!(page.render_with(&context).unwrap(), expected);
+        assert_eq!(
+            page.compile().unwrap().render_with(&context).unwrap(),
+            expected
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_context() {
+        let context = Context::from_json(&serde_json::json!({
+            "name": "Ann",
+            "age": 30,
+            "tags": ["a", "b"],
+        }));
+        assert_eq!(
+            context.interpolate("{name} is {age}: {tags}").unwrap(),
+            r#"Ann is 30: ["a","b"]"#
+        );
+    }
+}
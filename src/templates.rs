@@ -0,0 +1,731 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, PoisonError, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crate::{
+    error::line_column,
+    parse::parse_document,
+    prelude::*,
+    template::{Context, RenderError},
+};
+
+/// Extension of the template files loaded by a [`Registry`]
+pub const TEMPLATE_EXTENSION: &str = "rstml";
+
+/// A parsed template file
+#[derive(Debug)]
+pub struct Template {
+    /// Path of the file relative to the registry directory, e.g. `layouts/base.rstml`
+    pub name: String,
+    pub document: Document<'static>,
+    /// Registry epoch the file was loaded in, see [`Registry::epoch`]
+    pub epoch: u64,
+}
+
+impl Template {
+    /// Names of the templates this one extends or includes, in the order they
+    /// appear, without repeats
+    #[must_use]
+    pub fn dependencies(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        collect_dependencies(&self.document.children, &mut names);
+        names
+    }
+}
+
+fn collect_dependencies<'d>(nodes: &'d [Node<'static>], names: &mut Vec<&'d str>) {
+    for node in nodes {
+        match node {
+            Node::Directive(Directive::Extends(name) | Directive::Include(name))
+                if !names.contains(&name.as_ref()) =>
+            {
+                names.push(name);
+            }
+            Node::Element(element) => collect_dependencies(&element.children, names),
+            Node::Directive(directive) => collect_dependencies(directive.children(), names),
+            _ => {}
+        }
+    }
+}
+
+/// Error returned when a template cannot be loaded
+#[derive(Debug)]
+pub enum RegistryError {
+    Io(PathBuf, std::io::Error),
+    /// The file is not a valid RSTML document
+    Parse {
+        name: String,
+        message: String,
+    },
+    NotFound(String),
+    /// Templates extending or including each other, from the rendered template back
+    /// to the first repeated one
+    Cycle(Vec<String>),
+    /// An included template failed to load, `from` names the including template
+    Include {
+        from: String,
+        error: Box<RegistryError>,
+    },
+    /// The resolved template failed to render with the context
+    Render {
+        name: String,
+        error: RenderError,
+    },
+    #[cfg(feature = "watch")]
+    Watch(notify::Error),
+}
+
+impl std::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::Io(path, error) => write!(f, "Cannot read {}: {error}", path.display()),
+            RegistryError::Parse { name, message } => {
+                write!(f, "Invalid template {name}: {message}")
+            }
+            RegistryError::NotFound(name) => write!(f, "No template named {name}"),
+            RegistryError::Cycle(chain) => {
+                write!(
+                    f,
+                    "Templates extend or include each other: {}",
+                    chain.join(" -> ")
+                )
+            }
+            RegistryError::Include { from, error } => write!(f, "In {from}: {error}"),
+            RegistryError::Render { name, error } => write!(f, "Cannot render {name}: {error}"),
+            #[cfg(feature = "watch")]
+            RegistryError::Watch(error) => write!(f, "Cannot watch templates: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// Templates loaded from the `.rstml` files of a directory, by name
+///
+/// Changed files are re-parsed the next time any template is requested, either
+/// after [`Registry::mark_changed`] or, with the `watch` feature, automatically
+/// once [`Registry::watch`] is called. Each batch of changes starts a new epoch.
+///
+/// Templates are handed out as `Arc`s, so a render that holds one keeps a
+/// consistent version while the registry moves on to newer ones.
+#[derive(Debug)]
+pub struct Registry {
+    dir: PathBuf,
+    templates: RwLock<HashMap<String, Arc<Template>>>,
+    changed: Arc<Mutex<HashSet<PathBuf>>>,
+    epoch: AtomicU64,
+    #[cfg(feature = "watch")]
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+}
+
+// Parses a template file, `None` if it no longer exists
+fn read_template(path: &Path, name: &str, epoch: u64) -> Result<Option<Template>, RegistryError> {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(RegistryError::Io(path.to_path_buf(), error)),
+    };
+    parse_template(&source, name, epoch).map(Some)
+}
+
+// Parses a template's source, locating parse errors by line and column
+pub(crate) fn parse_template(
+    source: &str,
+    name: &str,
+    epoch: u64,
+) -> Result<Template, RegistryError> {
+    let document = parse_document(source).map_err(|error| RegistryError::Parse {
+        name: name.to_string(),
+        message: match error.offset(source) {
+            Some(offset) => {
+                let (line, column) = line_column(source, offset);
+                format!("{line}:{column}: {error}")
+            }
+            None => error.to_string(),
+        },
+    })?;
+    Ok(Template {
+        name: name.to_string(),
+        document: document.into_owned(),
+        epoch,
+    })
+}
+
+// Collects the template files below the directory
+fn template_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), RegistryError> {
+    let io_error = |error| RegistryError::Io(dir.to_path_buf(), error);
+    for entry in std::fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        if path.is_dir() {
+            template_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == TEMPLATE_EXTENSION)
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// The layout the template extends, named by its first top-level `@extends`
+fn extends<'d>(document: &'d Document) -> Option<&'d str> {
+    document.iter_nodes().find_map(|node| match node {
+        Node::Directive(Directive::Extends(name)) => Some(name.as_ref()),
+        _ => None,
+    })
+}
+
+// The content of a block with the name of the template it was written in
+type BlockContent<'d> = (&'d str, &'d [Node<'static>]);
+
+// Collects the content of the blocks at any depth, keeping blocks already collected
+// from templates further down the chain
+fn collect_blocks<'d>(
+    template: &'d str,
+    nodes: &'d [Node<'static>],
+    blocks: &mut HashMap<&'d str, BlockContent<'d>>,
+) {
+    for node in nodes {
+        match node {
+            Node::Directive(Directive::Block { name, children }) => {
+                blocks.entry(name).or_insert((template, children));
+                collect_blocks(template, children, blocks);
+            }
+            Node::Element(element) => collect_blocks(template, &element.children, blocks),
+            Node::Directive(directive) => collect_blocks(template, directive.children(), blocks),
+            _ => {}
+        }
+    }
+}
+
+/// A source of templates by name, e.g. a [`Registry`] reading a directory, a
+/// [`MemoryLoader`] holding embedded sources or a custom store
+///
+/// Loaders resolve the directives of their templates, see [`TemplateLoader::resolve`].
+pub trait TemplateLoader {
+    /// Returns the template with the name
+    ///
+    /// # Errors
+    /// Errors with [`RegistryError::NotFound`] if no template has the name, or if
+    /// the template cannot be read or parsed
+    fn load(&self, name: &str) -> Result<Arc<Template>, RegistryError>;
+
+    /// Returns the template with the layouts it extends and the partials it
+    /// includes applied
+    ///
+    /// A template starting with `@extends("layouts/base.rstml")` renders as that
+    /// layout, with each `@block(name) { ... }` of the layout replaced by the
+    /// template's block of the same name. Layouts may extend other layouts, the
+    /// block closest to the template wins, and blocks that no template replaces
+    /// keep their default content. Everything outside the template's blocks is
+    /// ignored. Each `@include("partials/nav.rstml")` is replaced by the resolved
+    /// partial. The result holds no layout directives, `@if` and `@for` are left for
+    /// rendering with a context.
+    ///
+    /// # Errors
+    /// Errors if a template in the chain cannot be loaded, wrapped in
+    /// [`RegistryError::Include`] for each include it is reached through, or if
+    /// templates extend or include each other in a cycle
+    fn resolve(&self, name: &str) -> Result<Document<'static>, RegistryError> {
+        let mut resolver = Resolver {
+            loader: self,
+            stack: Vec::new(),
+        };
+        let children = resolver.resolve(name)?;
+        Ok(Document::new().with_children(children))
+    }
+
+    /// Resolves the template and renders it with the context, see
+    /// [`TemplateLoader::resolve`] and [`Block::render_with`]
+    ///
+    /// # Errors
+    /// Errors if the template cannot be resolved or a placeholder has no value
+    fn render(&self, name: &str, context: &Context) -> Result<String, RegistryError> {
+        self.resolve(name)?
+            .render_with(context)
+            .map_err(|error| RegistryError::Render {
+                name: name.to_string(),
+                error,
+            })
+    }
+}
+
+// Resolves the directives of templates, tracking the templates being resolved
+struct Resolver<'l, L: ?Sized> {
+    loader: &'l L,
+    // Names of the templates being resolved, outermost first
+    stack: Vec<String>,
+}
+
+impl<L: TemplateLoader + ?Sized> Resolver<'_, L> {
+    // Loads a template, erroring if it is already being resolved
+    fn load(&mut self, name: &str) -> Result<Arc<Template>, RegistryError> {
+        if self.stack.iter().any(|resolving| resolving == name) {
+            let mut chain = self.stack.clone();
+            chain.push(name.to_string());
+            return Err(RegistryError::Cycle(chain));
+        }
+        let template = self.loader.load(name)?;
+        self.stack.push(name.to_string());
+        Ok(template)
+    }
+
+    fn resolve(&mut self, name: &str) -> Result<Vec<Node<'static>>, RegistryError> {
+        let depth = self.stack.len();
+        let mut chain = vec![self.load(name)?];
+        while let Some(layout) = extends(&chain[chain.len() - 1].document) {
+            let layout = self.load(layout)?;
+            chain.push(layout);
+        }
+        let mut blocks = HashMap::new();
+        for template in &chain {
+            collect_blocks(&template.name, &template.document.children, &mut blocks);
+        }
+        let root = &chain[chain.len() - 1];
+        let mut children = Vec::new();
+        self.fill(
+            &root.name,
+            &root.document.children,
+            &blocks,
+            &mut Vec::new(),
+            &mut children,
+        )?;
+        self.stack.truncate(depth);
+        Ok(children)
+    }
+
+    // Clones the nodes written in `template` with each block replaced by its content
+    // and each include by its template, dropping the `@extends` directives
+    fn fill<'d>(
+        &mut self,
+        template: &'d str,
+        nodes: &'d [Node<'static>],
+        blocks: &HashMap<&str, BlockContent<'d>>,
+        // Blocks being filled, whose own name inside their content means their default
+        filling: &mut Vec<&'d str>,
+        out: &mut Vec<Node<'static>>,
+    ) -> Result<(), RegistryError> {
+        for node in nodes {
+            match node {
+                Node::Directive(Directive::Block { name, children }) => {
+                    let (from, content) = match blocks.get(name.as_ref()) {
+                        Some(&content) if !filling.contains(&name.as_ref()) => content,
+                        _ => (template, children.as_slice()),
+                    };
+                    filling.push(name);
+                    self.fill(from, content, blocks, filling, out)?;
+                    filling.pop();
+                }
+                Node::Directive(Directive::Include(name)) => {
+                    let included = self.resolve(name).map_err(|error| match error {
+                        RegistryError::Cycle(_) => error,
+                        error => RegistryError::Include {
+                            from: template.to_string(),
+                            error: Box::new(error),
+                        },
+                    })?;
+                    out.extend(included);
+                }
+                Node::Directive(Directive::Extends(_)) => {}
+                // Conditions and loops are kept for rendering, with their content filled
+                Node::Directive(directive) => {
+                    let mut filled = directive.clone();
+                    if let Some(children) = filled.children_mut() {
+                        children.clear();
+                        self.fill(template, directive.children(), blocks, filling, children)?;
+                    }
+                    out.push(Node::Directive(filled));
+                }
+                Node::Element(element) => {
+                    let mut filled = element.clone();
+                    filled.children = Vec::with_capacity(element.children.len());
+                    self.fill(
+                        template,
+                        &element.children,
+                        blocks,
+                        filling,
+                        &mut filled.children,
+                    )?;
+                    out.push(filled.into());
+                }
+                node => out.push(node.clone()),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TemplateLoader for Registry {
+    fn load(&self, name: &str) -> Result<Arc<Template>, RegistryError> {
+        self.get(name)
+    }
+}
+
+/// Templates parsed from sources held in memory, e.g. files embedded with
+/// `include_str!`
+#[derive(Debug, Default)]
+pub struct MemoryLoader {
+    templates: HashMap<String, Arc<Template>>,
+}
+
+impl MemoryLoader {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the source as the template with the name, replacing any template
+    /// of the same name
+    ///
+    /// # Errors
+    /// Errors if the source is not a valid RSTML document
+    pub fn add(&mut self, name: impl Into<String>, source: &str) -> Result<(), RegistryError> {
+        let name = name.into();
+        let template = parse_template(source, &name, 0)?;
+        self.templates.insert(name, Arc::from(template));
+        Ok(())
+    }
+
+    /// Names of the templates, sorted
+    #[must_use]
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.templates.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+impl TemplateLoader for MemoryLoader {
+    fn load(&self, name: &str) -> Result<Arc<Template>, RegistryError> {
+        self.templates
+            .get(name)
+            .cloned()
+            .ok_or_else(|| RegistryError::NotFound(name.to_string()))
+    }
+}
+
+impl Registry {
+    /// Loads every `.rstml` file below the directory
+    ///
+    /// # Errors
+    /// Errors if the directory cannot be read or a template fails to parse
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, RegistryError> {
+        let dir = dir.as_ref();
+        let registry = Registry {
+            dir: dir
+                .canonicalize()
+                .map_err(|error| RegistryError::Io(dir.to_path_buf(), error))?,
+            templates: RwLock::default(),
+            changed: Arc::default(),
+            epoch: AtomicU64::new(0),
+            #[cfg(feature = "watch")]
+            watcher: Mutex::default(),
+        };
+        registry.reload()?;
+        Ok(registry)
+    }
+
+    /// Re-reads every template in the directory, starting a new epoch
+    ///
+    /// # Errors
+    /// Errors if the directory cannot be read or a template fails to parse, in
+    /// which case the previously loaded templates are kept
+    pub fn reload(&self) -> Result<(), RegistryError> {
+        let mut files = Vec::new();
+        template_files(&self.dir, &mut files)?;
+        let epoch = self.epoch.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut templates = HashMap::new();
+        for path in files {
+            let Some(name) = self.name_of(&path) else {
+                continue;
+            };
+            if let Some(template) = read_template(&path, &name, epoch)? {
+                templates.insert(name, Arc::from(template));
+            }
+        }
+        *self
+            .templates
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = templates;
+        Ok(())
+    }
+
+    /// Directory the templates are loaded from
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Current epoch, increased every time changed files are re-read
+    #[must_use]
+    pub fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::SeqCst)
+    }
+
+    /// Names of the loaded templates, sorted
+    #[must_use]
+    pub fn names(&self) -> Vec<String> {
+        let templates = self
+            .templates
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut names: Vec<_> = templates.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    // Name of a template file, its path relative to the directory with `/` separators
+    fn name_of(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.dir).ok()?;
+        if relative.extension()? != TEMPLATE_EXTENSION {
+            return None;
+        }
+        let parts: Option<Vec<&str>> = relative.iter().map(|part| part.to_str()).collect();
+        Some(parts?.join("/"))
+    }
+
+    /// Marks a file as changed, so it is re-read before the next template is served
+    ///
+    /// Paths may be absolute or relative to the registry directory.
+    pub fn mark_changed(&self, path: impl AsRef<Path>) {
+        let path = self.dir.join(path);
+        self.changed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(path);
+    }
+
+    // Re-reads changed files, returning the errors by template name
+    //
+    // Files that fail to load stay marked, so they are retried until fixed
+    pub(crate) fn apply_changes(&self) -> HashMap<String, RegistryError> {
+        let mut changed = self.changed.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut failed = HashMap::new();
+        if changed.is_empty() {
+            return failed;
+        }
+        let epoch = self.epoch.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut templates = self
+            .templates
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut retry = HashSet::new();
+        for path in changed.drain() {
+            let Some(name) = self.name_of(&path) else {
+                continue;
+            };
+            match read_template(&path, &name, epoch) {
+                Ok(Some(template)) => {
+                    templates.insert(name, Arc::from(template));
+                }
+                Ok(None) => {
+                    templates.remove(&name);
+                }
+                Err(error) => {
+                    failed.insert(name, error);
+                    retry.insert(path);
+                }
+            }
+        }
+        *changed = retry;
+        failed
+    }
+
+    /// Returns the latest version of the template, re-reading changed files first
+    ///
+    /// # Errors
+    /// Errors if no template has the name, or if its file changed and no longer parses
+    pub fn get(&self, name: &str) -> Result<Arc<Template>, RegistryError> {
+        if let Some(error) = self.apply_changes().remove(name) {
+            return Err(error);
+        }
+        self.templates
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .cloned()
+            .ok_or_else(|| RegistryError::NotFound(name.to_string()))
+    }
+
+    /// Watches the directory, marking files as changed when they are written,
+    /// created or removed
+    ///
+    /// The watch lasts as long as the registry.
+    ///
+    /// # Errors
+    /// Errors if the platform watcher cannot be started
+    #[cfg(feature = "watch")]
+    pub fn watch(&self) -> Result<(), RegistryError> {
+        use notify::Watcher;
+
+        let changed = Arc::clone(&self.changed);
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if event.kind.is_access() {
+                    return;
+                }
+                changed
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .extend(event.paths);
+            })
+            .map_err(RegistryError::Watch)?;
+        watcher
+            .watch(&self.dir, notify::RecursiveMode::Recursive)
+            .map_err(RegistryError::Watch)?;
+        *self.watcher.lock().unwrap_or_else(PoisonError::into_inner) = Some(watcher);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{MemoryLoader, Registry, RegistryError, TemplateLoader};
+    use crate::{prelude::*, template::Context};
+
+    #[test]
+    fn test_registry_reloads_changed_files() {
+        let dir = std::env::temp_dir().join(format!("rs-tml-registry-{}", std::process::id()));
+        fs::create_dir_all(dir.join("partials")).unwrap();
+        fs::write(dir.join("index.rstml"), r#"h1 { "Home" }"#).unwrap();
+        fs::write(dir.join("partials/nav.rstml"), "nav { // links\n }").unwrap();
+
+        let registry = Registry::load(&dir).unwrap();
+        assert_eq!(registry.names(), ["index.rstml", "partials/nav.rstml"]);
+        let first = registry.get("index.rstml").unwrap();
+        assert_eq!(first.document.render(), "<h1>Home</h1>");
+
+        fs::write(dir.join("index.rstml"), r#"h1 { "Welcome" }"#).unwrap();
+        registry.mark_changed("index.rstml");
+        let second = registry.get("index.rstml").unwrap();
+        assert_eq!(second.document.render(), "<h1>Welcome</h1>");
+        assert!(second.epoch > first.epoch);
+        // A render holding the first version is unaffected
+        assert_eq!(first.document.render(), "<h1>Home</h1>");
+
+        fs::write(dir.join("index.rstml"), r#"h1 { "Broken" "#).unwrap();
+        registry.mark_changed("index.rstml");
+        assert!(matches!(
+            registry.get("index.rstml"),
+            Err(RegistryError::Parse { .. })
+        ));
+        assert!(registry.get("partials/nav.rstml").is_ok());
+        assert!(matches!(
+            registry.get("missing.rstml"),
+            Err(RegistryError::NotFound(_))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_extends() {
+        let dir = std::env::temp_dir().join(format!("rs-tml-extends-{}", std::process::id()));
+        fs::create_dir_all(dir.join("layouts")).unwrap();
+        fs::write(
+            dir.join("layouts/base.rstml"),
+            r#"html { body {
+                @block(header) { h1 { "Site" } }
+                main { @block(content) { p { "Empty" } } }
+                @block(footer) { footer { "Site footer" } }
+            } }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("layouts/page.rstml"),
+            r#"@extends("layouts/base.rstml")
+            @block(content) { article { @block(body) {} } }
+            @block(footer) { footer { "Page footer" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("index.rstml"),
+            r#"@extends("layouts/page.rstml") @block(body) { p { "Hello {name}" } }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("a.rstml"), r#"@extends("b.rstml")"#).unwrap();
+        fs::write(dir.join("b.rstml"), r#"@extends("a.rstml")"#).unwrap();
+
+        let registry = Registry::load(&dir).unwrap();
+        let context = Context::new().with_value("name", "Ann");
+        assert_eq!(
+            registry.render("index.rstml", &context).unwrap(),
+            "<html><body><h1>Site</h1><main><article><p>Hello Ann</p></article></main>\
+             <footer>Page footer</footer></body></html>"
+        );
+        // A layout rendered on its own shows its defaults
+        assert!(
+            registry
+                .get("layouts/base.rstml")
+                .unwrap()
+                .document
+                .render()
+                .contains("<main><p>Empty</p></main>")
+        );
+        match registry.resolve("a.rstml") {
+            Err(RegistryError::Cycle(chain)) => {
+                assert_eq!(chain, ["a.rstml", "b.rstml", "a.rstml"])
+            }
+            other => panic!("expected a cycle, got {other:?}"),
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_include() {
+        let mut loader = MemoryLoader::new();
+        loader
+            .add(
+                "layouts/base.rstml",
+                r#"body { @include("partials/nav.rstml") @block(content) {} }"#,
+            )
+            .unwrap();
+        loader
+            .add(
+                "partials/nav.rstml",
+                r#"nav { @include("partials/link.rstml") }"#,
+            )
+            .unwrap();
+        loader
+            .add("partials/link.rstml", r#"a { .href = "/" "Home" }"#)
+            .unwrap();
+        loader
+            .add(
+                "index.rstml",
+                r#"@extends("layouts/base.rstml") @block(content) { @include("partials/missing.rstml") }"#,
+            )
+            .unwrap();
+        loader
+            .add("loop.rstml", r#"div { @include("partials/loop.rstml") }"#)
+            .unwrap();
+        loader
+            .add("partials/loop.rstml", r#"p { @include("loop.rstml") }"#)
+            .unwrap();
+
+        assert_eq!(
+            loader.resolve("layouts/base.rstml").unwrap().render(),
+            r#"<body><nav><a href="/">Home</a></nav></body>"#
+        );
+        // The missing partial is named along with the template including it
+        assert_eq!(
+            loader.resolve("index.rstml").unwrap_err().to_string(),
+            "In index.rstml: No template named partials/missing.rstml"
+        );
+        match loader.resolve("loop.rstml") {
+            Err(RegistryError::Cycle(chain)) => {
+                assert_eq!(chain, ["loop.rstml", "partials/loop.rstml", "loop.rstml"])
+            }
+            other => panic!("expected a cycle, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,802 @@
+use crate::{
+    convert::from_html,
+    edit::PREFORMATTED_ELEMENTS,
+    prelude::*,
+    render::{close_tag, is_raw_text, open_tag, render_node},
+};
+
+// Elements whose content is raw text, compared as the markup it renders to
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+fn canonical_nodes(nodes: &[Node], reparse: bool, out: &mut Vec<Node<'static>>) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push(Node::text(text.content.to_string())),
+            Node::Element(element) => out.push(canonical_element(element, reparse).into()),
+            Node::Shared(element) => out.push(canonical_element(element, reparse).into()),
+            // Raw markup is compared as the nodes a browser would parse from it
+            Node::Raw(raw) if reparse => canonical_nodes(&from_html(raw).children, false, out),
+            Node::Raw(raw) => out.push(Node::raw(raw.to_string())),
+            Node::Comment(comment) => out.push(Node::comment(comment.to_string())),
+            // Unfilled slots render nothing
+            Node::Slot(_) => {}
+            Node::Directive(directive) => canonical_nodes(directive.children(), reparse, out),
+            #[cfg(feature = "i18n")]
+            Node::Translation(_) => out.push(node.clone().into_owned()),
+        }
+    }
+}
+
+fn canonical_element(element: &Element, reparse: bool) -> Element<'static> {
+    let name = element.name.as_str().to_ascii_lowercase();
+    let mut canonical = Element::new(name.clone());
+    // The namespace declaration written when rendering foreign content
+    let mut attributes: Vec<Attribute<'static>> = element
+        .attributes
+        .iter()
+        .filter(|a| !(a.key == "xmlns" && a.value == element.namespace.uri()))
+        .map(|a| Attribute::new(a.key.to_ascii_lowercase(), a.value.to_string()))
+        .collect();
+    attributes.sort();
+    canonical.attributes = attributes;
+    if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+        let content: String = element.children.iter().map(Render::render).collect();
+        if !content.is_empty() {
+            canonical.add_child(Node::raw(content));
+        }
+    } else {
+        let mut children = Vec::with_capacity(element.children.len());
+        canonical_nodes(&element.children, reparse, &mut children);
+        canonical.add_children(children);
+    }
+    canonical
+}
+
+// Trims text outside preformatted elements, dropping text left empty
+fn trim_text(nodes: &mut Vec<Node>) {
+    for node in nodes.iter_mut() {
+        match node {
+            Node::Text(text) if text.content.trim().len() != text.content.len() => {
+                text.content = text.content.trim().to_string().into();
+            }
+            Node::Element(element) if !PREFORMATTED_ELEMENTS.contains(&element.name.as_str()) => {
+                trim_text(&mut element.children);
+            }
+            _ => {}
+        }
+    }
+    nodes.retain(|node| !matches!(node, Node::Text(text) if text.content.is_empty()));
+}
+
+/// Returns the document in the canonical form compared by [`assert_roundtrip`]
+///
+/// Tag and attribute names are lowercased and attributes sorted, shared elements
+/// become plain elements, raw markup is parsed into nodes and `script` and `style`
+/// contents become the raw text they render to. Slots, which render nothing, are
+/// dropped. Outside preformatted elements whitespace runs collapse to a single
+/// space, text is trimmed and whitespace-only text is dropped.
+#[must_use]
+pub fn normalize(document: &Document) -> Document<'static> {
+    let mut children = Vec::with_capacity(document.children.len());
+    canonical_nodes(&document.children, true, &mut children);
+    let mut normalized = Document::new().with_children(children);
+    normalized.normalize_whitespace();
+    trim_text(&mut normalized.children);
+    normalized
+}
+
+/// Asserts that the document survives rendering to HTML and parsing it back with
+/// [`from_html`], comparing both in their [`normalize`]d form
+///
+/// # Panics
+/// Panics with the rendered HTML and both trees if the parsed document differs,
+/// e.g. when a transform nests a `div` inside a `p`, which browsers move out.
+#[track_caller]
+pub fn assert_roundtrip(document: &Document) {
+    let html = document.render();
+    let expected = normalize(document);
+    let parsed = normalize(&from_html(&html));
+    assert!(
+        parsed == expected,
+        "Document changed after rendering and parsing it\nHTML:\n{html}\nExpected:\n{}\nParsed:\n{}\n{}",
+        expected.to_rstml(),
+        parsed.to_rstml(),
+        pretty_diff_documents(&expected, &parsed)
+    );
+}
+
+// Where two trees first differ, see `pretty_diff`
+struct Divergence<'d, 'a> {
+    path: NodePath,
+    // `None` where one side has fewer children
+    expected: Option<&'d Node<'a>>,
+    actual: Option<&'d Node<'a>>,
+    // The actual node's parent and siblings, for context
+    parent: Option<&'d Element<'a>>,
+    siblings: &'d [Node<'a>],
+}
+
+fn diverge_nodes<'d, 'a>(
+    expected: &'d [Node<'a>],
+    actual: &'d [Node<'a>],
+    path: &mut NodePath,
+    parent: Option<&'d Element<'a>>,
+) -> Option<Divergence<'d, 'a>> {
+    for index in 0..expected.len().max(actual.len()) {
+        path.push(index);
+        let divergence = match (expected.get(index), actual.get(index)) {
+            (Some(expected), Some(node)) => diverge_node(expected, node, path, parent, actual),
+            (expected, node) => Some(Divergence {
+                path: path.clone(),
+                expected,
+                actual: node,
+                parent,
+                siblings: actual,
+            }),
+        };
+        if divergence.is_some() {
+            return divergence;
+        }
+        path.pop();
+    }
+    None
+}
+
+fn diverge_node<'d, 'a>(
+    expected: &'d Node<'a>,
+    actual: &'d Node<'a>,
+    path: &mut NodePath,
+    parent: Option<&'d Element<'a>>,
+    siblings: &'d [Node<'a>],
+) -> Option<Divergence<'d, 'a>> {
+    if expected == actual {
+        return None;
+    }
+    // Elements differing only below them differ at a descendant
+    if let (Some(expected), Some(element)) = (expected.as_element(), actual.as_element())
+        && expected.name == element.name
+        && expected.namespace == element.namespace
+        && expected.attributes == element.attributes
+        && let Some(divergence) =
+            diverge_nodes(&expected.children, &element.children, path, Some(element))
+    {
+        return Some(divergence);
+    }
+    Some(Divergence {
+        path: path.clone(),
+        expected: Some(expected),
+        actual: Some(actual),
+        parent,
+        siblings,
+    })
+}
+
+// The tag and attributes of an element, e.g. `a .href = "/"`
+fn opening(element: &Element) -> String {
+    let mut line = element.name.as_str().to_string();
+    for attribute in &element.attributes {
+        line.push_str(&format!(" .{} = {:?}", attribute.key, attribute.value));
+    }
+    line
+}
+
+// One line describing a node, e.g. `p .class = "a" { 2 children }`
+fn summary(node: &Node) -> String {
+    match node {
+        Node::Text(text) => format!("{:?}", text.content),
+        node => match node.as_element() {
+            Some(element) => {
+                let children = match element.children.as_slice() {
+                    [] => "{}".to_string(),
+                    [Node::Text(text)] => format!("{{ {:?} }}", text.content),
+                    [_] => "{ 1 child }".to_string(),
+                    children => format!("{{ {} children }}", children.len()),
+                };
+                format!("{} {children}", opening(element))
+            }
+            None => format!("{node:?}"),
+        },
+    }
+}
+
+// Siblings shown on each side of the differing node
+const CONTEXT_SIBLINGS: usize = 2;
+
+fn write_divergence(divergence: &Divergence, out: &mut String) {
+    out.push_str(&format!("First difference at {}\n", divergence.path));
+    match (divergence.expected, divergence.actual) {
+        (Some(expected), Some(actual)) => {
+            out.push_str(&format!("  expected: {}\n", summary(expected)));
+            out.push_str(&format!("  actual:   {}\n", summary(actual)));
+            if let (Some(expected), Some(actual)) = (expected.as_element(), actual.as_element())
+                && expected.attributes != actual.attributes
+            {
+                out.push_str("Attributes:\n");
+                for attribute in &expected.attributes {
+                    match actual.attr(&attribute.key) {
+                        None => out
+                            .push_str(&format!("  - .{} = {:?}\n", attribute.key, attribute.value)),
+                        Some(value) if value != attribute.value => out.push_str(&format!(
+                            "  ~ .{} = {:?} -> {value:?}\n",
+                            attribute.key, attribute.value
+                        )),
+                        Some(_) => {}
+                    }
+                }
+                for attribute in &actual.attributes {
+                    if !expected.has_attr(&attribute.key) {
+                        out.push_str(&format!("  + .{} = {:?}\n", attribute.key, attribute.value));
+                    }
+                }
+            }
+        }
+        (Some(expected), None) => out.push_str(&format!("  missing:    {}\n", summary(expected))),
+        (None, Some(actual)) => out.push_str(&format!("  unexpected: {}\n", summary(actual))),
+        (None, None) => {}
+    }
+    let Some(&index) = divergence.path.indices().last() else {
+        return;
+    };
+    // The actual tree around the differing node, which is marked with `>`
+    out.push_str("Context:\n");
+    let indent = if let Some(parent) = divergence.parent {
+        out.push_str(&format!("    {} {{\n", opening(parent)));
+        "    "
+    } else {
+        ""
+    };
+    let start = index.saturating_sub(CONTEXT_SIBLINGS);
+    let end = (index + CONTEXT_SIBLINGS + 1).min(divergence.siblings.len());
+    if start > 0 {
+        out.push_str(&format!("    {indent}...\n"));
+    }
+    for (i, sibling) in divergence.siblings[start.min(end)..end]
+        .iter()
+        .enumerate()
+        .map(|(i, sibling)| (i + start, sibling))
+    {
+        let marker = if i == index { "  > " } else { "    " };
+        out.push_str(&format!("{marker}{indent}{}\n", summary(sibling)));
+    }
+    if end < divergence.siblings.len() {
+        out.push_str(&format!("    {indent}...\n"));
+    }
+    if divergence.parent.is_some() {
+        out.push_str("    }\n");
+    }
+}
+
+/// Describes where two trees first differ, for test failures
+///
+/// Shows the path of the first differing node relative to the compared nodes, both
+/// sides of it, the attributes added, removed or changed there and the surrounding
+/// nodes of the actual tree. Returns an empty string if the nodes are equal.
+#[must_use]
+pub fn pretty_diff(expected: &Node, actual: &Node) -> String {
+    let mut out = String::new();
+    if let Some(divergence) = diverge_node(expected, actual, &mut NodePath::root(), None, &[]) {
+        write_divergence(&divergence, &mut out);
+    }
+    out
+}
+
+/// Describes where two documents first differ, see [`pretty_diff`]
+#[must_use]
+pub fn pretty_diff_documents(expected: &Document, actual: &Document) -> String {
+    let mut out = String::new();
+    if let Some(divergence) = diverge_nodes(
+        &expected.children,
+        &actual.children,
+        &mut NodePath::root(),
+        None,
+    ) {
+        write_divergence(&divergence, &mut out);
+    }
+    out
+}
+
+// Prints one element, text or comment per line, indented by two spaces, so that a
+// diff points at the differing node. Elements holding only text stay on one line.
+fn write_indented(
+    nodes: &[Node],
+    parent: Namespace,
+    depth: usize,
+    out: &mut String,
+) -> std::fmt::Result {
+    let indent = "  ".repeat(depth);
+    for node in nodes {
+        out.push_str(&indent);
+        match node.as_element() {
+            Some(element)
+                if !is_raw_text(element)
+                    && element.children.iter().any(|child| !child.is_text()) =>
+            {
+                if open_tag(element, parent, out)? {
+                    out.push('\n');
+                    write_indented(&element.children, element.namespace, depth + 1, out)?;
+                    out.push_str(&indent);
+                    close_tag(element.name.as_str(), out)?;
+                }
+            }
+            _ => render_node(node, parent, None, out)?,
+        }
+        out.push('\n');
+    }
+    Ok(())
+}
+
+// Parses and normalizes HTML, printing it with `write_indented`
+fn indented_html(html: &str) -> String {
+    let mut out = String::new();
+    write_indented(
+        &normalize(&from_html(html)).children,
+        Namespace::Html,
+        0,
+        &mut out,
+    )
+    .expect("writing to a String cannot fail");
+    out
+}
+
+// Lines of context around each change in a unified diff
+const DIFF_CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Remove,
+    Insert,
+}
+
+// The shortest edit turning the old lines into the new ones, from their longest
+// common subsequence
+fn line_edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    // `common[i][j]` is the length of the longest common subsequence of the suffixes
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push(Edit::Keep);
+            (i, j) = (i + 1, j + 1);
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            edits.push(Edit::Remove);
+            i += 1;
+        } else {
+            edits.push(Edit::Insert);
+            j += 1;
+        }
+    }
+    edits
+}
+
+/// Returns a line-based unified diff turning `expected` into `actual`, with three
+/// lines of context around each change, or an empty string if they are equal
+#[must_use]
+pub fn unified_diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+    let edits = line_edits(&old, &new);
+    let changes: Vec<usize> = (0..edits.len())
+        .filter(|&k| edits[k] != Edit::Keep)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+    // Changes closer than twice the context share a hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &k in &changes {
+        let start = k.saturating_sub(DIFF_CONTEXT);
+        let end = (k + DIFF_CONTEXT + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    let mut out = String::from("--- expected\n+++ actual\n");
+    let (mut i, mut j, mut k) = (0, 0, 0);
+    for (start, end) in hunks {
+        // Advance to the hunk over kept lines
+        for edit in &edits[k..start] {
+            i += usize::from(*edit != Edit::Insert);
+            j += usize::from(*edit != Edit::Remove);
+        }
+        let hunk = &edits[start..end];
+        let old_len = hunk.iter().filter(|edit| **edit != Edit::Insert).count();
+        let new_len = hunk.iter().filter(|edit| **edit != Edit::Remove).count();
+        // Empty ranges start at the line before them
+        let old_start = if old_len == 0 { i } else { i + 1 };
+        let new_start = if new_len == 0 { j } else { j + 1 };
+        out.push_str(&format!(
+            "@@ -{old_start},{old_len} +{new_start},{new_len} @@\n"
+        ));
+        for edit in hunk {
+            let line = match edit {
+                Edit::Keep => {
+                    (i, j) = (i + 1, j + 1);
+                    format!(" {}", old[i - 1])
+                }
+                Edit::Remove => {
+                    i += 1;
+                    format!("-{}", old[i - 1])
+                }
+                Edit::Insert => {
+                    j += 1;
+                    format!("+{}", new[j - 1])
+                }
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+        k = end;
+    }
+    out
+}
+
+/// Asserts that the node, element or document renders to the expected HTML, see
+/// [`crate::assert_html_eq!`]
+///
+/// Both sides are parsed with [`from_html`] and [`normalize`]d, so whitespace
+/// between tags, runs of whitespace in text and the order of attributes do not
+/// matter.
+///
+/// # Panics
+/// Panics with a unified diff of both sides, printed one node per line, if they
+/// differ.
+#[track_caller]
+pub fn assert_html_eq(actual: &impl Render, expected: &str) {
+    let actual = indented_html(&actual.render());
+    let expected = indented_html(expected);
+    assert!(
+        actual == expected,
+        "HTML differs from the expected markup\n{}",
+        unified_diff(&expected, &actual)
+    );
+}
+
+/// Asserts that a node, element or document renders to the expected HTML, ignoring
+/// whitespace between tags and the order of attributes
+///
+/// Hand-written markup is easier to read than the equivalent `element(...)` chain,
+/// and failures print a unified diff of both sides, see
+/// [`testing::assert_html_eq`](crate::testing::assert_html_eq).
+///
+/// ```
+/// use rs_tml::{assert_html_eq, prelude::*};
+///
+/// let link = element("a").with_key_value("href", "/").with_key_value("class", "nav");
+/// assert_html_eq!(link, r#"<a class="nav" href="/"></a>"#);
+/// ```
+#[macro_export]
+macro_rules! assert_html_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::testing::assert_html_eq(&$actual, $expected)
+    };
+}
+
+// Elements holding flow content, i.e. block and phrasing elements
+const FLOW_TAGS: &[&str] = &["div", "section", "article", "aside", "blockquote", "main"];
+
+// Elements holding phrasing content only
+const PHRASING_TAGS: &[&str] = &["span", "em", "strong", "code", "b", "i", "small", "mark"];
+
+const WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "a & b",
+    "<tag>",
+    "\"quoted\"",
+    "it's",
+    "café",
+    "日本語",
+    "1 < 2",
+];
+
+/// Generates random documents from a seed, for checking that transforms preserve
+/// their semantics
+///
+/// Documents mix block and inline elements, lists, void elements, attributes and
+/// text with characters that need escaping. They follow the HTML content model, so
+/// they pass [`assert_roundtrip`]. The same seed always generates the same documents,
+/// which the generator yields as an iterator.
+///
+/// ```
+/// use rs_tml::testing::{TreeGenerator, assert_roundtrip};
+///
+/// for document in TreeGenerator::new(7).take(10) {
+///     assert_roundtrip(&document);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TreeGenerator {
+    state: u64,
+    max_depth: usize,
+    max_children: usize,
+}
+
+impl TreeGenerator {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        TreeGenerator {
+            // xorshift gets stuck on a zero state
+            state: seed ^ 0x9e37_79b9_7f4a_7c15,
+            max_depth: 4,
+            max_children: 4,
+        }
+    }
+
+    /// Sets how deeply elements nest, 4 by default
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the most children of an element, 4 by default
+    #[must_use]
+    pub fn with_max_children(mut self, max_children: usize) -> Self {
+        self.max_children = max_children;
+        self
+    }
+
+    // xorshift64*
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // A number in `0..n`
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+
+    fn pick<'t>(&mut self, items: &[&'t str]) -> &'t str {
+        items[self.below(items.len())]
+    }
+
+    fn text(&mut self) -> Node<'static> {
+        let words: Vec<&str> = (0..=self.below(3)).map(|_| self.pick(WORDS)).collect();
+        Node::text(words.join(" "))
+    }
+
+    fn add_attributes(&mut self, element: &mut Element<'static>) {
+        if self.below(3) == 0 {
+            element.add_key_value("class", self.pick(&["card", "wide", "card wide"]));
+        }
+        if self.below(4) == 0 {
+            element.add_key_value("title", self.pick(WORDS));
+        }
+        if self.below(6) == 0 {
+            element.add_key_value("data-id", self.below(100).to_string());
+        }
+        if self.below(8) == 0 {
+            element.add_attribute(Attribute::boolean("hidden"));
+        }
+    }
+
+    fn phrasing(&mut self, depth: usize) -> Node<'static> {
+        match self.below(8) {
+            0..=2 => self.text(),
+            3 => element(self.pick(&["br", "wbr"])).into(),
+            4 => {
+                let mut image = element("img").with_key_value("src", "/image.png");
+                image.add_key_value("alt", self.pick(WORDS));
+                image.into()
+            }
+            _ if depth >= self.max_depth => self.text(),
+            _ => {
+                let mut inline = element(self.pick(PHRASING_TAGS));
+                self.add_attributes(&mut inline);
+                for _ in 0..self.below(self.max_children + 1) {
+                    inline.add_child(self.phrasing(depth + 1));
+                }
+                inline.into()
+            }
+        }
+    }
+
+    fn flow(&mut self, depth: usize) -> Node<'static> {
+        if depth >= self.max_depth {
+            return self.phrasing(depth);
+        }
+        match self.below(10) {
+            0 => element("hr").into(),
+            1..=3 => self.phrasing(depth),
+            4 | 5 => {
+                let mut paragraph = element(self.pick(&["p", "h2", "h3"]));
+                self.add_attributes(&mut paragraph);
+                for _ in 0..=self.below(self.max_children) {
+                    paragraph.add_child(self.phrasing(depth + 1));
+                }
+                paragraph.into()
+            }
+            6 => {
+                let mut list = element(self.pick(&["ul", "ol"]));
+                for _ in 0..=self.below(self.max_children) {
+                    let mut item = element("li");
+                    for _ in 0..self.below(self.max_children) {
+                        item.add_child(self.flow(depth + 2));
+                    }
+                    list.add_child(item);
+                }
+                list.into()
+            }
+            _ => self.element_at(depth).into(),
+        }
+    }
+
+    fn element_at(&mut self, depth: usize) -> Element<'static> {
+        let mut block = element(self.pick(FLOW_TAGS));
+        self.add_attributes(&mut block);
+        for _ in 0..self.below(self.max_children + 1) {
+            block.add_child(self.flow(depth + 1));
+        }
+        block
+    }
+
+    /// Generates a block element such as a `div` or `section` and its subtree
+    pub fn element(&mut self) -> Element<'static> {
+        self.element_at(0)
+    }
+
+    /// Generates a document of up to the maximum number of top-level nodes
+    pub fn document(&mut self) -> Document<'static> {
+        let children: Vec<_> = (0..=self.below(self.max_children))
+            .map(|_| self.flow(0))
+            .collect();
+        Document::new().with_children(children)
+    }
+}
+
+impl Iterator for TreeGenerator {
+    type Item = Document<'static>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.document())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::AssertUnwindSafe;
+
+    use super::{TreeGenerator, assert_roundtrip, normalize, pretty_diff, unified_diff};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_normalize() {
+        let (_, mut written) =
+            Document::parse(r#"div { .id = "x" .class = "a" "  Hello   world " span { "!" } }"#)
+                .unwrap();
+        written.children[0]
+            .as_element_mut()
+            .unwrap()
+            .add_child(Node::raw("<b>bold</b>"));
+        let built = Document::new().with_child(
+            element("DIV")
+                .with_key_value("class", "a")
+                .with_key_value("id", "x")
+                .with_child("Hello world")
+                .with_child(element("span").with_child("!"))
+                .with_child(element("b").with_child("bold")),
+        );
+        assert_eq!(normalize(&written), normalize(&built));
+        assert_roundtrip(&written);
+    }
+
+    #[test]
+    fn test_generated_roundtrip() {
+        for document in TreeGenerator::new(42).take(200) {
+            assert_roundtrip(&document);
+        }
+        let first: Vec<_> = TreeGenerator::new(1).take(3).collect();
+        assert_eq!(first, TreeGenerator::new(1).take(3).collect::<Vec<_>>());
+        assert_roundtrip(&Document::new().with_child(TreeGenerator::new(3).element()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Document changed")]
+    fn test_roundtrip_failure() {
+        assert_roundtrip(&Document::new().with_child(element("p").with_child(element("div"))));
+    }
+
+    #[test]
+    fn test_assert_html_eq() {
+        let card = element("div")
+            .with_key_value("id", "card")
+            .with_key_value("class", "card")
+            .with_child(element("h2").with_child("Title"))
+            .with_child(element("p").with_child("Body  text"));
+        crate::assert_html_eq!(
+            card,
+            r#"<div class="card" id="card">
+                <h2>Title</h2>
+                <p>Body text</p>
+            </div>"#
+        );
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            crate::assert_html_eq!(card, r#"<div class="card" id="card"><h2>Title</h2></div>"#);
+        }));
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.ends_with(concat!(
+            "--- expected\n+++ actual\n",
+            "@@ -1,3 +1,4 @@\n",
+            " <div class=\"card\" id=\"card\">\n",
+            "   <h2>Title</h2>\n",
+            "+  <p>Body text</p>\n",
+            " </div>\n",
+        )));
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let expected = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let actual = "a\nB\nc\nd\ne\nf\ng\nh\ni\n";
+        assert_eq!(
+            unified_diff(expected, actual),
+            concat!(
+                "--- expected\n+++ actual\n",
+                "@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n",
+                "@@ -7,4 +7,3 @@\n g\n h\n i\n-j\n",
+            )
+        );
+        assert_eq!(unified_diff(expected, expected), "");
+    }
+
+    #[test]
+    fn test_pretty_diff() {
+        let page = |paragraph: Element<'static>| -> Node<'static> {
+            element("div")
+                .with_child(element("h1").with_child("Title"))
+                .with_child(paragraph.with_child("Body"))
+                .with_child(element("footer"))
+                .into()
+        };
+        let expected = page(
+            element("p")
+                .with_key_value("class", "a")
+                .with_key_value("id", "x"),
+        );
+        let actual = page(
+            element("p")
+                .with_key_value("class", "b")
+                .with_key_value("title", "t"),
+        );
+        assert_eq!(
+            pretty_diff(&expected, &actual),
+            concat!(
+                "First difference at /1\n",
+                "  expected: p .class = \"a\" .id = \"x\" { \"Body\" }\n",
+                "  actual:   p .class = \"b\" .title = \"t\" { \"Body\" }\n",
+                "Attributes:\n",
+                "  ~ .class = \"a\" -> \"b\"\n",
+                "  - .id = \"x\"\n",
+                "  + .title = \"t\"\n",
+                "Context:\n",
+                "    div {\n",
+                "        h1 { \"Title\" }\n",
+                "  >     p .class = \"b\" .title = \"t\" { \"Body\" }\n",
+                "        footer {}\n",
+                "    }\n",
+            )
+        );
+        assert_eq!(pretty_diff(&expected, &expected), "");
+        let shorter = Node::from(element("ul").with_child(element("li")));
+        let longer = Node::from(element("ul").with_child(element("li")).with_child("extra"));
+        assert!(pretty_diff(&shorter, &longer).contains("  unexpected: \"extra\"\n"));
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    prelude::*,
+    style::{Rule, stylesheet},
+};
+
+// Deepest chain of tokens referring to other tokens that is resolved, so cycles end
+const MAX_DEPTH: usize = 8;
+
+/// How [`Block::apply_theme`] delivers the tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ThemeMode {
+    /// Keeps `var(--token)` references and declares the tokens in a `:root` rule
+    #[default]
+    Variables,
+    /// Replaces `var(--token)` references with the token values, for mail clients
+    /// and other renderers without custom property support
+    Inline,
+}
+
+/// Design tokens, the custom properties shared by a document's styles
+///
+/// Token names are stored without their leading `--`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Theme {
+    pub tokens: BTreeMap<String, String>,
+}
+
+impl Theme {
+    #[must_use]
+    pub fn new() -> Self {
+        Theme::default()
+    }
+
+    pub fn add_token(&mut self, name: &str, value: impl Into<String>) {
+        let name = name.strip_prefix("--").unwrap_or(name);
+        self.tokens.insert(name.to_string(), value.into());
+    }
+    #[must_use]
+    pub fn with_token(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.add_token(name, value);
+        self
+    }
+
+    /// Returns the value of the token, with or without its leading `--`
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        let name = name.strip_prefix("--").unwrap_or(name);
+        self.tokens.get(name).map(String::as_str)
+    }
+
+    /// The `:root` rule declaring every token as a custom property
+    #[must_use]
+    pub fn root_rule(&self) -> Rule {
+        self.tokens
+            .iter()
+            .fold(Rule::new(":root"), |rule, (name, value)| {
+                rule.with_declaration(format!("--{name}"), value)
+            })
+    }
+
+    /// Replaces the `var(--token)` references in a CSS value with the token values
+    ///
+    /// Unknown tokens use their fallback, e.g. `var(--gap, 1rem)`, or are kept as
+    /// they are when they have none.
+    #[must_use]
+    pub fn resolve(&self, css: &str) -> String {
+        self.resolve_depth(css, 0)
+    }
+
+    fn resolve_depth(&self, css: &str, depth: usize) -> String {
+        if depth > MAX_DEPTH {
+            return css.to_string();
+        }
+        let mut out = String::with_capacity(css.len());
+        let mut rest = css;
+        while let Some(start) = rest.find("var(") {
+            let inner = &rest[start + 4..];
+            let Some(end) = closing_paren(inner) else {
+                break;
+            };
+            out.push_str(&rest[..start]);
+            let (name, fallback) = match inner[..end].split_once(',') {
+                Some((name, fallback)) => (name.trim(), Some(fallback.trim())),
+                None => (inner[..end].trim(), None),
+            };
+            match self.get(name).or(fallback) {
+                Some(value) => out.push_str(&self.resolve_depth(value, depth + 1)),
+                None => out.push_str(&rest[start..start + 5 + end]),
+            }
+            rest = &inner[end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+// Index of the `)` closing a parenthesis that was just opened
+fn closing_paren(input: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn inline_nodes(nodes: &mut [Node], theme: &Theme) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => text.content = theme.resolve(&text.content).into(),
+            Node::Raw(raw) => *raw = theme.resolve(raw).into(),
+            _ => {}
+        }
+    }
+}
+
+fn inline_elements(nodes: &mut [Node], theme: &Theme) {
+    for element in nodes.iter_mut().filter_map(Node::as_element_mut) {
+        let is_style = element.name.as_str() == "style";
+        for attribute in &mut element.attributes {
+            // The attributes of a style block are declarations
+            if is_style || attribute.key == "style" {
+                attribute.value = theme.resolve(&attribute.value).into();
+            }
+        }
+        if is_style {
+            inline_nodes(&mut element.children, theme);
+        } else {
+            inline_elements(&mut element.children, theme);
+        }
+    }
+}
+
+impl Block<'_> {
+    /// Applies the theme to the document's styles, see [`ThemeMode`]
+    ///
+    /// In variables mode the `:root` rule is added as the first `style` element of
+    /// `head`, so the document's own styles can override the tokens. In inline mode
+    /// `style` attributes, `style {}` blocks and `style` elements are rewritten.
+    pub fn apply_theme(&mut self, theme: &Theme, mode: ThemeMode) {
+        match mode {
+            ThemeMode::Variables => {
+                if !theme.tokens.is_empty() {
+                    let root = stylesheet(&[theme.root_rule()]);
+                    self.head_mut().children.insert(0, root.into());
+                }
+            }
+            ThemeMode::Inline => inline_elements(&mut self.children, theme),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Theme, ThemeMode};
+    use crate::prelude::*;
+
+    const SOURCE: &str = r#"head { style { "a { color: var(--accent) }" } }
+        body { p { .style = "color: var(--accent); margin: var(--gap, 2px)" } }"#;
+
+    #[test]
+    fn test_apply_theme() {
+        let theme = Theme::new()
+            .with_token("--accent", "var(--red)")
+            .with_token("red", "#f00");
+        let (_, document) = Document::parse(SOURCE).unwrap();
+
+        let mut variables = document.clone();
+        variables.apply_theme(&theme, ThemeMode::Variables);
+        assert_eq!(
+            variables.render(),
+            concat!(
+                "<head><style>:root{--accent:var(--red);--red:#f00}</style>",
+                "<style>a { color: var(--accent) }</style></head>",
+                r#"<body><p style="color: var(--accent); margin: var(--gap, 2px)"></p></body>"#,
+            )
+        );
+
+        let mut inline = document;
+        inline.apply_theme(&theme, ThemeMode::Inline);
+        assert_eq!(
+            inline.render(),
+            concat!(
+                "<head><style>a { color: #f00 }</style></head>",
+                r#"<body><p style="color: #f00; margin: 2px"></p></body>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_resolve_unknown_and_cyclic() {
+        let theme = Theme::new().with_token("a", "var(--a)");
+        assert_eq!(
+            theme.resolve("var(--missing) var(--b, calc(1px + 2px))"),
+            "var(--missing) calc(1px + 2px)"
+        );
+        assert_eq!(theme.resolve("x var(--a"), "x var(--a");
+        assert_eq!(theme.resolve("var(--a)"), "var(--a)");
+    }
+}
@@ -0,0 +1,463 @@
+use std::{borrow::Cow, collections::HashMap, fmt::Write, sync::Arc};
+
+use crate::{
+    links::is_asset,
+    prelude::*,
+    render::{close_tag, open_tag_with, raw_text, render_node, write_attribute},
+};
+
+/// A hook adjusting or replacing every element as it is rendered with a
+/// [`RenderContext`], leaving the tree itself untouched
+pub trait NodeTransform: Send + Sync {
+    /// Adjusts the attributes the element is rendered with, which hold the
+    /// element's own attributes as changed by the transforms before this one
+    fn transform<'a>(
+        &self,
+        element: &Element<'a>,
+        attributes: &mut Vec<Attribute<'a>>,
+        context: &RenderContext,
+    ) {
+        let _ = (element, attributes, context);
+    }
+
+    /// Returns the nodes rendered in place of the element and its children, none
+    /// to remove it, or `None` to keep it
+    ///
+    /// Called before [`NodeTransform::transform`]. The replacement is rendered
+    /// through the transforms after this one only, so it is not replaced again.
+    fn replace<'a>(&self, element: &Element<'a>, context: &RenderContext) -> Option<Vec<Node<'a>>> {
+        let _ = (element, context);
+        None
+    }
+}
+
+/// Cross-cutting settings applied while rendering, through an ordered pipeline of
+/// [`NodeTransform`]s
+///
+/// Transforms run in the order they were added, on a copy of each element's
+/// attributes, so the same tree can be rendered with different contexts, e.g. a
+/// new CSP nonce per request. Text inside scripts and styles is escaped as by
+/// [`Render::render`].
+#[derive(Clone, Default)]
+pub struct RenderContext {
+    transforms: Vec<Arc<dyn NodeTransform>>,
+    nonce: Option<String>,
+    values: HashMap<String, String>,
+}
+
+impl std::fmt::Debug for RenderContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderContext")
+            .field("transforms", &self.transforms.len())
+            .field("nonce", &self.nonce)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl RenderContext {
+    #[must_use]
+    pub fn new() -> Self {
+        RenderContext::default()
+    }
+
+    pub fn add_transform(&mut self, transform: impl NodeTransform + 'static) {
+        self.transforms.push(Arc::new(transform));
+    }
+    #[must_use]
+    pub fn with_transform(mut self, transform: impl NodeTransform + 'static) -> Self {
+        self.add_transform(transform);
+        self
+    }
+
+    /// Sets the CSP nonce of the response, see [`CspNonce`]
+    pub fn set_nonce(&mut self, nonce: impl Into<String>) {
+        self.nonce = Some(nonce.into());
+    }
+    #[must_use]
+    pub fn with_nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.set_nonce(nonce);
+        self
+    }
+
+    #[must_use]
+    pub fn nonce(&self) -> Option<&str> {
+        self.nonce.as_deref()
+    }
+
+    /// Sets a value for custom transforms to read
+    pub fn set_value(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(key.into(), value.into());
+    }
+    #[must_use]
+    pub fn with_value(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set_value(key, value);
+        self
+    }
+
+    #[must_use]
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Renders the document through the transforms
+    #[must_use]
+    pub fn render(&self, block: &Block) -> String {
+        let mut out = String::new();
+        for node in block.iter_nodes() {
+            self.render_node(node, Namespace::Html, 0, &mut out)
+                .expect("writing to a String cannot fail");
+        }
+        out
+    }
+
+    /// Renders the element through the transforms
+    #[must_use]
+    pub fn render_element(&self, element: &Element) -> String {
+        let mut out = String::new();
+        self.write_element(element, Namespace::Html, 0, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    // Renders the node through the transforms from index `first` on
+    fn render_node<W: Write>(
+        &self,
+        node: &Node,
+        parent: Namespace,
+        first: usize,
+        out: &mut W,
+    ) -> std::fmt::Result {
+        match node {
+            Node::Directive(directive) => directive
+                .children()
+                .iter()
+                .try_for_each(|child| self.render_node(child, parent, first, out)),
+            node => match node.as_element() {
+                Some(element) => self.write_element(element, parent, first, out),
+                None => render_node(node, parent, None, out),
+            },
+        }
+    }
+
+    fn write_element<W: Write>(
+        &self,
+        element: &Element,
+        parent: Namespace,
+        first: usize,
+        out: &mut W,
+    ) -> std::fmt::Result {
+        let mut attributes = element.attributes.clone();
+        for (i, transform) in self.transforms.iter().enumerate().skip(first) {
+            if let Some(nodes) = transform.replace(element, self) {
+                return nodes
+                    .iter()
+                    .try_for_each(|node| self.render_node(node, parent, i + 1, out));
+            }
+            transform.transform(element, &mut attributes, self);
+        }
+        if open_tag_with(element, &attributes, parent, out, write_attribute)? {
+            for child in &element.children {
+                match raw_text(element, child) {
+                    Some(code) => out.write_str(&code)?,
+                    None => self.render_node(child, element.namespace, first, out)?,
+                }
+            }
+            close_tag(element.name.as_str(), out)?;
+        }
+        Ok(())
+    }
+}
+
+// Sets the attribute unless the element already has it
+fn add_missing<'a>(attributes: &mut Vec<Attribute<'a>>, key: &'static str, value: Cow<'a, str>) {
+    if !attributes.iter().any(|attribute| attribute.key == key) {
+        attributes.push(Attribute::new(key, value));
+    }
+}
+
+/// Adds the context's nonce to `script` and `style` elements, so a
+/// `Content-Security-Policy` with `'nonce-...'` allows them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CspNonce;
+
+impl NodeTransform for CspNonce {
+    fn transform<'a>(
+        &self,
+        element: &Element<'a>,
+        attributes: &mut Vec<Attribute<'a>>,
+        context: &RenderContext,
+    ) {
+        if let Some(nonce) = context.nonce()
+            && matches!(element.name.as_str(), "script" | "style")
+        {
+            add_missing(attributes, "nonce", Cow::Owned(nonce.to_string()));
+        }
+    }
+}
+
+/// Adds a `data-testid` to elements with an `id`, or a `name` for form controls,
+/// e.g. only in test builds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TestIds;
+
+impl NodeTransform for TestIds {
+    fn transform<'a>(
+        &self,
+        element: &Element<'a>,
+        attributes: &mut Vec<Attribute<'a>>,
+        _context: &RenderContext,
+    ) {
+        let find = |key: &str| {
+            attributes
+                .iter()
+                .find(|attribute| attribute.key == key)
+                .map(|attribute| attribute.value.clone())
+        };
+        let control = matches!(
+            element.name.as_str(),
+            "input" | "select" | "textarea" | "button"
+        );
+        let id = find("id").or_else(|| find("name").filter(|_| control));
+        if let Some(id) = id {
+            add_missing(attributes, "data-testid", id);
+        }
+    }
+}
+
+/// Prefixes root-relative asset URLs, e.g. `/app.js`, with a host such as a CDN
+///
+/// Assets are the `src`, `srcset` and `poster` attributes, and the `href` of
+/// `link` elements. Links to navigate to and URLs that are relative to the page or
+/// already absolute are left as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetPrefix {
+    prefix: String,
+}
+
+impl AssetPrefix {
+    /// Prefix without a trailing slash, e.g. `https://cdn.example.com`
+    pub fn new(prefix: impl Into<String>) -> Self {
+        let mut prefix = prefix.into();
+        while prefix.ends_with('/') {
+            prefix.pop();
+        }
+        AssetPrefix { prefix }
+    }
+
+    fn prefixed(&self, url: &str) -> Option<String> {
+        (url.starts_with('/') && !url.starts_with("//")).then(|| format!("{}{url}", self.prefix))
+    }
+}
+
+impl NodeTransform for AssetPrefix {
+    fn transform<'a>(
+        &self,
+        element: &Element<'a>,
+        attributes: &mut Vec<Attribute<'a>>,
+        _context: &RenderContext,
+    ) {
+        for attribute in attributes.iter_mut() {
+            if !is_asset(element, attribute) {
+                continue;
+            }
+            let value = if attribute.key == "srcset" {
+                let candidates = attribute.value.split(',').map(|candidate| {
+                    let candidate = candidate.trim();
+                    let (url, descriptor) = candidate
+                        .split_once(char::is_whitespace)
+                        .unwrap_or((candidate, ""));
+                    match self.prefixed(url) {
+                        Some(url) if descriptor.is_empty() => url,
+                        Some(url) => format!("{url} {}", descriptor.trim()),
+                        None => candidate.to_string(),
+                    }
+                });
+                Some(candidates.collect::<Vec<_>>().join(", "))
+                    .filter(|srcset| *srcset != attribute.value)
+            } else {
+                self.prefixed(attribute.value.trim())
+            };
+            if let Some(value) = value {
+                attribute.value = value.into();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AssetPrefix, CspNonce, NodeTransform, RenderContext, TestIds};
+    use crate::prelude::*;
+
+    // Marks external links with the site's name, read from the context
+    struct ExternalLinks;
+
+    impl NodeTransform for ExternalLinks {
+        fn transform<'a>(
+            &self,
+            element: &Element<'a>,
+            attributes: &mut Vec<Attribute<'a>>,
+            context: &RenderContext,
+        ) {
+            if element.name.as_str() == "a"
+                && element
+                    .attr("href")
+                    .is_some_and(|href| href.starts_with("http"))
+            {
+                let site = context.value("site").unwrap_or_default();
+                attributes.push(Attribute::new("rel", format!("noopener {site}")));
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_context() {
+        let page = Document::new()
+            .with_child(
+                element("link")
+                    .with_key_value("rel", "stylesheet")
+                    .with_key_value("href", "/app.css"),
+            )
+            .with_child(Node::script_src("/app.js", ScriptLoading::Blocking))
+            .with_child(
+                element("img")
+                    .with_key_value("srcset", "/a.png 1x, https://x.com/b.png 2x")
+                    .with_key_value("src", "a.png"),
+            )
+            .with_child(element("input").with_key_value("name", "email"))
+            .with_child(
+                element("a")
+                    .with_key_value("href", "https://example.com")
+                    .with_key_value("id", "out"),
+            )
+            .with_child(element("a").with_key_value("href", "/about"));
+        let context = RenderContext::new()
+            .with_transform(CspNonce)
+            .with_transform(AssetPrefix::new("https://cdn.example.com/"))
+            .with_transform(TestIds)
+            .with_transform(ExternalLinks)
+            .with_nonce("r4nd0m")
+            .with_value("site", "demo");
+        assert_eq!(
+            context.render(&page),
+            concat!(
+                r#"<link rel="stylesheet" href="https://cdn.example.com/app.css">"#,
+                r#"<script src="https://cdn.example.com/app.js" nonce="r4nd0m"></script>"#,
+                r#"<img srcset="https://cdn.example.com/a.png 1x, https://x.com/b.png 2x" src="a.png">"#,
+                r#"<input name="email" data-testid="email">"#,
+                r#"<a href="https://example.com" id="out" data-testid="out" rel="noopener demo"></a>"#,
+                r#"<a href="/about"></a>"#,
+            )
+        );
+        // The tree is unchanged and renders as usual without the context
+        assert!(!page.render().contains("cdn.example.com"));
+        assert_eq!(RenderContext::new().render(&page), page.render());
+    }
+
+    // Appends its name to the `class` set by the transforms before it
+    struct Mark(&'static str);
+
+    impl NodeTransform for Mark {
+        fn transform<'a>(
+            &self,
+            _element: &Element<'a>,
+            attributes: &mut Vec<Attribute<'a>>,
+            _context: &RenderContext,
+        ) {
+            match attributes.iter_mut().find(|attr| attr.key == "class") {
+                Some(attr) => attr.value = format!("{} {}", attr.value, self.0).into(),
+                None => attributes.push(Attribute::new("class", self.0)),
+            }
+        }
+    }
+
+    // Drops `<script>` elements along with their children
+    struct NoScripts;
+
+    impl NodeTransform for NoScripts {
+        fn replace<'a>(
+            &self,
+            element: &Element<'a>,
+            _context: &RenderContext,
+        ) -> Option<Vec<Node<'a>>> {
+            (element.name.as_str() == "script").then(Vec::new)
+        }
+    }
+
+    // Turns `<b>` into `<strong>` wrapping the same children
+    struct Strong;
+
+    impl NodeTransform for Strong {
+        fn replace<'a>(
+            &self,
+            bold: &Element<'a>,
+            _context: &RenderContext,
+        ) -> Option<Vec<Node<'a>>> {
+            (bold.name.as_str() == "b").then(|| {
+                vec![Node::Element(
+                    element("strong").with_children(bold.children.clone()),
+                )]
+            })
+        }
+    }
+
+    #[test]
+    fn test_hook_ordering() {
+        let page = Document::new()
+            .with_child(element("p").with_child(element("span")))
+            .with_child(element("i").with_key_value("class", "x"));
+        let context = RenderContext::new()
+            .with_transform(Mark("a"))
+            .with_transform(Mark("b"))
+            .with_transform(Mark("c"));
+        // Each transform sees the changes of the ones before it, in the order added
+        assert_eq!(
+            context.render(&page),
+            concat!(
+                r#"<p class="a b c"><span class="a b c"></span></p>"#,
+                r#"<i class="x a b c"></i>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_remove_elements() {
+        let page = Document::new()
+            .with_child(element("p").with_child(Node::text("kept")))
+            .with_child(element("script").with_child(Node::text("alert(1)")))
+            .with_child(
+                element("div")
+                    .with_child(Node::script_src("/app.js", ScriptLoading::Blocking))
+                    .with_child(Node::comment("note")),
+            );
+        let context = RenderContext::new().with_transform(NoScripts);
+        assert_eq!(context.render(&page), "<p>kept</p><div><!--note--></div>");
+        assert!(page.render().contains("alert(1)"));
+    }
+
+    #[test]
+    fn test_replace_elements() {
+        let page = Document::new().with_child(
+            element("p").with_child(
+                element("b")
+                    .with_key_value("class", "old")
+                    .with_child(Node::text("bold"))
+                    .with_child(element("b").with_child(Node::text("nested"))),
+            ),
+        );
+        let context = RenderContext::new()
+            .with_transform(Mark("before"))
+            .with_transform(Strong)
+            .with_transform(Mark("after"));
+        // The replacement and its children only go through the transforms after
+        // the one replacing it, so the nested `<b>` is kept as is
+        assert_eq!(
+            context.render(&page),
+            concat!(
+                r#"<p class="before after"><strong class="after">bold"#,
+                r#"<b class="after">nested</b></strong></p>"#,
+            )
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::VecDeque;
+
+use crate::prelude::*;
+
+/// Depth-first, pre-order iterator over nodes and their descendants
+///
+/// Created by [`Node::descendants`], [`Element::descendants`] and [`Block::iter`].
+#[derive(Debug, Clone)]
+pub struct Descendants<'s, 'a> {
+    stack: Vec<std::slice::Iter<'s, Node<'a>>>,
+}
+
+impl<'s, 'a> Descendants<'s, 'a> {
+    #[must_use]
+    pub fn new(nodes: &'s [Node<'a>]) -> Self {
+        Descendants {
+            stack: vec![nodes.iter()],
+        }
+    }
+}
+
+impl<'s, 'a> Iterator for Descendants<'s, 'a> {
+    type Item = &'s Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(node) = self.stack.last_mut()?.next() else {
+                self.stack.pop();
+                continue;
+            };
+            if let Some(element) = node.as_element() {
+                self.stack.push(element.children.iter());
+            }
+            return Some(node);
+        }
+    }
+}
+
+/// Breadth-first iterator over nodes and their descendants, level by level
+///
+/// Created by [`Node::breadth_first`], [`Element::breadth_first`] and [`Block::iter_breadth_first`].
+#[derive(Debug, Clone)]
+pub struct BreadthFirst<'s, 'a> {
+    queue: VecDeque<&'s Node<'a>>,
+}
+
+impl<'s, 'a> BreadthFirst<'s, 'a> {
+    #[must_use]
+    pub fn new(nodes: &'s [Node<'a>]) -> Self {
+        BreadthFirst {
+            queue: nodes.iter().collect(),
+        }
+    }
+}
+
+impl<'s, 'a> Iterator for BreadthFirst<'s, 'a> {
+    type Item = &'s Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        if let Some(element) = node.as_element() {
+            self.queue.extend(&element.children);
+        }
+        Some(node)
+    }
+}
+
+// Children of a node, text and other leaves have none
+fn child_nodes<'s, 'a>(node: &'s Node<'a>) -> &'s [Node<'a>] {
+    node.as_element()
+        .map_or(&[], |element| element.children.as_slice())
+}
+
+// Elements whose text is not displayed, skipped by `inner_text`
+const HIDDEN_TEXT_ELEMENTS: &[&str] = &["script", "style", "template"];
+
+fn text_content(nodes: Descendants) -> String {
+    nodes
+        .filter_map(|node| match node {
+            Node::Text(text) => Some(text.content.as_ref()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn inner_text(nodes: &[Node], separator: &str, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => {
+                let text = text.content.trim();
+                if text.is_empty() {
+                    continue;
+                }
+                if !out.is_empty() {
+                    out.push_str(separator);
+                }
+                out.push_str(text);
+            }
+            node => {
+                if let Some(element) = node.as_element()
+                    && !HIDDEN_TEXT_ELEMENTS.contains(&element.name.as_str())
+                {
+                    inner_text(&element.children, separator, out);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Node<'a> {
+    /// Concatenates the text of the node and all of its descendants, as is
+    #[must_use]
+    pub fn text_content(&self) -> String {
+        match self {
+            Node::Text(text) => text.content.to_string(),
+            _ => text_content(self.descendants()),
+        }
+    }
+
+    /// Iterates over all descendants depth-first, not including the node itself
+    #[must_use]
+    pub fn descendants(&self) -> Descendants<'_, 'a> {
+        Descendants::new(child_nodes(self))
+    }
+
+    /// Iterates over all descendants breadth-first, not including the node itself
+    #[must_use]
+    pub fn breadth_first(&self) -> BreadthFirst<'_, 'a> {
+        BreadthFirst::new(child_nodes(self))
+    }
+}
+
+impl<'a> Element<'a> {
+    /// Concatenates the text of all descendants, as is
+    #[must_use]
+    pub fn text_content(&self) -> String {
+        text_content(self.descendants())
+    }
+
+    /// Returns the visible text of the element, with each text node trimmed and
+    /// separated by a space
+    ///
+    /// Text inside `script`, `style` and `template` elements is skipped.
+    #[must_use]
+    pub fn inner_text(&self) -> String {
+        self.inner_text_with(" ")
+    }
+
+    /// Like [`Element::inner_text`], with a custom separator between text nodes
+    #[must_use]
+    pub fn inner_text_with(&self, separator: &str) -> String {
+        let mut out = String::new();
+        inner_text(&self.children, separator, &mut out);
+        out
+    }
+
+    /// Iterates over all descendants depth-first, not including the element itself
+    #[must_use]
+    pub fn descendants(&self) -> Descendants<'_, 'a> {
+        Descendants::new(&self.children)
+    }
+
+    /// Iterates over all descendants breadth-first, not including the element itself
+    #[must_use]
+    pub fn breadth_first(&self) -> BreadthFirst<'_, 'a> {
+        BreadthFirst::new(&self.children)
+    }
+
+    /// Iterates over the direct children that are elements
+    pub fn children_elements(&self) -> impl Iterator<Item = &Element<'a>> {
+        self.children.iter().filter_map(Node::as_element)
+    }
+
+    /// Iterates mutably over the direct children that are elements, shared elements are skipped
+    pub fn children_elements_mut(&mut self) -> impl Iterator<Item = &mut Element<'a>> {
+        self.children.iter_mut().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            _ => None,
+        })
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Concatenates the text of the whole document, as is
+    #[must_use]
+    pub fn text_content(&self) -> String {
+        text_content(self.iter())
+    }
+
+    /// Returns the visible text of the document, see [`Element::inner_text`]
+    #[must_use]
+    pub fn inner_text(&self) -> String {
+        self.inner_text_with(" ")
+    }
+
+    /// Returns the visible text of the document, see [`Element::inner_text_with`]
+    #[must_use]
+    pub fn inner_text_with(&self, separator: &str) -> String {
+        let mut out = String::new();
+        inner_text(&self.children, separator, &mut out);
+        out
+    }
+
+    /// Iterates over every node in the document depth-first, in document order
+    #[must_use]
+    pub fn iter(&self) -> Descendants<'_, 'a> {
+        Descendants::new(&self.children)
+    }
+
+    /// Iterates over every node in the document breadth-first
+    #[must_use]
+    pub fn iter_breadth_first(&self) -> BreadthFirst<'_, 'a> {
+        BreadthFirst::new(&self.children)
+    }
+
+    /// Iterates over the top-level nodes that are elements
+    pub fn children_elements(&self) -> impl Iterator<Item = &Element<'a>> {
+        self.children.iter().filter_map(Node::as_element)
+    }
+
+    /// Iterates mutably over the top-level nodes that are elements, shared elements are skipped
+    ///
+    /// There is no mutable depth-first iterator, since a node cannot be borrowed
+    /// mutably alongside its children, nested elements are reached level by level.
+    pub fn children_elements_mut(&mut self) -> impl Iterator<Item = &mut Element<'a>> {
+        self.children.iter_mut().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn document() -> Document<'static> {
+        Document::new()
+            .with_child(
+                element("ul")
+                    .with_child(element("li").with_child("one"))
+                    .with_child(element("li").with_child("two")),
+            )
+            .with_child(element("p").with_child("three"))
+    }
+
+    fn names<'s>(nodes: impl Iterator<Item = &'s Node<'s>>) -> Vec<String> {
+        nodes
+            .map(|node| match node {
+                Node::Element(element) => element.name.as_str().to_string(),
+                Node::Text(text) => text.to_string(),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_depth_first() {
+        let document = document();
+        assert_eq!(
+            names(document.iter()),
+            ["ul", "li", "one", "li", "two", "p", "three"]
+        );
+        assert_eq!(
+            names(document.children[0].descendants()),
+            ["li", "one", "li", "two"]
+        );
+    }
+
+    #[test]
+    fn test_breadth_first() {
+        let document = document();
+        assert_eq!(
+            names(document.iter_breadth_first()),
+            ["ul", "p", "li", "li", "three", "one", "two"]
+        );
+    }
+
+    #[test]
+    fn test_children_elements_mut() {
+        let mut document = document();
+        for list in document.children_elements_mut() {
+            for item in list.children_elements_mut() {
+                item.add_attribute(Attribute::class("item"));
+            }
+        }
+        let Node::Element(list) = &document.children[0] else {
+            panic!("Expected an element");
+        };
+        assert!(
+            list.children_elements()
+                .all(|li| li.attributes == [Attribute::class("item")])
+        );
+        assert_eq!(document.iter().filter(|node| node.is_element()).count(), 4);
+    }
+
+    #[test]
+    fn test_text_content() {
+        let article = element("article")
+            .with_child(element("h1").with_child("Title "))
+            .with_child(element("script").with_child("track()"))
+            .with_child(
+                element("p")
+                    .with_child("  First ")
+                    .with_child(element("b").with_child("bold")),
+            );
+        assert_eq!(article.text_content(), "Title track()  First bold");
+        assert_eq!(article.inner_text(), "Title First bold");
+        assert_eq!(article.inner_text_with("\n"), "Title\nFirst\nbold");
+        let document = Document::new().with_child(article);
+        assert_eq!(document.inner_text_with(" | "), "Title | First | bold");
+        assert_eq!(document.children[0].text_content(), document.text_content());
+    }
+}
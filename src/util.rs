@@ -1,3 +1,5 @@
+use memchr::{memchr_iter, memchr2, memmem};
+
 use crate::error::{ParseError, ParseResult};
 
 // Parses nested content within delimiters
@@ -24,36 +26,36 @@ pub fn nested<'a>(
     }
 
     let input = &input[start.len()..];
+    let bytes = input.as_bytes();
+    let (open, close) = (start.as_bytes(), end.as_bytes());
     let mut depth = 1;
 
-    let mut search_offset = 0;
-    while let Some(next_delim_pos) = input[search_offset..]
-        .find(start)
-        .map(|p| (p, true))
-        .into_iter()
-        .chain(input[search_offset..].find(end).map(|p| (p, false)))
-        .min_by_key(|&(p, _)| p)
-    {
-        let (relative_pos, is_start_delim) = next_delim_pos;
-        search_offset += relative_pos;
+    // Jumps between bytes that can begin a delimiter, then checks the whole delimiter
+    let mut position = 0;
+    while let Some(found) = memchr2(open[0], close[0], &bytes[position..]) {
+        let at = position + found;
+        let rest = &bytes[at..];
+        let is_start_delim = rest.starts_with(open);
+        if !is_start_delim && !rest.starts_with(close) {
+            position = at + 1;
+            continue;
+        }
 
         // Check for escape character. If found, skip this delimiter and continue searching.
-        if search_offset > 0 && input.as_bytes()[search_offset - 1] == b'\\' {
-            search_offset += 1;
+        if is_escaped(bytes, at) {
+            position = at + 1;
             continue;
         }
 
         if is_start_delim {
             depth += 1;
-            search_offset += start.len();
+            position = at + open.len();
         } else {
             depth -= 1;
             if depth == 0 {
-                let content = &input[..search_offset];
-                let rest = &input[search_offset + end.len()..];
-                return Ok((rest, content));
+                return Ok((&input[at + close.len()..], &input[..at]));
             }
-            search_offset += end.len();
+            position = at + close.len();
         }
     }
 
@@ -72,27 +74,76 @@ pub fn delimited<'a>(input: &'a str, delim: &'a str) -> ParseResult<'a, &'a str>
     }
     let input = &input[delim.len()..];
     // Look for the next occurrence of delim that is not escaped
-
-    for (i, _) in input.match_indices(delim) {
-        if i == 0 || &input[i - 1..i] != "\\" {
-            let content = &input[..i];
-            let rest = &input[i + delim.len()..];
-            return Ok((rest, content));
-        }
+    let bytes = input.as_bytes();
+    let unescaped = |&i: &usize| !is_escaped(bytes, i);
+    let found = match delim.as_bytes() {
+        [byte] => memchr_iter(*byte, bytes).find(unescaped),
+        delim => memmem::find_iter(bytes, delim).find(unescaped),
+    };
+    if let Some(i) = found {
+        return Ok((&input[i + delim.len()..], &input[..i]));
     }
 
     Err(ParseError::missing_delimiter(delim, "end of input"))
 }
 
+// Whether the delimiter at `at` is preceded by a backslash
+fn is_escaped(bytes: &[u8], at: usize) -> bool {
+    at > 0 && bytes[at - 1] == b'\\'
+}
+
 // Parses content nested within double quotes
 pub fn quote_nested(input: &str) -> ParseResult<'_, &str> {
     delimited(input, "\"")
 }
 
+// Byte offset of `part` in `source`, if it is a slice of it
+pub fn offset_in(source: &str, part: &str) -> Option<usize> {
+    let start = part.as_ptr().addr().checked_sub(source.as_ptr().addr())?;
+    (start + part.len() <= source.len()).then_some(start)
+}
+
 #[cfg(test)]
 pub(crate) mod test_util {
     use super::{ParseError, ParseResult};
-    pub fn assert_parse_eq<'a, T: PartialEq + std::fmt::Debug>(
+    use crate::{
+        prelude::*,
+        testing::{pretty_diff, pretty_diff_documents},
+    };
+
+    // Parsed values, trees also describe where they differ from the expected one
+    pub trait ParsedValue: PartialEq + std::fmt::Debug {
+        fn diff(&self, _expected: &Self) -> String {
+            String::new()
+        }
+    }
+
+    impl ParsedValue for &str {}
+    impl ParsedValue for Attribute<'_> {}
+    impl ParsedValue for Tag<'_> {}
+    impl ParsedValue for Text<'_> {}
+    impl ParsedValue for crate::parse::Comment<'_> {}
+    impl ParsedValue for crate::select::AttributeSelector<'_> {}
+
+    impl ParsedValue for Node<'_> {
+        fn diff(&self, expected: &Self) -> String {
+            pretty_diff(expected, self)
+        }
+    }
+
+    impl ParsedValue for Element<'_> {
+        fn diff(&self, expected: &Self) -> String {
+            pretty_diff(&expected.clone().into(), &self.clone().into())
+        }
+    }
+
+    impl ParsedValue for Block<'_> {
+        fn diff(&self, expected: &Self) -> String {
+            pretty_diff_documents(expected, self)
+        }
+    }
+
+    pub fn assert_parse_eq<'a, T: ParsedValue>(
         result: ParseResult<'a, T>,
         expected_value: T,
         expected_rest: &'a str,
@@ -101,8 +152,12 @@ pub(crate) mod test_util {
 
         if rest != expected_rest || value != expected_value {
             panic!(
-                "Assertion failed:\nExpected rest: {:#?}\nActual rest:   {:#?}\nExpected value: {:?}\nActual value:   {:?}",
-                expected_rest, rest, expected_value, value
+                "Assertion failed:\nExpected rest: {:#?}\nActual rest:   {:#?}\nExpected value: {:?}\nActual value:   {:?}\n{}",
+                expected_rest,
+                rest,
+                expected_value,
+                value,
+                value.diff(&expected_value)
             );
         }
     }
@@ -164,6 +219,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_multi_byte_delimiters() {
+        let input = "/* a / b * c /* d */ e */ rest";
+        assert_parse_eq(
+            super::nested(input, "/*", Some("*/")),
+            " a / b * c /* d */ e ",
+            " rest",
+        );
+        assert_parse_eq(
+            super::delimited("~~a ~ b \\~~ c~~d", "~~"),
+            "a ~ b \\~~ c",
+            "d",
+        );
+    }
+
     #[test]
     fn test_missing_end_delimiter() {
         let input = "{ level 1 { level 2 } level 1 continued rest";
@@ -0,0 +1,302 @@
+use std::collections::{HashMap, hash_map::Entry};
+
+use crate::{
+    lint::{P_CLOSING_TAGS, can_contain},
+    prelude::*,
+    render::is_void_element,
+};
+
+// Elements that may not contain another element of the same kind at any depth
+const UNNESTABLE_TAGS: &[&str] = &["a", "button", "form"];
+
+// Elements ending the search for an open `<p>`, so a `<p>` above them is not closed
+const P_SCOPE_BOUNDARIES: &[&str] = &[
+    "applet", "button", "caption", "html", "marquee", "object", "table", "td", "template", "th",
+];
+
+// Elements whose text children browsers move out in front of the table
+const TABLE_SECTIONS: &[&str] = &["table", "thead", "tbody", "tfoot", "tr"];
+
+/// A problem found at a node of a document, see [`Block::validate`] and
+/// [`crate::a11y::check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Path of the offending node
+    pub path: NodePath,
+    /// Tag of the offending element, or of the parent of offending text
+    pub element: String,
+    /// Name of the broken rule, e.g. `invalid-nesting`
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(
+        path: NodePath,
+        element: impl Into<String>,
+        code: &'static str,
+        message: impl Into<String>,
+    ) -> Self {
+        Violation {
+            path,
+            element: element.into(),
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <{}>: {}", self.path, self.element, self.message)
+    }
+}
+
+/// An `id` shared by several elements, see [`Block::duplicate_ids`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateId {
+    pub id: String,
+    /// Paths of every element with the id, in document order
+    pub paths: Vec<NodePath>,
+}
+
+impl std::fmt::Display for DuplicateId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Duplicate id `{}` at ", self.id)?;
+        for (i, path) in self.paths.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{path}")?;
+        }
+        Ok(())
+    }
+}
+
+// Records the path of every element with an id, in document order
+fn collect_ids<'n>(nodes: &'n [Node], path: &mut NodePath, ids: &mut Vec<(&'n str, NodePath)>) {
+    for (index, element) in nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, node)| Some((index, node.as_element()?)))
+    {
+        path.push(index);
+        if let Some(id) = element.id() {
+            ids.push((id, path.clone()));
+        }
+        collect_ids(&element.children, path, ids);
+        path.pop();
+    }
+}
+
+struct Validator<'n> {
+    violations: Vec<Violation>,
+    path: NodePath,
+    // Tags of the elements enclosing the current node, outermost first
+    ancestors: Vec<&'n str>,
+}
+
+impl<'n> Validator<'n> {
+    fn check_nodes(&mut self, nodes: &'n [Node]) {
+        for (index, node) in nodes.iter().enumerate() {
+            self.path.push(index);
+            match node {
+                Node::Text(text) => self.check_text(text),
+                node => {
+                    if let Some(element) = node.as_element() {
+                        self.check_element(element);
+                    }
+                }
+            }
+            self.path.pop();
+        }
+    }
+
+    fn check_text(&mut self, text: &Text) {
+        if self.ancestors.last() == Some(&"script") && !text.content.trim().is_empty() {
+            self.violations.push(Violation::new(
+                self.path.clone(),
+                "script",
+                "untrusted-script-text",
+                "Text inside `<script>` is rendered as code, it must come from a trusted source",
+            ));
+        }
+        if let Some(&parent) = self.ancestors.last()
+            && TABLE_SECTIONS.contains(&parent)
+            && !text.content.trim().is_empty()
+        {
+            self.violations.push(Violation::new(
+                self.path.clone(),
+                parent,
+                "misplaced-text",
+                format!("Text cannot appear directly inside `<{parent}>`"),
+            ));
+        }
+    }
+
+    // The open `<p>` the tag would close, i.e. one not hidden by a scope boundary
+    fn open_paragraph(&self) -> bool {
+        self.ancestors
+            .iter()
+            .rev()
+            .take_while(|tag| !P_SCOPE_BOUNDARIES.contains(tag))
+            .any(|&tag| tag == "p")
+    }
+
+    fn check_element(&mut self, element: &'n Element) {
+        let tag = element.name.as_str();
+        let parent = self.ancestors.last().copied();
+        let problem = if UNNESTABLE_TAGS.contains(&tag) && self.ancestors.contains(&tag) {
+            Some(format!(
+                "`<{tag}>` cannot be nested inside another `<{tag}>`"
+            ))
+        } else if P_CLOSING_TAGS.contains(&tag) && self.open_paragraph() {
+            Some(format!("`<{tag}>` cannot be nested inside `<p>`"))
+        } else {
+            parent
+                .filter(|parent| !can_contain(parent, tag))
+                .map(|parent| format!("`<{tag}>` cannot be nested inside `<{parent}>`"))
+        };
+        if let Some(message) = problem {
+            self.violations.push(Violation::new(
+                self.path.clone(),
+                tag,
+                "invalid-nesting",
+                message,
+            ));
+        }
+        for attribute in element.attributes.iter().filter(|a| !a.has_valid_key()) {
+            self.violations.push(Violation::new(
+                self.path.clone(),
+                tag,
+                "invalid-attribute-name",
+                format!(
+                    "Attribute name `{}` is not valid and is not rendered",
+                    attribute.key
+                ),
+            ));
+        }
+        if is_void_element(tag) && !element.children.is_empty() {
+            self.violations.push(Violation::new(
+                self.path.clone(),
+                tag,
+                "void-children",
+                format!("Void element `<{tag}>` cannot have children"),
+            ));
+        }
+        // Foreign content follows its own rules
+        if matches!(tag, "svg" | "math") {
+            return;
+        }
+        self.ancestors.push(tag);
+        self.check_nodes(&element.children);
+        self.ancestors.pop();
+    }
+}
+
+impl Block<'_> {
+    /// Checks the document against the parts of the HTML content model that
+    /// browsers enforce by moving elements, in document order
+    ///
+    /// Violations cover block elements inside `p`, nested links, buttons and forms,
+    /// list items, table parts and other elements outside their required parents,
+    /// disallowed children of lists, tables and selects, text directly inside
+    /// tables, void elements with children, attribute names that are not rendered
+    /// and text inside scripts, which runs as code. Top-level nodes may have any parent, so fragments such as a row
+    /// rendered on its own are valid. `svg` and `math` subtrees are not checked.
+    #[must_use]
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut validator = Validator {
+            violations: Vec::new(),
+            path: NodePath::root(),
+            ancestors: Vec::new(),
+        };
+        validator.check_nodes(&self.children);
+        validator.violations
+    }
+
+    /// Finds the ids used by more than one element, e.g. by an element repeated in
+    /// a loop, ordered by their first use
+    #[must_use]
+    pub fn duplicate_ids(&self) -> Vec<DuplicateId> {
+        let mut ids = Vec::new();
+        collect_ids(&self.children, &mut NodePath::root(), &mut ids);
+        // Every id with its paths, ordered by first use
+        let mut groups: Vec<DuplicateId> = Vec::new();
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        for (id, path) in ids {
+            match positions.entry(id) {
+                Entry::Occupied(position) => groups[*position.get()].paths.push(path),
+                Entry::Vacant(position) => {
+                    position.insert(groups.len());
+                    groups.push(DuplicateId {
+                        id: id.to_string(),
+                        paths: vec![path],
+                    });
+                }
+            }
+        }
+        groups.retain(|group| group.paths.len() > 1);
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_validate() {
+        let (_, document) = Document::parse(
+            r#"tr { td { "fragment" } }
+            p { span { div {} } button { div {} } }
+            a { span { a {} } }
+            div { li {} }
+            table { "oops" tbody { td {} } div {} }
+            img { "alt" }
+            svg { li {} }"#,
+        )
+        .unwrap();
+        let violations: Vec<String> = document
+            .validate()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            violations,
+            [
+                "/1/0/0 <div>: `<div>` cannot be nested inside `<p>`",
+                "/2/0/0 <a>: `<a>` cannot be nested inside another `<a>`",
+                "/3/0 <li>: `<li>` cannot be nested inside `<div>`",
+                "/4/0 <table>: Text cannot appear directly inside `<table>`",
+                "/4/1/0 <td>: `<td>` cannot be nested inside `<tbody>`",
+                "/4/2 <div>: `<div>` cannot be nested inside `<table>`",
+                "/5 <img>: Void element `<img>` cannot have children",
+            ]
+        );
+        let (_, valid) = Document::parse(
+            r#"ul { li { p { "item" } } } table { tr { th { "a" } } } dl { div { dt {} dd {} } }"#,
+        )
+        .unwrap();
+        assert_eq!(valid.validate(), []);
+    }
+
+    #[test]
+    fn test_duplicate_ids() {
+        let (_, document) =
+            Document::parse(r#"div { #a p { #b } } p { #a } ul { li { #b } li { #a } } p { #c }"#)
+                .unwrap();
+        let duplicates: Vec<String> = document
+            .duplicate_ids()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            duplicates,
+            [
+                "Duplicate id `a` at /0, /1, /2/1",
+                "Duplicate id `b` at /0/0, /2/0"
+            ]
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use std::{borrow::Cow, collections::BTreeMap, fmt::Write};
+
+#[cfg(feature = "serde")]
+mod ser;
+#[cfg(feature = "serde")]
+pub use ser::{SerializeError, to_value};
+
+/// A value in a runtime template [`crate::template::Context`]
+///
+/// Values render as text in placeholders, decide `@if` conditions by their
+/// truthiness and hold the items that `@for` loops over.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value<'v> {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(Cow<'v, str>),
+    List(Vec<Value<'v>>),
+    Map(BTreeMap<Cow<'v, str>, Value<'v>>),
+}
+
+impl<'v> Value<'v> {
+    /// Whether the value counts as true in an `@if`, i.e. it is not `null`,
+    /// `false`, zero or empty
+    #[must_use]
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(value) => *value,
+            Value::Int(value) => *value != 0,
+            Value::Float(value) => *value != 0.0,
+            Value::String(value) => !value.is_empty(),
+            Value::List(items) => !items.is_empty(),
+            Value::Map(entries) => !entries.is_empty(),
+        }
+    }
+
+    /// The entry of a map, or the item of a list at an index written as a number
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value<'v>> {
+        match self {
+            Value::Map(entries) => entries.get(key),
+            Value::List(items) => items.get(key.parse::<usize>().ok()?),
+            _ => None,
+        }
+    }
+
+    /// The value at a dot-separated path of map keys and list indices, e.g.
+    /// `customer.name` or `items.0.price`
+    #[must_use]
+    pub fn lookup(&self, path: &str) -> Option<&Value<'v>> {
+        path.split('.')
+            .try_fold(self, |value, segment| value.get(segment))
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as written in a placeholder, strings without quotes, `null` as
+    /// nothing and lists and maps as JSON
+    #[must_use]
+    pub fn to_text(&self) -> Cow<'_, str> {
+        match self {
+            Value::String(value) => Cow::Borrowed(value),
+            Value::Null => Cow::Borrowed(""),
+            value => Cow::Owned(value.to_string()),
+        }
+    }
+
+    /// The value as JSON, with strings quoted
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        struct Json<'j, 'v>(&'j Value<'v>);
+        impl std::fmt::Display for Json<'_, '_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write_json(self.0, f)
+            }
+        }
+        Json(self).to_string()
+    }
+
+    /// Compares values the way `==` does in conditions, numbers by their value
+    /// whether they are integers or floats
+    #[must_use]
+    pub fn loosely_equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Int(a), Value::Float(b)) | (Value::Float(b), Value::Int(a)) => *a as f64 == *b,
+            _ => self == other,
+        }
+    }
+
+    /// Copies all borrowed data
+    #[must_use]
+    pub fn into_owned(self) -> Value<'static> {
+        match self {
+            Value::Null => Value::Null,
+            Value::Bool(value) => Value::Bool(value),
+            Value::Int(value) => Value::Int(value),
+            Value::Float(value) => Value::Float(value),
+            Value::String(value) => Value::String(value.into_owned().into()),
+            Value::List(items) => Value::List(items.into_iter().map(Value::into_owned).collect()),
+            Value::Map(entries) => Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned().into(), value.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// Writes a string as a quoted JSON string
+fn write_json_string(value: &str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+// Writes a value nested in a list or map, where strings are quoted
+fn write_json(value: &Value, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match value {
+        Value::String(value) => write_json_string(value, f),
+        Value::Null => f.write_str("null"),
+        value => write!(f, "{value}"),
+    }
+}
+
+/// Writes strings as they are, `null` as `null` and lists and maps as JSON
+impl std::fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value}"),
+            Value::String(value) => f.write_str(value),
+            Value::List(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_json(item, f)?;
+                }
+                f.write_char(']')
+            }
+            Value::Map(entries) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_json_string(key, f)?;
+                    f.write_char(':')?;
+                    write_json(value, f)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+impl<'v> From<&'v str> for Value<'v> {
+    fn from(value: &'v str) -> Self {
+        Value::String(Cow::Borrowed(value))
+    }
+}
+
+impl From<String> for Value<'_> {
+    fn from(value: String) -> Self {
+        Value::String(Cow::Owned(value))
+    }
+}
+
+impl<'v> From<Cow<'v, str>> for Value<'v> {
+    fn from(value: Cow<'v, str>) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<bool> for Value<'_> {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Value<'_> {
+                fn from(value: $int) -> Self {
+                    Value::Int(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<f64> for Value<'_> {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl<'v, T: Into<Value<'v>>> From<Vec<T>> for Value<'v> {
+    fn from(items: Vec<T>) -> Self {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<'v, T: Into<Value<'v>>> From<Option<T>> for Value<'v> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<'v, K, V> FromIterator<(K, V)> for Value<'v>
+where
+    K: Into<Cow<'v, str>>,
+    V: Into<Value<'v>>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Value::Map(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Conversion of a value into a typed argument of a template function, see
+/// [`crate::environment::Environment::register_function`]
+pub trait FromValue: Sized {
+    /// # Errors
+    /// Errors with a message naming the expected type if the value does not convert
+    fn from_value(value: &Value) -> Result<Self, String>;
+}
+
+// The message for a value of the wrong type
+fn expected(kind: &str, value: &Value) -> String {
+    match value {
+        Value::Null => format!("expected {kind}, got null"),
+        value => format!("expected {kind}, got `{value}`"),
+    }
+}
+
+impl FromValue for Value<'static> {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        Ok(value.clone().into_owned())
+    }
+}
+
+/// Strings, numbers and booleans convert to their text
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(_) | Value::Int(_) | Value::Float(_) | Value::Bool(_) => {
+                Ok(value.to_text().into_owned())
+            }
+            value => Err(expected("a string", value)),
+        }
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Int(value) => Ok(*value),
+            value => Err(expected("an integer", value)),
+        }
+    }
+}
+
+/// Integers convert to floats as well
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Int(value) => Ok(*value as f64),
+            Value::Float(value) => Ok(*value),
+            value => Err(expected("a number", value)),
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Bool(value) => Ok(*value),
+            value => Err(expected("a boolean", value)),
+        }
+    }
+}
+
+/// `null` converts to `None`
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::List(items) => items.iter().map(T::from_value).collect(),
+            value => Err(expected("a list", value)),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&serde_json::Value> for Value<'static> {
+    fn from(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(value) => Value::Bool(*value),
+            serde_json::Value::Number(number) => match number.as_i64() {
+                Some(value) => Value::Int(value),
+                None => Value::Float(number.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(value) => Value::String(value.clone().into()),
+            serde_json::Value::Array(items) => Value::List(items.iter().map(Value::from).collect()),
+            serde_json::Value::Object(entries) => Value::Map(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.clone().into(), Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+
+    #[test]
+    fn test_value_text() {
+        let value: Value = [
+            ("name", Value::from("Ann \"A\"")),
+            ("tags", vec!["a", "b"].into()),
+            ("age", 30.into()),
+            ("ratio", 0.5.into()),
+            ("none", Value::Null),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            value.to_text(),
+            r#"{"age":30,"name":"Ann \"A\"","none":null,"ratio":0.5,"tags":["a","b"]}"#
+        );
+        assert_eq!(Value::Null.to_text(), "");
+        assert!(!Value::from("").is_truthy() && Value::from(vec![0]).is_truthy());
+        assert!(Value::Int(2).loosely_equals(&Value::Float(2.0)));
+        assert_eq!(value.lookup("tags.1"), Some(&Value::from("b")));
+        assert_eq!(value.lookup("tags.2"), None);
+        assert_eq!(value.lookup("age.0"), None);
+    }
+}
@@ -0,0 +1,436 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use serde::ser::{self, Serialize};
+
+use super::Value;
+
+/// Error returned when a type cannot be converted into a [`Value`], see [`to_value`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializeError(String);
+
+impl std::fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cannot convert into a template value: {}", self.0)
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+impl ser::Error for SerializeError {
+    fn custom<T: std::fmt::Display>(message: T) -> Self {
+        SerializeError(message.to_string())
+    }
+}
+
+/// Converts any `Serialize` type into a [`Value`]
+///
+/// Structs and maps become maps, sequences and tuples lists, `None` and unit
+/// `null`. Enum variants are written like `serde_json` writes them, unit variants
+/// as their name and other variants as a map from their name to their content.
+///
+/// # Errors
+/// Errors if the type fails to serialize, or a map has keys that are not strings,
+/// numbers or booleans
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value<'static>, SerializeError> {
+    value.serialize(Serializer)
+}
+
+// Serializes into a value
+struct Serializer;
+
+// Collects the items of a sequence, with the variant name for tuple variants
+struct SerializeList {
+    variant: Option<&'static str>,
+    items: Vec<Value<'static>>,
+}
+
+// Collects the entries of a map or struct, with the variant name for struct variants
+struct SerializeMap {
+    variant: Option<&'static str>,
+    entries: BTreeMap<Cow<'static, str>, Value<'static>>,
+    key: Option<String>,
+}
+
+// Wraps the content of an enum variant in a map from the variant name
+fn variant(name: &'static str, content: Value<'static>) -> Value<'static> {
+    Value::Map(BTreeMap::from([(Cow::Borrowed(name), content)]))
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Value<'static>;
+    type Error = SerializeError;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        Ok(v.into())
+    }
+
+    // Integers too large for an `i64` lose precision as floats
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        Ok(i64::try_from(v).map_or(Value::Float(v as f64), Value::Int))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(v.to_string().into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(v.to_string().into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::List(v.iter().map(|&byte| byte.into()).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(variant.into())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(variant(name, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeList {
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SerializeList {
+            variant: Some(name),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(SerializeMap {
+            variant: None,
+            entries: BTreeMap::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeMap {
+            variant: Some(name),
+            entries: BTreeMap::new(),
+            key: None,
+        })
+    }
+}
+
+impl SerializeList {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Value<'static> {
+        let list = Value::List(self.items);
+        match self.variant {
+            Some(name) => variant(name, list),
+            None => list,
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Value<'static>;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Value<'static>;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Value<'static>;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = Value<'static>;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeMap {
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        key: String,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        self.entries
+            .insert(key.into(), value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Value<'static> {
+        let map = Value::Map(self.entries);
+        match self.variant {
+            Some(name) => variant(name, map),
+            None => map,
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value<'static>;
+    type Error = SerializeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        let key = match key.serialize(Serializer)? {
+            Value::Map(_) | Value::List(_) | Value::Null => {
+                return Err(SerializeError("map keys must be strings".into()));
+            }
+            key => key.to_text().into_owned(),
+        };
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerializeError("map value without a key".into()))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Value<'static>;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = Value<'static>;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use crate::{prelude::*, template::Context};
+
+    #[derive(Serialize)]
+    struct Customer {
+        name: String,
+        tags: Vec<&'static str>,
+        plan: Plan,
+        note: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    enum Plan {
+        Free,
+        Paid { seats: u32 },
+    }
+
+    #[derive(Serialize)]
+    struct Order {
+        customer: Customer,
+        gift: Customer,
+    }
+
+    #[test]
+    fn test_from_serialize() {
+        let order = Order {
+            customer: Customer {
+                name: "Ann".into(),
+                tags: vec!["new"],
+                plan: Plan::Paid { seats: 3 },
+                note: None,
+            },
+            gift: Customer {
+                name: "Bob".into(),
+                tags: Vec::new(),
+                plan: Plan::Free,
+                note: Some("Hi".into()),
+            },
+        };
+        let context = Context::from_serialize(&order).unwrap();
+        let (_, page) = Block::parse_ignoring_comments(
+            r#"p { "{customer.name} {customer.tags.0} {customer.plan.Paid.seats} {customer.note}" }
+            p { "{gift.name} {gift.plan} {gift.note}" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            page.render_with(&context).unwrap(),
+            "<p>Ann new 3 </p><p>Bob Free Hi</p>"
+        );
+        assert!(Context::from_serialize(&[1, 2]).is_err());
+    }
+}
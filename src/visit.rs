@@ -0,0 +1,236 @@
+use std::{borrow::Cow, ops::ControlFlow, sync::Arc};
+
+use crate::prelude::*;
+
+/// Whether to walk into an element after visiting it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Walk {
+    /// Visit the element's attributes and children
+    #[default]
+    Children,
+    /// Prune the subtree, continuing with the element's next sibling
+    Skip,
+}
+
+/// Visits the nodes of a tree in document order, see [`Block::walk`]
+///
+/// Every method defaults to continuing the walk. Returning `ControlFlow::Break`
+/// from any of them stops the walk entirely.
+pub trait Visitor<'a> {
+    fn visit_element(&mut self, _element: &Element<'a>) -> ControlFlow<(), Walk> {
+        ControlFlow::Continue(Walk::Children)
+    }
+    /// Called after an element's children have been walked, unless it was skipped
+    fn leave_element(&mut self, _element: &Element<'a>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_attribute(&mut self, _attribute: &Attribute<'a>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_text(&mut self, _text: &Text<'a>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_raw(&mut self, _raw: &str) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_comment(&mut self, _comment: &str) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_slot(&mut self, _name: &str) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Mutable counterpart of [`Visitor`], see [`Block::walk_mut`]
+///
+/// Elements are visited before their children, so changes to the children of
+/// an element are walked as well.
+pub trait VisitorMut<'a> {
+    fn visit_element(&mut self, _element: &mut Element<'a>) -> ControlFlow<(), Walk> {
+        ControlFlow::Continue(Walk::Children)
+    }
+    /// Called after an element's children have been walked, unless it was skipped
+    fn leave_element(&mut self, _element: &mut Element<'a>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_attribute(&mut self, _attribute: &mut Attribute<'a>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_text(&mut self, _text: &mut Text<'a>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_raw(&mut self, _raw: &mut Cow<'a, str>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_comment(&mut self, _comment: &mut Cow<'a, str>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn visit_slot(&mut self, _name: &mut Cow<'a, str>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    /// Called for shared elements, which are not walked into since other trees hold them too
+    ///
+    /// Use `Arc::make_mut` to give this tree its own copy before changing one.
+    fn visit_shared(&mut self, _element: &mut Arc<Element<'a>>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Walks the nodes and their descendants with the visitor
+///
+/// Returns `ControlFlow::Break` if the visitor stopped the walk
+pub fn walk<'a>(nodes: &[Node<'a>], visitor: &mut impl Visitor<'a>) -> ControlFlow<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => visitor.visit_text(text)?,
+            Node::Raw(raw) => visitor.visit_raw(raw)?,
+            Node::Comment(comment) => visitor.visit_comment(comment)?,
+            Node::Slot(name) => visitor.visit_slot(name)?,
+            #[cfg(feature = "i18n")]
+            Node::Translation(_) => {}
+            Node::Element(element) => walk_element(element, visitor)?,
+            // Shared elements are walked like any other
+            Node::Shared(element) => walk_element(element, visitor)?,
+            Node::Directive(directive) => walk(directive.children(), visitor)?,
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+fn walk_element<'a>(element: &Element<'a>, visitor: &mut impl Visitor<'a>) -> ControlFlow<()> {
+    if visitor.visit_element(element)? == Walk::Skip {
+        return ControlFlow::Continue(());
+    }
+    for attribute in &element.attributes {
+        visitor.visit_attribute(attribute)?;
+    }
+    walk(&element.children, visitor)?;
+    visitor.leave_element(element)
+}
+
+/// Walks the nodes and their descendants with the mutable visitor
+///
+/// Returns `ControlFlow::Break` if the visitor stopped the walk
+pub fn walk_mut<'a>(nodes: &mut [Node<'a>], visitor: &mut impl VisitorMut<'a>) -> ControlFlow<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => visitor.visit_text(text)?,
+            Node::Raw(raw) => visitor.visit_raw(raw)?,
+            Node::Comment(comment) => visitor.visit_comment(comment)?,
+            Node::Slot(name) => visitor.visit_slot(name)?,
+            #[cfg(feature = "i18n")]
+            Node::Translation(_) => {}
+            Node::Shared(element) => visitor.visit_shared(element)?,
+            Node::Directive(directive) => {
+                if let Some(children) = directive.children_mut() {
+                    walk_mut(children, visitor)?;
+                }
+            }
+            Node::Element(element) => {
+                if visitor.visit_element(element)? == Walk::Skip {
+                    continue;
+                }
+                for attribute in &mut element.attributes {
+                    visitor.visit_attribute(attribute)?;
+                }
+                walk_mut(&mut element.children, visitor)?;
+                visitor.leave_element(element)?;
+            }
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+impl<'a> Node<'a> {
+    /// Walks the node and its descendants with the visitor
+    pub fn walk(&self, visitor: &mut impl Visitor<'a>) -> ControlFlow<()> {
+        walk(std::slice::from_ref(self), visitor)
+    }
+    /// Walks the node and its descendants with the mutable visitor
+    pub fn walk_mut(&mut self, visitor: &mut impl VisitorMut<'a>) -> ControlFlow<()> {
+        walk_mut(std::slice::from_mut(self), visitor)
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Walks every node in the document with the visitor
+    pub fn walk(&self, visitor: &mut impl Visitor<'a>) -> ControlFlow<()> {
+        walk(&self.children, visitor)
+    }
+    /// Walks every node in the document with the mutable visitor
+    pub fn walk_mut(&mut self, visitor: &mut impl VisitorMut<'a>) -> ControlFlow<()> {
+        walk_mut(&mut self.children, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use super::{Visitor, VisitorMut, Walk};
+    use crate::prelude::*;
+
+    fn document() -> Document<'static> {
+        Document::new()
+            .with_child(element("nav").with_child(element("a").with_child("Home")))
+            .with_child(
+                element("main")
+                    .with_attribute(Attribute::id("content"))
+                    .with_child(element("p").with_child("Hello"))
+                    .with_child(element("p").with_child("World")),
+            )
+    }
+
+    #[test]
+    fn test_visitor_prunes_subtrees() {
+        #[derive(Default)]
+        struct Texts(Vec<String>);
+        impl Visitor<'_> for Texts {
+            fn visit_element(&mut self, element: &Element) -> ControlFlow<(), Walk> {
+                ControlFlow::Continue(if element.name.as_str() == "nav" {
+                    Walk::Skip
+                } else {
+                    Walk::Children
+                })
+            }
+            fn visit_text(&mut self, text: &Text) -> ControlFlow<()> {
+                self.0.push(text.to_string());
+                ControlFlow::Continue(())
+            }
+        }
+
+        let mut texts = Texts::default();
+        assert_eq!(document().walk(&mut texts), ControlFlow::Continue(()));
+        assert_eq!(texts.0, ["Hello", "World"]);
+    }
+
+    #[test]
+    fn test_visitor_mut_breaks() {
+        struct UppercaseFirst;
+        impl<'a> VisitorMut<'a> for UppercaseFirst {
+            fn visit_attribute(&mut self, attribute: &mut Attribute<'a>) -> ControlFlow<()> {
+                attribute.value = attribute.value.to_uppercase().into();
+                ControlFlow::Continue(())
+            }
+            fn visit_text(&mut self, text: &mut Text<'a>) -> ControlFlow<()> {
+                *text = Text::new(text.to_string().to_uppercase());
+                ControlFlow::Break(())
+            }
+        }
+
+        let mut document = document();
+        assert_eq!(
+            document.walk_mut(&mut UppercaseFirst),
+            ControlFlow::Break(())
+        );
+        let expected = Document::new()
+            .with_child(element("nav").with_child(element("a").with_child("HOME")))
+            .with_child(
+                element("main")
+                    .with_attribute(Attribute::id("content"))
+                    .with_child(element("p").with_child("Hello"))
+                    .with_child(element("p").with_child("World")),
+            );
+        assert_eq!(document, expected);
+    }
+}